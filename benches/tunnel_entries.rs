@@ -0,0 +1,60 @@
+//! Tunnel `entries` parsing: the element-wise visitor against the old
+//! approach of buffering each element as a `serde_json::Value`.
+//!
+//! Run with `cargo bench --bench tunnel_entries`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde::Deserialize;
+use spur::{Tunnel, TunnelEntry};
+
+/// A tunnel with `count` entries, alternating string and object forms.
+fn tunnel_json(count: usize) -> String {
+    let entries: Vec<String> = (0..count)
+        .map(|i| {
+            let ip = format!("10.{}.{}.{}", i >> 16 & 255, i >> 8 & 255, i & 255);
+            if i % 2 == 0 {
+                format!("\"{}\"", ip)
+            } else {
+                format!(
+                    r#"{{"ip": "{}", "location": {{"country": "NL"}}, "as": {{"number": 9009}}}}"#,
+                    ip
+                )
+            }
+        })
+        .collect();
+    format!(
+        r#"{{"type": "VPN", "operator": "NORD_VPN", "entries": [{}]}}"#,
+        entries.join(", ")
+    )
+}
+
+/// The previous implementation: every element goes through a `Value`.
+fn entries_via_value(json: &str) -> Vec<TunnelEntry> {
+    #[derive(Deserialize)]
+    struct Raw {
+        entries: Vec<serde_json::Value>,
+    }
+    let raw: Raw = serde_json::from_str(json).unwrap();
+    raw.entries
+        .into_iter()
+        .map(|value| match value {
+            serde_json::Value::String(ip) => TunnelEntry::from_ip(ip),
+            value => serde_json::from_value(value).unwrap(),
+        })
+        .collect()
+}
+
+fn bench_tunnel_entries(c: &mut Criterion) {
+    let json = tunnel_json(1_000);
+    let mut group = c.benchmark_group("tunnel_entries");
+    group.bench_function("visitor", |b| {
+        b.iter(|| serde_json::from_str::<Tunnel>(black_box(&json)).unwrap())
+    });
+    group.bench_function("value_round_trip", |b| {
+        b.iter(|| entries_via_value(black_box(&json)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_tunnel_entries);
+criterion_main!(benches);
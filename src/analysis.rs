@@ -0,0 +1,441 @@
+//! Analyses over batches of contexts.
+//!
+//! | Function | Result |
+//! |----------|--------|
+//! | [`cluster_by_geohash`] | Contexts grouped by client concentration cell |
+//! | [`asn_report`] | Per-ASN tunnel rates, risks and client spread |
+//! | [`anomalies`] | Internally inconsistent field combinations in one context |
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::analysis::cluster_by_geohash;
+//! use spur::test_utils::{ClientBuilder, ConcentrationBuilder, IpContextBuilder};
+//!
+//! let in_metro = |geohash: &str| {
+//!     let concentration = ConcentrationBuilder::new().geohash(geohash).density(0.8).build();
+//!     IpContextBuilder::new()
+//!         .with_client(ClientBuilder::new().concentration(concentration).build())
+//!         .build()
+//! };
+//! let contexts = [in_metro("dr5ru7"), in_metro("dr5rsj"), in_metro("9q8yyk")];
+//!
+//! let clusters = cluster_by_geohash(&contexts, 4);
+//! assert_eq!(clusters[0].geohash, "dr5r");
+//! assert_eq!(clusters[0].size(), 2);
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::context::{DeviceType, Infrastructure, IpContext, Risk};
+
+/// Contexts whose client concentration falls in the same geohash cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeohashCluster<'a> {
+    /// The shared geohash prefix, in lowercase.
+    pub geohash: String,
+    /// The contexts in the cell, in input order.
+    pub contexts: Vec<&'a IpContext>,
+    /// Mean concentration density of the contexts that report one.
+    pub mean_density: Option<f64>,
+    /// Highest concentration density in the cell.
+    pub max_density: Option<f64>,
+}
+
+impl GeohashCluster<'_> {
+    /// Returns the number of contexts in the cluster.
+    pub fn size(&self) -> usize {
+        self.contexts.len()
+    }
+}
+
+/// Group contexts by the first `precision` characters of their client
+/// concentration geohash.
+///
+/// Lower precision means larger cells: 4 characters is roughly a metro
+/// area, 6 a neighbourhood. Contexts without a concentration geohash, or
+/// with one shorter than `precision`, are left out. Clusters are returned
+/// largest first, ties ordered by geohash.
+pub fn cluster_by_geohash<'a, I>(contexts: I, precision: usize) -> Vec<GeohashCluster<'a>>
+where
+    I: IntoIterator<Item = &'a IpContext>,
+{
+    let mut cells: BTreeMap<String, Vec<&'a IpContext>> = BTreeMap::new();
+    for context in contexts {
+        let geohash = context
+            .client
+            .as_ref()
+            .and_then(|c| c.concentration.as_ref())
+            .and_then(|c| c.geohash.as_deref());
+        if let Some(prefix) = geohash.and_then(|g| g.get(..precision)) {
+            cells
+                .entry(prefix.to_ascii_lowercase())
+                .or_default()
+                .push(context);
+        }
+    }
+
+    let mut clusters: Vec<GeohashCluster<'a>> = cells
+        .into_iter()
+        .map(|(geohash, contexts)| {
+            let densities: Vec<f64> = contexts
+                .iter()
+                .filter_map(|c| c.client.as_ref()?.concentration.as_ref()?.density)
+                .collect();
+            let mean_density = (!densities.is_empty())
+                .then(|| densities.iter().sum::<f64>() / densities.len() as f64);
+            let max_density = densities.iter().copied().reduce(f64::max);
+            GeohashCluster {
+                geohash,
+                contexts,
+                mean_density,
+                max_density,
+            }
+        })
+        .collect();
+    // Cells come out of the map ordered by geohash; the stable sort keeps
+    // that order among clusters of equal size.
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.size()));
+    clusters
+}
+
+/// Reputation of one autonomous system across a batch of contexts.
+///
+/// Produced by [`asn_report`]. Serializable so rollups can be stored and
+/// compared between runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AsnReputation {
+    /// The autonomous system number.
+    pub asn: u32,
+    /// The first organization name seen for this AS.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    /// Number of contexts announced by this AS.
+    pub contexts: u64,
+    /// Fraction of those contexts with at least one tunnel (0.0 to 1.0).
+    pub tunnel_rate: f64,
+    /// Contexts per risk, keyed by API string.
+    pub risks: BTreeMap<String, u64>,
+    /// Mean `client.spread` of the contexts that report one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean_client_spread: Option<f64>,
+    /// Mean `client.count` of the contexts that report one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean_client_count: Option<f64>,
+}
+
+impl AsnReputation {
+    /// Returns the fraction of contexts (0.0 to 1.0) that list `risk`.
+    pub fn risk_rate(&self, risk: &str) -> f64 {
+        match self.contexts {
+            0 => 0.0,
+            n => self.risks.get(risk).copied().unwrap_or(0) as f64 / n as f64,
+        }
+    }
+}
+
+/// Roll contexts up by autonomous system.
+///
+/// Contexts without an AS number are skipped. Risks are counted once per
+/// context. Records are returned with the most contexts first, ties
+/// ordered by AS number.
+///
+/// ```rust
+/// use spur::analysis::asn_report;
+/// use spur::test_utils::{generate_many, Profile};
+///
+/// let contexts = generate_many(1, Profile::Vpn, 200);
+/// for record in asn_report(&contexts).iter().take(3) {
+///     println!("AS{} {:.0}% tunnels", record.asn, record.tunnel_rate * 100.0);
+/// }
+/// ```
+pub fn asn_report<'a, I>(contexts: I) -> Vec<AsnReputation>
+where
+    I: IntoIterator<Item = &'a IpContext>,
+{
+    #[derive(Default)]
+    struct Totals {
+        organization: Option<String>,
+        contexts: u64,
+        tunneled: u64,
+        risks: BTreeMap<String, u64>,
+        spread: (f64, u64),
+        count: (f64, u64),
+    }
+
+    let mut by_asn: BTreeMap<u32, Totals> = BTreeMap::new();
+    for context in contexts {
+        let Some(asn) = context.autonomous_system.as_ref() else {
+            continue;
+        };
+        let Some(number) = asn.number else {
+            continue;
+        };
+        let totals = by_asn.entry(number).or_default();
+        if totals.organization.is_none() {
+            totals.organization.clone_from(&asn.organization);
+        }
+        totals.contexts += 1;
+        if context.tunnels.as_ref().is_some_and(|t| !t.is_empty()) {
+            totals.tunneled += 1;
+        }
+        let risks: HashSet<&str> = context.risks.iter().flatten().map(|r| r.as_str()).collect();
+        for risk in risks {
+            *totals.risks.entry(risk.to_string()).or_default() += 1;
+        }
+        if let Some(client) = &context.client {
+            if let Some(spread) = client.spread {
+                totals.spread.0 += spread as f64;
+                totals.spread.1 += 1;
+            }
+            if let Some(count) = client.count {
+                totals.count.0 += count as f64;
+                totals.count.1 += 1;
+            }
+        }
+    }
+
+    let mean = |(sum, n): (f64, u64)| (n > 0).then(|| sum / n as f64);
+    let mut report: Vec<AsnReputation> = by_asn
+        .into_iter()
+        .map(|(asn, totals)| AsnReputation {
+            asn,
+            organization: totals.organization,
+            contexts: totals.contexts,
+            tunnel_rate: totals.tunneled as f64 / totals.contexts as f64,
+            risks: totals.risks,
+            mean_client_spread: mean(totals.spread),
+            mean_client_count: mean(totals.count),
+        })
+        .collect();
+    report.sort_by_key(|r| std::cmp::Reverse(r.contexts));
+    report
+}
+
+/// Client count at or above which a residential address is suspicious.
+const FAN_OUT_CLIENTS: u64 = 200;
+
+/// Client country count at or above which a residential address is
+/// suspicious.
+const FAN_OUT_COUNTRIES: u32 = 45;
+
+/// A combination of fields that is unusual for one context.
+///
+/// Anomalies are not errors in the data; they point at addresses worth a
+/// closer look, such as residential proxies that are not yet labelled.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Anomaly {
+    /// A residential address used by at least 200 clients from at least 45
+    /// countries, more than one household explains.
+    ResidentialFanOut {
+        /// Number of distinct clients observed.
+        clients: u64,
+        /// Number of distinct client countries observed.
+        countries: u32,
+    },
+    /// A datacenter address whose clients include mobile devices.
+    DatacenterMobileClients,
+    /// An anonymous tunnel on an address without the `TUNNEL` risk.
+    UntaggedAnonymousTunnel,
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ResidentialFanOut { clients, countries } => write!(
+                f,
+                "residential address with {} clients across {} countries",
+                clients, countries
+            ),
+            Self::DatacenterMobileClients => {
+                f.write_str("datacenter address with mobile device clients")
+            }
+            Self::UntaggedAnonymousTunnel => f.write_str("anonymous tunnel without TUNNEL risk"),
+        }
+    }
+}
+
+/// Flag internally suspicious field combinations in `context`.
+///
+/// ```rust
+/// use spur::analysis::{anomalies, Anomaly};
+/// use spur::test_utils::IpContextBuilder;
+/// use spur::Infrastructure;
+///
+/// let context = IpContextBuilder::new()
+///     .infrastructure(Infrastructure::Residential)
+///     .client(450, 60)
+///     .build();
+///
+/// assert_eq!(
+///     anomalies(&context),
+///     vec![Anomaly::ResidentialFanOut { clients: 450, countries: 60 }]
+/// );
+/// ```
+pub fn anomalies(context: &IpContext) -> Vec<Anomaly> {
+    let mut found = Vec::new();
+    let client = context.client.as_ref();
+
+    if context.infrastructure == Some(Infrastructure::Residential) {
+        let clients = client.and_then(|c| c.count).unwrap_or(0);
+        let countries = client.and_then(|c| c.countries).unwrap_or(0);
+        if clients >= FAN_OUT_CLIENTS && countries >= FAN_OUT_COUNTRIES {
+            found.push(Anomaly::ResidentialFanOut { clients, countries });
+        }
+    }
+
+    let mobile_clients = client
+        .and_then(|c| c.types.as_ref())
+        .is_some_and(|types| types.contains(&DeviceType::Mobile));
+    if context.infrastructure == Some(Infrastructure::Datacenter) && mobile_clients {
+        found.push(Anomaly::DatacenterMobileClients);
+    }
+
+    let anonymous_tunnel = context
+        .tunnels
+        .iter()
+        .flatten()
+        .any(|t| t.anonymous == Some(true));
+    let tunnel_risk = context
+        .risks
+        .as_ref()
+        .is_some_and(|risks| risks.contains(&Risk::Tunnel));
+    if anonymous_tunnel && !tunnel_risk {
+        found.push(Anomaly::UntaggedAnonymousTunnel);
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ClientBuilder, ConcentrationBuilder, IpContextBuilder};
+    use crate::Risk;
+
+    fn at(geohash: &str, density: Option<f64>) -> IpContext {
+        let mut concentration = ConcentrationBuilder::new().geohash(geohash);
+        if let Some(density) = density {
+            concentration = concentration.density(density);
+        }
+        IpContextBuilder::new()
+            .with_client(
+                ClientBuilder::new()
+                    .concentration(concentration.build())
+                    .build(),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_clusters_by_prefix() {
+        let contexts = [
+            at("u33dc0", Some(0.2)),
+            at("gcpvj0", Some(0.9)),
+            at("U33DB1", Some(0.6)),
+            at("u33", None),
+            at("gcpuuz", None),
+            IpContext::default(),
+        ];
+        let clusters = cluster_by_geohash(&contexts, 4);
+
+        assert_eq!(clusters.len(), 3);
+        assert_eq!(clusters[0].geohash, "u33d");
+        assert_eq!(clusters[0].size(), 2);
+        assert!((clusters[0].mean_density.unwrap() - 0.4).abs() < 1e-9);
+        assert_eq!(clusters[0].max_density, Some(0.6));
+        assert_eq!(clusters[1].geohash, "gcpu");
+        assert_eq!(clusters[1].mean_density, None);
+        assert_eq!(clusters[2].geohash, "gcpv");
+    }
+
+    #[test]
+    fn test_asn_report() {
+        let hosting = |vpn: bool| {
+            let builder = IpContextBuilder::new()
+                .asn(9009, "M247")
+                .add_risk(Risk::Tunnel)
+                .add_risk(Risk::Tunnel)
+                .with_client(ClientBuilder::new().count(10).spread(100).build());
+            if vpn {
+                builder.vpn("NORD_VPN").build()
+            } else {
+                builder.build()
+            }
+        };
+        let contexts = [
+            hosting(true),
+            hosting(false),
+            IpContextBuilder::new().asn(7922, "Comcast").build(),
+            hosting(true),
+            hosting(true),
+            IpContext::default(),
+        ];
+        let report = asn_report(&contexts);
+
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].asn, 9009);
+        assert_eq!(report[0].organization.as_deref(), Some("M247"));
+        assert_eq!(report[0].contexts, 4);
+        assert_eq!(report[0].tunnel_rate, 0.75);
+        assert_eq!(report[0].risk_rate("TUNNEL"), 1.0);
+        assert_eq!(report[0].mean_client_spread, Some(100.0));
+        assert_eq!(report[1].asn, 7922);
+        assert_eq!(report[1].tunnel_rate, 0.0);
+        assert_eq!(report[1].mean_client_count, None);
+
+        let json = serde_json::to_string(&report[1]).unwrap();
+        assert_eq!(
+            json,
+            r#"{"asn":7922,"organization":"Comcast","contexts":1,"tunnel_rate":0.0,"risks":{}}"#
+        );
+        let back: AsnReputation = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, report[1]);
+    }
+
+    #[test]
+    fn test_anomalies() {
+        let datacenter_vpn = IpContextBuilder::new()
+            .infrastructure(Infrastructure::Datacenter)
+            .vpn("NORD_VPN")
+            .client_types(vec![DeviceType::Desktop, DeviceType::Mobile])
+            .build();
+        assert_eq!(
+            anomalies(&datacenter_vpn),
+            vec![
+                Anomaly::DatacenterMobileClients,
+                Anomaly::UntaggedAnonymousTunnel
+            ]
+        );
+
+        let tagged = IpContextBuilder::new()
+            .vpn("NORD_VPN")
+            .add_risk(Risk::Tunnel)
+            .build();
+        assert!(anomalies(&tagged).is_empty());
+
+        let busy_home = |count, countries| {
+            IpContextBuilder::new()
+                .infrastructure(Infrastructure::Residential)
+                .client(count, countries)
+                .build()
+        };
+        assert_eq!(anomalies(&busy_home(200, 44)), vec![]);
+        assert_eq!(
+            anomalies(&busy_home(200, 45))[0].to_string(),
+            "residential address with 200 clients across 45 countries"
+        );
+        assert!(anomalies(&IpContext::default()).is_empty());
+    }
+
+    #[test]
+    fn test_precision_bounds() {
+        let contexts = [at("u33dc0", None), at("gcpvj0", None)];
+        assert_eq!(cluster_by_geohash(&contexts, 0).len(), 1);
+        assert!(cluster_by_geohash(&contexts, 7).is_empty());
+        assert!(cluster_by_geohash(&[], 4).is_empty());
+    }
+}
@@ -0,0 +1,267 @@
+//! Batch processing of many contexts at once.
+//!
+//! Offline jobs over archived enrichment dumps deal with millions of
+//! records. The helpers here work on whole batches so callers do not have to
+//! write the same loops around `serde_json` for every job.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::batch;
+//!
+//! let lines = [r#"{"ip": "1.2.3.4"}"#, "not json", r#"{"ip": "5.6.7.8"}"#];
+//! let parsed = batch::parse_lines(&lines);
+//!
+//! assert!(parsed[0].is_ok());
+//! assert!(parsed[1].is_err());
+//! ```
+//!
+//! With the `rayon` feature, [`parse_parallel`] spreads the same work over
+//! every core. [`dedup`] collapses identical enrichments using
+//! [`IpContext::content_hash`]. Long jobs can report [`Progress`] with
+//! [`parse_lines_with_progress`], or with
+//! [`ContextReader::on_progress`](crate::io::ContextReader::on_progress) when
+//! streaming a feed.
+
+use std::collections::HashMap;
+
+use crate::context::IpContext;
+use crate::ip_utils;
+
+/// Parse each line as an [`IpContext`], in order.
+///
+/// Blank lines are parse errors, like any other invalid input, so results
+/// always line up with the input by index.
+pub fn parse_lines(lines: &[&str]) -> Vec<serde_json::Result<IpContext>> {
+    lines
+        .iter()
+        .map(|line| serde_json::from_str(line))
+        .collect()
+}
+
+/// How far a batch job has come, passed to progress callbacks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Records processed so far, including the ones that failed.
+    pub done: u64,
+    /// Records in the whole job, if known.
+    pub total: Option<u64>,
+    /// Records that failed so far.
+    pub errors: u64,
+}
+
+impl Progress {
+    /// Returns the share of the job done, from `0.0` to `1.0`, if the total
+    /// is known. An empty job is complete.
+    pub fn fraction(&self) -> Option<f64> {
+        let total = self.total?;
+        if total == 0 {
+            return Some(1.0);
+        }
+        Some((self.done as f64 / total as f64).min(1.0))
+    }
+}
+
+/// Parse each line like [`parse_lines`], calling `on_progress` after every
+/// `every` lines and once more after the last one.
+///
+/// An `every` of zero is treated as one.
+///
+/// ```rust
+/// use spur::batch::{self, Progress};
+///
+/// let lines = [r#"{"ip": "1.2.3.4"}"#, "not json", r#"{"ip": "5.6.7.8"}"#];
+/// let mut reports = Vec::new();
+/// batch::parse_lines_with_progress(&lines, 2, |p: Progress| reports.push((p.done, p.errors)));
+///
+/// assert_eq!(reports, [(2, 1), (3, 1)]);
+/// ```
+pub fn parse_lines_with_progress<F>(
+    lines: &[&str],
+    every: u64,
+    mut on_progress: F,
+) -> Vec<serde_json::Result<IpContext>>
+where
+    F: FnMut(Progress),
+{
+    let every = every.max(1);
+    let mut progress = Progress {
+        done: 0,
+        total: Some(lines.len() as u64),
+        errors: 0,
+    };
+    let mut results = Vec::with_capacity(lines.len());
+    for line in lines {
+        let result = serde_json::from_str(line);
+        progress.done += 1;
+        progress.errors += u64::from(result.is_err());
+        results.push(result);
+        if progress.done % every == 0 || progress.done == lines.len() as u64 {
+            on_progress(progress);
+        }
+    }
+    results
+}
+
+/// Parse each line as an [`IpContext`] on the rayon thread pool.
+///
+/// Returns the same results as [`parse_lines`], in input order.
+///
+/// ```rust
+/// let lines = vec![r#"{"ip": "1.2.3.4"}"#; 10_000];
+/// let parsed = spur::batch::parse_parallel(&lines);
+///
+/// assert_eq!(parsed.len(), 10_000);
+/// assert!(parsed.iter().all(|r| r.is_ok()));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn parse_parallel(lines: &[&str]) -> Vec<serde_json::Result<IpContext>> {
+    use rayon::prelude::*;
+
+    lines
+        .par_iter()
+        .map(|line| serde_json::from_str(line))
+        .collect()
+}
+
+/// Remove duplicate contexts, keeping the first occurrence of each.
+///
+/// Contexts are bucketed by [`IpContext::content_hash`] and compared in
+/// full only on a hash match, so the cost stays close to one hash per
+/// record. `ip` fields are rewritten in [`ip_utils::canonicalize`] form
+/// first, so different spellings of the same address are duplicates.
+/// [`IpContext::freshness`] is ignored, so the same enrichment fetched at
+/// different times is a duplicate too; the kept context has the timestamps
+/// of its first occurrence. Order of the remaining contexts is preserved.
+///
+/// ```rust
+/// use spur::{batch, IpContext};
+///
+/// let a = IpContext { ip: Some("1.2.3.4".to_string()), ..Default::default() };
+/// let b = IpContext { ip: Some("5.6.7.8".to_string()), ..Default::default() };
+///
+/// let unique = batch::dedup(vec![a.clone(), b.clone(), a.clone()]);
+/// assert_eq!(unique, vec![a, b]);
+/// ```
+pub fn dedup<I>(contexts: I) -> Vec<IpContext>
+where
+    I: IntoIterator<Item = IpContext>,
+{
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut unique: Vec<IpContext> = Vec::new();
+    let mut freshness = Vec::new();
+    for mut context in contexts {
+        if let Some(ip) = context.ip.as_deref().and_then(ip_utils::canonicalize) {
+            context.ip = Some(ip);
+        }
+        // Compared without timestamps, restored once the set is final.
+        let fetched = context.freshness.take();
+        let bucket = seen.entry(context.content_hash()).or_default();
+        if bucket.iter().all(|&i| unique[i] != context) {
+            bucket.push(unique.len());
+            unique.push(context);
+            freshness.push(fetched);
+        }
+    }
+    for (context, fetched) in unique.iter_mut().zip(freshness) {
+        context.freshness = fetched;
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+    use crate::context::Freshness;
+    use crate::test_utils::{generate_many, Profile};
+
+    fn dump() -> Vec<String> {
+        generate_many(3, Profile::Vpn, 500)
+            .iter()
+            .map(|c| serde_json::to_string(c).unwrap())
+            .chain(["{broken".to_string(), String::new()])
+            .collect()
+    }
+
+    #[test]
+    fn test_dedup_canonicalizes_ips() {
+        let spelled = |ip: &str| IpContext {
+            ip: Some(ip.to_string()),
+            ..Default::default()
+        };
+        let unique = dedup(vec![
+            spelled("2001:DB8::1"),
+            spelled("2001:db8:0:0:0:0:0:1"),
+        ]);
+        assert_eq!(unique, vec![spelled("2001:db8::1")]);
+    }
+
+    #[test]
+    fn test_dedup_ignores_freshness() {
+        let fetched = |secs: u64| IpContext {
+            ip: Some("1.2.3.4".to_string()),
+            freshness: Some(Freshness::new(UNIX_EPOCH + Duration::from_secs(secs))),
+            ..Default::default()
+        };
+        assert_eq!(dedup(vec![fetched(10), fetched(20)]), vec![fetched(10)]);
+    }
+
+    #[test]
+    fn test_parse_lines_keeps_order_and_errors() {
+        let dump = dump();
+        let lines: Vec<&str> = dump.iter().map(String::as_str).collect();
+        let parsed = parse_lines(&lines);
+
+        assert_eq!(parsed.len(), 502);
+        assert_eq!(
+            parsed[0].as_ref().unwrap(),
+            &generate_many(3, Profile::Vpn, 1)[0]
+        );
+        assert!(parsed[500].is_err());
+        assert!(parsed[501].is_err());
+    }
+
+    #[test]
+    fn test_parse_lines_with_progress() {
+        let dump = dump();
+        let lines: Vec<&str> = dump.iter().map(String::as_str).collect();
+        let mut reports = Vec::new();
+        let parsed = parse_lines_with_progress(&lines, 200, |p| reports.push(p));
+
+        assert_eq!(parsed.len(), lines.len());
+        let done: Vec<u64> = reports.iter().map(|p| p.done).collect();
+        assert_eq!(done, [200, 400, 502]);
+        let last = reports.last().unwrap();
+        assert_eq!((last.errors, last.fraction()), (2, Some(1.0)));
+        assert_eq!(reports[0].fraction(), Some(200.0 / 502.0));
+
+        let mut calls = 0;
+        assert!(parse_lines_with_progress(&[], 0, |_| calls += 1).is_empty());
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let contexts = generate_many(8, Profile::Residential, 50);
+        let doubled: Vec<_> = contexts.iter().chain(&contexts).cloned().collect();
+        assert_eq!(dedup(doubled), dedup(contexts.clone()));
+        assert_eq!(dedup(contexts.clone()).len(), contexts.len());
+        assert!(dedup(Vec::new()).is_empty());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parse_parallel_matches_sequential() {
+        let dump = dump();
+        let lines: Vec<&str> = dump.iter().map(String::as_str).collect();
+        let sequential = parse_lines(&lines);
+        let parallel = parse_parallel(&lines);
+
+        assert_eq!(parallel.len(), sequential.len());
+        for (a, b) in parallel.iter().zip(&sequential) {
+            assert_eq!(a.as_ref().ok(), b.as_ref().ok());
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Planning re-lookups of stale cache entries within a query budget.
+
+use std::cmp::Ordering;
+use std::net::IpAddr;
+use std::time::SystemTime;
+
+use super::{CacheEntry, TtlPolicy};
+
+/// Re-lookups chosen by [`backfill_plan`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackfillPlan {
+    /// Addresses to look up again, most overdue first; at most the budget.
+    pub lookups: Vec<IpAddr>,
+    /// Stale entries left out because the budget ran out.
+    pub deferred: usize,
+}
+
+impl BackfillPlan {
+    /// Returns the number of stale entries found.
+    pub fn stale(&self) -> usize {
+        self.lookups.len() + self.deferred
+    }
+}
+
+/// Pick the cached entries to look up again, sized to fit `budget` queries.
+///
+/// An entry is stale once its age at `now` reaches the TTL `policy` assigns
+/// it; entries without a TTL never are. Stale entries are ranked by how far
+/// past their TTL they are, relative to the TTL, so a mobile address an hour
+/// overdue comes before a datacenter address a day overdue. Ties go to the
+/// older entry. Pass the remaining quota (less whatever live traffic needs)
+/// as `budget`.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::{Duration, SystemTime};
+/// use spur::cache::{backfill_plan, ContextCache, InfrastructureTtl, LruContextCache};
+/// use spur::{Infrastructure, IpContext};
+///
+/// let context = |infrastructure| IpContext {
+///     infrastructure: Some(infrastructure),
+///     ..Default::default()
+/// };
+/// let cache = LruContextCache::new(100);
+/// cache.insert("89.39.106.191".parse().unwrap(), context(Infrastructure::Datacenter));
+/// cache.insert("172.58.0.1".parse().unwrap(), context(Infrastructure::Mobile));
+///
+/// // Two hours later, only the mobile entry (1 hour TTL) is stale
+/// let later = SystemTime::now() + Duration::from_secs(7200);
+/// let plan = backfill_plan(cache.entries(), &InfrastructureTtl::default(), 100, later);
+/// assert_eq!(plan.lookups, ["172.58.0.1".parse::<std::net::IpAddr>().unwrap()]);
+/// ```
+pub fn backfill_plan<I, P>(entries: I, policy: &P, budget: u64, now: SystemTime) -> BackfillPlan
+where
+    I: IntoIterator<Item = (IpAddr, CacheEntry)>,
+    P: TtlPolicy + ?Sized,
+{
+    let mut stale: Vec<(f64, SystemTime, IpAddr)> = entries
+        .into_iter()
+        .filter_map(|(ip, entry)| {
+            let ttl = policy.ttl(&entry.context)?;
+            let expiring = entry.expiring(Some(ttl));
+            if !expiring.is_expired(now) {
+                return None;
+            }
+            let age = expiring.age(now);
+            let overdue = if ttl.is_zero() {
+                f64::INFINITY
+            } else {
+                (age - ttl).as_secs_f64() / ttl.as_secs_f64()
+            };
+            Some((overdue, entry.inserted_at, ip))
+        })
+        .collect();
+    stale.sort_by(|a, b| {
+        b.0.partial_cmp(&a.0)
+            .unwrap_or(Ordering::Equal)
+            .then(a.1.cmp(&b.1))
+    });
+
+    let budget = usize::try_from(budget).unwrap_or(usize::MAX);
+    let deferred = stale.len().saturating_sub(budget);
+    stale.truncate(budget);
+    BackfillPlan {
+        lookups: stale.into_iter().map(|(_, _, ip)| ip).collect(),
+        deferred,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::cache::InfrastructureTtl;
+    use crate::context::{Infrastructure, IpContext};
+
+    const HOUR: u64 = 3600;
+
+    fn entry(ip: &str, infrastructure: Infrastructure, age_hours: u64) -> (IpAddr, CacheEntry) {
+        let context = IpContext {
+            infrastructure: Some(infrastructure),
+            ..Default::default()
+        };
+        let entry = CacheEntry {
+            context,
+            inserted_at: now() - Duration::from_secs(age_hours * HOUR),
+        };
+        (ip.parse().unwrap(), entry)
+    }
+
+    fn now() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1000 * HOUR)
+    }
+
+    #[test]
+    fn test_ranks_by_relative_overdue() {
+        let entries = vec![
+            entry("10.0.0.1", Infrastructure::Datacenter, 24 * 8),
+            entry("10.0.0.2", Infrastructure::Mobile, 3),
+            entry("10.0.0.3", Infrastructure::Residential, 30),
+            entry("10.0.0.4", Infrastructure::Residential, 2),
+            entry("10.0.0.5", Infrastructure::Mobile, 4),
+        ];
+        let plan = backfill_plan(entries, &InfrastructureTtl::default(), 100, now());
+        let ips: Vec<String> = plan.lookups.iter().map(IpAddr::to_string).collect();
+        assert_eq!(ips, ["10.0.0.5", "10.0.0.2", "10.0.0.3", "10.0.0.1"]);
+        assert_eq!((plan.deferred, plan.stale()), (0, 4));
+    }
+
+    #[test]
+    fn test_budget_and_no_ttl() {
+        let entries = vec![
+            entry("10.0.0.1", Infrastructure::Mobile, 2),
+            entry("10.0.0.2", Infrastructure::Mobile, 5),
+            entry("10.0.0.3", Infrastructure::Mobile, 9),
+        ];
+        let plan = backfill_plan(entries.clone(), &InfrastructureTtl::default(), 1, now());
+        assert_eq!(plan.lookups, ["10.0.0.3".parse::<IpAddr>().unwrap()]);
+        assert_eq!(plan.deferred, 2);
+
+        let never = |_: &IpContext| None;
+        assert_eq!(
+            backfill_plan(entries, &never, 10, now()),
+            BackfillPlan::default()
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Context cache adapter over the `moka` concurrent cache.
+
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use moka::notification::RemovalCause;
+use moka::sync::Cache;
+use moka::Expiry;
+
+use super::stats::StatsCounters;
+use super::ttl::TtlPolicy;
+use super::{CacheEntry, CacheStats, ContextCache};
+use crate::context::IpContext;
+use crate::ip_utils::canonical_ip;
+
+/// A [`ContextCache`] backed by [`moka`], a high-performance concurrent cache.
+///
+/// Use this instead of [`LruContextCache`](super::LruContextCache) for
+/// heavily concurrent services: moka avoids a global lock, uses a
+/// TinyLFU admission policy, and expires entries individually according to
+/// the configured [`TtlPolicy`].
+///
+/// [`ContextCache`] is a synchronous trait, so the adapter wraps
+/// `moka::sync::Cache`; its operations never block on I/O and are safe to
+/// call from async handlers. Because moka applies size bounds and
+/// expirations lazily, `len` and `stats` first flush pending maintenance.
+///
+/// Requires the `moka` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::cache::{ContextCache, InfrastructureTtl, MokaContextCache};
+/// use spur::IpContext;
+///
+/// let cache = MokaContextCache::new(100_000).with_ttl_policy(InfrastructureTtl::default());
+///
+/// let ip = "89.39.106.191".parse().unwrap();
+/// cache.insert(ip, IpContext::default());
+/// assert!(cache.get(&ip).is_some());
+/// ```
+pub struct MokaContextCache {
+    capacity: u64,
+    ttl: Option<Arc<dyn TtlPolicy>>,
+    cache: Cache<IpAddr, CacheEntry>,
+    counters: Arc<StatsCounters>,
+}
+
+impl fmt::Debug for MokaContextCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MokaContextCache")
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Adapts a [`TtlPolicy`] to moka's per-entry expiry hooks.
+struct PolicyExpiry(Arc<dyn TtlPolicy>);
+
+impl Expiry<IpAddr, CacheEntry> for PolicyExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &IpAddr,
+        value: &CacheEntry,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        self.remaining(value)
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &IpAddr,
+        value: &CacheEntry,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        self.remaining(value)
+    }
+}
+
+impl PolicyExpiry {
+    /// The entry's TTL minus its age; entries restored from a snapshot have
+    /// already used up part of their lifetime.
+    fn remaining(&self, entry: &CacheEntry) -> Option<Duration> {
+        entry
+            .expiring(self.0.ttl(&entry.context))
+            .remaining(SystemTime::now())
+    }
+}
+
+impl MokaContextCache {
+    /// Create a cache holding at most `capacity` entries, with no TTL.
+    pub fn new(capacity: u64) -> Self {
+        Self::build(capacity, None, Arc::default())
+    }
+
+    /// Expire every entry once it is older than `ttl`.
+    ///
+    /// See [`with_ttl_policy`](Self::with_ttl_policy) for how existing
+    /// entries are handled.
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        self.with_ttl_policy(move |_: &IpContext| Some(ttl))
+    }
+
+    /// Assign each entry a TTL based on its content.
+    ///
+    /// moka fixes its expiry hooks when a cache is built, so this builds a
+    /// new underlying cache. Entries already inserted are moved into it and
+    /// expire under the new policy, and the statistics carry over. Cheapest
+    /// when called before inserting.
+    pub fn with_ttl_policy(self, policy: impl TtlPolicy + 'static) -> Self {
+        let rebuilt = Self::build(
+            self.capacity,
+            Some(Arc::new(policy)),
+            Arc::clone(&self.counters),
+        );
+        for (ip, entry) in self.cache.iter() {
+            rebuilt.cache.insert(*ip, entry);
+        }
+        rebuilt
+    }
+
+    /// Returns the maximum number of entries.
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Returns the TTL the configured policy assigns to `context`.
+    pub fn ttl_for(&self, context: &IpContext) -> Option<Duration> {
+        self.ttl.as_ref().and_then(|policy| policy.ttl(context))
+    }
+
+    /// Returns the cached entry for `ip` including its insertion time.
+    pub fn entry(&self, ip: &IpAddr) -> Option<CacheEntry> {
+        let entry = self.cache.get(&canonical_ip(*ip));
+        match entry {
+            Some(_) => self.counters.hit(),
+            None => self.counters.miss(),
+        }
+        entry
+    }
+
+    /// Returns the underlying moka cache.
+    pub fn inner(&self) -> &Cache<IpAddr, CacheEntry> {
+        &self.cache
+    }
+
+    fn build(capacity: u64, ttl: Option<Arc<dyn TtlPolicy>>, counters: Arc<StatsCounters>) -> Self {
+        let listener_counters = Arc::clone(&counters);
+        let mut builder = Cache::builder().max_capacity(capacity).eviction_listener(
+            move |_key, _value, cause| match cause {
+                RemovalCause::Size => listener_counters.evicted(1),
+                RemovalCause::Expired => listener_counters.expired(1),
+                RemovalCause::Explicit | RemovalCause::Replaced => {}
+            },
+        );
+        if let Some(policy) = &ttl {
+            builder = builder.expire_after(PolicyExpiry(Arc::clone(policy)));
+        }
+        Self {
+            capacity,
+            ttl,
+            cache: builder.build(),
+            counters,
+        }
+    }
+}
+
+impl ContextCache for MokaContextCache {
+    fn get(&self, ip: &IpAddr) -> Option<IpContext> {
+        self.entry(ip).map(|entry| entry.context)
+    }
+
+    fn insert_entry(&self, ip: IpAddr, entry: CacheEntry) {
+        self.cache.insert(canonical_ip(ip), entry);
+    }
+
+    fn remove(&self, ip: &IpAddr) -> Option<IpContext> {
+        self.cache
+            .remove(&canonical_ip(*ip))
+            .map(|entry| entry.context)
+    }
+
+    fn len(&self) -> usize {
+        self.cache.run_pending_tasks();
+        self.cache.entry_count() as usize
+    }
+
+    fn clear(&self) {
+        self.cache.invalidate_all();
+        self.cache.run_pending_tasks();
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.cache.run_pending_tasks();
+        let oldest = self.cache.iter().map(|(_, e)| e.inserted_at).min();
+        self.counters
+            .snapshot(self.cache.entry_count() as usize, oldest)
+    }
+
+    fn keys(&self) -> Box<dyn Iterator<Item = IpAddr> + '_> {
+        Box::new(self.cache.iter().map(|(ip, _)| *ip))
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (IpAddr, CacheEntry)> + '_> {
+        Box::new(self.cache.iter().map(|(ip, entry)| (*ip, entry)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Infrastructure;
+    use std::time::SystemTime;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn context(ip: &str) -> IpContext {
+        IpContext {
+            ip: Some(ip.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let cache = MokaContextCache::new(100);
+        cache.insert(ip("1.2.3.4"), context("1.2.3.4"));
+
+        assert_eq!(
+            cache.get(&ip("1.2.3.4")).unwrap().ip.as_deref(),
+            Some("1.2.3.4")
+        );
+        assert!(cache.get(&ip("5.6.7.8")).is_none());
+        assert_eq!(cache.len(), 1);
+
+        assert!(cache.remove(&ip("1.2.3.4")).is_some());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_ttl_policy() {
+        let cache = MokaContextCache::new(100).with_ttl_policy(|ctx: &IpContext| {
+            match ctx.infrastructure {
+                Some(Infrastructure::Mobile) => Some(Duration::ZERO),
+                _ => None,
+            }
+        });
+        let mobile = IpContext {
+            infrastructure: Some(Infrastructure::Mobile),
+            ..context("1.1.1.1")
+        };
+        cache.insert(ip("1.1.1.1"), mobile);
+        cache.insert(ip("2.2.2.2"), context("2.2.2.2"));
+
+        assert!(cache.get(&ip("1.1.1.1")).is_none());
+        assert!(cache.get(&ip("2.2.2.2")).is_some());
+    }
+
+    #[test]
+    fn test_ttl_change_keeps_entries_and_stats() {
+        let cache = MokaContextCache::new(100);
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+        cache.insert(ip("2.2.2.2"), context("2.2.2.2"));
+        cache.get(&ip("1.1.1.1"));
+
+        let cache = cache.with_ttl_policy(|ctx: &IpContext| {
+            (ctx.ip.as_deref() == Some("2.2.2.2")).then_some(Duration::ZERO)
+        });
+        assert_eq!(cache.stats().hits, 1);
+        assert!(cache.get(&ip("1.1.1.1")).is_some());
+        assert!(cache.get(&ip("2.2.2.2")).is_none());
+
+        let cache = cache.with_ttl(Duration::from_secs(60));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.stats().hits, 2);
+    }
+
+    #[test]
+    fn test_stats_and_keys() {
+        let cache = MokaContextCache::new(100);
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+        cache.insert(ip("2.2.2.2"), context("2.2.2.2"));
+        cache.get(&ip("1.1.1.1"));
+        cache.get(&ip("3.3.3.3"));
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.size, 2);
+        assert!(stats.oldest_entry.is_some());
+
+        let mut keys: Vec<IpAddr> = cache.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec![ip("1.1.1.1"), ip("2.2.2.2")]);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_imported_entries_keep_their_age() {
+        let source = MokaContextCache::new(100);
+        source.insert_entry(
+            ip("1.1.1.1"),
+            CacheEntry {
+                context: context("1.1.1.1"),
+                inserted_at: SystemTime::now() - Duration::from_secs(3600),
+            },
+        );
+        source.insert(ip("2.2.2.2"), context("2.2.2.2"));
+
+        let mut snapshot = Vec::new();
+        assert_eq!(source.export_snapshot(&mut snapshot).unwrap(), 2);
+
+        let restored = MokaContextCache::new(100).with_ttl(Duration::from_secs(60));
+        let summary = restored.import_snapshot(snapshot.as_slice()).unwrap();
+        assert_eq!(summary.loaded, 2);
+        assert!(restored.get(&ip("1.1.1.1")).is_none());
+        assert!(restored.get(&ip("2.2.2.2")).is_some());
+    }
+}
@@ -0,0 +1,225 @@
+//! AES-256-GCM encryption of stores and snapshots at rest (requires the
+//! `crypto` feature).
+//!
+//! A sealed file is laid out as:
+//!
+//! ```text
+//! header      magic "SPURENC\0" | version: u32
+//! nonce       [u8; 12], random per file
+//! ciphertext  the store or snapshot bytes, followed by the 16-byte tag
+//! ```
+//!
+//! The header is authenticated along with the ciphertext.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+
+use super::store::ContextStore;
+use super::{snapshot, ContextCache, LoadSummary};
+
+const MAGIC: &[u8; 8] = b"SPURENC\0";
+const HEADER_LEN: usize = 12;
+const NONCE_LEN: usize = 12;
+
+/// Format version written by [`StoreCipher::seal`].
+pub const ENCRYPTED_VERSION: u32 = 1;
+
+/// Encrypts [`ContextStore`] files and cache snapshots with AES-256-GCM.
+///
+/// Create one with [`ContextStore::encrypted`] (or [`StoreCipher::new`]),
+/// and pass it to `RedbContextCache::with_cipher` to encrypt the records of
+/// the persistent cache as well. Every sealed file gets a fresh random
+/// nonce, and opening fails with an [`io::ErrorKind::InvalidData`] error if
+/// the key is wrong or the data was modified. Key management is up to the
+/// application; the key is never written anywhere.
+///
+/// # Example
+///
+/// ```rust
+/// use std::net::IpAddr;
+/// use spur::cache::{ContextCache, ContextStore, ContextStoreWriter, LruContextCache};
+/// use spur::test_utils::fixtures;
+///
+/// let key = [7u8; 32]; // from a secrets manager in practice
+/// let cipher = ContextStore::encrypted(&key);
+///
+/// let ip: IpAddr = "89.39.106.191".parse().unwrap();
+/// let cache = LruContextCache::new(100);
+/// cache.insert(ip, fixtures::datacenter_ip());
+///
+/// let mut writer = ContextStoreWriter::new(Vec::new()).unwrap();
+/// for (ip, entry) in cache.entries() {
+///     writer.append(ip, &entry).unwrap();
+/// }
+/// let sealed = cipher.seal(&writer.finish().unwrap()).unwrap();
+///
+/// let store = cipher.open_store(&sealed).unwrap();
+/// assert!(store.get(&ip).unwrap().is_some());
+/// assert!(ContextStore::encrypted(&[0u8; 32]).open_store(&sealed).is_err());
+/// ```
+#[derive(Clone)]
+pub struct StoreCipher {
+    cipher: Aes256Gcm,
+}
+
+impl ContextStore<Vec<u8>> {
+    /// Returns a cipher for encrypting stores and snapshots under the
+    /// 256-bit `key`.
+    pub fn encrypted(key: &[u8; 32]) -> StoreCipher {
+        StoreCipher::new(key)
+    }
+}
+
+impl StoreCipher {
+    /// Create a cipher using the 256-bit `key`.
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new(key.into()),
+        }
+    }
+
+    /// Encrypt `plaintext`, such as the bytes of a store.
+    pub fn seal(&self, plaintext: &[u8]) -> io::Result<Vec<u8>> {
+        let mut header = [0u8; HEADER_LEN];
+        header[..8].copy_from_slice(MAGIC);
+        header[8..].copy_from_slice(&ENCRYPTED_VERSION.to_le_bytes());
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: plaintext,
+            aad: &header,
+        };
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, payload)
+            .map_err(|_| invalid_data("data too large to encrypt"))?;
+
+        let mut sealed = Vec::with_capacity(HEADER_LEN + NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(&header);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Decrypt bytes written by [`seal`](Self::seal).
+    pub fn open(&self, sealed: &[u8]) -> io::Result<Vec<u8>> {
+        if sealed.len() < HEADER_LEN + NONCE_LEN || &sealed[..8] != MAGIC {
+            return Err(invalid_data("not an encrypted context file"));
+        }
+        let version = u32::from_le_bytes(sealed[8..HEADER_LEN].try_into().expect("4 bytes"));
+        if version > ENCRYPTED_VERSION {
+            return Err(invalid_data("unsupported encrypted file version"));
+        }
+        let (header, rest) = sealed.split_at(HEADER_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: header,
+        };
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map_err(|_| invalid_data("decryption failed: wrong key or corrupt data"))
+    }
+
+    /// Decrypt and open a store sealed with [`seal`](Self::seal).
+    pub fn open_store(&self, sealed: &[u8]) -> io::Result<ContextStore<Vec<u8>>> {
+        ContextStore::open(self.open(sealed)?)
+    }
+
+    /// Write an encrypted snapshot of `cache`, like
+    /// [`ContextCache::export_snapshot`].
+    ///
+    /// The snapshot is built in memory before it is encrypted.
+    pub fn export_snapshot<C, W>(&self, cache: &C, mut writer: W) -> io::Result<usize>
+    where
+        C: ContextCache + ?Sized,
+        W: Write,
+    {
+        let mut plaintext = Vec::new();
+        let written = snapshot::export(cache, &mut plaintext)?;
+        writer.write_all(&self.seal(&plaintext)?)?;
+        writer.flush()?;
+        Ok(written)
+    }
+
+    /// Restore entries from a snapshot written by
+    /// [`export_snapshot`](Self::export_snapshot).
+    pub fn import_snapshot<C, R>(&self, cache: &C, mut reader: R) -> io::Result<LoadSummary>
+    where
+        C: ContextCache + ?Sized,
+        R: Read,
+    {
+        let mut sealed = Vec::new();
+        reader.read_to_end(&mut sealed)?;
+        snapshot::import(cache, self.open(&sealed)?.as_slice())
+    }
+}
+
+impl fmt::Debug for StoreCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoreCipher").finish_non_exhaustive()
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::*;
+    use crate::cache::LruContextCache;
+    use crate::test_utils::fixtures;
+
+    const KEY: [u8; 32] = [42; 32];
+
+    #[test]
+    fn test_seal_and_open() {
+        let cipher = ContextStore::encrypted(&KEY);
+        let sealed = cipher.seal(b"hello").unwrap();
+        assert_eq!(&sealed[..8], MAGIC);
+        assert_eq!(sealed.len(), HEADER_LEN + NONCE_LEN + 5 + 16);
+        assert_eq!(cipher.open(&sealed).unwrap(), b"hello");
+
+        // Fresh nonce every time
+        assert_ne!(cipher.seal(b"hello").unwrap(), sealed);
+    }
+
+    #[test]
+    fn test_rejects_tampering() {
+        let cipher = StoreCipher::new(&KEY);
+        let sealed = cipher.seal(b"hello").unwrap();
+
+        for i in [0, 8, HEADER_LEN, sealed.len() - 1] {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 1;
+            let err = cipher.open(&tampered).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+        assert!(StoreCipher::new(&[0; 32]).open(&sealed).is_err());
+        assert!(cipher.open(b"SPURENC\0").is_err());
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let ip: IpAddr = "198.51.100.7".parse().unwrap();
+        let source = LruContextCache::new(10);
+        source.insert(ip, fixtures::vpn_ip());
+
+        let cipher = StoreCipher::new(&KEY);
+        let mut sealed = Vec::new();
+        assert_eq!(cipher.export_snapshot(&source, &mut sealed).unwrap(), 1);
+        assert!(!sealed.windows(7).any(|w| w == b"NordVPN"));
+
+        let restored = LruContextCache::new(10);
+        let summary = cipher
+            .import_snapshot(&restored, sealed.as_slice())
+            .unwrap();
+        assert_eq!(summary.loaded, 1);
+        assert_eq!(restored.get(&ip), source.get(&ip));
+    }
+}
@@ -0,0 +1,458 @@
+//! In-memory LRU cache for IP contexts.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use super::stats::StatsCounters;
+use super::ttl::{is_expired, TtlPolicy};
+use super::{CacheEntry, CacheStats, ContextCache};
+use crate::context::IpContext;
+use crate::ip_utils::canonical_ip;
+
+/// An in-memory, least-recently-used cache of [`IpContext`] values.
+///
+/// The cache holds at most `capacity` entries; inserting into a full cache
+/// evicts the entry that was read or written longest ago. When a TTL or
+/// [`TtlPolicy`] is set, entries older than their TTL are treated as absent
+/// and dropped on access.
+///
+/// The cache is internally synchronized and can be shared across threads.
+///
+/// # Example
+///
+/// ```rust
+/// use std::net::IpAddr;
+/// use std::time::Duration;
+/// use spur::cache::{ContextCache, LruContextCache};
+/// use spur::IpContext;
+///
+/// let cache = LruContextCache::new(2).with_ttl(Duration::from_secs(600));
+///
+/// let a: IpAddr = "1.1.1.1".parse().unwrap();
+/// let b: IpAddr = "2.2.2.2".parse().unwrap();
+/// let c: IpAddr = "3.3.3.3".parse().unwrap();
+///
+/// cache.insert(a, IpContext::default());
+/// cache.insert(b, IpContext::default());
+/// cache.get(&a); // `a` is now the most recently used
+/// cache.insert(c, IpContext::default()); // evicts `b`
+///
+/// assert!(cache.get(&a).is_some());
+/// assert!(cache.get(&b).is_none());
+/// ```
+pub struct LruContextCache {
+    capacity: usize,
+    ttl: Option<Arc<dyn TtlPolicy>>,
+    inner: Mutex<Inner>,
+    counters: StatsCounters,
+}
+
+impl fmt::Debug for LruContextCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LruContextCache")
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: HashMap<IpAddr, Slot>,
+    /// Recency order: lowest tick is least recently used.
+    order: BTreeMap<u64, IpAddr>,
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct Slot {
+    entry: CacheEntry,
+    tick: u64,
+}
+
+impl Inner {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn touch(&mut self, ip: &IpAddr) {
+        let tick = self.next_tick();
+        if let Some(slot) = self.entries.get_mut(ip) {
+            self.order.remove(&slot.tick);
+            slot.tick = tick;
+            self.order.insert(tick, *ip);
+        }
+    }
+
+    fn remove(&mut self, ip: &IpAddr) -> Option<CacheEntry> {
+        let slot = self.entries.remove(ip)?;
+        self.order.remove(&slot.tick);
+        Some(slot.entry)
+    }
+
+    fn evict_lru(&mut self) -> Option<IpAddr> {
+        let (_, ip) = self.order.pop_first()?;
+        self.entries.remove(&ip);
+        Some(ip)
+    }
+}
+
+impl LruContextCache {
+    /// Create a cache holding at most `capacity` entries, with no TTL.
+    ///
+    /// A capacity of zero produces a cache that stores nothing.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ttl: None,
+            inner: Mutex::new(Inner::default()),
+            counters: StatsCounters::default(),
+        }
+    }
+
+    /// Expire every entry once it is older than `ttl`.
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        self.with_ttl_policy(move |_: &IpContext| Some(ttl))
+    }
+
+    /// Assign each entry a TTL based on its content.
+    pub fn with_ttl_policy(mut self, policy: impl TtlPolicy + 'static) -> Self {
+        self.ttl = Some(Arc::new(policy));
+        self
+    }
+
+    /// Returns the maximum number of entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the TTL the configured policy assigns to `context`.
+    pub fn ttl_for(&self, context: &IpContext) -> Option<Duration> {
+        self.ttl.as_ref().and_then(|policy| policy.ttl(context))
+    }
+
+    /// Returns the cached entry for `ip` including its insertion time.
+    ///
+    /// Like [`ContextCache::get`], this marks the entry as recently used and
+    /// drops it if it has expired.
+    pub fn entry(&self, ip: &IpAddr) -> Option<CacheEntry> {
+        let ip = &canonical_ip(*ip);
+        let mut inner = self.lock();
+        let Some(slot) = inner.entries.get(ip) else {
+            self.counters.miss();
+            return None;
+        };
+        if self.is_expired(&slot.entry) {
+            inner.remove(ip);
+            self.counters.expired(1);
+            self.counters.miss();
+            return None;
+        }
+        self.counters.hit();
+        inner.touch(ip);
+        inner.entries.get(ip).map(|slot| slot.entry.clone())
+    }
+
+    /// Drop every expired entry, returning how many were removed.
+    pub fn purge_expired(&self) -> usize {
+        if self.ttl.is_none() {
+            return 0;
+        }
+        let mut inner = self.lock();
+        let expired: Vec<IpAddr> = inner
+            .entries
+            .iter()
+            .filter(|(_, slot)| self.is_expired(&slot.entry))
+            .map(|(ip, _)| *ip)
+            .collect();
+        for ip in &expired {
+            inner.remove(ip);
+        }
+        self.counters.expired(expired.len() as u64);
+        expired.len()
+    }
+
+    fn is_expired(&self, entry: &CacheEntry) -> bool {
+        is_expired(self.ttl.as_deref(), entry)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        // A panic while holding the lock cannot leave the maps inconsistent
+        // in a way that matters for a cache, so recover from poisoning.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl ContextCache for LruContextCache {
+    fn get(&self, ip: &IpAddr) -> Option<IpContext> {
+        self.entry(ip).map(|entry| entry.context)
+    }
+
+    fn insert_entry(&self, ip: IpAddr, entry: CacheEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        let ip = canonical_ip(ip);
+        let mut inner = self.lock();
+        inner.remove(&ip);
+        while inner.entries.len() >= self.capacity {
+            if inner.evict_lru().is_none() {
+                break;
+            }
+            self.counters.evicted(1);
+        }
+        let tick = inner.next_tick();
+        inner.order.insert(tick, ip);
+        inner.entries.insert(ip, Slot { entry, tick });
+    }
+
+    fn remove(&self, ip: &IpAddr) -> Option<IpContext> {
+        self.lock()
+            .remove(&canonical_ip(*ip))
+            .map(|entry| entry.context)
+    }
+
+    fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    fn clear(&self) {
+        let mut inner = self.lock();
+        inner.entries.clear();
+        inner.order.clear();
+    }
+
+    fn stats(&self) -> CacheStats {
+        let inner = self.lock();
+        let oldest = inner
+            .entries
+            .values()
+            .map(|slot| slot.entry.inserted_at)
+            .min();
+        self.counters.snapshot(inner.entries.len(), oldest)
+    }
+
+    fn keys(&self) -> Box<dyn Iterator<Item = IpAddr> + '_> {
+        // Least recently used first.
+        let keys: Vec<IpAddr> = self.lock().order.values().copied().collect();
+        Box::new(keys.into_iter())
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (IpAddr, CacheEntry)> + '_> {
+        let inner = self.lock();
+        let entries: Vec<(IpAddr, CacheEntry)> = inner
+            .order
+            .values()
+            .filter_map(|ip| Some((*ip, inner.entries.get(ip)?.entry.clone())))
+            .collect();
+        Box::new(entries.into_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn context(ip: &str) -> IpContext {
+        IpContext {
+            ip: Some(ip.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ipv4_mapped_shares_entry() {
+        let cache = LruContextCache::new(10);
+        cache.insert(ip("::ffff:192.0.2.1"), context("192.0.2.1"));
+        assert!(cache.get(&ip("192.0.2.1")).is_some());
+        assert_eq!(cache.keys().collect::<Vec<_>>(), [ip("192.0.2.1")]);
+        assert!(cache.remove(&ip("::ffff:192.0.2.1")).is_some());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_insert_and_get() {
+        let cache = LruContextCache::new(10);
+        cache.insert(ip("1.2.3.4"), context("1.2.3.4"));
+
+        let cached = cache.get(&ip("1.2.3.4")).unwrap();
+        assert_eq!(cached.ip.as_deref(), Some("1.2.3.4"));
+        assert!(cache.get(&ip("5.6.7.8")).is_none());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = LruContextCache::new(2);
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+        cache.insert(ip("2.2.2.2"), context("2.2.2.2"));
+        cache.get(&ip("1.1.1.1"));
+        cache.insert(ip("3.3.3.3"), context("3.3.3.3"));
+
+        assert!(cache.get(&ip("1.1.1.1")).is_some());
+        assert!(cache.get(&ip("2.2.2.2")).is_none());
+        assert!(cache.get(&ip("3.3.3.3")).is_some());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_replace_does_not_evict() {
+        let cache = LruContextCache::new(2);
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+        cache.insert(ip("2.2.2.2"), context("2.2.2.2"));
+        cache.insert(ip("1.1.1.1"), context("9.9.9.9"));
+
+        assert_eq!(cache.len(), 2);
+        let cached = cache.get(&ip("1.1.1.1")).unwrap();
+        assert_eq!(cached.ip.as_deref(), Some("9.9.9.9"));
+    }
+
+    #[test]
+    fn test_ttl_expiry() {
+        let cache = LruContextCache::new(10).with_ttl(Duration::ZERO);
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(&ip("1.1.1.1")).is_none());
+        assert!(cache.is_empty());
+
+        cache.insert(ip("2.2.2.2"), context("2.2.2.2"));
+        assert_eq!(cache.purge_expired(), 1);
+    }
+
+    #[test]
+    fn test_entry_has_insertion_time() {
+        let cache = LruContextCache::new(10).with_ttl(Duration::from_secs(60));
+        let before = SystemTime::now();
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+
+        let entry = cache.entry(&ip("1.1.1.1")).unwrap();
+        assert!(entry.inserted_at >= before);
+        assert_eq!(cache.purge_expired(), 0);
+    }
+
+    #[test]
+    fn test_ttl_policy_per_entry() {
+        use crate::context::Infrastructure;
+
+        let cache =
+            LruContextCache::new(10).with_ttl_policy(|ctx: &IpContext| match ctx.infrastructure {
+                Some(Infrastructure::Mobile) => Some(Duration::ZERO),
+                _ => None,
+            });
+        let mobile = IpContext {
+            infrastructure: Some(Infrastructure::Mobile),
+            ..context("1.1.1.1")
+        };
+        cache.insert(ip("1.1.1.1"), mobile);
+        cache.insert(ip("2.2.2.2"), context("2.2.2.2"));
+
+        assert!(cache.get(&ip("1.1.1.1")).is_none());
+        assert!(cache.get(&ip("2.2.2.2")).is_some());
+        assert_eq!(cache.ttl_for(&context("2.2.2.2")), None);
+    }
+
+    #[test]
+    fn test_preload_from_feed() {
+        let feed = "{\"ip\": \"1.1.1.1\"}\n\n{\"infrastructure\": \"MOBILE\"}\n{\"ip\": \"::1\"}\n";
+        let cache = LruContextCache::new(10);
+        let summary = cache.preload_from_feed(feed.as_bytes()).unwrap();
+
+        assert_eq!(summary.loaded, 2);
+        assert_eq!(summary.skipped, 1);
+        assert!(cache.get(&ip("1.1.1.1")).is_some());
+        assert!(cache.get(&ip("::1")).is_some());
+    }
+
+    #[test]
+    fn test_stats_and_keys() {
+        let cache = LruContextCache::new(2);
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+        cache.insert(ip("2.2.2.2"), context("2.2.2.2"));
+        cache.get(&ip("1.1.1.1"));
+        cache.get(&ip("9.9.9.9"));
+        cache.insert(ip("3.3.3.3"), context("3.3.3.3"));
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.size, 2);
+        assert!(stats.oldest_entry.is_some());
+
+        let keys: Vec<IpAddr> = cache.keys().collect();
+        assert_eq!(keys, vec![ip("1.1.1.1"), ip("3.3.3.3")]);
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let source = LruContextCache::new(10);
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        source.insert_entry(
+            ip("1.1.1.1"),
+            CacheEntry {
+                context: context("1.1.1.1"),
+                inserted_at: old,
+            },
+        );
+        source.insert(ip("2001:db8::1"), context("2001:db8::1"));
+
+        let mut snapshot = Vec::new();
+        assert_eq!(source.export_snapshot(&mut snapshot).unwrap(), 2);
+        assert_eq!(source.stats().hits, 0);
+
+        snapshot.extend_from_slice(b"not json\n");
+        let restored = LruContextCache::new(10).with_ttl(Duration::from_secs(60));
+        let summary = restored.import_snapshot(snapshot.as_slice()).unwrap();
+        assert_eq!(summary.loaded, 2);
+        assert_eq!(summary.skipped, 1);
+
+        // The restored entry keeps its age, so it is already past the TTL.
+        assert!(restored.get(&ip("1.1.1.1")).is_none());
+        assert_eq!(
+            restored.get(&ip("2001:db8::1")).unwrap().ip.as_deref(),
+            Some("2001:db8::1")
+        );
+    }
+
+    #[test]
+    fn test_expirations_counted() {
+        let cache = LruContextCache::new(10).with_ttl(Duration::ZERO);
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+        cache.insert(ip("2.2.2.2"), context("2.2.2.2"));
+        cache.get(&ip("1.1.1.1"));
+        cache.purge_expired();
+
+        let stats = cache.stats();
+        assert_eq!(stats.expirations, 2);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.size, 0);
+        assert_eq!(stats.oldest_entry, None);
+    }
+
+    #[test]
+    fn test_zero_capacity_stores_nothing() {
+        let cache = LruContextCache::new(0);
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_remove_and_clear() {
+        let cache = LruContextCache::new(10);
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+        cache.insert(ip("2.2.2.2"), context("2.2.2.2"));
+
+        assert!(cache.remove(&ip("1.1.1.1")).is_some());
+        assert!(cache.remove(&ip("1.1.1.1")).is_none());
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,275 @@
+//! # Context Caching
+//!
+//! Spur lookups spend API quota, and most traffic comes from a small set of
+//! repeat addresses. This module provides cache implementations keyed by
+//! [`IpAddr`] that can be used with any HTTP stack: look the address up in
+//! the cache first, call the Context API on a miss, and insert the result.
+//!
+//! ## Key Types
+//!
+//! | Type | Purpose |
+//! |------|---------|
+//! | [`ContextCache`] | Common interface implemented by every cache backend |
+//! | [`LruContextCache`] | In-memory cache with size and TTL bounds |
+//! | [`CacheEntry`] | A cached context together with its insertion time |
+//! | [`TtlPolicy`] | Per-entry lifetimes derived from context content |
+//! | [`InfrastructureTtl`] | Built-in policy keyed on infrastructure type |
+//! | [`CacheStats`] | Hit/miss counters, evictions, size and oldest entry |
+//! | [`backfill_plan`] | Stale entries to look up again, ranked to fit a query budget |
+//! | [`ContextStore`] | Versioned binary container with an index by IP |
+//! | `StoreCipher` | Encrypted stores, snapshots and redb records (requires the `crypto` feature) |
+//! | `RedbContextCache` | Persistent on-disk cache (requires the `redb` feature) |
+//! | `MokaContextCache` | Concurrent cache backed by moka (requires the `moka` feature) |
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::net::IpAddr;
+//! use std::time::Duration;
+//! use spur::cache::{ContextCache, LruContextCache};
+//! use spur::IpContext;
+//!
+//! let cache = LruContextCache::new(10_000).with_ttl(Duration::from_secs(3600));
+//! let ip: IpAddr = "89.39.106.191".parse().unwrap();
+//!
+//! if cache.get(&ip).is_none() {
+//!     // ... call the Context API ...
+//!     let context = IpContext { ip: Some(ip.to_string()), ..Default::default() };
+//!     cache.insert(ip, context);
+//! }
+//!
+//! assert!(cache.get(&ip).is_some());
+//! ```
+
+#[cfg(feature = "json")]
+use std::io::{self, BufRead, Write};
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime};
+
+use crate::context::IpContext;
+use crate::expiry::Expiring;
+#[cfg(feature = "json")]
+use crate::ip_utils;
+
+mod backfill;
+#[cfg(feature = "moka")]
+mod concurrent;
+#[cfg(feature = "crypto")]
+mod encrypted;
+mod lru;
+#[cfg(feature = "redb")]
+mod persistent;
+#[cfg(feature = "json")]
+mod snapshot;
+mod stats;
+#[cfg(feature = "json")]
+mod store;
+mod ttl;
+
+pub use backfill::*;
+#[cfg(feature = "moka")]
+pub use concurrent::*;
+#[cfg(feature = "crypto")]
+pub use encrypted::*;
+pub use lru::*;
+#[cfg(feature = "redb")]
+pub use persistent::*;
+pub use stats::CacheStats;
+#[cfg(feature = "json")]
+pub use store::{ContextStore, ContextStoreWriter, CONTEXT_STORE_VERSION};
+pub use ttl::*;
+
+/// Outcome of [`ContextCache::preload_from_feed`] and
+/// [`ContextCache::import_snapshot`].
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoadSummary {
+    /// Records inserted into the cache.
+    pub loaded: usize,
+    /// Non-empty lines skipped because they could not be parsed.
+    pub skipped: usize,
+}
+
+/// A cached [`IpContext`] together with the time it was inserted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheEntry {
+    /// The cached context.
+    pub context: IpContext,
+
+    /// When the context was inserted into the cache.
+    pub inserted_at: SystemTime,
+}
+
+impl CacheEntry {
+    /// Returns the cached context with its insertion time and `ttl`, for
+    /// checking staleness.
+    ///
+    /// Pass the TTL a [`TtlPolicy`] assigns the entry, or `None` if it never
+    /// expires.
+    pub fn expiring(&self, ttl: Option<Duration>) -> Expiring<&IpContext> {
+        Expiring::new(&self.context, self.inserted_at, ttl)
+    }
+}
+
+/// Common interface for caches of [`IpContext`] values keyed by IP address.
+///
+/// Methods take `&self` so a cache can be shared between request handlers
+/// (for example behind an `Arc`); implementations use interior mutability.
+/// The built-in backends key entries by
+/// [`canonical_ip`](crate::ip_utils::canonical_ip), so an IPv4-mapped IPv6
+/// address finds the entry of the IPv4 address.
+pub trait ContextCache {
+    /// Returns the cached context for `ip`, if present and not expired.
+    fn get(&self, ip: &IpAddr) -> Option<IpContext>;
+
+    /// Inserts or replaces the context for `ip`, stamped with the current
+    /// time.
+    fn insert(&self, ip: IpAddr, context: IpContext) {
+        self.insert_entry(
+            ip,
+            CacheEntry {
+                context,
+                inserted_at: SystemTime::now(),
+            },
+        );
+    }
+
+    /// Inserts or replaces the entry for `ip`, keeping its insertion time.
+    ///
+    /// TTLs are measured from `entry.inserted_at`, so restoring an old entry
+    /// does not extend its lifetime.
+    fn insert_entry(&self, ip: IpAddr, entry: CacheEntry);
+
+    /// Removes and returns the context for `ip`.
+    fn remove(&self, ip: &IpAddr) -> Option<IpContext>;
+
+    /// Returns the number of entries currently stored, including any that
+    /// have expired but not yet been purged.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the cache holds no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every entry.
+    fn clear(&self);
+
+    /// Returns hit/miss counters and a summary of the current contents.
+    fn stats(&self) -> CacheStats;
+
+    /// Returns the IP addresses currently stored, including expired
+    /// entries not yet purged.
+    ///
+    /// The iterator works on a snapshot, so the cache may be modified while
+    /// it is being consumed.
+    fn keys(&self) -> Box<dyn Iterator<Item = IpAddr> + '_>;
+
+    /// Returns every stored entry, including expired entries not yet purged.
+    ///
+    /// Unlike [`get`](Self::get), this does not count hits or misses or
+    /// change recency. The iterator works on a snapshot.
+    fn entries(&self) -> Box<dyn Iterator<Item = (IpAddr, CacheEntry)> + '_>;
+
+    /// Seed the cache from a newline-delimited JSON feed of contexts.
+    ///
+    /// Each line is parsed as an [`IpContext`] and inserted under its `ip`
+    /// field, so a freshly started service doesn't spend API quota on
+    /// addresses the feed already covers. Malformed lines and records
+    /// without an IP are skipped and counted rather than aborting the load.
+    /// Compressed feeds should be wrapped in a decoder first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spur::cache::{ContextCache, LruContextCache};
+    ///
+    /// let feed = r#"{"ip": "89.39.106.191", "infrastructure": "DATACENTER"}
+    /// {"ip": "2001:db8::1", "infrastructure": "RESIDENTIAL"}
+    /// not json
+    /// "#;
+    ///
+    /// let cache = LruContextCache::new(100);
+    /// let summary = cache.preload_from_feed(feed.as_bytes()).unwrap();
+    /// assert_eq!(summary.loaded, 2);
+    /// assert_eq!(summary.skipped, 1);
+    /// ```
+    #[cfg(feature = "json")]
+    fn preload_from_feed<R>(&self, reader: R) -> io::Result<LoadSummary>
+    where
+        R: BufRead,
+        Self: Sized,
+    {
+        let mut summary = LoadSummary::default();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let parsed = serde_json::from_str::<IpContext>(&line)
+                .ok()
+                .and_then(|ctx| Some((ip_utils::parse(ctx.ip.as_deref()?)?, ctx)));
+            match parsed {
+                Some((ip, context)) => {
+                    self.insert(ip, context);
+                    summary.loaded += 1;
+                }
+                None => summary.skipped += 1,
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Write every entry to `writer` as newline-delimited JSON, returning the
+    /// number of entries written.
+    ///
+    /// Each line holds the address, the insertion time in milliseconds since
+    /// the Unix epoch and the context:
+    ///
+    /// ```json
+    /// {"ip":"89.39.106.191","inserted_at":1767225600000,"context":{"ip":"89.39.106.191"}}
+    /// ```
+    ///
+    /// Snapshots can be read back into any backend with
+    /// [`import_snapshot`](Self::import_snapshot), for example to back up a
+    /// cache before a deploy or ship a warm cache to another environment.
+    #[cfg(feature = "json")]
+    fn export_snapshot<W>(&self, writer: W) -> io::Result<usize>
+    where
+        W: Write,
+        Self: Sized,
+    {
+        snapshot::export(self, writer)
+    }
+
+    /// Restore entries written by [`export_snapshot`](Self::export_snapshot).
+    ///
+    /// Insertion times are preserved, so entries keep aging under this
+    /// cache's TTL policy from when they were first cached. Malformed lines
+    /// are skipped and counted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spur::cache::{ContextCache, LruContextCache};
+    /// use spur::IpContext;
+    ///
+    /// let source = LruContextCache::new(100);
+    /// source.insert("89.39.106.191".parse().unwrap(), IpContext::default());
+    ///
+    /// let mut snapshot = Vec::new();
+    /// source.export_snapshot(&mut snapshot).unwrap();
+    ///
+    /// let restored = LruContextCache::new(100);
+    /// let summary = restored.import_snapshot(snapshot.as_slice()).unwrap();
+    /// assert_eq!(summary.loaded, 1);
+    /// ```
+    #[cfg(feature = "json")]
+    fn import_snapshot<R>(&self, reader: R) -> io::Result<LoadSummary>
+    where
+        R: BufRead,
+        Self: Sized,
+    {
+        snapshot::import(self, reader)
+    }
+}
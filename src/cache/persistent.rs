@@ -0,0 +1,614 @@
+//! Persistent context cache backed by an embedded `redb` database.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use redb::{Database, ReadableTable, ReadableTableMetadata, TableDefinition};
+
+#[cfg(feature = "crypto")]
+use super::encrypted::StoreCipher;
+use super::stats::StatsCounters;
+use super::store::{
+    decode_ip, decode_record, encode_ip, encode_record, ContextStore, ContextStoreWriter,
+};
+use super::ttl::{is_expired, TtlPolicy};
+use super::{CacheEntry, CacheStats, ContextCache};
+use crate::context::IpContext;
+
+/// Table mapping the encoded address to its [`ContextStore`] record.
+const CONTEXTS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("context_records");
+
+/// Errors returned by the fallible [`RedbContextCache`] methods.
+#[derive(Debug)]
+pub enum PersistentCacheError {
+    /// The embedded database failed to open, read or write.
+    Storage(Box<redb::Error>),
+    /// A stored record could not be encoded or decoded.
+    Encoding(io::Error),
+}
+
+impl fmt::Display for PersistentCacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Storage(e) => write!(f, "cache storage error: {}", e),
+            Self::Encoding(e) => write!(f, "cache encoding error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PersistentCacheError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Storage(e) => Some(e.as_ref()),
+            Self::Encoding(e) => Some(e),
+        }
+    }
+}
+
+impl<E: Into<redb::Error>> From<E> for PersistentCacheError {
+    fn from(e: E) -> Self {
+        Self::Storage(Box::new(e.into()))
+    }
+}
+
+/// A [`ContextCache`] persisted to disk with [`redb`].
+///
+/// Entries survive process restarts, so single-node tools (CLIs, collectors)
+/// do not spend API quota re-enriching addresses they already looked up.
+/// Each value is a [`ContextStore`] record keyed by its encoded address, so
+/// [`export_store`](Self::export_store) copies records into a store file
+/// as they are, and [`import_store`](Self::import_store) loads one back.
+/// Entries are not evicted by size; use a TTL (or [`TtlPolicy`]) and
+/// [`purge_expired`] to bound growth.
+///
+/// With the `crypto` feature, [`with_cipher`](Self::with_cipher) encrypts
+/// every stored record. The keys stay in plaintext so that lookups work,
+/// which means the set of cached addresses is visible in the database file.
+/// Without a cipher the file is plaintext.
+///
+/// The [`ContextCache`] methods are best-effort: storage errors are treated
+/// as cache misses. Use the `try_*` methods to observe them.
+///
+/// Requires the `redb` feature.
+///
+/// [`purge_expired`]: RedbContextCache::purge_expired
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use spur::cache::{ContextCache, RedbContextCache};
+///
+/// let cache = RedbContextCache::open("spur-cache.redb")
+///     .unwrap()
+///     .with_ttl(Duration::from_secs(7 * 24 * 3600));
+///
+/// let ip = "89.39.106.191".parse().unwrap();
+/// if cache.get(&ip).is_none() {
+///     // ... call the Context API and insert the result ...
+/// }
+/// ```
+pub struct RedbContextCache {
+    db: Database,
+    ttl: Option<Arc<dyn TtlPolicy>>,
+    #[cfg(feature = "crypto")]
+    cipher: Option<StoreCipher>,
+    counters: StatsCounters,
+}
+
+impl fmt::Debug for RedbContextCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedbContextCache").finish_non_exhaustive()
+    }
+}
+
+impl RedbContextCache {
+    /// Open (or create) a cache database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, PersistentCacheError> {
+        let db = Database::create(path)?;
+        let txn = db.begin_write()?;
+        txn.open_table(CONTEXTS)?;
+        txn.commit()?;
+        Ok(Self {
+            db,
+            ttl: None,
+            #[cfg(feature = "crypto")]
+            cipher: None,
+            counters: StatsCounters::default(),
+        })
+    }
+
+    /// Expire every entry once it is older than `ttl`.
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        self.with_ttl_policy(move |_: &IpContext| Some(ttl))
+    }
+
+    /// Assign each entry a TTL based on its content.
+    ///
+    /// The policy is applied when entries are read, so changing it also
+    /// affects entries written by earlier runs.
+    pub fn with_ttl_policy(mut self, policy: impl TtlPolicy + 'static) -> Self {
+        self.ttl = Some(Arc::new(policy));
+        self
+    }
+
+    /// Encrypt stored records with `cipher` (requires the `crypto` feature).
+    ///
+    /// Records are sealed individually as they are written. Records written
+    /// without this cipher, or under a different key, fail to decode: reads
+    /// return an error and [`purge_expired`](Self::purge_expired) deletes
+    /// them. Addresses are stored in plaintext either way.
+    #[cfg(feature = "crypto")]
+    pub fn with_cipher(mut self, cipher: StoreCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Returns the TTL the configured policy assigns to `context`.
+    pub fn ttl_for(&self, context: &IpContext) -> Option<Duration> {
+        self.ttl.as_ref().and_then(|policy| policy.ttl(context))
+    }
+
+    /// Returns the stored entry for `ip` including its insertion time.
+    ///
+    /// Expired entries are reported as absent but left on disk until
+    /// [`purge_expired`](Self::purge_expired) runs.
+    pub fn entry(&self, ip: &IpAddr) -> Result<Option<CacheEntry>, PersistentCacheError> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(CONTEXTS)?;
+        let Some(bytes) = table.get(&encode_ip(*ip)[..])? else {
+            self.counters.miss();
+            return Ok(None);
+        };
+        let entry = self.decode(bytes.value())?;
+        if self.is_expired(&entry) {
+            self.counters.miss();
+            return Ok(None);
+        }
+        self.counters.hit();
+        Ok(Some(entry))
+    }
+
+    /// Fallible version of [`ContextCache::get`].
+    pub fn try_get(&self, ip: &IpAddr) -> Result<Option<IpContext>, PersistentCacheError> {
+        Ok(self.entry(ip)?.map(|entry| entry.context))
+    }
+
+    /// Fallible version of [`ContextCache::insert`].
+    pub fn try_insert(&self, ip: IpAddr, context: &IpContext) -> Result<(), PersistentCacheError> {
+        self.try_insert_entry(
+            ip,
+            &CacheEntry {
+                context: context.clone(),
+                inserted_at: SystemTime::now(),
+            },
+        )
+    }
+
+    /// Fallible version of [`ContextCache::insert_entry`].
+    pub fn try_insert_entry(
+        &self,
+        ip: IpAddr,
+        entry: &CacheEntry,
+    ) -> Result<(), PersistentCacheError> {
+        let record = self.encode(ip, entry)?;
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(CONTEXTS)?;
+            table.insert(&encode_ip(ip)[..], record.as_slice())?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Fallible version of [`ContextCache::remove`].
+    pub fn try_remove(&self, ip: &IpAddr) -> Result<Option<IpContext>, PersistentCacheError> {
+        let txn = self.db.begin_write()?;
+        let removed = {
+            let mut table = txn.open_table(CONTEXTS)?;
+            let removed = table.remove(&encode_ip(*ip)[..])?;
+            match removed {
+                Some(bytes) => Some(self.decode(bytes.value())?.context),
+                None => None,
+            }
+        };
+        txn.commit()?;
+        Ok(removed)
+    }
+
+    /// Delete every expired entry, returning how many were removed.
+    pub fn purge_expired(&self) -> Result<usize, PersistentCacheError> {
+        if self.ttl.is_none() {
+            return Ok(0);
+        }
+        let txn = self.db.begin_write()?;
+        let mut purged = 0;
+        {
+            let mut table = txn.open_table(CONTEXTS)?;
+            table.retain(|_, bytes| {
+                // Undecodable entries are dropped along with expired ones.
+                let keep = self
+                    .decode(bytes)
+                    .map(|entry| !self.is_expired(&entry))
+                    .unwrap_or(false);
+                if !keep {
+                    purged += 1;
+                }
+                keep
+            })?;
+        }
+        txn.commit()?;
+        self.counters.expired(purged as u64);
+        Ok(purged)
+    }
+
+    fn try_len(&self) -> Result<usize, PersistentCacheError> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(CONTEXTS)?;
+        Ok(table.len()? as usize)
+    }
+
+    /// Fallible version of [`ContextCache::stats`].
+    ///
+    /// Finding the oldest entry reads every stored record.
+    pub fn try_stats(&self) -> Result<CacheStats, PersistentCacheError> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(CONTEXTS)?;
+        let mut oldest = None;
+        for item in table.iter()? {
+            let (_, bytes) = item?;
+            if let Ok(entry) = self.decode(bytes.value()) {
+                oldest = Some(match oldest {
+                    Some(t) if t <= entry.inserted_at => t,
+                    _ => entry.inserted_at,
+                });
+            }
+        }
+        Ok(self.counters.snapshot(table.len()? as usize, oldest))
+    }
+
+    /// Fallible version of [`ContextCache::keys`], collected into a `Vec`.
+    pub fn try_keys(&self) -> Result<Vec<IpAddr>, PersistentCacheError> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(CONTEXTS)?;
+        let mut keys = Vec::new();
+        for item in table.iter()? {
+            let (key, _) = item?;
+            if let Ok(ip) = decode_ip(key.value()) {
+                keys.push(ip);
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Fallible version of [`ContextCache::entries`], collected into a `Vec`.
+    ///
+    /// Undecodable records are skipped.
+    pub fn try_entries(&self) -> Result<Vec<(IpAddr, CacheEntry)>, PersistentCacheError> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(CONTEXTS)?;
+        let mut entries = Vec::new();
+        for item in table.iter()? {
+            let (key, bytes) = item?;
+            let Ok(ip) = decode_ip(key.value()) else {
+                continue;
+            };
+            if let Ok(entry) = self.decode(bytes.value()) {
+                entries.push((ip, entry));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Write every entry to `writer` in the [`ContextStore`] format and
+    /// return the writer and the number of entries written.
+    ///
+    /// The store is written decrypted; seal it with a `StoreCipher` to keep
+    /// it encrypted.
+    ///
+    /// Records are copied as stored, expired ones included; run
+    /// [`purge_expired`](Self::purge_expired) first to leave them out.
+    pub fn export_store<W: Write>(&self, writer: W) -> Result<(W, usize), PersistentCacheError> {
+        let txn = self.db.begin_read()?;
+        let table = txn.open_table(CONTEXTS)?;
+        let mut store = ContextStoreWriter::new(writer).map_err(PersistentCacheError::Encoding)?;
+        let mut count = 0;
+        for item in table.iter()? {
+            let (_, record) = item?;
+            store
+                .append_record(&self.unseal(record.value())?)
+                .map_err(PersistentCacheError::Encoding)?;
+            count += 1;
+        }
+        let writer = store.finish().map_err(PersistentCacheError::Encoding)?;
+        Ok((writer, count))
+    }
+
+    /// Insert every record of `store` in one transaction, keeping the
+    /// recorded insertion times, and return how many records were read.
+    ///
+    /// Addresses that appear more than once keep their last record.
+    pub fn import_store<B: AsRef<[u8]>>(
+        &self,
+        store: &ContextStore<B>,
+    ) -> Result<usize, PersistentCacheError> {
+        let txn = self.db.begin_write()?;
+        let mut count = 0;
+        {
+            let mut table = txn.open_table(CONTEXTS)?;
+            for item in store.iter() {
+                let (ip, entry) = item.map_err(PersistentCacheError::Encoding)?;
+                table.insert(&encode_ip(ip)[..], self.encode(ip, &entry)?.as_slice())?;
+                count += 1;
+            }
+        }
+        txn.commit()?;
+        Ok(count)
+    }
+
+    fn try_clear(&self) -> Result<(), PersistentCacheError> {
+        let txn = self.db.begin_write()?;
+        txn.open_table(CONTEXTS)?.retain(|_, _| false)?;
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn encode(&self, ip: IpAddr, entry: &CacheEntry) -> Result<Vec<u8>, PersistentCacheError> {
+        let record = encode_record(ip, entry).map_err(PersistentCacheError::Encoding)?;
+        #[cfg(feature = "crypto")]
+        if let Some(cipher) = &self.cipher {
+            return cipher.seal(&record).map_err(PersistentCacheError::Encoding);
+        }
+        Ok(record)
+    }
+
+    fn decode(&self, stored: &[u8]) -> Result<CacheEntry, PersistentCacheError> {
+        decode_record(&self.unseal(stored)?)
+            .map(|(_, entry, _)| entry)
+            .map_err(PersistentCacheError::Encoding)
+    }
+
+    /// Returns the plaintext record of a stored value.
+    fn unseal<'a>(&self, stored: &'a [u8]) -> Result<Cow<'a, [u8]>, PersistentCacheError> {
+        #[cfg(feature = "crypto")]
+        if let Some(cipher) = &self.cipher {
+            return cipher
+                .open(stored)
+                .map(Cow::Owned)
+                .map_err(PersistentCacheError::Encoding);
+        }
+        Ok(Cow::Borrowed(stored))
+    }
+
+    fn is_expired(&self, entry: &CacheEntry) -> bool {
+        is_expired(self.ttl.as_deref(), entry)
+    }
+}
+
+impl ContextCache for RedbContextCache {
+    fn get(&self, ip: &IpAddr) -> Option<IpContext> {
+        self.try_get(ip).ok().flatten()
+    }
+
+    fn insert_entry(&self, ip: IpAddr, entry: CacheEntry) {
+        let _ = self.try_insert_entry(ip, &entry);
+    }
+
+    fn remove(&self, ip: &IpAddr) -> Option<IpContext> {
+        self.try_remove(ip).ok().flatten()
+    }
+
+    fn len(&self) -> usize {
+        self.try_len().unwrap_or(0)
+    }
+
+    fn clear(&self) {
+        let _ = self.try_clear();
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.try_stats()
+            .unwrap_or_else(|_| self.counters.snapshot(0, None))
+    }
+
+    fn keys(&self) -> Box<dyn Iterator<Item = IpAddr> + '_> {
+        Box::new(self.try_keys().unwrap_or_default().into_iter())
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (IpAddr, CacheEntry)> + '_> {
+        Box::new(self.try_entries().unwrap_or_default().into_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::snapshot::from_millis;
+    use std::path::PathBuf;
+
+    /// A database file in the system temp dir, removed on drop.
+    struct TempDb(PathBuf);
+
+    impl TempDb {
+        fn new(name: &str) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("spur-{}-{}.redb", name, std::process::id()));
+            let _ = std::fs::remove_file(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn context(ip: &str) -> IpContext {
+        IpContext {
+            ip: Some(ip.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let db = TempDb::new("insert");
+        let cache = RedbContextCache::open(&db.0).unwrap();
+
+        cache.insert(ip("1.2.3.4"), context("1.2.3.4"));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(
+            cache.get(&ip("1.2.3.4")).unwrap().ip.as_deref(),
+            Some("1.2.3.4")
+        );
+        assert!(cache.get(&ip("5.6.7.8")).is_none());
+
+        assert!(cache.remove(&ip("1.2.3.4")).is_some());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_survives_reopen() {
+        let db = TempDb::new("reopen");
+        {
+            let cache = RedbContextCache::open(&db.0).unwrap();
+            cache.insert(ip("2001:db8::1"), context("2001:db8::1"));
+        }
+        let cache = RedbContextCache::open(&db.0).unwrap();
+        let entry = cache.entry(&ip("2001:db8::1")).unwrap().unwrap();
+        assert_eq!(entry.context.ip.as_deref(), Some("2001:db8::1"));
+        assert!(entry.inserted_at <= SystemTime::now());
+    }
+
+    #[test]
+    fn test_ttl_and_purge() {
+        let db = TempDb::new("ttl");
+        let cache = RedbContextCache::open(&db.0)
+            .unwrap()
+            .with_ttl(Duration::ZERO);
+
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+        assert!(cache.get(&ip("1.1.1.1")).is_none());
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.purge_expired().unwrap(), 1);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_stats_and_keys() {
+        let db = TempDb::new("stats");
+        let cache = RedbContextCache::open(&db.0).unwrap();
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+        cache.insert(ip("2.2.2.2"), context("2.2.2.2"));
+        cache.get(&ip("1.1.1.1"));
+        cache.get(&ip("3.3.3.3"));
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.size, 2);
+        assert!(stats.oldest_entry.is_some());
+
+        let mut keys: Vec<IpAddr> = cache.keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec![ip("1.1.1.1"), ip("2.2.2.2")]);
+    }
+
+    #[test]
+    fn test_snapshot_preserves_insertion_time() {
+        let db = TempDb::new("snapshot");
+        let cache = RedbContextCache::open(&db.0).unwrap();
+        let inserted_at = from_millis(1_767_225_600_000);
+        cache.insert_entry(
+            ip("1.1.1.1"),
+            CacheEntry {
+                context: context("1.1.1.1"),
+                inserted_at,
+            },
+        );
+
+        let mut snapshot = Vec::new();
+        assert_eq!(cache.export_snapshot(&mut snapshot).unwrap(), 1);
+
+        let restored = crate::cache::LruContextCache::new(10);
+        restored.import_snapshot(snapshot.as_slice()).unwrap();
+        let entry = restored.entry(&ip("1.1.1.1")).unwrap();
+        assert_eq!(entry.inserted_at, inserted_at);
+    }
+
+    #[test]
+    fn test_store_export_and_import() {
+        let db = TempDb::new("store");
+        let cache = RedbContextCache::open(&db.0).unwrap();
+        let entry = |ip: &str| CacheEntry {
+            context: context(ip),
+            inserted_at: from_millis(1_767_225_600_000),
+        };
+        cache.insert_entry(ip("1.1.1.1"), entry("1.1.1.1"));
+        cache.insert_entry(ip("2001:db8::1"), entry("2001:db8::1"));
+
+        let (bytes, count) = cache.export_store(Vec::new()).unwrap();
+        assert_eq!(count, 2);
+        let store = ContextStore::open(bytes).unwrap();
+        assert_eq!(store.get(&ip("1.1.1.1")).unwrap(), Some(entry("1.1.1.1")));
+
+        let mut writer = ContextStoreWriter::new(Vec::new()).unwrap();
+        writer.append(ip("1.1.1.1"), &entry("1.1.1.1")).unwrap();
+        writer.append(ip("1.1.1.1"), &entry("9.9.9.9")).unwrap();
+        let store = ContextStore::open(writer.finish().unwrap()).unwrap();
+        assert_eq!(cache.import_store(&store).unwrap(), 2);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(
+            cache.entry(&ip("::ffff:1.1.1.1")).unwrap(),
+            Some(entry("9.9.9.9"))
+        );
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_encrypted_records() {
+        let db = TempDb::new("encrypted");
+        let cipher = || ContextStore::encrypted(&[7u8; 32]);
+        {
+            let cache = RedbContextCache::open(&db.0).unwrap().with_cipher(cipher());
+            cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+            let (bytes, _) = cache.export_store(Vec::new()).unwrap();
+            assert!(ContextStore::open(bytes)
+                .unwrap()
+                .get(&ip("1.1.1.1"))
+                .unwrap()
+                .is_some());
+        }
+        let file = std::fs::read(&db.0).unwrap();
+        assert!(!file.windows(7).any(|w| w == b"1.1.1.1"));
+
+        let wrong_key = RedbContextCache::open(&db.0)
+            .unwrap()
+            .with_cipher(ContextStore::encrypted(&[0u8; 32]));
+        assert!(wrong_key.try_get(&ip("1.1.1.1")).is_err());
+        drop(wrong_key);
+
+        let cache = RedbContextCache::open(&db.0).unwrap().with_cipher(cipher());
+        assert!(cache.get(&ip("1.1.1.1")).is_some());
+    }
+
+    #[test]
+    fn test_clear() {
+        let db = TempDb::new("clear");
+        let cache = RedbContextCache::open(&db.0).unwrap();
+        cache.insert(ip("1.1.1.1"), context("1.1.1.1"));
+        cache.insert(ip("2.2.2.2"), context("2.2.2.2"));
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+//! NDJSON snapshot format shared by every cache backend.
+
+use std::io::{self, BufRead, Write};
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::{CacheEntry, ContextCache, LoadSummary};
+use crate::context::IpContext;
+
+/// One line of a snapshot.
+#[derive(Serialize, Deserialize)]
+struct Record<C> {
+    ip: IpAddr,
+    /// Insertion time in milliseconds since the Unix epoch.
+    inserted_at: u64,
+    context: C,
+}
+
+/// Converts a timestamp to milliseconds since the Unix epoch.
+pub(crate) fn to_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Converts milliseconds since the Unix epoch to a timestamp.
+pub(crate) fn from_millis(millis: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis)
+}
+
+pub(crate) fn export<C, W>(cache: &C, mut writer: W) -> io::Result<usize>
+where
+    C: ContextCache + ?Sized,
+    W: Write,
+{
+    let mut written = 0;
+    for (ip, entry) in cache.entries() {
+        let record = Record {
+            ip,
+            inserted_at: to_millis(entry.inserted_at),
+            context: &entry.context,
+        };
+        serde_json::to_writer(&mut writer, &record)?;
+        writer.write_all(b"\n")?;
+        written += 1;
+    }
+    writer.flush()?;
+    Ok(written)
+}
+
+pub(crate) fn import<C, R>(cache: &C, reader: R) -> io::Result<LoadSummary>
+where
+    C: ContextCache + ?Sized,
+    R: BufRead,
+{
+    let mut summary = LoadSummary::default();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Record<IpContext>>(&line) {
+            Ok(record) => {
+                cache.insert_entry(
+                    record.ip,
+                    CacheEntry {
+                        context: record.context,
+                        inserted_at: from_millis(record.inserted_at),
+                    },
+                );
+                summary.loaded += 1;
+            }
+            Err(_) => summary.skipped += 1,
+        }
+    }
+    Ok(summary)
+}
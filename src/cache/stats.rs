@@ -0,0 +1,95 @@
+//! Cache statistics.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+/// A point-in-time view of a cache's effectiveness and contents.
+///
+/// Counters start at zero when the cache is created and are kept in memory
+/// only; persistent caches do not carry them across restarts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups that returned a cached context.
+    pub hits: u64,
+    /// Lookups that found nothing, or only an expired entry.
+    pub misses: u64,
+    /// Entries dropped to make room for new ones.
+    pub evictions: u64,
+    /// Entries dropped because their TTL ran out.
+    pub expirations: u64,
+    /// Entries currently stored, including expired ones not yet purged.
+    pub size: usize,
+    /// Insertion time of the oldest stored entry.
+    pub oldest_entry: Option<SystemTime>,
+}
+
+impl CacheStats {
+    /// Fraction of lookups that were hits, or `0.0` before any lookup.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Lock-free counters shared by the cache implementations.
+#[derive(Debug, Default)]
+pub(crate) struct StatsCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
+}
+
+impl StatsCounters {
+    pub(crate) fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn evicted(&self, n: u64) {
+        self.evictions.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub(crate) fn expired(&self, n: u64) {
+        self.expirations.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Combine the counters with the current size and oldest entry.
+    pub(crate) fn snapshot(&self, size: usize, oldest_entry: Option<SystemTime>) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            expirations: self.expirations.load(Ordering::Relaxed),
+            size,
+            oldest_entry,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hit_rate() {
+        assert_eq!(CacheStats::default().hit_rate(), 0.0);
+
+        let counters = StatsCounters::default();
+        counters.hit();
+        counters.hit();
+        counters.hit();
+        counters.miss();
+        let stats = counters.snapshot(3, None);
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hit_rate(), 0.75);
+    }
+}
@@ -0,0 +1,430 @@
+//! Versioned binary container for large numbers of cached contexts.
+//!
+//! The layout is designed to be read in place, for example from a
+//! memory-mapped file, without parsing anything up front:
+//!
+//! ```text
+//! header   magic "SPURCTX\0" | version: u32 | reserved: u32
+//! records  { len: u32 | ip: [u8; 17] | inserted_at: u64 | context JSON: [u8; len] }*
+//! index    { ip: [u8; 17] | record offset: u64 }*   sorted by ip
+//! footer   index offset: u64 | index entries: u64 | magic "SPURIDX\0"
+//! ```
+//!
+//! Integers are little-endian. Addresses are encoded as a family byte (4 or
+//! 6) followed by the 16-byte IPv6 form; IPv4-mapped IPv6 addresses are
+//! stored as IPv4. `inserted_at` is milliseconds since the Unix epoch.
+//! Records can be scanned sequentially in write order; the index allows
+//! lookups by address with a binary search.
+//!
+//! The framing and index are read in place, but each record's context is
+//! JSON and is parsed when the record is accessed. `RedbContextCache` (with
+//! the `redb` feature) stores its values as these records, keyed by the
+//! same 17-byte address encoding.
+
+use std::io::{self, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use super::snapshot::{from_millis, to_millis};
+use super::CacheEntry;
+use crate::ip_utils::canonical_ip;
+
+const HEADER_MAGIC: &[u8; 8] = b"SPURCTX\0";
+const FOOTER_MAGIC: &[u8; 8] = b"SPURIDX\0";
+const HEADER_LEN: usize = 16;
+const FOOTER_LEN: usize = 24;
+const IP_LEN: usize = 17;
+const RECORD_HEADER_LEN: usize = 4 + IP_LEN + 8;
+const INDEX_ENTRY_LEN: usize = IP_LEN + 8;
+
+/// Format version written by [`ContextStoreWriter`].
+pub const CONTEXT_STORE_VERSION: u32 = 1;
+
+/// Streams cache entries into the [`ContextStore`] format.
+///
+/// Records are written as they are appended; the index is kept in memory
+/// (25 bytes per record) and written by [`finish`](Self::finish). When an
+/// address is appended more than once, lookups return the last record.
+///
+/// # Example
+///
+/// ```rust
+/// use std::net::IpAddr;
+/// use spur::cache::{ContextCache, ContextStore, ContextStoreWriter, LruContextCache};
+/// use spur::IpContext;
+///
+/// let cache = LruContextCache::new(100);
+/// let ip: IpAddr = "89.39.106.191".parse().unwrap();
+/// cache.insert(ip, IpContext { ip: Some(ip.to_string()), ..Default::default() });
+///
+/// let mut writer = ContextStoreWriter::new(Vec::new()).unwrap();
+/// for (ip, entry) in cache.entries() {
+///     writer.append(ip, &entry).unwrap();
+/// }
+/// let bytes = writer.finish().unwrap();
+///
+/// let store = ContextStore::open(bytes).unwrap();
+/// assert_eq!(store.len(), 1);
+/// assert!(store.get(&ip).unwrap().is_some());
+/// ```
+#[derive(Debug)]
+pub struct ContextStoreWriter<W: Write> {
+    writer: W,
+    offset: u64,
+    index: Vec<([u8; IP_LEN], u64)>,
+}
+
+impl<W: Write> ContextStoreWriter<W> {
+    /// Start a store, writing the header to `writer`.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(HEADER_MAGIC)?;
+        writer.write_all(&CONTEXT_STORE_VERSION.to_le_bytes())?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            offset: HEADER_LEN as u64,
+            index: Vec::new(),
+        })
+    }
+
+    /// Append the entry for `ip`.
+    pub fn append(&mut self, ip: IpAddr, entry: &CacheEntry) -> io::Result<()> {
+        self.append_record(&encode_record(ip, entry)?)
+    }
+
+    /// Append a record already produced by [`encode_record`].
+    pub(super) fn append_record(&mut self, record: &[u8]) -> io::Result<()> {
+        if record.len() < RECORD_HEADER_LEN {
+            return Err(invalid_data("context store record out of bounds"));
+        }
+        let mut key = [0u8; IP_LEN];
+        key.copy_from_slice(&record[4..4 + IP_LEN]);
+        self.writer.write_all(record)?;
+        self.index.push((key, self.offset));
+        self.offset += record.len() as u64;
+        Ok(())
+    }
+
+    /// Write the index and footer and return the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        // A stable sort keeps appends in order, so the last one wins below.
+        self.index.sort_by_key(|&(key, _)| key);
+        let mut deduped: Vec<([u8; IP_LEN], u64)> = Vec::with_capacity(self.index.len());
+        for (key, offset) in self.index {
+            match deduped.last_mut() {
+                Some(last) if last.0 == key => last.1 = offset,
+                _ => deduped.push((key, offset)),
+            }
+        }
+
+        let index_offset = self.offset;
+        for (key, offset) in &deduped {
+            self.writer.write_all(key)?;
+            self.writer.write_all(&offset.to_le_bytes())?;
+        }
+        self.writer.write_all(&index_offset.to_le_bytes())?;
+        self.writer
+            .write_all(&(deduped.len() as u64).to_le_bytes())?;
+        self.writer.write_all(FOOTER_MAGIC)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Read-only view of a store written by [`ContextStoreWriter`].
+///
+/// Works over any byte buffer: a `Vec<u8>` read from disk, a `&[u8]`, or a
+/// memory map. Opening checks the header, footer and index bounds; records
+/// are decoded only when accessed.
+#[derive(Debug, Clone)]
+pub struct ContextStore<B: AsRef<[u8]>> {
+    bytes: B,
+    version: u32,
+    index_offset: usize,
+    index_len: usize,
+}
+
+impl<B: AsRef<[u8]>> ContextStore<B> {
+    /// Open a store, validating its framing.
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error for buffers that are
+    /// not stores, are truncated, or were written by a newer format version.
+    pub fn open(bytes: B) -> io::Result<Self> {
+        let data = bytes.as_ref();
+        if data.len() < HEADER_LEN + FOOTER_LEN || &data[..8] != HEADER_MAGIC {
+            return Err(invalid_data("not a context store"));
+        }
+        let version = read_u32(data, 8);
+        if version > CONTEXT_STORE_VERSION {
+            return Err(invalid_data("unsupported context store version"));
+        }
+        let footer = data.len() - FOOTER_LEN;
+        if &data[footer + 16..] != FOOTER_MAGIC {
+            return Err(invalid_data("context store is truncated"));
+        }
+        let index_offset = usize::try_from(read_u64(data, footer)).unwrap_or(usize::MAX);
+        let index_len = usize::try_from(read_u64(data, footer + 8)).unwrap_or(usize::MAX);
+        let index_end = index_len
+            .checked_mul(INDEX_ENTRY_LEN)
+            .and_then(|size| size.checked_add(index_offset));
+        if index_offset < HEADER_LEN || index_end != Some(footer) {
+            return Err(invalid_data("context store index is corrupt"));
+        }
+        Ok(Self {
+            bytes,
+            version,
+            index_offset,
+            index_len,
+        })
+    }
+
+    /// Returns the format version the store was written with.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the number of distinct addresses in the store.
+    pub fn len(&self) -> usize {
+        self.index_len
+    }
+
+    /// Returns `true` if the store holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.index_len == 0
+    }
+
+    /// Look up the entry for `ip`.
+    pub fn get(&self, ip: &IpAddr) -> io::Result<Option<CacheEntry>> {
+        let key = encode_ip(*ip);
+        let (mut low, mut high) = (0, self.index_len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (mid_key, offset) = self.index_entry(mid);
+            match mid_key.cmp(&key[..]) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => {
+                    return self.record(offset).map(|(_, entry, _)| Some(entry))
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Iterate over every record in write order, including addresses that
+    /// were appended more than once.
+    pub fn iter(&self) -> impl Iterator<Item = io::Result<(IpAddr, CacheEntry)>> + '_ {
+        let mut offset = HEADER_LEN;
+        let mut failed = false;
+        std::iter::from_fn(move || {
+            if failed || offset >= self.index_offset {
+                return None;
+            }
+            match self.record(offset) {
+                Ok((ip, entry, next)) => {
+                    offset = next;
+                    Some(Ok((ip, entry)))
+                }
+                Err(e) => {
+                    failed = true;
+                    Some(Err(e))
+                }
+            }
+        })
+    }
+
+    fn index_entry(&self, i: usize) -> (&[u8], usize) {
+        let data = self.bytes.as_ref();
+        let at = self.index_offset + i * INDEX_ENTRY_LEN;
+        let offset = usize::try_from(read_u64(data, at + IP_LEN)).unwrap_or(usize::MAX);
+        (&data[at..at + IP_LEN], offset)
+    }
+
+    /// Decode the record at `offset`, returning it and the next offset.
+    fn record(&self, offset: usize) -> io::Result<(IpAddr, CacheEntry, usize)> {
+        let data = &self.bytes.as_ref()[..self.index_offset];
+        let record = data
+            .get(offset..)
+            .ok_or_else(|| invalid_data("context store record out of bounds"))?;
+        let (ip, entry, len) = decode_record(record)?;
+        Ok((ip, entry, offset + len))
+    }
+}
+
+/// Encode the entry for `ip` as one store record.
+///
+/// `RedbContextCache` stores its values in the same encoding, so they can
+/// be copied into a store without decoding.
+pub(super) fn encode_record(ip: IpAddr, entry: &CacheEntry) -> io::Result<Vec<u8>> {
+    let json = serde_json::to_vec(&entry.context)?;
+    let len = u32::try_from(json.len())
+        .map_err(|_| invalid_data("context too large for a store record"))?;
+    let mut record = Vec::with_capacity(RECORD_HEADER_LEN + json.len());
+    record.extend_from_slice(&len.to_le_bytes());
+    record.extend_from_slice(&encode_ip(ip));
+    record.extend_from_slice(&to_millis(entry.inserted_at).to_le_bytes());
+    record.extend_from_slice(&json);
+    Ok(record)
+}
+
+/// Decode the record at the start of `data`, returning it and its length.
+pub(super) fn decode_record(data: &[u8]) -> io::Result<(IpAddr, CacheEntry, usize)> {
+    if data.len() < RECORD_HEADER_LEN {
+        return Err(invalid_data("context store record out of bounds"));
+    }
+    let len = read_u32(data, 0) as usize;
+    let end = RECORD_HEADER_LEN
+        .checked_add(len)
+        .filter(|&end| end <= data.len())
+        .ok_or_else(|| invalid_data("context store record out of bounds"))?;
+    let ip = decode_ip(&data[4..4 + IP_LEN])?;
+    let inserted_at = from_millis(read_u64(data, 4 + IP_LEN));
+    let context = serde_json::from_slice(&data[RECORD_HEADER_LEN..end])?;
+    Ok((
+        ip,
+        CacheEntry {
+            context,
+            inserted_at,
+        },
+        end,
+    ))
+}
+
+pub(super) fn encode_ip(ip: IpAddr) -> [u8; IP_LEN] {
+    let mut key = [0u8; IP_LEN];
+    let (family, octets) = match canonical_ip(ip) {
+        IpAddr::V4(v4) => (4, v4.to_ipv6_mapped().octets()),
+        IpAddr::V6(v6) => (6, v6.octets()),
+    };
+    key[0] = family;
+    key[1..].copy_from_slice(&octets);
+    key
+}
+
+pub(super) fn decode_ip(key: &[u8]) -> io::Result<IpAddr> {
+    let mut octets = [0u8; 16];
+    octets.copy_from_slice(&key[1..]);
+    let v6 = Ipv6Addr::from(octets);
+    match key[0] {
+        4 => {
+            let [a, b, c, d] = [octets[12], octets[13], octets[14], octets[15]];
+            Ok(IpAddr::V4(Ipv4Addr::new(a, b, c, d)))
+        }
+        6 => Ok(IpAddr::V6(v6)),
+        _ => Err(invalid_data("invalid address family in context store")),
+    }
+}
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(data[at..at + 4].try_into().expect("4-byte slice"))
+}
+
+fn read_u64(data: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(data[at..at + 8].try_into().expect("8-byte slice"))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{generate_many, Profile};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn entries() -> Vec<(IpAddr, CacheEntry)> {
+        generate_many(11, Profile::ResidentialProxy, 200)
+            .into_iter()
+            .enumerate()
+            .map(|(i, context)| {
+                // Round-trip first: serde_json does not preserve every f64 bit.
+                let context: crate::IpContext =
+                    serde_json::from_slice(&serde_json::to_vec(&context).unwrap()).unwrap();
+                let ip: IpAddr = context.ip.as_deref().unwrap().parse().unwrap();
+                let inserted_at = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000 + i as u64);
+                (
+                    ip,
+                    CacheEntry {
+                        context,
+                        inserted_at,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn write(entries: &[(IpAddr, CacheEntry)]) -> Vec<u8> {
+        let mut writer = ContextStoreWriter::new(Vec::new()).unwrap();
+        for (ip, entry) in entries {
+            writer.append(*ip, entry).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_lookup_and_scan() {
+        let entries = entries();
+        let store = ContextStore::open(write(&entries)).unwrap();
+
+        assert_eq!(store.version(), CONTEXT_STORE_VERSION);
+        for (ip, entry) in &entries {
+            assert_eq!(store.get(ip).unwrap().as_ref(), Some(entry));
+        }
+        let scanned: Vec<_> = store.iter().map(Result::unwrap).collect();
+        assert_eq!(scanned, entries);
+        assert!(store.get(&"192.0.2.1".parse().unwrap()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_last_append_wins_and_mapped_addresses_are_canonical() {
+        let v4: IpAddr = "1.2.3.4".parse().unwrap();
+        let mapped: IpAddr = "::ffff:1.2.3.4".parse().unwrap();
+        let entry = |org: &str| CacheEntry {
+            context: crate::IpContext {
+                organization: Some(org.to_string()),
+                ..Default::default()
+            },
+            inserted_at: UNIX_EPOCH,
+        };
+        let store = ContextStore::open(write(&[
+            (v4, entry("first")),
+            (mapped, entry("mapped")),
+            (v4, entry("second")),
+        ]))
+        .unwrap();
+
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.iter().count(), 3);
+        let org = |ip| {
+            store
+                .get(&ip)
+                .unwrap()
+                .unwrap()
+                .context
+                .organization
+                .unwrap()
+        };
+        assert_eq!(org(v4), "second");
+        assert_eq!(org(mapped), "second");
+        assert!(store.iter().all(|record| record.unwrap().0 == v4));
+    }
+
+    #[test]
+    fn test_empty_store() {
+        let store = ContextStore::open(write(&[])).unwrap();
+        assert!(store.is_empty());
+        assert_eq!(store.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_rejects_invalid_buffers() {
+        let bytes = write(&entries()[..3]);
+        assert!(ContextStore::open(&b"not a store at all, clearly"[..]).is_err());
+        assert!(ContextStore::open(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut newer = bytes.clone();
+        newer[8] = 2;
+        let err = ContextStore::open(&newer[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,159 @@
+//! Per-entry TTL policies.
+
+use std::time::{Duration, SystemTime};
+
+use super::CacheEntry;
+use crate::context::{Infrastructure, IpContext};
+
+/// Decides how long a cached context stays fresh.
+///
+/// Spur classifications differ a lot in how quickly they go stale: a
+/// datacenter range rarely changes hands, while a mobile carrier address may
+/// serve a different subscriber within the hour. A policy lets the cache
+/// assign each entry a lifetime based on its content.
+///
+/// Any `Fn(&IpContext) -> Option<Duration>` closure is a policy. Returning
+/// `None` means the entry never expires.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use spur::cache::{InfrastructureTtl, LruContextCache};
+/// use spur::{Infrastructure, IpContext};
+///
+/// // Built-in policy keyed on infrastructure
+/// let cache = LruContextCache::new(1000).with_ttl_policy(InfrastructureTtl::default());
+///
+/// // Custom policy as a closure
+/// let cache = LruContextCache::new(1000).with_ttl_policy(|ctx: &IpContext| {
+///     match ctx.infrastructure {
+///         Some(Infrastructure::Datacenter) => Some(Duration::from_secs(7 * 86400)),
+///         _ => Some(Duration::from_secs(3600)),
+///     }
+/// });
+/// ```
+pub trait TtlPolicy: Send + Sync {
+    /// Returns the lifetime for `context`, or `None` for no expiry.
+    fn ttl(&self, context: &IpContext) -> Option<Duration>;
+}
+
+impl<F> TtlPolicy for F
+where
+    F: Fn(&IpContext) -> Option<Duration> + Send + Sync,
+{
+    fn ttl(&self, context: &IpContext) -> Option<Duration> {
+        self(context)
+    }
+}
+
+/// A TTL policy keyed on [`IpContext::infrastructure`].
+///
+/// The defaults reflect how stable each classification tends to be:
+///
+/// | Infrastructure | Default TTL |
+/// |----------------|-------------|
+/// | Datacenter | 7 days |
+/// | Business | 3 days |
+/// | Residential | 1 day |
+/// | Mobile | 1 hour |
+/// | Other / missing | 1 day |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InfrastructureTtl {
+    /// TTL for [`Infrastructure::Datacenter`].
+    pub datacenter: Duration,
+    /// TTL for [`Infrastructure::Business`].
+    pub business: Duration,
+    /// TTL for [`Infrastructure::Residential`].
+    pub residential: Duration,
+    /// TTL for [`Infrastructure::Mobile`].
+    pub mobile: Duration,
+    /// TTL for unknown or missing infrastructure.
+    pub fallback: Duration,
+}
+
+impl Default for InfrastructureTtl {
+    fn default() -> Self {
+        const HOUR: u64 = 3600;
+        const DAY: u64 = 24 * HOUR;
+        Self {
+            datacenter: Duration::from_secs(7 * DAY),
+            business: Duration::from_secs(3 * DAY),
+            residential: Duration::from_secs(DAY),
+            mobile: Duration::from_secs(HOUR),
+            fallback: Duration::from_secs(DAY),
+        }
+    }
+}
+
+impl TtlPolicy for InfrastructureTtl {
+    fn ttl(&self, context: &IpContext) -> Option<Duration> {
+        Some(match context.infrastructure {
+            Some(Infrastructure::Datacenter) => self.datacenter,
+            Some(Infrastructure::Business) => self.business,
+            Some(Infrastructure::Residential) => self.residential,
+            Some(Infrastructure::Mobile) => self.mobile,
+            Some(Infrastructure::Other(_)) | None => self.fallback,
+        })
+    }
+}
+
+/// Returns `true` if `entry` has outlived the TTL `policy` assigns it.
+pub(crate) fn is_expired(policy: Option<&dyn TtlPolicy>, entry: &CacheEntry) -> bool {
+    let ttl = policy.and_then(|p| p.ttl(&entry.context));
+    entry.expiring(ttl).is_expired(SystemTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn context(infra: Option<Infrastructure>) -> IpContext {
+        IpContext {
+            infrastructure: infra,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_infrastructure_ttl_defaults() {
+        let policy = InfrastructureTtl::default();
+        assert_eq!(
+            policy.ttl(&context(Some(Infrastructure::Datacenter))),
+            Some(Duration::from_secs(7 * 86400))
+        );
+        assert_eq!(
+            policy.ttl(&context(Some(Infrastructure::Mobile))),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(policy.ttl(&context(None)), Some(policy.fallback));
+        assert_eq!(
+            policy.ttl(&context(Some(Infrastructure::Other("SATELLITE".into())))),
+            Some(policy.fallback)
+        );
+    }
+
+    #[test]
+    fn test_closure_policy() {
+        let policy = |ctx: &IpContext| ctx.ip.as_ref().map(|_| Duration::from_secs(5));
+        assert_eq!(policy.ttl(&context(None)), None);
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let entry = CacheEntry {
+            context: context(Some(Infrastructure::Mobile)),
+            inserted_at: SystemTime::now() - Duration::from_secs(7200),
+        };
+        let policy = InfrastructureTtl::default();
+        assert!(is_expired(Some(&policy), &entry));
+        assert!(!is_expired(None, &entry));
+
+        let fresh = CacheEntry {
+            context: context(Some(Infrastructure::Datacenter)),
+            ..entry
+        };
+        assert!(!is_expired(Some(&policy), &fresh));
+    }
+}
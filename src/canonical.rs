@@ -0,0 +1,70 @@
+//! Canonical JSON: object keys sorted at every level.
+//!
+//! `serde_json::Map` keeps keys sorted only while no crate in the build
+//! enables serde_json's `preserve_order` feature; with it, keys stay in
+//! struct declaration order. Hashes, signatures and golden files computed
+//! over JSON must not depend on the dependency graph, so the keys are
+//! sorted here explicitly.
+
+use serde_json::Value;
+
+/// Returns `value` with every object's keys sorted.
+///
+/// Entries are re-inserted in sorted order, which a sorted map and an
+/// insertion-ordered map both preserve.
+pub(crate) fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Unsorted {
+        zulu: u8,
+        alpha: Vec<Nested>,
+    }
+
+    #[derive(Serialize)]
+    struct Nested {
+        yankee: bool,
+        bravo: Option<u8>,
+    }
+
+    #[test]
+    fn test_keys_sorted_at_every_level() {
+        let value = Unsorted {
+            zulu: 1,
+            alpha: vec![Nested {
+                yankee: true,
+                bravo: None,
+            }],
+        };
+        let keys =
+            |value: &Value| -> Vec<String> { value.as_object().unwrap().keys().cloned().collect() };
+
+        let value = sort_keys(serde_json::to_value(&value).unwrap());
+        assert_eq!(keys(&value), ["alpha", "zulu"]);
+        assert_eq!(keys(&value["alpha"][0]), ["bravo", "yankee"]);
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"alpha":[{"bravo":null,"yankee":true}],"zulu":1}"#
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Cross-checking Spur locations against other geolocation sources.
+//!
+//! Teams that also run a GeoIP database (such as MaxMind) regularly need to
+//! record where the two disagree. [`against_geoip`] compares a context's
+//! location with the other source's answer and returns a
+//! [`GeoComparison`] that serializes into a consistent record.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::compare::{against_geoip, Confidence};
+//! use spur::test_utils::IpContextBuilder;
+//!
+//! let context = IpContextBuilder::new().location("NL", Some("Amsterdam")).build();
+//!
+//! let report = against_geoip(&context, Some("DE"), None);
+//! assert_eq!(report.country_match, Some(false));
+//! assert_eq!(report.confidence, Confidence::Low);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::context::IpContext;
+use crate::geo::haversine_km;
+
+/// Distance within which two locations count as the same place.
+pub const NEAR_KM: f64 = 100.0;
+
+/// How far two locations in the same country may be apart before the
+/// agreement is downgraded to [`Confidence::Medium`].
+pub const FAR_KM: f64 = 500.0;
+
+/// How well two location sources agree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Confidence {
+    /// One of the sources has no country, so nothing can be said.
+    Unknown,
+    /// The countries differ.
+    Low,
+    /// The countries match, but the coordinates are missing or more than
+    /// [`FAR_KM`] apart.
+    Medium,
+    /// The countries match and the coordinates are within [`FAR_KM`].
+    High,
+}
+
+/// Result of [`against_geoip`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeoComparison {
+    /// The country Spur reports, in uppercase.
+    pub spur_country: Option<String>,
+    /// The country the other source reports, in uppercase.
+    pub other_country: Option<String>,
+    /// Whether the countries match; `None` if either is missing.
+    pub country_match: Option<bool>,
+    /// Great-circle distance between the two coordinates; `None` if either
+    /// side has none.
+    pub distance_km: Option<f64>,
+    /// Whether the coordinates are within [`NEAR_KM`] of each other;
+    /// `None` if the distance is unknown.
+    pub near: Option<bool>,
+    /// Overall agreement.
+    pub confidence: Confidence,
+}
+
+impl GeoComparison {
+    /// Returns `true` if the sources put the address in different
+    /// countries.
+    pub fn is_disagreement(&self) -> bool {
+        self.country_match == Some(false)
+    }
+}
+
+/// Compare the context's location with another source's country and
+/// `(latitude, longitude)` coordinates.
+///
+/// Country codes are compared without regard to case.
+pub fn against_geoip(
+    context: &IpContext,
+    other_country: Option<&str>,
+    other_coords: Option<(f64, f64)>,
+) -> GeoComparison {
+    let location = context.location.as_ref();
+    let spur_country = location
+        .and_then(|l| l.country.as_deref())
+        .map(str::to_ascii_uppercase);
+    let other_country = other_country.map(str::to_ascii_uppercase);
+    let spur_coords = location.and_then(|l| Some((l.latitude?, l.longitude?)));
+
+    let country_match = match (&spur_country, &other_country) {
+        (Some(a), Some(b)) => Some(a == b),
+        _ => None,
+    };
+    let distance_km = spur_coords
+        .zip(other_coords)
+        .map(|(a, b)| haversine_km(a, b));
+    let confidence = match (country_match, distance_km) {
+        (None, _) => Confidence::Unknown,
+        (Some(false), _) => Confidence::Low,
+        (Some(true), Some(d)) if d <= FAR_KM => Confidence::High,
+        (Some(true), _) => Confidence::Medium,
+    };
+
+    GeoComparison {
+        spur_country,
+        other_country,
+        country_match,
+        distance_km,
+        near: distance_km.map(|d| d <= NEAR_KM),
+        confidence,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Location;
+
+    fn located(country: &str, coords: Option<(f64, f64)>) -> IpContext {
+        IpContext {
+            location: Some(Location {
+                country: Some(country.to_string()),
+                latitude: coords.map(|c| c.0),
+                longitude: coords.map(|c| c.1),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_agreement() {
+        let amsterdam = (52.37, 4.90);
+        let context = located("nl", Some(amsterdam));
+
+        let report = against_geoip(&context, Some("NL"), Some((52.09, 5.12)));
+        assert_eq!(report.spur_country.as_deref(), Some("NL"));
+        assert_eq!(report.country_match, Some(true));
+        assert_eq!(report.near, Some(true));
+        assert_eq!(report.confidence, Confidence::High);
+
+        let groningen = (53.22, 6.57);
+        let report = against_geoip(&context, Some("NL"), Some(groningen));
+        assert_eq!(report.near, Some(false));
+        assert_eq!(report.confidence, Confidence::High);
+
+        let report = against_geoip(
+            &located("US", Some((40.7, -74.0))),
+            Some("US"),
+            Some((34.05, -118.24)),
+        );
+        assert_eq!(report.confidence, Confidence::Medium);
+        assert_eq!(
+            against_geoip(&context, Some("NL"), None).confidence,
+            Confidence::Medium
+        );
+    }
+
+    #[test]
+    fn test_disagreement() {
+        let report = against_geoip(&located("NL", None), Some("BE"), Some((50.85, 4.35)));
+        assert!(report.is_disagreement());
+        assert_eq!(report.distance_km, None);
+        assert_eq!(report.confidence, Confidence::Low);
+
+        let unknown = against_geoip(&IpContext::default(), Some("BE"), None);
+        assert_eq!(unknown.confidence, Confidence::Unknown);
+        assert!(!unknown.is_disagreement());
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["confidence"], "LOW");
+    }
+}
@@ -0,0 +1,109 @@
+//! Interpretation helpers for the concentration metrics.
+
+use super::types::{Client, Concentration};
+
+impl Concentration {
+    /// Density at or above which [`is_tight`](Self::is_tight) holds: at
+    /// least half of the clients are in the concentration area.
+    pub const TIGHT_DENSITY: f64 = 0.5;
+
+    /// Returns `true` if most clients behind the address are in one place.
+    ///
+    /// `density` is the share of the clients, from `0.0` to `1.0`, that were
+    /// observed inside the concentration area (the `geohash` cell). It is
+    /// not a population density. A tight concentration is typical of a
+    /// household, office or carrier NAT serving one city; a low density
+    /// means the clients are scattered, as with VPNs and proxy exits.
+    ///
+    /// Returns `false` if no density is reported.
+    ///
+    /// ```rust
+    /// use spur::Concentration;
+    ///
+    /// let office = Concentration { density: Some(0.9), ..Default::default() };
+    /// assert!(office.is_tight());
+    /// assert!(!Concentration::default().is_tight());
+    /// ```
+    pub fn is_tight(&self) -> bool {
+        self.density.is_some_and(|d| d >= Self::TIGHT_DENSITY)
+    }
+
+    /// Returns how far the concentration lies from the address, relative to
+    /// how widely `client` is spread.
+    ///
+    /// `skew` is the distance in kilometers between the geolocation of the
+    /// address and the center of the concentration. `client.spread` is the
+    /// area in square kilometers the clients were observed over; neither is
+    /// a score. The ratio divides the skew by the radius of a circle with
+    /// that area:
+    ///
+    /// - below `1.0`, the address is located within the area its clients
+    ///   cover, as expected for access networks
+    /// - well above `1.0`, the clients are somewhere else entirely, a sign
+    ///   that the address relays traffic for them
+    ///
+    /// Returns `None` if either metric is missing. A spread of zero (all
+    /// clients at one point) gives infinity for any non-zero skew.
+    ///
+    /// ```rust
+    /// use spur::{Client, Concentration};
+    ///
+    /// let concentration = Concentration { skew: Some(800), ..Default::default() };
+    /// let client = Client { spread: Some(3_142), ..Default::default() };
+    /// let ratio = concentration.spread_ratio(&client).unwrap();
+    /// assert!(ratio > 20.0);
+    /// ```
+    pub fn spread_ratio(&self, client: &Client) -> Option<f64> {
+        let skew = self.skew? as f64;
+        let radius = (client.spread? as f64 / std::f64::consts::PI).sqrt();
+        if skew == 0.0 {
+            return Some(0.0);
+        }
+        Some(skew / radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn concentration(density: Option<f64>, skew: Option<u64>) -> Concentration {
+        Concentration {
+            density,
+            skew,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_is_tight() {
+        assert!(concentration(Some(0.5), None).is_tight());
+        assert!(!concentration(Some(0.49), None).is_tight());
+        assert!(!concentration(None, None).is_tight());
+    }
+
+    #[test]
+    fn test_spread_ratio() {
+        let client = |spread| Client {
+            spread: Some(spread),
+            ..Default::default()
+        };
+        // Radius of a 31_416 km² circle is 100 km
+        let ratio = concentration(None, Some(50)).spread_ratio(&client(31_416));
+        assert!((ratio.unwrap() - 0.5).abs() < 1e-3);
+
+        assert_eq!(
+            concentration(None, Some(0)).spread_ratio(&client(0)),
+            Some(0.0)
+        );
+        assert_eq!(
+            concentration(None, Some(10)).spread_ratio(&client(0)),
+            Some(f64::INFINITY)
+        );
+        assert_eq!(concentration(None, None).spread_ratio(&client(10)), None);
+        assert_eq!(
+            concentration(None, Some(10)).spread_ratio(&Client::default()),
+            None
+        );
+    }
+}
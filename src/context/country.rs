@@ -0,0 +1,428 @@
+//! ISO 3166-1 alpha-2 country codes.
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::types::Location;
+
+/// An ISO 3166-1 alpha-2 country code, stored in uppercase.
+///
+/// The API reports countries as plain strings; [`Location::country_code`]
+/// converts them. With the `i18n` feature, codes can also be rendered as
+/// English display names and emoji flags.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::context::CountryCode;
+///
+/// let code = CountryCode::new("nl").unwrap();
+/// assert_eq!(code.as_str(), "NL");
+/// assert_eq!(code.to_string(), "NL");
+/// assert!(CountryCode::new("NLD").is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CountryCode([u8; 2]);
+
+impl CountryCode {
+    /// Parse a two-letter code, in any case.
+    ///
+    /// Returns `None` unless `code` is exactly two ASCII letters. Whether
+    /// the code is actually assigned is not checked.
+    pub fn new(code: &str) -> Option<Self> {
+        match code.as_bytes() {
+            &[a, b] if a.is_ascii_alphabetic() && b.is_ascii_alphabetic() => {
+                Some(Self([a.to_ascii_uppercase(), b.to_ascii_uppercase()]))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the code as an uppercase string slice.
+    pub fn as_str(&self) -> &str {
+        // Both bytes are ASCII letters, checked in `new`.
+        std::str::from_utf8(&self.0).expect("country code is ASCII")
+    }
+
+    /// Returns the English display name for this code in `locale`.
+    ///
+    /// Only English is bundled: `locale` must be `en` or an English
+    /// variant such as `en-US` or `en_GB`, otherwise `None` is returned.
+    /// Unassigned codes also return `None`. Requires the `i18n` feature.
+    ///
+    /// ```rust
+    /// use spur::context::CountryCode;
+    ///
+    /// let code = CountryCode::new("NL").unwrap();
+    /// assert_eq!(code.display_name("en"), Some("Netherlands"));
+    /// assert_eq!(code.display_name("de-DE"), None);
+    /// ```
+    #[cfg(feature = "i18n")]
+    pub fn display_name(&self, locale: &str) -> Option<&'static str> {
+        let language = locale.split(['-', '_']).next().unwrap_or_default();
+        if !language.eq_ignore_ascii_case("en") {
+            return None;
+        }
+        NAMES_EN
+            .binary_search_by_key(&self.as_str(), |(code, _)| *code)
+            .ok()
+            .map(|index| NAMES_EN[index].1)
+    }
+
+    /// Returns the emoji flag for this code, as a pair of regional
+    /// indicator symbols. Requires the `i18n` feature.
+    ///
+    /// Whether a flag is drawn for an unassigned code depends on the font.
+    ///
+    /// ```rust
+    /// use spur::context::CountryCode;
+    ///
+    /// assert_eq!(CountryCode::new("NL").unwrap().flag(), "🇳🇱");
+    /// ```
+    #[cfg(feature = "i18n")]
+    pub fn flag(&self) -> String {
+        const REGIONAL_INDICATOR_A: u32 = 0x1F1E6;
+        self.0
+            .iter()
+            .filter_map(|&b| char::from_u32(REGIONAL_INDICATOR_A + u32::from(b - b'A')))
+            .collect()
+    }
+
+    /// Returns the display name followed by the flag, such as
+    /// `"Netherlands 🇳🇱"`, falling back to the code when no name is
+    /// known in `locale`. Requires the `i18n` feature.
+    #[cfg(feature = "i18n")]
+    pub fn label(&self, locale: &str) -> String {
+        let name = self.display_name(locale).unwrap_or(self.as_str());
+        format!("{} {}", name, self.flag())
+    }
+}
+
+impl fmt::Display for CountryCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for CountryCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for CountryCode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = String::deserialize(deserializer)?;
+        Self::new(&code).ok_or_else(|| {
+            serde::de::Error::invalid_value(
+                serde::de::Unexpected::Str(&code),
+                &"a two-letter country code",
+            )
+        })
+    }
+}
+
+impl Location {
+    /// Returns the country as a [`CountryCode`], if it is set and well
+    /// formed.
+    pub fn country_code(&self) -> Option<CountryCode> {
+        self.country.as_deref().and_then(CountryCode::new)
+    }
+}
+
+/// English country names by code, sorted by code.
+///
+/// Based on the public-domain `iso3166.tab` from the tz database, with
+/// names adjusted to their common English forms.
+#[cfg(feature = "i18n")]
+const NAMES_EN: &[(&str, &str)] = &[
+    ("AD", "Andorra"),
+    ("AE", "United Arab Emirates"),
+    ("AF", "Afghanistan"),
+    ("AG", "Antigua and Barbuda"),
+    ("AI", "Anguilla"),
+    ("AL", "Albania"),
+    ("AM", "Armenia"),
+    ("AO", "Angola"),
+    ("AQ", "Antarctica"),
+    ("AR", "Argentina"),
+    ("AS", "American Samoa"),
+    ("AT", "Austria"),
+    ("AU", "Australia"),
+    ("AW", "Aruba"),
+    ("AX", "Åland Islands"),
+    ("AZ", "Azerbaijan"),
+    ("BA", "Bosnia and Herzegovina"),
+    ("BB", "Barbados"),
+    ("BD", "Bangladesh"),
+    ("BE", "Belgium"),
+    ("BF", "Burkina Faso"),
+    ("BG", "Bulgaria"),
+    ("BH", "Bahrain"),
+    ("BI", "Burundi"),
+    ("BJ", "Benin"),
+    ("BL", "Saint Barthélemy"),
+    ("BM", "Bermuda"),
+    ("BN", "Brunei"),
+    ("BO", "Bolivia"),
+    ("BQ", "Caribbean NL"),
+    ("BR", "Brazil"),
+    ("BS", "Bahamas"),
+    ("BT", "Bhutan"),
+    ("BV", "Bouvet Island"),
+    ("BW", "Botswana"),
+    ("BY", "Belarus"),
+    ("BZ", "Belize"),
+    ("CA", "Canada"),
+    ("CC", "Cocos (Keeling) Islands"),
+    ("CD", "Congo (DRC)"),
+    ("CF", "Central African Rep."),
+    ("CG", "Congo"),
+    ("CH", "Switzerland"),
+    ("CI", "Côte d’Ivoire"),
+    ("CK", "Cook Islands"),
+    ("CL", "Chile"),
+    ("CM", "Cameroon"),
+    ("CN", "China"),
+    ("CO", "Colombia"),
+    ("CR", "Costa Rica"),
+    ("CU", "Cuba"),
+    ("CV", "Cape Verde"),
+    ("CW", "Curaçao"),
+    ("CX", "Christmas Island"),
+    ("CY", "Cyprus"),
+    ("CZ", "Czechia"),
+    ("DE", "Germany"),
+    ("DJ", "Djibouti"),
+    ("DK", "Denmark"),
+    ("DM", "Dominica"),
+    ("DO", "Dominican Republic"),
+    ("DZ", "Algeria"),
+    ("EC", "Ecuador"),
+    ("EE", "Estonia"),
+    ("EG", "Egypt"),
+    ("EH", "Western Sahara"),
+    ("ER", "Eritrea"),
+    ("ES", "Spain"),
+    ("ET", "Ethiopia"),
+    ("FI", "Finland"),
+    ("FJ", "Fiji"),
+    ("FK", "Falkland Islands"),
+    ("FM", "Micronesia"),
+    ("FO", "Faroe Islands"),
+    ("FR", "France"),
+    ("GA", "Gabon"),
+    ("GB", "United Kingdom"),
+    ("GD", "Grenada"),
+    ("GE", "Georgia"),
+    ("GF", "French Guiana"),
+    ("GG", "Guernsey"),
+    ("GH", "Ghana"),
+    ("GI", "Gibraltar"),
+    ("GL", "Greenland"),
+    ("GM", "Gambia"),
+    ("GN", "Guinea"),
+    ("GP", "Guadeloupe"),
+    ("GQ", "Equatorial Guinea"),
+    ("GR", "Greece"),
+    ("GS", "South Georgia and the South Sandwich Islands"),
+    ("GT", "Guatemala"),
+    ("GU", "Guam"),
+    ("GW", "Guinea-Bissau"),
+    ("GY", "Guyana"),
+    ("HK", "Hong Kong"),
+    ("HM", "Heard Island and McDonald Islands"),
+    ("HN", "Honduras"),
+    ("HR", "Croatia"),
+    ("HT", "Haiti"),
+    ("HU", "Hungary"),
+    ("ID", "Indonesia"),
+    ("IE", "Ireland"),
+    ("IL", "Israel"),
+    ("IM", "Isle of Man"),
+    ("IN", "India"),
+    ("IO", "British Indian Ocean Territory"),
+    ("IQ", "Iraq"),
+    ("IR", "Iran"),
+    ("IS", "Iceland"),
+    ("IT", "Italy"),
+    ("JE", "Jersey"),
+    ("JM", "Jamaica"),
+    ("JO", "Jordan"),
+    ("JP", "Japan"),
+    ("KE", "Kenya"),
+    ("KG", "Kyrgyzstan"),
+    ("KH", "Cambodia"),
+    ("KI", "Kiribati"),
+    ("KM", "Comoros"),
+    ("KN", "Saint Kitts and Nevis"),
+    ("KP", "North Korea"),
+    ("KR", "South Korea"),
+    ("KW", "Kuwait"),
+    ("KY", "Cayman Islands"),
+    ("KZ", "Kazakhstan"),
+    ("LA", "Laos"),
+    ("LB", "Lebanon"),
+    ("LC", "Saint Lucia"),
+    ("LI", "Liechtenstein"),
+    ("LK", "Sri Lanka"),
+    ("LR", "Liberia"),
+    ("LS", "Lesotho"),
+    ("LT", "Lithuania"),
+    ("LU", "Luxembourg"),
+    ("LV", "Latvia"),
+    ("LY", "Libya"),
+    ("MA", "Morocco"),
+    ("MC", "Monaco"),
+    ("MD", "Moldova"),
+    ("ME", "Montenegro"),
+    ("MF", "Saint Martin"),
+    ("MG", "Madagascar"),
+    ("MH", "Marshall Islands"),
+    ("MK", "North Macedonia"),
+    ("ML", "Mali"),
+    ("MM", "Myanmar"),
+    ("MN", "Mongolia"),
+    ("MO", "Macau"),
+    ("MP", "Northern Mariana Islands"),
+    ("MQ", "Martinique"),
+    ("MR", "Mauritania"),
+    ("MS", "Montserrat"),
+    ("MT", "Malta"),
+    ("MU", "Mauritius"),
+    ("MV", "Maldives"),
+    ("MW", "Malawi"),
+    ("MX", "Mexico"),
+    ("MY", "Malaysia"),
+    ("MZ", "Mozambique"),
+    ("NA", "Namibia"),
+    ("NC", "New Caledonia"),
+    ("NE", "Niger"),
+    ("NF", "Norfolk Island"),
+    ("NG", "Nigeria"),
+    ("NI", "Nicaragua"),
+    ("NL", "Netherlands"),
+    ("NO", "Norway"),
+    ("NP", "Nepal"),
+    ("NR", "Nauru"),
+    ("NU", "Niue"),
+    ("NZ", "New Zealand"),
+    ("OM", "Oman"),
+    ("PA", "Panama"),
+    ("PE", "Peru"),
+    ("PF", "French Polynesia"),
+    ("PG", "Papua New Guinea"),
+    ("PH", "Philippines"),
+    ("PK", "Pakistan"),
+    ("PL", "Poland"),
+    ("PM", "Saint Pierre and Miquelon"),
+    ("PN", "Pitcairn"),
+    ("PR", "Puerto Rico"),
+    ("PS", "Palestine"),
+    ("PT", "Portugal"),
+    ("PW", "Palau"),
+    ("PY", "Paraguay"),
+    ("QA", "Qatar"),
+    ("RE", "Réunion"),
+    ("RO", "Romania"),
+    ("RS", "Serbia"),
+    ("RU", "Russia"),
+    ("RW", "Rwanda"),
+    ("SA", "Saudi Arabia"),
+    ("SB", "Solomon Islands"),
+    ("SC", "Seychelles"),
+    ("SD", "Sudan"),
+    ("SE", "Sweden"),
+    ("SG", "Singapore"),
+    ("SH", "Saint Helena"),
+    ("SI", "Slovenia"),
+    ("SJ", "Svalbard and Jan Mayen"),
+    ("SK", "Slovakia"),
+    ("SL", "Sierra Leone"),
+    ("SM", "San Marino"),
+    ("SN", "Senegal"),
+    ("SO", "Somalia"),
+    ("SR", "Suriname"),
+    ("SS", "South Sudan"),
+    ("ST", "São Tomé and Príncipe"),
+    ("SV", "El Salvador"),
+    ("SX", "Sint Maarten"),
+    ("SY", "Syria"),
+    ("SZ", "Eswatini"),
+    ("TC", "Turks and Caicos Islands"),
+    ("TD", "Chad"),
+    ("TF", "French S. Terr."),
+    ("TG", "Togo"),
+    ("TH", "Thailand"),
+    ("TJ", "Tajikistan"),
+    ("TK", "Tokelau"),
+    ("TL", "East Timor"),
+    ("TM", "Turkmenistan"),
+    ("TN", "Tunisia"),
+    ("TO", "Tonga"),
+    ("TR", "Türkiye"),
+    ("TT", "Trinidad and Tobago"),
+    ("TV", "Tuvalu"),
+    ("TW", "Taiwan"),
+    ("TZ", "Tanzania"),
+    ("UA", "Ukraine"),
+    ("UG", "Uganda"),
+    ("UM", "US minor outlying islands"),
+    ("US", "United States"),
+    ("UY", "Uruguay"),
+    ("UZ", "Uzbekistan"),
+    ("VA", "Vatican City"),
+    ("VC", "Saint Vincent and the Grenadines"),
+    ("VE", "Venezuela"),
+    ("VG", "British Virgin Islands"),
+    ("VI", "U.S. Virgin Islands"),
+    ("VN", "Vietnam"),
+    ("VU", "Vanuatu"),
+    ("WF", "Wallis and Futuna"),
+    ("WS", "Samoa"),
+    ("YE", "Yemen"),
+    ("YT", "Mayotte"),
+    ("ZA", "South Africa"),
+    ("ZM", "Zambia"),
+    ("ZW", "Zimbabwe"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(CountryCode::new("de").unwrap().as_str(), "DE");
+        for invalid in ["", "D", "DEU", "D1", "ÄÖ"] {
+            assert!(CountryCode::new(invalid).is_none(), "{}", invalid);
+        }
+        let location = Location {
+            country: Some("us".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(location.country_code(), CountryCode::new("US"));
+        assert_eq!(Location::default().country_code(), None);
+    }
+
+    #[test]
+    fn test_serde() {
+        let code: CountryCode = serde_json::from_str(r#""gb""#).unwrap();
+        assert_eq!(serde_json::to_string(&code).unwrap(), r#""GB""#);
+        assert!(serde_json::from_str::<CountryCode>(r#""GBR""#).is_err());
+    }
+
+    #[cfg(feature = "i18n")]
+    #[test]
+    fn test_names_and_flags() {
+        assert!(NAMES_EN.windows(2).all(|w| w[0].0 < w[1].0));
+        let code = CountryCode::new("NL").unwrap();
+        assert_eq!(code.display_name("en_GB"), Some("Netherlands"));
+        assert_eq!(code.display_name("EN"), Some("Netherlands"));
+        assert_eq!(code.label("en"), "Netherlands 🇳🇱");
+        assert_eq!(code.label("fr"), "NL 🇳🇱");
+        assert_eq!(CountryCode::new("ZZ").unwrap().display_name("en"), None);
+        assert_eq!(CountryCode::new("US").unwrap().flag(), "\u{1F1FA}\u{1F1F8}");
+    }
+}
@@ -46,6 +46,9 @@ macro_rules! impl_serde_enum {
         }
 
         impl $enum_name {
+            /// API strings of every known variant, in declaration order.
+            pub const KNOWN_VALUES: &'static [&'static str] = &[$($str),+];
+
             /// Returns the string representation of this variant.
             pub fn as_str(&self) -> &str {
                 match self {
@@ -59,6 +62,24 @@ macro_rules! impl_serde_enum {
                 matches!(self, Self::Other(_))
             }
         }
+
+        #[cfg(feature = "arbitrary")]
+        impl<'a> arbitrary::Arbitrary<'a> for $enum_name {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+                let known = [$(Self::$variant),+];
+                let index = u.choose_index(known.len() + 1)?;
+                if let Some(variant) = known.get(index) {
+                    return Ok(variant.clone());
+                }
+                // Map strings that spell a known value to that variant, so
+                // generated values survive a serde round trip unchanged.
+                let s = String::arbitrary(u)?;
+                Ok(match s.as_str() {
+                    $($str => Self::$variant,)+
+                    _ => Self::Other(s),
+                })
+            }
+        }
     };
 }
 
@@ -92,6 +113,37 @@ impl Default for Infrastructure {
     }
 }
 
+impl Infrastructure {
+    /// Returns `true` for networks where addresses are handed to people:
+    /// residential and mobile access, business networks and `SATELLITE`
+    /// access (not yet a named variant).
+    ///
+    /// Rules written against this group keep working when Spur adds new
+    /// access network types, as they are added here rather than at every
+    /// call site. Other unknown values are neither end-user nor server-side.
+    ///
+    /// ```rust
+    /// use spur::Infrastructure;
+    ///
+    /// assert!(Infrastructure::Mobile.is_end_user());
+    /// assert!(Infrastructure::Other("SATELLITE".into()).is_end_user());
+    /// assert!(!Infrastructure::Datacenter.is_end_user());
+    /// ```
+    pub fn is_end_user(&self) -> bool {
+        match self {
+            Self::Residential | Self::Mobile | Self::Business => true,
+            Self::Datacenter => false,
+            Self::Other(s) => s.eq_ignore_ascii_case("SATELLITE"),
+        }
+    }
+
+    /// Returns `true` for hosting networks, where traffic comes from
+    /// servers rather than people: datacenters and cloud providers.
+    pub fn is_server_side(&self) -> bool {
+        matches!(self, Self::Datacenter)
+    }
+}
+
 /// Risk factors or suspicious behaviors identified for an IP.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Risk {
@@ -120,6 +172,63 @@ impl Default for Risk {
     }
 }
 
+impl Risk {
+    /// Returns a short explanation of the risk, for display in UIs and
+    /// alerts.
+    ///
+    /// Unknown risks get a generic explanation; show
+    /// [`as_str`](Self::as_str) next to it so the raw value is not lost.
+    ///
+    /// ```rust
+    /// use spur::Risk;
+    ///
+    /// let risk = Risk::CallbackProxy;
+    /// println!("{}: {} {}", risk, risk.description(), risk.recommended_action());
+    /// ```
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Tunnel => {
+                "Traffic from this address is relayed through a VPN, proxy or \
+                 other tunnel, so the real client is hidden."
+            }
+            Self::Spam => "This address has been observed sending spam or abusive traffic.",
+            Self::CallbackProxy => {
+                "This address is a callback proxy: software on a device lets \
+                 third parties route their traffic through it, often without \
+                 the owner knowing."
+            }
+            Self::GeoMismatch => {
+                "The location of the address does not match where its clients \
+                 were observed."
+            }
+            Self::Other(_) => "A risk factor this version of the library does not describe.",
+        }
+    }
+
+    /// Returns a suggested response to the risk.
+    ///
+    /// The hints are starting points for policy, not decisions; combine
+    /// them with the rest of the context.
+    pub fn recommended_action(&self) -> &'static str {
+        match self {
+            Self::Tunnel => {
+                "Do not rely on the address for location or identity; ask for \
+                 step-up verification on sensitive actions."
+            }
+            Self::Spam => "Rate limit or hold submissions from this address for review.",
+            Self::CallbackProxy => {
+                "Treat sessions as possibly not from the device owner; \
+                 challenge logins and payments."
+            }
+            Self::GeoMismatch => {
+                "Do not use the address location for geo-restricted decisions \
+                 without another signal."
+            }
+            Self::Other(_) => "Review the Spur documentation for this risk value.",
+        }
+    }
+}
+
 /// Network services or protocols detected on an IP.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Service {
@@ -241,6 +350,23 @@ mod tests {
         assert!(parsed.is_other());
     }
 
+    #[test]
+    fn test_infrastructure_groups() {
+        use Infrastructure::*;
+
+        for infra in [
+            Residential,
+            Mobile,
+            Business,
+            Other("satellite".to_string()),
+        ] {
+            assert!(infra.is_end_user() && !infra.is_server_side(), "{}", infra);
+        }
+        assert!(Datacenter.is_server_side() && !Datacenter.is_end_user());
+        let unknown = Other("NEW_TYPE".to_string());
+        assert!(!unknown.is_end_user() && !unknown.is_server_side());
+    }
+
     #[test]
     fn test_risk_serde() {
         let risk = Risk::CallbackProxy;
@@ -305,9 +431,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_risk_descriptions() {
+        let unknown = Risk::Other("NEW_RISK_TYPE".to_string());
+        let known = [
+            Risk::Tunnel,
+            Risk::Spam,
+            Risk::CallbackProxy,
+            Risk::GeoMismatch,
+        ];
+        assert_eq!(known.len(), Risk::KNOWN_VALUES.len());
+        for (i, risk) in known.iter().enumerate() {
+            assert_ne!(risk.description(), unknown.description());
+            assert_ne!(risk.recommended_action(), unknown.recommended_action());
+            for other in &known[i + 1..] {
+                assert_ne!(risk.description(), other.description());
+            }
+        }
+    }
+
     #[test]
     fn test_display() {
         assert_eq!(format!("{}", Risk::Tunnel), "TUNNEL");
         assert_eq!(format!("{}", Risk::Other("CUSTOM".to_string())), "CUSTOM");
     }
+
+    #[test]
+    fn test_known_values_deserialize_to_known_variants() {
+        assert_eq!(
+            Risk::KNOWN_VALUES,
+            ["TUNNEL", "SPAM", "CALLBACK_PROXY", "GEO_MISMATCH"]
+        );
+        for value in Service::KNOWN_VALUES {
+            let service: Service = serde_json::from_value(serde_json::json!(value)).unwrap();
+            assert!(!service.is_other(), "{}", value);
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary_round_trips() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        // Deterministic pseudo-random input covering every variant index.
+        let bytes: Vec<u8> = (0..4096u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect();
+        let mut u = Unstructured::new(&bytes);
+        while !u.is_empty() {
+            let risk = Risk::arbitrary(&mut u).unwrap();
+            let json = serde_json::to_string(&risk).unwrap();
+            assert_eq!(serde_json::from_str::<Risk>(&json).unwrap(), risk);
+
+            let infra = Infrastructure::arbitrary(&mut u).unwrap();
+            let json = serde_json::to_string(&infra).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Infrastructure>(&json).unwrap(),
+                infra
+            );
+        }
+    }
 }
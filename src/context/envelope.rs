@@ -0,0 +1,90 @@
+//! Response bodies wrapped by proxies and gateways (requires the `json`
+//! feature).
+
+use serde::{Deserialize, Serialize};
+
+use super::parse::{parse_slice, ParseError};
+use super::types::IpContext;
+
+/// A body wrapped as `{"data": ..., "meta": {...}}`.
+///
+/// API gateways and internal proxies often wrap the upstream body this way.
+/// `result` and `payload` are accepted in place of `data`; other top-level
+/// keys are ignored.
+///
+/// ```rust
+/// use spur::{Envelope, IpContext};
+///
+/// let body = r#"{"data": [{"ip": "1.2.3.4"}], "meta": {"request_id": "abc"}}"#;
+/// let envelope: Envelope<Vec<IpContext>> = serde_json::from_str(body).unwrap();
+/// assert_eq!(envelope.data.len(), 1);
+/// assert_eq!(envelope.meta.unwrap()["request_id"], "abc");
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    /// The wrapped body.
+    #[serde(alias = "result", alias = "payload")]
+    pub data: T,
+
+    /// Whatever metadata the wrapper added, such as request IDs or timings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<serde_json::Value>,
+}
+
+impl<T> Envelope<T> {
+    /// Wrap `data` without metadata.
+    pub fn new(data: T) -> Self {
+        Self { data, meta: None }
+    }
+
+    /// Returns the wrapped body.
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+}
+
+impl IpContext {
+    /// Parse a context from a body wrapped in an [`Envelope`].
+    ///
+    /// Error paths include the wrapper, e.g. `data.tunnels[0].type`.
+    ///
+    /// ```rust
+    /// use spur::IpContext;
+    ///
+    /// let body = br#"{"data": {"ip": "1.2.3.4"}, "meta": {"cached": true}}"#;
+    /// let context = IpContext::from_enveloped_json(body).unwrap();
+    /// assert_eq!(context.ip.as_deref(), Some("1.2.3.4"));
+    /// ```
+    pub fn from_enveloped_json(bytes: &[u8]) -> Result<Self, ParseError> {
+        parse_slice::<Envelope<Self>>(bytes).map(Envelope::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases_and_meta() {
+        for key in ["data", "result", "payload"] {
+            let body = format!(r#"{{"{}": {{"ip": "1.2.3.4"}}, "status": 200}}"#, key);
+            let context = IpContext::from_enveloped_json(body.as_bytes()).unwrap();
+            assert_eq!(context.ip.as_deref(), Some("1.2.3.4"));
+        }
+
+        let envelope = Envelope::new(IpContext::default());
+        let json = serde_json::to_string(&envelope).unwrap();
+        assert_eq!(json, r#"{"data":{}}"#);
+        let parsed: Envelope<IpContext> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, envelope);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = IpContext::from_enveloped_json(br#"{"data": {"ip": 5}}"#).unwrap_err();
+        assert_eq!(err.path(), Some("data.ip"));
+
+        let err = IpContext::from_enveloped_json(br#"{"ip": "1.2.3.4"}"#).unwrap_err();
+        assert!(err.to_string().contains("missing field `data`"), "{}", err);
+    }
+}
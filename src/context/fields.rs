@@ -0,0 +1,52 @@
+//! The curated, low-cardinality field set shared by the logging adapters.
+
+use super::types::IpContext;
+
+/// A field value, borrowed from the context where possible.
+#[derive(Debug, Clone)]
+pub(crate) enum FieldValue<'a> {
+    Str(&'a str),
+    String(String),
+    U64(u64),
+    Bool(bool),
+}
+
+impl IpContext {
+    /// Returns the curated fields that are present, in `TRACING_FIELDS` order:
+    /// the infrastructure type, AS number, country code, the first tunnel's
+    /// type and operator, whether any tunnel is anonymous, and the risks
+    /// joined with `,`.
+    pub(crate) fn curated_fields(&self) -> Vec<(&'static str, FieldValue<'_>)> {
+        let mut fields = Vec::new();
+        if let Some(infrastructure) = &self.infrastructure {
+            fields.push((
+                "spur.infrastructure",
+                FieldValue::Str(infrastructure.as_str()),
+            ));
+        }
+        if let Some(asn) = self.autonomous_system.as_ref().and_then(|a| a.number) {
+            fields.push(("spur.asn", FieldValue::U64(asn.into())));
+        }
+        if let Some(country) = self.location.as_ref().and_then(|l| l.country.as_deref()) {
+            fields.push(("spur.country", FieldValue::Str(country)));
+        }
+        let tunnels = self.tunnels.as_deref().unwrap_or_default();
+        if let Some(tunnel) = tunnels.first() {
+            if let Some(tunnel_type) = &tunnel.tunnel_type {
+                fields.push(("spur.tunnel_type", FieldValue::Str(tunnel_type.as_str())));
+            }
+            if let Some(operator) = &tunnel.operator {
+                fields.push(("spur.operator", FieldValue::Str(operator)));
+            }
+        }
+        if tunnels.iter().any(|t| t.anonymous.is_some()) {
+            let anonymous = tunnels.iter().any(|t| t.anonymous == Some(true));
+            fields.push(("spur.anonymous", FieldValue::Bool(anonymous)));
+        }
+        if let Some(risks) = self.risks.as_deref().filter(|r| !r.is_empty()) {
+            let risks: Vec<&str> = risks.iter().map(|r| r.as_str()).collect();
+            fields.push(("spur.risks", FieldValue::String(risks.join(","))));
+        }
+        fields
+    }
+}
@@ -0,0 +1,281 @@
+//! Freshness timestamps on IP contexts.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::types::IpContext;
+use crate::expiry::Expiring;
+
+/// When an enrichment was produced and when its address was last observed.
+///
+/// Not part of the API response: the caller fills it in when the context
+/// is fetched, typically from the response `Date` header or the date of
+/// the feed it was read from. With the `chrono` feature,
+/// [`from_headers`](Self::from_headers) and
+/// [`from_feed_date`](Self::from_feed_date) do the parsing.
+///
+/// [`IpContext`] skips it when serializing and deserializing, so keys of
+/// the same name in a response never affect parsing. On its own it
+/// serializes with timestamps as whole seconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Freshness {
+    /// When the enrichment was produced.
+    #[serde(skip_serializing_if = "Option::is_none", with = "unix_secs")]
+    pub as_of: Option<SystemTime>,
+
+    /// When the address was last observed with this enrichment, where the
+    /// source reports it.
+    #[serde(skip_serializing_if = "Option::is_none", with = "unix_secs")]
+    pub last_seen: Option<SystemTime>,
+}
+
+impl Freshness {
+    /// Freshness of an enrichment produced at `as_of`.
+    pub fn new(as_of: SystemTime) -> Self {
+        Self {
+            as_of: Some(as_of),
+            last_seen: None,
+        }
+    }
+
+    /// Set when the address was last observed.
+    pub fn with_last_seen(mut self, last_seen: SystemTime) -> Self {
+        self.last_seen = Some(last_seen);
+        self
+    }
+
+    /// Returns how old the enrichment is at `now`, or `None` if `as_of` is
+    /// not set. A time after `now` counts as age zero.
+    pub fn age(&self, now: SystemTime) -> Option<Duration> {
+        self.as_of
+            .map(|as_of| now.duration_since(as_of).unwrap_or_default())
+    }
+
+    /// Freshness from the headers of an API response: `as_of` is the
+    /// `Date` header.
+    ///
+    /// `headers` is every `(name, value)` pair on the response; names are
+    /// matched case-insensitively. Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn from_headers<I, K, V>(headers: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let as_of = headers
+            .into_iter()
+            .find(|(name, _)| name.as_ref().eq_ignore_ascii_case("date"))
+            .and_then(|(_, value)| parse_timestamp(value.as_ref()));
+        Self {
+            as_of: as_of.map(SystemTime::from),
+            last_seen: None,
+        }
+    }
+
+    /// Freshness of a context read from the feed dated `date`, such as
+    /// `2024-01-15`. Returns `None` if `date` does not parse with
+    /// [`parse_timestamp`]. Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn from_feed_date(date: &str) -> Option<Self> {
+        parse_timestamp(date).map(|as_of| Self::new(as_of.into()))
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Freshness {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut time = || -> arbitrary::Result<Option<SystemTime>> {
+            let secs = Option::<u32>::arbitrary(u)?;
+            Ok(secs.map(|secs| UNIX_EPOCH + Duration::from_secs(secs.into())))
+        };
+        Ok(Self {
+            as_of: time()?,
+            last_seen: time()?,
+        })
+    }
+}
+
+impl IpContext {
+    /// Returns how old the enrichment is at `now`, based on
+    /// [`Freshness::as_of`].
+    ///
+    /// Returns `None` if the context carries no `as_of` time.
+    pub fn age(&self, now: SystemTime) -> Option<Duration> {
+        self.freshness.and_then(|freshness| freshness.age(now))
+    }
+
+    /// Returns the context with its [`Freshness::as_of`] time and `ttl`, for
+    /// checking staleness the same way as cached entries and history.
+    ///
+    /// Returns `None` if the context carries no `as_of` time; whether such a
+    /// context counts as stale is up to the caller.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use spur::context::Freshness;
+    /// use spur::IpContext;
+    ///
+    /// let fetched_at = UNIX_EPOCH + Duration::from_secs(1_705_305_600);
+    /// let context = IpContext {
+    ///     freshness: Some(Freshness::new(fetched_at)),
+    ///     ..Default::default()
+    /// };
+    /// let now = fetched_at + Duration::from_secs(25 * 3600);
+    ///
+    /// let day = context.expiring(Some(Duration::from_secs(24 * 3600))).unwrap();
+    /// assert!(day.is_expired(now));
+    /// let two_days = context.expiring(Some(Duration::from_secs(48 * 3600))).unwrap();
+    /// assert!(!two_days.is_expired(now));
+    /// assert!(IpContext::default().expiring(None).is_none());
+    /// ```
+    pub fn expiring(&self, ttl: Option<Duration>) -> Option<Expiring<&IpContext>> {
+        let as_of = self.freshness?.as_of?;
+        Some(Expiring::new(self, as_of, ttl))
+    }
+}
+
+/// Parse a timestamp for [`Freshness`] (requires the `chrono` feature).
+///
+/// Accepts the formats the timestamps usually arrive in:
+///
+/// - HTTP dates from `Date` / `Last-Modified` headers
+///   (`Mon, 15 Jan 2024 08:00:00 GMT`)
+/// - RFC 3339 (`2024-01-15T08:00:00Z`)
+/// - Feed dates (`2024-01-15` or `20240115`), taken as midnight UTC
+///
+/// Returns `None` for anything else.
+#[cfg(feature = "chrono")]
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    if let Ok(time) = DateTime::parse_from_rfc2822(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y%m%d"))
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|time| time.and_utc())
+}
+
+/// Serializes an optional `SystemTime` as whole seconds since the Unix
+/// epoch.
+mod unix_secs {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        time: &Option<SystemTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match time {
+            Some(time) => {
+                let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+                serializer.serialize_u64(secs.as_secs())
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<SystemTime>, D::Error> {
+        Option::<u64>::deserialize(deserializer)
+            .map(|secs| secs.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_parse_timestamp_formats() {
+        let expected = parse_timestamp("2024-01-15T00:00:00Z").unwrap();
+        assert_eq!(
+            parse_timestamp("Mon, 15 Jan 2024 00:00:00 GMT"),
+            Some(expected)
+        );
+        assert_eq!(parse_timestamp("2024-01-15T01:00:00+01:00"), Some(expected));
+        assert_eq!(parse_timestamp("2024-01-15"), Some(expected));
+        assert_eq!(parse_timestamp(" 20240115 "), Some(expected));
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_populate() {
+        let headers = [
+            ("Content-Type", "application/json"),
+            ("date", "Mon, 15 Jan 2024 08:00:00 GMT"),
+        ];
+        assert_eq!(
+            Freshness::from_headers(headers),
+            Freshness::new(at(1_705_305_600))
+        );
+        assert_eq!(Freshness::from_headers([("Date", "soon")]).as_of, None);
+        assert_eq!(
+            Freshness::from_feed_date("20240115"),
+            Some(Freshness::new(at(1_705_276_800)))
+        );
+        assert_eq!(Freshness::from_feed_date("latest"), None);
+    }
+
+    #[test]
+    fn test_age_and_expiry() {
+        let now = at(43_200);
+        let mut context = IpContext::default();
+        assert_eq!(context.age(now), None);
+        assert!(context.expiring(None).is_none());
+
+        context.freshness = Some(Freshness::new(at(0)));
+        assert_eq!(context.age(now), Some(Duration::from_secs(43_200)));
+        let expiring = |secs| context.expiring(Some(Duration::from_secs(secs))).unwrap();
+        assert!(expiring(43_200).is_expired(now));
+        assert!(!expiring(43_201).is_expired(now));
+        assert!(!context.expiring(None).unwrap().is_expired(now));
+        assert_eq!(
+            context.age(at(0) - Duration::from_secs(5)),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_timestamps_round_trip() {
+        let json = r#"{"as_of":1705305600,"last_seen":1705276799}"#;
+        let freshness = Freshness::new(at(1_705_305_600)).with_last_seen(at(1_705_276_799));
+        assert_eq!(serde_json::from_str::<Freshness>(json).unwrap(), freshness);
+        assert_eq!(serde_json::to_string(&freshness).unwrap(), json);
+    }
+
+    #[test]
+    fn test_not_on_the_wire() {
+        let context = IpContext {
+            ip: Some("1.2.3.4".to_string()),
+            freshness: Some(Freshness::new(at(1_705_305_600))),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&context).unwrap(),
+            r#"{"ip":"1.2.3.4"}"#
+        );
+
+        let json = r#"{"as_of": "2024-01-15T08:00:00Z", "freshness": {"as_of": "soon"}}"#;
+        let parsed: IpContext = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.freshness, None);
+    }
+}
@@ -0,0 +1,189 @@
+//! Curated context fields for the `log` and `slog` crates (requires the
+//! `log` or `slog` feature).
+
+use super::fields::FieldValue;
+use super::types::IpContext;
+
+/// The curated fields of a context as structured key-values, from
+/// [`IpContext::log_fields`].
+///
+/// Implements `log::kv::Source` with the `log` feature and `slog::KV` with
+/// the `slog` feature. The keys and values are the ones
+/// `IpContext::record_on` writes to tracing spans (see `TRACING_FIELDS`,
+/// with the `tracing` feature), so logs look the same whichever framework a
+/// service uses.
+#[derive(Debug, Clone)]
+pub struct LogFields<'a> {
+    fields: Vec<(&'static str, FieldValue<'a>)>,
+}
+
+impl IpContext {
+    /// Returns the curated fields of this context for `log` or `slog`.
+    ///
+    /// Absent values are left out.
+    pub fn log_fields(&self) -> LogFields<'_> {
+        LogFields {
+            fields: self.curated_fields(),
+        }
+    }
+}
+
+impl LogFields<'_> {
+    /// Returns the number of fields present.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Returns `true` if the context had none of the curated fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+/// Attach the fields to a `log` record:
+///
+/// ```rust
+/// use spur::test_utils::fixtures;
+///
+/// let context = fixtures::vpn_ip();
+/// let fields = context.log_fields();
+/// log::logger().log(
+///     &log::Record::builder()
+///         .level(log::Level::Info)
+///         .args(format_args!("login blocked"))
+///         .key_values(&fields)
+///         .build(),
+/// );
+/// ```
+#[cfg(feature = "log")]
+impl log::kv::Source for LogFields<'_> {
+    fn visit<'kvs>(
+        &'kvs self,
+        visitor: &mut dyn log::kv::VisitSource<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        use log::kv::{Key, Value};
+
+        for (name, value) in &self.fields {
+            let value = match value {
+                FieldValue::Str(value) => Value::from(*value),
+                FieldValue::String(value) => Value::from(value.as_str()),
+                FieldValue::U64(value) => Value::from(*value),
+                FieldValue::Bool(value) => Value::from(*value),
+            };
+            visitor.visit_pair(Key::from_str(name), value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Pass the fields to a `slog` macro after the message:
+///
+/// ```rust
+/// use spur::test_utils::fixtures;
+///
+/// let logger = slog::Logger::root(slog::Discard, slog::o!());
+/// slog::info!(logger, "login blocked"; fixtures::vpn_ip().log_fields());
+/// ```
+#[cfg(feature = "slog")]
+impl slog::KV for LogFields<'_> {
+    fn serialize(
+        &self,
+        _record: &slog::Record<'_>,
+        serializer: &mut dyn slog::Serializer,
+    ) -> slog::Result {
+        for (name, value) in &self.fields {
+            match value {
+                FieldValue::Str(value) => serializer.emit_str(name, value)?,
+                FieldValue::String(value) => serializer.emit_str(name, value)?,
+                FieldValue::U64(value) => serializer.emit_u64(name, *value)?,
+                FieldValue::Bool(value) => serializer.emit_bool(name, *value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::IpContextBuilder;
+    use crate::{Infrastructure, IpContext, Risk};
+
+    fn context() -> IpContext {
+        IpContextBuilder::new()
+            .infrastructure(Infrastructure::Datacenter)
+            .asn(9009, "M247")
+            .location("NL", None)
+            .vpn("NORD_VPN")
+            .risks(vec![Risk::Tunnel, Risk::Spam])
+            .build()
+    }
+
+    const EXPECTED: &[(&str, &str)] = &[
+        ("spur.infrastructure", "DATACENTER"),
+        ("spur.asn", "9009"),
+        ("spur.country", "NL"),
+        ("spur.tunnel_type", "VPN"),
+        ("spur.operator", "NORD_VPN"),
+        ("spur.anonymous", "true"),
+        ("spur.risks", "TUNNEL,SPAM"),
+    ];
+
+    #[test]
+    fn test_empty_context() {
+        assert!(IpContext::default().log_fields().is_empty());
+        assert_eq!(context().log_fields().len(), EXPECTED.len());
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_log_source() {
+        use log::kv::{Error, Key, Source, Value, VisitSource};
+
+        struct Collect(Vec<(String, String)>);
+
+        impl<'kvs> VisitSource<'kvs> for Collect {
+            fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+                self.0.push((key.to_string(), value.to_string()));
+                Ok(())
+            }
+        }
+
+        let context = context();
+        let mut collect = Collect(Vec::new());
+        context.log_fields().visit(&mut collect).unwrap();
+        let expected: Vec<_> = EXPECTED
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(collect.0, expected);
+    }
+
+    #[cfg(feature = "slog")]
+    #[test]
+    fn test_slog_kv() {
+        use std::fmt;
+
+        use slog::{Key, Serializer, KV};
+
+        struct Collect(Vec<(String, String)>);
+
+        impl Serializer for Collect {
+            fn emit_arguments(&mut self, key: Key, value: &fmt::Arguments<'_>) -> slog::Result {
+                self.0.push((key.to_string(), value.to_string()));
+                Ok(())
+            }
+        }
+
+        let context = context();
+        let fields = context.log_fields();
+        let mut collect = Collect(Vec::new());
+        let args = format_args!("");
+        let record = slog::record!(slog::Level::Info, "", &args, slog::b!());
+        fields.serialize(&record, &mut collect).unwrap();
+        let expected: Vec<_> = EXPECTED
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(collect.0, expected);
+    }
+}
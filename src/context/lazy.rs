@@ -0,0 +1,195 @@
+//! Partially parsed IP contexts.
+
+use std::cell::OnceCell;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+
+use super::enums::{Infrastructure, Risk, Service};
+use super::freshness::Freshness;
+use super::types::{
+    deserialize_delimited, Ai, AutonomousSystem, Client, IpContext, Location, Tunnel,
+};
+
+/// An [`IpContext`] whose heavy sections are parsed on first access.
+///
+/// The small top-level fields are parsed up front. `client`, `location` and
+/// `tunnels` are only validated as JSON and kept as slices of the input;
+/// each is deserialized the first time its accessor is called and cached
+/// afterwards. Hot paths that only look at `infrastructure` and `risks`
+/// skip most of the deserialization work.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::context::IpContextLazy;
+/// use spur::Infrastructure;
+///
+/// let json = r#"{
+///     "ip": "89.39.106.191",
+///     "infrastructure": "DATACENTER",
+///     "risks": ["TUNNEL"],
+///     "tunnels": [{ "type": "VPN", "operator": "NordVPN" }]
+/// }"#;
+///
+/// let context = IpContextLazy::parse(json).unwrap();
+/// if context.infrastructure == Some(Infrastructure::Datacenter) {
+///     let tunnels = context.tunnels().unwrap().unwrap();
+///     assert_eq!(tunnels[0].operator.as_deref(), Some("NordVPN"));
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct IpContextLazy<'a> {
+    /// AI activity observed from this IP address.
+    pub ai: Option<Ai>,
+
+    /// BGP autonomous system information.
+    #[serde(rename = "as", alias = "autonomous_system")]
+    pub autonomous_system: Option<AutonomousSystem>,
+
+    /// When the enrichment was produced and last observed. Never parsed;
+    /// set by the caller.
+    #[serde(skip)]
+    pub freshness: Option<Freshness>,
+
+    /// Infrastructure type classification.
+    pub infrastructure: Option<Infrastructure>,
+
+    /// IPv4 or IPv6 address associated with the connection.
+    pub ip: Option<String>,
+
+    /// The organization currently assigned to use the IP address.
+    pub organization: Option<String>,
+
+    /// List of identified risk factors or behaviors.
+    #[serde(deserialize_with = "deserialize_delimited")]
+    pub risks: Option<Vec<Risk>>,
+
+    /// List of services or protocols in use.
+    #[serde(deserialize_with = "deserialize_delimited")]
+    pub services: Option<Vec<Service>>,
+
+    #[serde(borrow, rename = "client")]
+    raw_client: Option<&'a RawValue>,
+    #[serde(borrow, rename = "location")]
+    raw_location: Option<&'a RawValue>,
+    #[serde(borrow, rename = "tunnels")]
+    raw_tunnels: Option<&'a RawValue>,
+
+    #[serde(skip)]
+    client: OnceCell<Option<Client>>,
+    #[serde(skip)]
+    location: OnceCell<Option<Location>>,
+    #[serde(skip)]
+    tunnels: OnceCell<Option<Vec<Tunnel>>>,
+}
+
+impl<'a> IpContextLazy<'a> {
+    /// Parse the top-level fields of `json`, deferring the heavy sections.
+    ///
+    /// Malformed JSON anywhere in the document is still an error; type
+    /// errors inside a deferred section are reported by its accessor.
+    pub fn parse(json: &'a str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Descriptive data about the connecting client, parsed on first call.
+    pub fn client(&self) -> serde_json::Result<Option<&Client>> {
+        section(&self.client, self.raw_client).map(Option::as_ref)
+    }
+
+    /// Geolocation of the IP, parsed on first call.
+    pub fn location(&self) -> serde_json::Result<Option<&Location>> {
+        section(&self.location, self.raw_location).map(Option::as_ref)
+    }
+
+    /// Tunnels in use, parsed on first call.
+    pub fn tunnels(&self) -> serde_json::Result<Option<&[Tunnel]>> {
+        section(&self.tunnels, self.raw_tunnels).map(Option::as_deref)
+    }
+
+    /// Parse every deferred section and return the full [`IpContext`].
+    pub fn into_context(self) -> serde_json::Result<IpContext> {
+        Ok(IpContext {
+            client: take(self.client, self.raw_client)?,
+            location: take(self.location, self.raw_location)?,
+            tunnels: take(self.tunnels, self.raw_tunnels)?,
+            ai: self.ai,
+            autonomous_system: self.autonomous_system,
+            freshness: self.freshness,
+            infrastructure: self.infrastructure,
+            ip: self.ip,
+            organization: self.organization,
+            risks: self.risks,
+            services: self.services,
+        })
+    }
+}
+
+/// Returns the cached section, parsing `raw` on the first call.
+///
+/// Errors are not cached, so a failing section fails on every call.
+fn section<'c, T: DeserializeOwned>(
+    cell: &'c OnceCell<Option<T>>,
+    raw: Option<&RawValue>,
+) -> serde_json::Result<&'c Option<T>> {
+    if let Some(value) = cell.get() {
+        return Ok(value);
+    }
+    let value = parse_raw(raw)?;
+    Ok(cell.get_or_init(|| value))
+}
+
+fn take<T: DeserializeOwned>(
+    cell: OnceCell<Option<T>>,
+    raw: Option<&RawValue>,
+) -> serde_json::Result<Option<T>> {
+    match cell.into_inner() {
+        Some(value) => Ok(value),
+        None => parse_raw(raw),
+    }
+}
+
+fn parse_raw<T: DeserializeOwned>(raw: Option<&RawValue>) -> serde_json::Result<Option<T>> {
+    raw.map(|raw| serde_json::from_str(raw.get())).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_json, fixture_names};
+
+    #[test]
+    fn test_lazy_matches_full_parse() {
+        for name in fixture_names() {
+            let json = fixture_json(name).unwrap();
+            let full: IpContext = serde_json::from_str(json).unwrap();
+            let lazy = IpContextLazy::parse(json).unwrap();
+
+            assert_eq!(lazy.infrastructure, full.infrastructure, "{}", name);
+            assert_eq!(lazy.client().unwrap(), full.client.as_ref(), "{}", name);
+            assert_eq!(lazy.tunnels().unwrap(), full.tunnels.as_deref(), "{}", name);
+            assert_eq!(lazy.into_context().unwrap(), full, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_sections_are_parsed_on_access() {
+        let json = r#"{"ip": "1.2.3.4", "risks": "TUNNEL", "client": {"count": "many"}}"#;
+        let lazy = IpContextLazy::parse(json).unwrap();
+
+        assert_eq!(lazy.risks, Some(vec![Risk::Tunnel]));
+        assert!(lazy.location().unwrap().is_none());
+        assert!(lazy.client().is_err());
+        assert!(lazy.into_context().is_err());
+        assert!(IpContextLazy::parse(r#"{"client": {"#).is_err());
+    }
+
+    #[test]
+    fn test_null_sections() {
+        let lazy = IpContextLazy::parse(r#"{"tunnels": null}"#).unwrap();
+        assert!(lazy.tunnels().unwrap().is_none());
+    }
+}
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// All fields may be omitted if their value is null.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(default, rename_all = "camelCase")]
 pub struct TagMetadata {
     /// Whether the service supports or facilitates crypto-based payments or platforms.
@@ -90,6 +91,7 @@ pub struct TagMetadata {
 
 /// Metrics and statistics for a tagged service.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(default, rename_all = "camelCase")]
 pub struct TagMetrics {
     /// Average number of devices observed.
@@ -9,12 +9,22 @@
 //! | Type | Purpose |
 //! |------|---------|
 //! | [`IpContext`] | Complete IP address intelligence (main response type) |
+//! | [`IpContextLazy`] | `IpContext` with `client`, `location` and `tunnels` parsed on demand |
 //! | [`Tunnel`] | VPN/proxy/Tor tunnel information |
 //! | [`Location`] | Geographic location data |
+//! | [`CountryCode`] | ISO 3166-1 alpha-2 code, with names and flags under `i18n` |
 //! | [`AutonomousSystem`] | BGP AS number and organization |
 //! | [`Client`] | Client behavior and device information |
+//! | [`Freshness`] | When an enrichment was produced and last observed, set by the caller |
 //! | [`TagMetadata`] | Service tag metadata and metrics |
+//! | [`TagRef`] | A service tag referenced by a context, for resolving its metadata |
 //! | [`ApiStatus`] | API account status and quota |
+//! | [`RiskView`] / [`GeoView`] | Borrowed views of the risk and geographic parts of a context |
+//! | [`LookupOutcome`] | A lookup result that tells "no data" apart from an all-null context |
+//! | [`SharingProfile`] | Fields kept by `IpContext::sanitized_for_partner` when sharing intel |
+//! | [`ContextVersion`] | API version of an archived response (see [`parse_versioned`]) |
+//! | [`ParseError`] | Parse failure from `IpContext::from_slice`, with its byte offset |
+//! | [`Envelope`] | A body wrapped as `{"data": ..., "meta": ...}` by a gateway |
 //!
 //! ## Strongly Typed Enums
 //!
@@ -24,12 +34,26 @@
 //! | [`Risk`] | Risk factors (Tunnel, Spam, CallbackProxy, GeoMismatch) |
 //! | [`Service`] | Protocols (OpenVpn, Ipsec, Wireguard, Ssh) |
 //! | [`TunnelType`] | Tunnel type (Vpn, Proxy, Tor) |
+//! | [`TunnelClass`] | Derived tunnel class (commercial or corporate VPN, hosting proxy, Tor) |
+//! | [`Severity`] | How serious a risk is (Low, Medium, High), for ordering findings |
 //! | [`Behavior`] | Client behaviors (FileSharing, TorProxyUser) |
 //! | [`DeviceType`] | Device types (Mobile, Desktop) |
 //!
 //! All enums include an `Other(String)` variant for forward compatibility
 //! with new API values.
 //!
+//! [`IpContext::freshness`] carries [`Freshness`] timestamps for judging
+//! how fresh an enrichment is; they are filled in by the caller. With the
+//! `chrono` feature, `Freshness::from_headers`, `Freshness::from_feed_date`
+//! and `parse_timestamp` parse them from response headers and feed dates.
+//!
+//! With the `tracing` feature, `IpContext::record_on` records a fixed set of
+//! low-cardinality fields onto a span, named as listed in `TRACING_FIELDS`.
+//! With the `tz` feature, `Location::timezone` infers the IANA time zone of
+//! a location for checks that combine Spur geo with local timestamps.
+//! With the `log` or `slog` feature, `IpContext::log_fields` carries the
+//! same fields as structured key-values for those crates.
+//!
 //! ## Example
 //!
 //! ```rust
@@ -45,12 +69,60 @@
 //! assert_eq!(context.infrastructure, Some(Infrastructure::Datacenter));
 //! ```
 
+mod concentration;
+mod country;
 mod enums;
+#[cfg(feature = "json")]
+mod envelope;
+#[cfg(any(feature = "tracing", feature = "log", feature = "slog"))]
+mod fields;
+mod freshness;
+#[cfg(any(feature = "log", feature = "slog"))]
+mod kv;
+#[cfg(feature = "json")]
+mod lazy;
 mod metadata;
+mod outcome;
+#[cfg(feature = "json")]
+mod parse;
+mod proxy;
+mod sanitize;
+mod severity;
 mod status;
+mod tags;
+#[cfg(feature = "tz")]
+mod timezone;
+#[cfg(feature = "tracing")]
+mod trace;
+mod tunnel_class;
 mod types;
+#[cfg(feature = "json")]
+mod version;
+mod views;
 
+pub use country::*;
 pub use enums::*;
+#[cfg(feature = "json")]
+pub use envelope::*;
+pub use freshness::*;
+#[cfg(any(feature = "log", feature = "slog"))]
+pub use kv::*;
+#[cfg(feature = "json")]
+pub use lazy::*;
 pub use metadata::*;
+pub use outcome::*;
+#[cfg(feature = "json")]
+pub(crate) use parse::parse_value;
+#[cfg(feature = "json")]
+pub use parse::*;
+pub use sanitize::*;
+pub use severity::*;
 pub use status::*;
+pub use tags::*;
+#[cfg(feature = "tracing")]
+pub use trace::*;
+pub use tunnel_class::*;
 pub use types::*;
+#[cfg(feature = "json")]
+pub use version::*;
+pub use views::*;
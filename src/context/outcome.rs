@@ -0,0 +1,149 @@
+//! Lookup results that tell "no data" apart from an empty context.
+
+use std::net::IpAddr;
+
+use super::types::IpContext;
+
+/// The result of looking up one address.
+///
+/// For addresses Spur has no data on (for example unrouted ones), the API
+/// answers with an empty or omitted body. Parsing that as an [`IpContext`]
+/// gives a context with every field `None`, which looks the same as a
+/// response whose fields all happened to be null. [`LookupOutcome::from_body`]
+/// keeps the two apart.
+// Boxing the context would cost an allocation on every successful lookup.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum LookupOutcome {
+    /// Spur returned a context.
+    Found(IpContext),
+    /// Spur returned no data for the address.
+    NoData,
+    /// The address is in a private or reserved range and was not looked
+    /// up; holds the range description from
+    /// [`ip_utils::bogon_range`](crate::ip_utils::bogon_range).
+    Bogon(&'static str),
+}
+
+impl LookupOutcome {
+    /// Returns the outcome for `ip` without a lookup, if one is not needed.
+    ///
+    /// Private, loopback, link-local, multicast and other reserved
+    /// addresses give [`Bogon`](Self::Bogon), so internal traffic does not
+    /// spend quota. Returns `None` for addresses worth looking up.
+    ///
+    /// ```rust
+    /// use spur::LookupOutcome;
+    ///
+    /// let internal = "192.168.1.20".parse().unwrap();
+    /// assert_eq!(LookupOutcome::prefilter(internal), Some(LookupOutcome::Bogon("private")));
+    /// assert_eq!(LookupOutcome::prefilter("89.39.106.191".parse().unwrap()), None);
+    /// ```
+    pub fn prefilter(ip: IpAddr) -> Option<Self> {
+        crate::ip_utils::bogon_range(ip).map(Self::Bogon)
+    }
+
+    /// Parse a response body.
+    ///
+    /// An empty or whitespace-only body, `null` and `{}` are
+    /// [`NoData`](Self::NoData); any other JSON object is parsed as a
+    /// context, even if all of its fields are null.
+    ///
+    /// ```rust
+    /// use spur::LookupOutcome;
+    ///
+    /// assert_eq!(LookupOutcome::from_body(b"").unwrap(), LookupOutcome::NoData);
+    /// assert_eq!(LookupOutcome::from_body(b"{}").unwrap(), LookupOutcome::NoData);
+    ///
+    /// let outcome = LookupOutcome::from_body(br#"{"ip": "1.2.3.4"}"#).unwrap();
+    /// assert_eq!(outcome.context().unwrap().ip.as_deref(), Some("1.2.3.4"));
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_body(body: &[u8]) -> Result<Self, super::ParseError> {
+        let trimmed = trim(body);
+        let is_empty_object = trimmed.len() >= 2
+            && trimmed[0] == b'{'
+            && trimmed[trimmed.len() - 1] == b'}'
+            && trim(&trimmed[1..trimmed.len() - 1]).is_empty();
+        if trimmed.is_empty() || trimmed == b"null" || is_empty_object {
+            return Ok(Self::NoData);
+        }
+        IpContext::from_slice(body).map(Self::Found)
+    }
+
+    /// Returns the context, if Spur returned one.
+    pub fn context(&self) -> Option<&IpContext> {
+        match self {
+            Self::Found(context) => Some(context),
+            Self::NoData | Self::Bogon(_) => None,
+        }
+    }
+
+    /// Returns the context, if Spur returned one.
+    pub fn into_context(self) -> Option<IpContext> {
+        match self {
+            Self::Found(context) => Some(context),
+            Self::NoData | Self::Bogon(_) => None,
+        }
+    }
+
+    /// Returns `true` if Spur returned no data.
+    pub fn is_no_data(&self) -> bool {
+        matches!(self, Self::NoData)
+    }
+}
+
+/// Strips leading and trailing ASCII whitespace.
+#[cfg(feature = "json")]
+fn trim(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace());
+    let end = bytes.iter().rposition(|b| !b.is_ascii_whitespace());
+    match (start, end) {
+        (Some(start), Some(end)) => &bytes[start..=end],
+        _ => &[],
+    }
+}
+
+impl From<Option<IpContext>> for LookupOutcome {
+    fn from(context: Option<IpContext>) -> Self {
+        context.map_or(Self::NoData, Self::Found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_data_bodies() {
+        for body in ["", "  \n", "null", "{}", " { \n } "] {
+            let outcome = LookupOutcome::from_body(body.as_bytes()).unwrap();
+            assert!(outcome.is_no_data(), "{:?}", body);
+            assert_eq!(outcome.context(), None);
+        }
+    }
+
+    #[test]
+    fn test_all_null_fields_are_found() {
+        let outcome = LookupOutcome::from_body(br#"{"ip": null, "risks": null}"#).unwrap();
+        assert_eq!(outcome, LookupOutcome::Found(IpContext::default()));
+        assert_eq!(outcome.into_context(), Some(IpContext::default()));
+    }
+
+    #[test]
+    fn test_prefilter() {
+        let outcome = LookupOutcome::prefilter("::ffff:10.0.0.1".parse().unwrap()).unwrap();
+        assert_eq!(outcome, LookupOutcome::Bogon("private"));
+        assert_eq!(outcome.context(), None);
+        assert!(!outcome.is_no_data());
+        assert_eq!(LookupOutcome::prefilter("1.1.1.1".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn test_invalid_body() {
+        let err = LookupOutcome::from_body(b"{\"ip\": 5}").unwrap_err();
+        assert_eq!(err.path(), Some("ip"));
+        assert!(LookupOutcome::from_body(b"{").is_err());
+        assert_eq!(LookupOutcome::from(None), LookupOutcome::NoData);
+    }
+}
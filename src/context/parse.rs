@@ -0,0 +1,239 @@
+//! Parsing contexts from bytes and readers, with error positions.
+
+use std::fmt;
+use std::io::{self, Read};
+
+use serde::de::DeserializeOwned;
+
+use super::types::IpContext;
+
+/// Error returned by [`IpContext::from_slice`],
+/// [`IpContext::from_reader`] and [`IpContext::from_enveloped_json`].
+///
+/// Adds the path of the offending field (e.g. `tunnels[0].entries[2]`) and
+/// the byte offset of the failure to the line and column that `serde_json`
+/// reports, so a failure on a novel payload can be diagnosed from a log line
+/// and the offending part of a body or file cut out directly.
+#[derive(Debug)]
+pub struct ParseError {
+    source: serde_json::Error,
+    path: Option<String>,
+    offset: Option<usize>,
+}
+
+impl ParseError {
+    fn new(source: serde_json::Error, input: &[u8]) -> Self {
+        let offset = (!source.is_io()).then(|| byte_offset(input, source.line(), source.column()));
+        Self {
+            source,
+            path: None,
+            offset,
+        }
+    }
+
+    /// Returns the path of the field that failed to parse, using JSON field
+    /// names, or `None` if the failure was not inside any field.
+    ///
+    /// ```rust
+    /// use spur::IpContext;
+    ///
+    /// let json = br#"{"tunnels": [{"entries": ["1.1.1.1", "2.2.2.2", 3]}]}"#;
+    /// let err = IpContext::from_slice(json).unwrap_err();
+    /// assert_eq!(err.path(), Some("tunnels[0].entries[2]"));
+    /// ```
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Returns the zero-based byte offset of the last byte read before the
+    /// error was detected, or `None` for I/O errors and errors from
+    /// streaming parsers.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Returns the one-based line of the error, as reported by
+    /// `serde_json`.
+    pub fn line(&self) -> usize {
+        self.source.line()
+    }
+
+    /// Returns the one-based column of the error, as reported by
+    /// `serde_json`.
+    pub fn column(&self) -> usize {
+        self.source.column()
+    }
+
+    /// Returns `true` if reading the input failed, rather than parsing it.
+    pub fn is_io(&self) -> bool {
+        self.source.is_io()
+    }
+
+    /// Returns the underlying `serde_json` error.
+    pub fn into_inner(self) -> serde_json::Error {
+        self.source
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}: ", path)?;
+        }
+        write!(f, "{}", self.source)?;
+        if let Some(offset) = self.offset {
+            write!(f, " (byte {})", offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<ParseError> for io::Error {
+    fn from(e: ParseError) -> Self {
+        e.source.into()
+    }
+}
+
+/// Converts `serde_json`'s one-based line and column into a zero-based
+/// byte offset into `input`.
+fn byte_offset(input: &[u8], line: usize, column: usize) -> usize {
+    let line_start = input
+        .iter()
+        .enumerate()
+        .filter(|(_, &b)| b == b'\n')
+        .nth(line.saturating_sub(2))
+        .map_or(0, |(i, _)| if line > 1 { i + 1 } else { 0 });
+    (line_start + column.saturating_sub(1)).min(input.len().saturating_sub(1))
+}
+
+/// Parse `bytes` as a `T`, recording the path and offset of any failure.
+pub(super) fn parse_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ParseError> {
+    let mut de = serde_json::Deserializer::from_slice(bytes);
+    let value = serde_path_to_error::deserialize(&mut de).map_err(|e| {
+        let path = field_path(&e);
+        ParseError {
+            path,
+            ..ParseError::new(e.into_inner(), bytes)
+        }
+    })?;
+    de.end().map_err(|e| ParseError::new(e, bytes))?;
+    Ok(value)
+}
+
+/// Parse one `T` from the front of `reader`, recording the path of any
+/// failure.
+///
+/// Nothing after the value is read as long as it is a JSON object or array,
+/// so the caller can go on reading whatever follows it.
+pub(crate) fn parse_value<T: DeserializeOwned, R: Read>(reader: R) -> Result<T, ParseError> {
+    let mut de = serde_json::Deserializer::from_reader(reader);
+    serde_path_to_error::deserialize(&mut de).map_err(|e| ParseError {
+        path: field_path(&e),
+        source: e.into_inner(),
+        offset: None,
+    })
+}
+
+/// Returns the path of the failing field, if the failure was inside one.
+fn field_path(e: &serde_path_to_error::Error<serde_json::Error>) -> Option<String> {
+    // A path of only unknown segments (`?`) names no field.
+    e.path()
+        .iter()
+        .any(|s| !matches!(s, serde_path_to_error::Segment::Unknown))
+        .then(|| e.path().to_string())
+}
+
+impl IpContext {
+    /// Parse a context from JSON bytes, such as an HTTP response body.
+    ///
+    /// ```rust
+    /// use spur::IpContext;
+    ///
+    /// let context = IpContext::from_slice(br#"{"ip": "1.2.3.4"}"#).unwrap();
+    /// assert_eq!(context.ip.as_deref(), Some("1.2.3.4"));
+    ///
+    /// let err = IpContext::from_slice(br#"{"ip": 5}"#).unwrap_err();
+    /// assert_eq!(err.offset(), Some(7));
+    /// ```
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, ParseError> {
+        parse_slice(bytes)
+    }
+
+    /// Parse a context from a reader, such as a file or a streaming body.
+    ///
+    /// The input is read to the end first, which is faster than parsing
+    /// byte by byte from the reader and lets errors report their byte
+    /// offset.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, ParseError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|e| ParseError::new(serde_json::Error::io(e), &bytes))?;
+        Self::from_slice(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_reader() {
+        let json = br#"{"ip": "1.2.3.4", "infrastructure": "DATACENTER"}"#;
+        let context = IpContext::from_reader(&json[..]).unwrap();
+        assert_eq!(context, IpContext::from_slice(json).unwrap());
+    }
+
+    #[test]
+    fn test_error_offsets() {
+        let json = b"{\n  \"ip\": \"1.2.3.4\",\n  \"risks\": [7]\n}";
+        let err = IpContext::from_slice(json).unwrap_err();
+        assert_eq!((err.line(), err.column()), (3, 13));
+        assert_eq!(err.offset(), Some(33));
+        assert_eq!(json[33], b'7');
+        assert_eq!(err.path(), Some("risks[0]"));
+        assert!(err.to_string().starts_with("risks[0]: "));
+        assert!(err.to_string().ends_with("(byte 33)"));
+
+        let err = IpContext::from_reader(&b"{\"ip\": \"x\""[..]).unwrap_err();
+        assert_eq!(err.offset(), Some(9));
+        assert_eq!(err.path(), None);
+        assert_eq!(IpContext::from_slice(b"").unwrap_err().offset(), Some(0));
+    }
+
+    #[test]
+    fn test_error_paths() {
+        let cases: [(&[u8], Option<&str>); 4] = [
+            (br#"{"as": {"number": "x"}}"#, Some("as.number")),
+            (
+                br#"{"location": {"latitude": true}}"#,
+                Some("location.latitude"),
+            ),
+            (br#"{"client": {"count": -1}}"#, Some("client.count")),
+            (br#"{"ip": "1.2.3.4"} trailing"#, None),
+        ];
+        for (json, path) in cases {
+            assert_eq!(IpContext::from_slice(json).unwrap_err().path(), path);
+        }
+    }
+
+    #[test]
+    fn test_io_error() {
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"))
+            }
+        }
+        let err = IpContext::from_reader(Failing).unwrap_err();
+        assert!(err.is_io());
+        assert_eq!(err.offset(), None);
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::ConnectionReset);
+    }
+}
@@ -0,0 +1,152 @@
+//! Residential proxy likelihood heuristic.
+
+use super::enums::{Infrastructure, Risk};
+use super::types::{Concentration, IpContext};
+
+/// Weight of known proxy networks among the clients or in the risks.
+const PROXY_TAG_WEIGHT: f32 = 0.45;
+/// Weight of the number of distinct clients.
+const CLIENT_COUNT_WEIGHT: f32 = 0.2;
+/// Weight of the number of distinct client countries.
+const COUNTRIES_WEIGHT: f32 = 0.2;
+/// Weight of clients concentrated away from the IP's own location.
+const SKEW_WEIGHT: f32 = 0.15;
+
+/// Client count at which the client count signal saturates.
+const SATURATING_CLIENTS: f32 = 100.0;
+/// Number of client countries at which the countries signal saturates.
+const SATURATING_COUNTRIES: f32 = 5.0;
+/// Concentration skew, in kilometers, at which the skew signal saturates.
+const SATURATING_SKEW_KM: f32 = 1000.0;
+
+impl IpContext {
+    /// Returns how likely this address is a residential proxy exit, from
+    /// `0.0` to `1.0`.
+    ///
+    /// Four signals are combined as a weighted sum:
+    ///
+    /// - known proxy networks in `client.proxies`, or a `CALLBACK_PROXY` or
+    ///   `RESIDENTIAL_PROXY` risk (weight 0.45)
+    /// - many distinct clients, saturating at 100 (0.2)
+    /// - clients from several countries, saturating at 5 (0.2)
+    /// - clients concentrated far from the IP's location: the concentration
+    ///   skew, saturating at 1000 km, or a concentration country other than
+    ///   the location's, scaled by density (0.15)
+    ///
+    /// The sum is then scaled by infrastructure: residential addresses keep
+    /// it in full, mobile ones (where carrier NAT inflates client counts)
+    /// 60%, unknown ones half and datacenter or business ones 10%.
+    ///
+    /// This is a heuristic for ranking and thresholds, not a calibrated
+    /// probability.
+    ///
+    /// ```rust
+    /// use spur::test_utils::fixtures;
+    ///
+    /// assert!(fixtures::residential_proxy_ip().residential_proxy_likelihood() > 0.4);
+    /// assert!(fixtures::residential_ip().residential_proxy_likelihood() < 0.1);
+    /// ```
+    pub fn residential_proxy_likelihood(&self) -> f32 {
+        let client = self.client.as_ref();
+
+        let proxy_tagged = client
+            .and_then(|c| c.proxies.as_ref())
+            .is_some_and(|p| !p.is_empty())
+            || self.risks.iter().flatten().any(|r| {
+                *r == Risk::CallbackProxy || r.as_str().eq_ignore_ascii_case("RESIDENTIAL_PROXY")
+            });
+        let proxy_signal = if proxy_tagged { 1.0 } else { 0.0 };
+
+        let count_signal = match client.and_then(|c| c.count) {
+            Some(count) if count > 1 => ((count as f32).ln() / SATURATING_CLIENTS.ln()).min(1.0),
+            _ => 0.0,
+        };
+
+        let countries_signal = match client.and_then(|c| c.countries) {
+            Some(countries) if countries > 1 => {
+                ((countries - 1) as f32 / (SATURATING_COUNTRIES - 1.0)).min(1.0)
+            }
+            _ => 0.0,
+        };
+
+        let skew_signal = client
+            .and_then(|c| c.concentration.as_ref())
+            .map_or(0.0, |c| self.skew_signal(c));
+
+        let score = PROXY_TAG_WEIGHT * proxy_signal
+            + CLIENT_COUNT_WEIGHT * count_signal
+            + COUNTRIES_WEIGHT * countries_signal
+            + SKEW_WEIGHT * skew_signal;
+
+        let infrastructure = match &self.infrastructure {
+            Some(Infrastructure::Residential) => 1.0,
+            Some(Infrastructure::Mobile) => 0.6,
+            None => 0.5,
+            Some(_) => 0.1,
+        };
+        (score * infrastructure).clamp(0.0, 1.0)
+    }
+
+    /// Returns how far from the IP's location its clients are concentrated,
+    /// from `0.0` to `1.0`.
+    fn skew_signal(&self, concentration: &Concentration) -> f32 {
+        let skew = concentration
+            .skew
+            .map_or(0.0, |km| (km as f32 / SATURATING_SKEW_KM).min(1.0));
+        let location = self.location.as_ref().and_then(|l| l.country.as_deref());
+        let elsewhere = match (concentration.country.as_deref(), location) {
+            (Some(a), Some(b)) if !a.eq_ignore_ascii_case(b) => concentration
+                .density
+                .map_or(1.0, |d| d.clamp(0.0, 1.0) as f32),
+            _ => 0.0,
+        };
+        skew.max(elsewhere)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::{Concentration, Infrastructure};
+    use crate::test_utils::{fixtures, ClientBuilder, IpContextBuilder};
+
+    #[test]
+    fn test_fixtures_rank_as_expected() {
+        let likelihood = |c: crate::IpContext| c.residential_proxy_likelihood();
+        let proxy = likelihood(fixtures::residential_proxy_ip());
+        let callback = likelihood(fixtures::callback_proxy_ip());
+        let residential = likelihood(fixtures::residential_ip());
+        let mobile = likelihood(fixtures::mobile_ip());
+        let datacenter_proxy = likelihood(fixtures::proxy_ip());
+
+        assert!(proxy > 0.4, "{}", proxy);
+        assert!(callback > 0.5, "{}", callback);
+        assert_eq!(residential, 0.0);
+        assert!(mobile < 0.15, "{}", mobile);
+        assert!(datacenter_proxy < proxy, "{}", datacenter_proxy);
+    }
+
+    #[test]
+    fn test_all_signals_saturate_at_one() {
+        let client = ClientBuilder::new()
+            .count(10_000)
+            .countries(40)
+            .add_proxy("IPROYAL_PROXY")
+            .concentration(Concentration {
+                skew: Some(5_000),
+                ..Default::default()
+            })
+            .build();
+        let context = IpContextBuilder::new()
+            .infrastructure(Infrastructure::Residential)
+            .with_client(client.clone())
+            .build();
+        assert!((context.residential_proxy_likelihood() - 1.0).abs() < 1e-6);
+
+        let unknown = IpContextBuilder::new().with_client(client).build();
+        assert!((unknown.residential_proxy_likelihood() - 0.5).abs() < 1e-6);
+        assert_eq!(
+            crate::IpContext::default().residential_proxy_likelihood(),
+            0.0
+        );
+    }
+}
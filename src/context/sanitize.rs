@@ -0,0 +1,284 @@
+//! Field-level sanitizing of contexts before sharing them with partners.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{Client, IpContext};
+
+/// A part of an [`IpContext`] that a [`SharingProfile`] can keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SharedField {
+    /// The `ip` address itself.
+    Ip,
+    /// AI activity.
+    Ai,
+    /// Autonomous system number and organization.
+    AutonomousSystem,
+    /// The `organization` name.
+    Organization,
+    /// The infrastructure class.
+    Infrastructure,
+    /// Country, state and city.
+    Location,
+    /// Latitude and longitude; only kept together with `Location`.
+    Coordinates,
+    /// Risk factors.
+    Risks,
+    /// Services (protocols) observed.
+    Services,
+    /// Tunnel types, operators and anonymity.
+    Tunnels,
+    /// Tunnel entry addresses; only kept together with `Tunnels`.
+    TunnelEntries,
+    /// Client counts, countries, spread and device types.
+    ClientStats,
+    /// Client behaviors such as file sharing.
+    ClientBehaviors,
+    /// Proxy networks observed among the clients.
+    ClientProxies,
+    /// Where the clients are concentrated.
+    ClientConcentration,
+    /// The [`Freshness`](super::Freshness) timestamps.
+    Timestamps,
+}
+
+/// The set of fields that may be shared with a partner.
+///
+/// Profiles serialize as a list of field names, so they can live in the
+/// configuration of each data agreement:
+///
+/// ```rust
+/// use spur::{SharedField, SharingProfile};
+///
+/// let profile: SharingProfile = serde_json::from_str(r#"["infrastructure", "risks"]"#).unwrap();
+/// assert!(profile.keeps(SharedField::Risks));
+/// assert!(!profile.keeps(SharedField::ClientBehaviors));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SharingProfile {
+    fields: BTreeSet<SharedField>,
+}
+
+impl SharingProfile {
+    /// A profile that shares nothing.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// A profile that keeps the network classification: the IP, autonomous
+    /// system, infrastructure, risks, services and tunnels, without tunnel
+    /// entries, location or any client data.
+    pub fn infrastructure_and_risks() -> Self {
+        Self::none()
+            .with(SharedField::Ip)
+            .with(SharedField::AutonomousSystem)
+            .with(SharedField::Infrastructure)
+            .with(SharedField::Risks)
+            .with(SharedField::Services)
+            .with(SharedField::Tunnels)
+    }
+
+    /// Keep `field`.
+    pub fn with(mut self, field: SharedField) -> Self {
+        self.fields.insert(field);
+        self
+    }
+
+    /// Do not keep `field`.
+    pub fn without(mut self, field: SharedField) -> Self {
+        self.fields.remove(&field);
+        self
+    }
+
+    /// Returns `true` if the profile keeps `field`.
+    pub fn keeps(&self, field: SharedField) -> bool {
+        self.fields.contains(&field)
+    }
+}
+
+impl FromIterator<SharedField> for SharingProfile {
+    fn from_iter<I: IntoIterator<Item = SharedField>>(iter: I) -> Self {
+        Self {
+            fields: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IpContext {
+    /// Returns a copy of this context with only the fields `profile` keeps.
+    ///
+    /// ```rust
+    /// use spur::test_utils::fixtures;
+    /// use spur::SharingProfile;
+    ///
+    /// let context = fixtures::residential_proxy_ip();
+    /// let shared = context.sanitized_for_partner(&SharingProfile::infrastructure_and_risks());
+    ///
+    /// assert_eq!(shared.infrastructure, context.infrastructure);
+    /// assert_eq!(shared.risks, context.risks);
+    /// assert!(shared.client.is_none());
+    /// assert!(shared.location.is_none());
+    /// ```
+    pub fn sanitized_for_partner(&self, profile: &SharingProfile) -> IpContext {
+        use SharedField as F;
+
+        let keep = |field: SharedField| profile.keeps(field);
+        let mut shared = IpContext::default();
+        if keep(F::Ip) {
+            shared.ip.clone_from(&self.ip);
+        }
+        if keep(F::Ai) {
+            shared.ai.clone_from(&self.ai);
+        }
+        if keep(F::AutonomousSystem) {
+            shared.autonomous_system.clone_from(&self.autonomous_system);
+        }
+        if keep(F::Organization) {
+            shared.organization.clone_from(&self.organization);
+        }
+        if keep(F::Infrastructure) {
+            shared.infrastructure.clone_from(&self.infrastructure);
+        }
+        if keep(F::Location) {
+            shared.location.clone_from(&self.location);
+            if !keep(F::Coordinates) {
+                if let Some(location) = shared.location.as_mut() {
+                    location.latitude = None;
+                    location.longitude = None;
+                }
+            }
+        }
+        if keep(F::Risks) {
+            shared.risks.clone_from(&self.risks);
+        }
+        if keep(F::Services) {
+            shared.services.clone_from(&self.services);
+        }
+        if keep(F::Tunnels) {
+            shared.tunnels.clone_from(&self.tunnels);
+            if !keep(F::TunnelEntries) {
+                for tunnel in shared.tunnels.iter_mut().flatten() {
+                    tunnel.entries = None;
+                }
+            }
+        }
+        if keep(F::Timestamps) {
+            shared.freshness = self.freshness;
+        }
+        shared.client = self
+            .client
+            .as_ref()
+            .map(|client| sanitized_client(client, profile))
+            .filter(|client| *client != Client::default());
+        shared
+    }
+}
+
+fn sanitized_client(client: &Client, profile: &SharingProfile) -> Client {
+    let mut shared = Client::default();
+    if profile.keeps(SharedField::ClientStats) {
+        shared.count = client.count;
+        shared.countries = client.countries;
+        shared.spread = client.spread;
+        shared.types.clone_from(&client.types);
+    }
+    if profile.keeps(SharedField::ClientBehaviors) {
+        shared.behaviors.clone_from(&client.behaviors);
+    }
+    if profile.keeps(SharedField::ClientProxies) {
+        shared.proxies.clone_from(&client.proxies);
+    }
+    if profile.keeps(SharedField::ClientConcentration) {
+        shared.concentration.clone_from(&client.concentration);
+    }
+    shared
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixtures, IpContextBuilder};
+
+    #[test]
+    fn test_empty_and_full_profiles() {
+        let context = fixtures::residential_proxy_ip();
+        assert_eq!(
+            context.sanitized_for_partner(&SharingProfile::none()),
+            IpContext::default()
+        );
+
+        let all: SharingProfile = [
+            SharedField::Ip,
+            SharedField::Ai,
+            SharedField::AutonomousSystem,
+            SharedField::Organization,
+            SharedField::Infrastructure,
+            SharedField::Location,
+            SharedField::Coordinates,
+            SharedField::Risks,
+            SharedField::Services,
+            SharedField::Tunnels,
+            SharedField::TunnelEntries,
+            SharedField::ClientStats,
+            SharedField::ClientBehaviors,
+            SharedField::ClientProxies,
+            SharedField::ClientConcentration,
+            SharedField::Timestamps,
+        ]
+        .into_iter()
+        .collect();
+        for (_, context) in crate::test_utils::load_all_fixtures() {
+            assert_eq!(context.sanitized_for_partner(&all), context);
+        }
+    }
+
+    #[test]
+    fn test_dependent_fields() {
+        let context = IpContextBuilder::new()
+            .location("CH", Some("Zurich"))
+            .vpn_with_entry("PROTON_VPN", "198.51.100.20", "CH")
+            .build();
+        let mut located = context.clone();
+        located.location.as_mut().unwrap().latitude = Some(47.37);
+
+        let profile = SharingProfile::none()
+            .with(SharedField::Location)
+            .with(SharedField::Tunnels);
+        let shared = located.sanitized_for_partner(&profile);
+        let location = shared.location.unwrap();
+        assert_eq!(location.city.as_deref(), Some("Zurich"));
+        assert_eq!(location.latitude, None);
+        let tunnel = &shared.tunnels.unwrap()[0];
+        assert_eq!(tunnel.operator.as_deref(), Some("PROTON_VPN"));
+        assert_eq!(tunnel.entries, None);
+    }
+
+    #[test]
+    fn test_client_subfields() {
+        let context = fixtures::callback_proxy_ip();
+        let profile = SharingProfile::infrastructure_and_risks().with(SharedField::ClientProxies);
+        let client = context.sanitized_for_partner(&profile).client.unwrap();
+        assert_eq!(client.proxies, context.client.as_ref().unwrap().proxies);
+        assert_eq!((client.count, client.types), (None, None));
+
+        let profile = profile.without(SharedField::ClientProxies);
+        assert_eq!(context.sanitized_for_partner(&profile).client, None);
+    }
+
+    #[test]
+    fn test_profile_serde() {
+        let profile = SharingProfile::infrastructure_and_risks();
+        let json = serde_json::to_string(&profile).unwrap();
+        assert_eq!(
+            json,
+            r#"["ip","autonomous_system","infrastructure","risks","services","tunnels"]"#
+        );
+        assert_eq!(
+            serde_json::from_str::<SharingProfile>(&json).unwrap(),
+            profile
+        );
+    }
+}
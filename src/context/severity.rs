@@ -0,0 +1,125 @@
+//! Severity of risk factors, for ordering findings.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::enums::Risk;
+use super::types::IpContext;
+
+/// How serious a [`Risk`] is, from [`Risk::severity`].
+///
+/// Variants are ordered from least to most severe, so `max()` and sorting
+/// work as expected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Worth recording, rarely worth acting on alone.
+    Low,
+    /// Worth a closer look or a step-up check.
+    Medium,
+    /// Likely abuse or a hidden client.
+    High,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        })
+    }
+}
+
+impl Risk {
+    /// Returns how serious this risk is.
+    ///
+    /// | Risk | Severity |
+    /// |------|----------|
+    /// | `CALLBACK_PROXY` | High |
+    /// | `SPAM` | Medium |
+    /// | `TUNNEL` | Medium |
+    /// | `GEO_MISMATCH` | Low |
+    /// | unknown | Low |
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::CallbackProxy => Severity::High,
+            Self::Spam | Self::Tunnel => Severity::Medium,
+            Self::GeoMismatch | Self::Other(_) => Severity::Low,
+        }
+    }
+}
+
+impl IpContext {
+    /// Returns up to `n` distinct risks, most severe first.
+    ///
+    /// Risks of equal severity keep the order the API returned them in, so
+    /// the result is deterministic for a given context.
+    ///
+    /// ```rust
+    /// use spur::test_utils::IpContextBuilder;
+    /// use spur::Risk;
+    ///
+    /// let context = IpContextBuilder::new()
+    ///     .risks(vec![Risk::GeoMismatch, Risk::Tunnel, Risk::CallbackProxy])
+    ///     .build();
+    /// assert_eq!(context.top_risks(2), [&Risk::CallbackProxy, &Risk::Tunnel]);
+    /// ```
+    pub fn top_risks(&self, n: usize) -> Vec<&Risk> {
+        let mut risks: Vec<&Risk> = Vec::new();
+        for risk in self.risks.iter().flatten() {
+            if !risks.contains(&risk) {
+                risks.push(risk);
+            }
+        }
+        risks.sort_by_key(|r| std::cmp::Reverse(r.severity()));
+        risks.truncate(n);
+        risks
+    }
+
+    /// Returns the severity of the most severe risk, or `None` without
+    /// risks.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.risks.iter().flatten().map(Risk::severity).max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::IpContextBuilder;
+
+    #[test]
+    fn test_top_risks() {
+        let context = IpContextBuilder::new()
+            .risks(vec![
+                Risk::Other("NEW_RISK".to_string()),
+                Risk::Spam,
+                Risk::Tunnel,
+                Risk::Spam,
+                Risk::CallbackProxy,
+            ])
+            .build();
+        assert_eq!(
+            context.top_risks(10),
+            [
+                &Risk::CallbackProxy,
+                &Risk::Spam,
+                &Risk::Tunnel,
+                &Risk::Other("NEW_RISK".to_string())
+            ]
+        );
+        assert!(context.top_risks(0).is_empty());
+        assert_eq!(context.max_severity(), Some(Severity::High));
+    }
+
+    #[test]
+    fn test_no_risks() {
+        assert!(IpContext::default().top_risks(3).is_empty());
+        assert_eq!(IpContext::default().max_severity(), None);
+        let tunnel = IpContextBuilder::new().add_risk(Risk::Tunnel).build();
+        assert_eq!(tunnel.max_severity(), Some(Severity::Medium));
+        assert_eq!(Severity::High.to_string(), "high");
+    }
+}
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// All fields may be omitted if their value is null.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(default, rename_all = "camelCase")]
 pub struct ApiStatus {
     /// Whether the API token is active.
@@ -0,0 +1,163 @@
+//! References from contexts to service tags.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::metadata::TagMetadata;
+use super::types::{IpContext, Tunnel};
+
+/// The tag of a service, such as `NORD_VPN` or `OXYLABS_PROXY`.
+///
+/// Contexts name services by tag in tunnel operators and client proxies;
+/// the Tag Metadata API describes each tag in a [`TagMetadata`]. A `TagRef`
+/// links the two. Serializes as a plain string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TagRef(String);
+
+impl TagRef {
+    /// Wrap a tag.
+    pub fn new(tag: impl Into<String>) -> Self {
+        Self(tag.into())
+    }
+
+    /// Returns the tag.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns `true` if `metadata` describes this tag.
+    pub fn matches(&self, metadata: &TagMetadata) -> bool {
+        metadata.tag.as_deref() == Some(self.as_str())
+    }
+}
+
+impl fmt::Display for TagRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for TagRef {
+    fn from(tag: &str) -> Self {
+        Self::new(tag)
+    }
+}
+
+impl Tunnel {
+    /// Returns the tag of the operator.
+    pub fn tag_ref(&self) -> Option<TagRef> {
+        self.operator.as_deref().map(TagRef::from)
+    }
+}
+
+impl TagMetadata {
+    /// Returns the tag this metadata describes.
+    pub fn tag_ref(&self) -> Option<TagRef> {
+        self.tag.as_deref().map(TagRef::from)
+    }
+}
+
+impl IpContext {
+    /// Returns the service tags this context references: tunnel operators,
+    /// then client proxies, without duplicates.
+    pub fn tag_refs(&self) -> Vec<TagRef> {
+        let operators = self.tunnels.iter().flatten().filter_map(Tunnel::tag_ref);
+        let proxies = self
+            .client
+            .iter()
+            .flat_map(|c| c.proxies.iter().flatten())
+            .map(|p| TagRef::from(p.as_str()));
+
+        let mut tags = Vec::new();
+        for tag in operators.chain(proxies) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    /// Look up the metadata of every tag in [`tag_refs`](Self::tag_refs).
+    ///
+    /// `lookup` fetches the metadata of one tag, for example from the Tag
+    /// Metadata API or a local copy of it; tags it returns `None` for are
+    /// left out. It is called once per tag, in order.
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use spur::test_utils::{fixtures, IpContextBuilder};
+    /// use spur::TagRef;
+    ///
+    /// let known: HashMap<TagRef, _> = [fixtures::oxylabs_like()]
+    ///     .into_iter()
+    ///     .filter_map(|m| Some((m.tag_ref()?, m)))
+    ///     .collect();
+    ///
+    /// let context = IpContextBuilder::new().proxy("OXYLABS_PROXY").vpn("NORD_VPN").build();
+    /// let resolved = context.resolve_tags(|tag| known.get(tag).cloned());
+    /// assert_eq!(resolved.len(), 1);
+    /// assert_eq!(resolved[0].1.name.as_deref(), Some("Oxylabs"));
+    /// ```
+    pub fn resolve_tags<F>(&self, mut lookup: F) -> Vec<(TagRef, TagMetadata)>
+    where
+        F: FnMut(&TagRef) -> Option<TagMetadata>,
+    {
+        self.tag_refs()
+            .into_iter()
+            .filter_map(|tag| {
+                let metadata = lookup(&tag)?;
+                Some((tag, metadata))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Client;
+    use crate::test_utils::{IpContextBuilder, TagMetadataBuilder};
+
+    #[test]
+    fn test_tag_refs() {
+        let mut context = IpContextBuilder::new()
+            .vpn("NORD_VPN")
+            .proxy("OXYLABS_PROXY")
+            .build();
+        context.client = Some(Client {
+            proxies: Some(vec!["OXYLABS_PROXY".into(), "NETNUT_PROXY".into()]),
+            ..Default::default()
+        });
+
+        let tags: Vec<String> = context.tag_refs().iter().map(TagRef::to_string).collect();
+        assert_eq!(tags, ["NORD_VPN", "OXYLABS_PROXY", "NETNUT_PROXY"]);
+        assert!(IpContext::default().tag_refs().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_tags() {
+        let netnut = TagMetadataBuilder::new().tag("NETNUT_PROXY").build();
+        let context = IpContextBuilder::new()
+            .vpn("NORD_VPN")
+            .proxy("NETNUT_PROXY")
+            .build();
+
+        let mut asked = Vec::new();
+        let resolved = context.resolve_tags(|tag| {
+            asked.push(tag.clone());
+            Some(netnut.clone()).filter(|m| tag.matches(m))
+        });
+        assert_eq!(resolved, [(TagRef::from("NETNUT_PROXY"), netnut.clone())]);
+        assert_eq!(asked, context.tag_refs());
+        assert_eq!(netnut.tag_ref(), Some(TagRef::new("NETNUT_PROXY")));
+    }
+
+    #[test]
+    fn test_serde() {
+        let tag: TagRef = serde_json::from_str(r#""NORD_VPN""#).unwrap();
+        assert_eq!(tag.as_str(), "NORD_VPN");
+        assert_eq!(serde_json::to_string(&tag).unwrap(), r#""NORD_VPN""#);
+    }
+}
@@ -0,0 +1,518 @@
+//! Time zone inference from locations (requires the `tz` feature).
+
+use chrono_tz::Tz;
+
+use super::types::Location;
+use crate::geo::haversine_km;
+
+impl Location {
+    /// Returns the IANA time zone this location most likely observes.
+    ///
+    /// The lookup uses the reference point of every zone in the tz
+    /// database's `zone.tab`. With coordinates, the closest reference point
+    /// wins, restricted to the location's country when it has one; this is
+    /// exact for single-zone countries and a good approximation elsewhere,
+    /// though points near a zone boundary may get the neighbouring zone.
+    /// Without coordinates, a zone is only returned for countries with a
+    /// single zone.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spur::Location;
+    ///
+    /// let denver = Location {
+    ///     country: Some("US".to_string()),
+    ///     latitude: Some(39.74),
+    ///     longitude: Some(-104.99),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(denver.timezone(), Some(chrono_tz::America::Denver));
+    ///
+    /// let netherlands = Location { country: Some("NL".to_string()), ..Default::default() };
+    /// assert_eq!(netherlands.timezone(), Some(chrono_tz::Europe::Amsterdam));
+    /// ```
+    pub fn timezone(&self) -> Option<Tz> {
+        let candidates: Vec<&Zone> = match self.country.as_deref() {
+            Some(country) => ZONES
+                .iter()
+                .filter(|zone| zone.0.eq_ignore_ascii_case(country))
+                .collect(),
+            None => ZONES.iter().collect(),
+        };
+        let zone = match (self.latitude, self.longitude) {
+            (Some(lat), Some(lon)) => candidates.into_iter().min_by(|a, b| {
+                let a = haversine_km((lat, lon), (a.1, a.2));
+                let b = haversine_km((lat, lon), (b.1, b.2));
+                a.total_cmp(&b)
+            }),
+            _ if candidates.len() == 1 && self.country.is_some() => candidates.first().copied(),
+            _ => None,
+        }?;
+        zone.3.parse().ok()
+    }
+}
+
+/// Country code, reference latitude and longitude, and zone name.
+type Zone = (&'static str, f64, f64, &'static str);
+
+/// Zones from the tz database's public-domain `zone.tab`, sorted by
+/// country.
+const ZONES: &[Zone] = &[
+    ("AD", 42.50, 1.52, "Europe/Andorra"),
+    ("AE", 25.30, 55.30, "Asia/Dubai"),
+    ("AF", 34.52, 69.20, "Asia/Kabul"),
+    ("AG", 17.05, -61.80, "America/Antigua"),
+    ("AI", 18.20, -63.07, "America/Anguilla"),
+    ("AL", 41.33, 19.83, "Europe/Tirane"),
+    ("AM", 40.18, 44.50, "Asia/Yerevan"),
+    ("AO", -8.80, 13.23, "Africa/Luanda"),
+    ("AQ", -66.28, 110.52, "Antarctica/Casey"),
+    ("AQ", -68.58, 77.97, "Antarctica/Davis"),
+    ("AQ", -66.67, 140.02, "Antarctica/DumontDUrville"),
+    ("AQ", -67.60, 62.88, "Antarctica/Mawson"),
+    ("AQ", -77.83, 166.60, "Antarctica/McMurdo"),
+    ("AQ", -64.80, -64.10, "Antarctica/Palmer"),
+    ("AQ", -67.57, -68.13, "Antarctica/Rothera"),
+    ("AQ", -69.01, 39.59, "Antarctica/Syowa"),
+    ("AQ", -72.01, 2.53, "Antarctica/Troll"),
+    ("AQ", -78.40, 106.90, "Antarctica/Vostok"),
+    ("AR", -34.60, -58.45, "America/Argentina/Buenos_Aires"),
+    ("AR", -28.47, -65.78, "America/Argentina/Catamarca"),
+    ("AR", -31.40, -64.18, "America/Argentina/Cordoba"),
+    ("AR", -24.18, -65.30, "America/Argentina/Jujuy"),
+    ("AR", -29.43, -66.85, "America/Argentina/La_Rioja"),
+    ("AR", -32.88, -68.82, "America/Argentina/Mendoza"),
+    ("AR", -51.63, -69.22, "America/Argentina/Rio_Gallegos"),
+    ("AR", -24.78, -65.42, "America/Argentina/Salta"),
+    ("AR", -31.53, -68.52, "America/Argentina/San_Juan"),
+    ("AR", -33.32, -66.35, "America/Argentina/San_Luis"),
+    ("AR", -26.82, -65.22, "America/Argentina/Tucuman"),
+    ("AR", -54.80, -68.30, "America/Argentina/Ushuaia"),
+    ("AS", -14.27, -170.70, "Pacific/Pago_Pago"),
+    ("AT", 48.22, 16.33, "Europe/Vienna"),
+    ("AU", -54.50, 158.95, "Antarctica/Macquarie"),
+    ("AU", -34.92, 138.58, "Australia/Adelaide"),
+    ("AU", -27.47, 153.03, "Australia/Brisbane"),
+    ("AU", -31.95, 141.45, "Australia/Broken_Hill"),
+    ("AU", -12.47, 130.83, "Australia/Darwin"),
+    ("AU", -31.72, 128.87, "Australia/Eucla"),
+    ("AU", -42.88, 147.32, "Australia/Hobart"),
+    ("AU", -20.27, 149.00, "Australia/Lindeman"),
+    ("AU", -31.55, 159.08, "Australia/Lord_Howe"),
+    ("AU", -37.82, 144.97, "Australia/Melbourne"),
+    ("AU", -31.95, 115.85, "Australia/Perth"),
+    ("AU", -33.87, 151.22, "Australia/Sydney"),
+    ("AW", 12.50, -69.97, "America/Aruba"),
+    ("AX", 60.10, 19.95, "Europe/Mariehamn"),
+    ("AZ", 40.38, 49.85, "Asia/Baku"),
+    ("BA", 43.87, 18.42, "Europe/Sarajevo"),
+    ("BB", 13.10, -59.62, "America/Barbados"),
+    ("BD", 23.72, 90.42, "Asia/Dhaka"),
+    ("BE", 50.83, 4.33, "Europe/Brussels"),
+    ("BF", 12.37, -1.52, "Africa/Ouagadougou"),
+    ("BG", 42.68, 23.32, "Europe/Sofia"),
+    ("BH", 26.38, 50.58, "Asia/Bahrain"),
+    ("BI", -3.38, 29.37, "Africa/Bujumbura"),
+    ("BJ", 6.48, 2.62, "Africa/Porto-Novo"),
+    ("BL", 17.88, -62.85, "America/St_Barthelemy"),
+    ("BM", 32.28, -64.77, "Atlantic/Bermuda"),
+    ("BN", 4.93, 114.92, "Asia/Brunei"),
+    ("BO", -16.50, -68.15, "America/La_Paz"),
+    ("BQ", 12.15, -68.28, "America/Kralendijk"),
+    ("BR", -7.20, -48.20, "America/Araguaina"),
+    ("BR", -12.98, -38.52, "America/Bahia"),
+    ("BR", -1.45, -48.48, "America/Belem"),
+    ("BR", 2.82, -60.67, "America/Boa_Vista"),
+    ("BR", -20.45, -54.62, "America/Campo_Grande"),
+    ("BR", -15.58, -56.08, "America/Cuiaba"),
+    ("BR", -6.67, -69.87, "America/Eirunepe"),
+    ("BR", -3.72, -38.50, "America/Fortaleza"),
+    ("BR", -9.67, -35.72, "America/Maceio"),
+    ("BR", -3.13, -60.02, "America/Manaus"),
+    ("BR", -3.85, -32.42, "America/Noronha"),
+    ("BR", -8.77, -63.90, "America/Porto_Velho"),
+    ("BR", -8.05, -34.90, "America/Recife"),
+    ("BR", -9.97, -67.80, "America/Rio_Branco"),
+    ("BR", -2.43, -54.87, "America/Santarem"),
+    ("BR", -23.53, -46.62, "America/Sao_Paulo"),
+    ("BS", 25.08, -77.35, "America/Nassau"),
+    ("BT", 27.47, 89.65, "Asia/Thimphu"),
+    ("BW", -24.65, 25.92, "Africa/Gaborone"),
+    ("BY", 53.90, 27.57, "Europe/Minsk"),
+    ("BZ", 17.50, -88.20, "America/Belize"),
+    ("CA", 48.76, -91.62, "America/Atikokan"),
+    ("CA", 51.42, -57.12, "America/Blanc-Sablon"),
+    ("CA", 69.11, -105.05, "America/Cambridge_Bay"),
+    ("CA", 49.10, -116.52, "America/Creston"),
+    ("CA", 64.07, -139.42, "America/Dawson"),
+    ("CA", 55.77, -120.23, "America/Dawson_Creek"),
+    ("CA", 53.55, -113.47, "America/Edmonton"),
+    ("CA", 58.80, -122.70, "America/Fort_Nelson"),
+    ("CA", 46.20, -59.95, "America/Glace_Bay"),
+    ("CA", 53.33, -60.42, "America/Goose_Bay"),
+    ("CA", 44.65, -63.60, "America/Halifax"),
+    ("CA", 68.35, -133.72, "America/Inuvik"),
+    ("CA", 63.73, -68.47, "America/Iqaluit"),
+    ("CA", 46.10, -64.78, "America/Moncton"),
+    ("CA", 62.82, -92.08, "America/Rankin_Inlet"),
+    ("CA", 50.40, -104.65, "America/Regina"),
+    ("CA", 74.70, -94.83, "America/Resolute"),
+    ("CA", 47.57, -52.72, "America/St_Johns"),
+    ("CA", 50.28, -107.83, "America/Swift_Current"),
+    ("CA", 43.65, -79.38, "America/Toronto"),
+    ("CA", 49.27, -123.12, "America/Vancouver"),
+    ("CA", 60.72, -135.05, "America/Whitehorse"),
+    ("CA", 49.88, -97.15, "America/Winnipeg"),
+    ("CC", -12.17, 96.92, "Indian/Cocos"),
+    ("CD", -4.30, 15.30, "Africa/Kinshasa"),
+    ("CD", -11.67, 27.47, "Africa/Lubumbashi"),
+    ("CF", 4.37, 18.58, "Africa/Bangui"),
+    ("CG", -4.27, 15.28, "Africa/Brazzaville"),
+    ("CH", 47.38, 8.53, "Europe/Zurich"),
+    ("CI", 5.32, -4.03, "Africa/Abidjan"),
+    ("CK", -21.23, -159.77, "Pacific/Rarotonga"),
+    ("CL", -53.15, -70.92, "America/Punta_Arenas"),
+    ("CL", -33.45, -70.67, "America/Santiago"),
+    ("CL", -27.15, -109.43, "Pacific/Easter"),
+    ("CM", 4.05, 9.70, "Africa/Douala"),
+    ("CN", 31.23, 121.47, "Asia/Shanghai"),
+    ("CN", 43.80, 87.58, "Asia/Urumqi"),
+    ("CO", 4.60, -74.08, "America/Bogota"),
+    ("CR", 9.93, -84.08, "America/Costa_Rica"),
+    ("CU", 23.13, -82.37, "America/Havana"),
+    ("CV", 14.92, -23.52, "Atlantic/Cape_Verde"),
+    ("CW", 12.18, -69.00, "America/Curacao"),
+    ("CX", -10.42, 105.72, "Indian/Christmas"),
+    ("CY", 35.12, 33.95, "Asia/Famagusta"),
+    ("CY", 35.17, 33.37, "Asia/Nicosia"),
+    ("CZ", 50.08, 14.43, "Europe/Prague"),
+    ("DE", 52.50, 13.37, "Europe/Berlin"),
+    ("DE", 47.70, 8.68, "Europe/Busingen"),
+    ("DJ", 11.60, 43.15, "Africa/Djibouti"),
+    ("DK", 55.67, 12.58, "Europe/Copenhagen"),
+    ("DM", 15.30, -61.40, "America/Dominica"),
+    ("DO", 18.47, -69.90, "America/Santo_Domingo"),
+    ("DZ", 36.78, 3.05, "Africa/Algiers"),
+    ("EC", -2.17, -79.83, "America/Guayaquil"),
+    ("EC", -0.90, -89.60, "Pacific/Galapagos"),
+    ("EE", 59.42, 24.75, "Europe/Tallinn"),
+    ("EG", 30.05, 31.25, "Africa/Cairo"),
+    ("EH", 27.15, -13.20, "Africa/El_Aaiun"),
+    ("ER", 15.33, 38.88, "Africa/Asmara"),
+    ("ES", 35.88, -5.32, "Africa/Ceuta"),
+    ("ES", 28.10, -15.40, "Atlantic/Canary"),
+    ("ES", 40.40, -3.68, "Europe/Madrid"),
+    ("ET", 9.03, 38.70, "Africa/Addis_Ababa"),
+    ("FI", 60.17, 24.97, "Europe/Helsinki"),
+    ("FJ", -18.13, 178.42, "Pacific/Fiji"),
+    ("FK", -51.70, -57.85, "Atlantic/Stanley"),
+    ("FM", 7.42, 151.78, "Pacific/Chuuk"),
+    ("FM", 5.32, 162.98, "Pacific/Kosrae"),
+    ("FM", 6.97, 158.22, "Pacific/Pohnpei"),
+    ("FO", 62.02, -6.77, "Atlantic/Faroe"),
+    ("FR", 48.87, 2.33, "Europe/Paris"),
+    ("GA", 0.38, 9.45, "Africa/Libreville"),
+    ("GB", 51.51, -0.13, "Europe/London"),
+    ("GD", 12.05, -61.75, "America/Grenada"),
+    ("GE", 41.72, 44.82, "Asia/Tbilisi"),
+    ("GF", 4.93, -52.33, "America/Cayenne"),
+    ("GG", 49.45, -2.54, "Europe/Guernsey"),
+    ("GH", 5.55, -0.22, "Africa/Accra"),
+    ("GI", 36.13, -5.35, "Europe/Gibraltar"),
+    ("GL", 76.77, -18.67, "America/Danmarkshavn"),
+    ("GL", 64.18, -51.73, "America/Nuuk"),
+    ("GL", 70.48, -21.97, "America/Scoresbysund"),
+    ("GL", 76.57, -68.78, "America/Thule"),
+    ("GM", 13.47, -16.65, "Africa/Banjul"),
+    ("GN", 9.52, -13.72, "Africa/Conakry"),
+    ("GP", 16.23, -61.53, "America/Guadeloupe"),
+    ("GQ", 3.75, 8.78, "Africa/Malabo"),
+    ("GR", 37.97, 23.72, "Europe/Athens"),
+    ("GS", -54.27, -36.53, "Atlantic/South_Georgia"),
+    ("GT", 14.63, -90.52, "America/Guatemala"),
+    ("GU", 13.47, 144.75, "Pacific/Guam"),
+    ("GW", 11.85, -15.58, "Africa/Bissau"),
+    ("GY", 6.80, -58.17, "America/Guyana"),
+    ("HK", 22.28, 114.15, "Asia/Hong_Kong"),
+    ("HN", 14.10, -87.22, "America/Tegucigalpa"),
+    ("HR", 45.80, 15.97, "Europe/Zagreb"),
+    ("HT", 18.53, -72.33, "America/Port-au-Prince"),
+    ("HU", 47.50, 19.08, "Europe/Budapest"),
+    ("ID", -6.17, 106.80, "Asia/Jakarta"),
+    ("ID", -2.53, 140.70, "Asia/Jayapura"),
+    ("ID", -5.12, 119.40, "Asia/Makassar"),
+    ("ID", -0.03, 109.33, "Asia/Pontianak"),
+    ("IE", 53.33, -6.25, "Europe/Dublin"),
+    ("IL", 31.78, 35.22, "Asia/Jerusalem"),
+    ("IM", 54.15, -4.47, "Europe/Isle_of_Man"),
+    ("IN", 22.53, 88.37, "Asia/Kolkata"),
+    ("IO", -7.33, 72.42, "Indian/Chagos"),
+    ("IQ", 33.35, 44.42, "Asia/Baghdad"),
+    ("IR", 35.67, 51.43, "Asia/Tehran"),
+    ("IS", 64.15, -21.85, "Atlantic/Reykjavik"),
+    ("IT", 41.90, 12.48, "Europe/Rome"),
+    ("JE", 49.18, -2.11, "Europe/Jersey"),
+    ("JM", 17.97, -76.79, "America/Jamaica"),
+    ("JO", 31.95, 35.93, "Asia/Amman"),
+    ("JP", 35.65, 139.74, "Asia/Tokyo"),
+    ("KE", -1.28, 36.82, "Africa/Nairobi"),
+    ("KG", 42.90, 74.60, "Asia/Bishkek"),
+    ("KH", 11.55, 104.92, "Asia/Phnom_Penh"),
+    ("KI", -2.78, -171.72, "Pacific/Kanton"),
+    ("KI", 1.87, -157.33, "Pacific/Kiritimati"),
+    ("KI", 1.42, 173.00, "Pacific/Tarawa"),
+    ("KM", -11.68, 43.27, "Indian/Comoro"),
+    ("KN", 17.30, -62.72, "America/St_Kitts"),
+    ("KP", 39.02, 125.75, "Asia/Pyongyang"),
+    ("KR", 37.55, 126.97, "Asia/Seoul"),
+    ("KW", 29.33, 47.98, "Asia/Kuwait"),
+    ("KY", 19.30, -81.38, "America/Cayman"),
+    ("KZ", 43.25, 76.95, "Asia/Almaty"),
+    ("KZ", 44.52, 50.27, "Asia/Aqtau"),
+    ("KZ", 50.28, 57.17, "Asia/Aqtobe"),
+    ("KZ", 47.12, 51.93, "Asia/Atyrau"),
+    ("KZ", 51.22, 51.35, "Asia/Oral"),
+    ("KZ", 53.20, 63.62, "Asia/Qostanay"),
+    ("KZ", 44.80, 65.47, "Asia/Qyzylorda"),
+    ("LA", 17.97, 102.60, "Asia/Vientiane"),
+    ("LB", 33.88, 35.50, "Asia/Beirut"),
+    ("LC", 14.02, -61.00, "America/St_Lucia"),
+    ("LI", 47.15, 9.52, "Europe/Vaduz"),
+    ("LK", 6.93, 79.85, "Asia/Colombo"),
+    ("LR", 6.30, -10.78, "Africa/Monrovia"),
+    ("LS", -29.47, 27.50, "Africa/Maseru"),
+    ("LT", 54.68, 25.32, "Europe/Vilnius"),
+    ("LU", 49.60, 6.15, "Europe/Luxembourg"),
+    ("LV", 56.95, 24.10, "Europe/Riga"),
+    ("LY", 32.90, 13.18, "Africa/Tripoli"),
+    ("MA", 33.65, -7.58, "Africa/Casablanca"),
+    ("MC", 43.70, 7.38, "Europe/Monaco"),
+    ("MD", 47.00, 28.83, "Europe/Chisinau"),
+    ("ME", 42.43, 19.27, "Europe/Podgorica"),
+    ("MF", 18.07, -63.08, "America/Marigot"),
+    ("MG", -18.92, 47.52, "Indian/Antananarivo"),
+    ("MH", 9.08, 167.33, "Pacific/Kwajalein"),
+    ("MH", 7.15, 171.20, "Pacific/Majuro"),
+    ("MK", 41.98, 21.43, "Europe/Skopje"),
+    ("ML", 12.65, -8.00, "Africa/Bamako"),
+    ("MM", 16.78, 96.17, "Asia/Yangon"),
+    ("MN", 48.02, 91.65, "Asia/Hovd"),
+    ("MN", 47.92, 106.88, "Asia/Ulaanbaatar"),
+    ("MO", 22.20, 113.54, "Asia/Macau"),
+    ("MP", 15.20, 145.75, "Pacific/Saipan"),
+    ("MQ", 14.60, -61.08, "America/Martinique"),
+    ("MR", 18.10, -15.95, "Africa/Nouakchott"),
+    ("MS", 16.72, -62.22, "America/Montserrat"),
+    ("MT", 35.90, 14.52, "Europe/Malta"),
+    ("MU", -20.17, 57.50, "Indian/Mauritius"),
+    ("MV", 4.17, 73.50, "Indian/Maldives"),
+    ("MW", -15.78, 35.00, "Africa/Blantyre"),
+    ("MX", 20.80, -105.25, "America/Bahia_Banderas"),
+    ("MX", 21.08, -86.77, "America/Cancun"),
+    ("MX", 28.63, -106.08, "America/Chihuahua"),
+    ("MX", 31.73, -106.48, "America/Ciudad_Juarez"),
+    ("MX", 29.07, -110.97, "America/Hermosillo"),
+    ("MX", 25.83, -97.50, "America/Matamoros"),
+    ("MX", 23.22, -106.42, "America/Mazatlan"),
+    ("MX", 20.97, -89.62, "America/Merida"),
+    ("MX", 19.40, -99.15, "America/Mexico_City"),
+    ("MX", 25.67, -100.32, "America/Monterrey"),
+    ("MX", 29.57, -104.42, "America/Ojinaga"),
+    ("MX", 32.53, -117.02, "America/Tijuana"),
+    ("MY", 3.17, 101.70, "Asia/Kuala_Lumpur"),
+    ("MY", 1.55, 110.33, "Asia/Kuching"),
+    ("MZ", -25.97, 32.58, "Africa/Maputo"),
+    ("NA", -22.57, 17.10, "Africa/Windhoek"),
+    ("NC", -22.27, 166.45, "Pacific/Noumea"),
+    ("NE", 13.52, 2.12, "Africa/Niamey"),
+    ("NF", -29.05, 167.97, "Pacific/Norfolk"),
+    ("NG", 6.45, 3.40, "Africa/Lagos"),
+    ("NI", 12.15, -86.28, "America/Managua"),
+    ("NL", 52.37, 4.90, "Europe/Amsterdam"),
+    ("NO", 59.92, 10.75, "Europe/Oslo"),
+    ("NP", 27.72, 85.32, "Asia/Kathmandu"),
+    ("NR", -0.52, 166.92, "Pacific/Nauru"),
+    ("NU", -19.02, -169.92, "Pacific/Niue"),
+    ("NZ", -36.87, 174.77, "Pacific/Auckland"),
+    ("NZ", -43.95, -176.55, "Pacific/Chatham"),
+    ("OM", 23.60, 58.58, "Asia/Muscat"),
+    ("PA", 8.97, -79.53, "America/Panama"),
+    ("PE", -12.05, -77.05, "America/Lima"),
+    ("PF", -23.13, -134.95, "Pacific/Gambier"),
+    ("PF", -9.00, -139.50, "Pacific/Marquesas"),
+    ("PF", -17.53, -149.57, "Pacific/Tahiti"),
+    ("PG", -6.22, 155.57, "Pacific/Bougainville"),
+    ("PG", -9.50, 147.17, "Pacific/Port_Moresby"),
+    ("PH", 14.59, 120.97, "Asia/Manila"),
+    ("PK", 24.87, 67.05, "Asia/Karachi"),
+    ("PL", 52.25, 21.00, "Europe/Warsaw"),
+    ("PM", 47.05, -56.33, "America/Miquelon"),
+    ("PN", -25.07, -130.08, "Pacific/Pitcairn"),
+    ("PR", 18.47, -66.11, "America/Puerto_Rico"),
+    ("PS", 31.50, 34.47, "Asia/Gaza"),
+    ("PS", 31.53, 35.09, "Asia/Hebron"),
+    ("PT", 37.73, -25.67, "Atlantic/Azores"),
+    ("PT", 32.63, -16.90, "Atlantic/Madeira"),
+    ("PT", 38.72, -9.13, "Europe/Lisbon"),
+    ("PW", 7.33, 134.48, "Pacific/Palau"),
+    ("PY", -25.27, -57.67, "America/Asuncion"),
+    ("QA", 25.28, 51.53, "Asia/Qatar"),
+    ("RE", -20.87, 55.47, "Indian/Reunion"),
+    ("RO", 44.43, 26.10, "Europe/Bucharest"),
+    ("RS", 44.83, 20.50, "Europe/Belgrade"),
+    ("RU", 64.75, 177.48, "Asia/Anadyr"),
+    ("RU", 53.37, 83.75, "Asia/Barnaul"),
+    ("RU", 52.05, 113.47, "Asia/Chita"),
+    ("RU", 52.27, 104.33, "Asia/Irkutsk"),
+    ("RU", 53.02, 158.65, "Asia/Kamchatka"),
+    ("RU", 62.66, 135.55, "Asia/Khandyga"),
+    ("RU", 56.02, 92.83, "Asia/Krasnoyarsk"),
+    ("RU", 59.57, 150.80, "Asia/Magadan"),
+    ("RU", 53.75, 87.12, "Asia/Novokuznetsk"),
+    ("RU", 55.03, 82.92, "Asia/Novosibirsk"),
+    ("RU", 55.00, 73.40, "Asia/Omsk"),
+    ("RU", 46.97, 142.70, "Asia/Sakhalin"),
+    ("RU", 67.47, 153.72, "Asia/Srednekolymsk"),
+    ("RU", 56.50, 84.97, "Asia/Tomsk"),
+    ("RU", 64.56, 143.23, "Asia/Ust-Nera"),
+    ("RU", 43.17, 131.93, "Asia/Vladivostok"),
+    ("RU", 62.00, 129.67, "Asia/Yakutsk"),
+    ("RU", 56.85, 60.60, "Asia/Yekaterinburg"),
+    ("RU", 46.35, 48.05, "Europe/Astrakhan"),
+    ("RU", 54.72, 20.50, "Europe/Kaliningrad"),
+    ("RU", 58.60, 49.65, "Europe/Kirov"),
+    ("RU", 55.76, 37.62, "Europe/Moscow"),
+    ("RU", 53.20, 50.15, "Europe/Samara"),
+    ("RU", 51.57, 46.03, "Europe/Saratov"),
+    ("RU", 54.33, 48.40, "Europe/Ulyanovsk"),
+    ("RU", 48.73, 44.42, "Europe/Volgograd"),
+    ("RW", -1.95, 30.07, "Africa/Kigali"),
+    ("SA", 24.63, 46.72, "Asia/Riyadh"),
+    ("SB", -9.53, 160.20, "Pacific/Guadalcanal"),
+    ("SC", -4.67, 55.47, "Indian/Mahe"),
+    ("SD", 15.60, 32.53, "Africa/Khartoum"),
+    ("SE", 59.33, 18.05, "Europe/Stockholm"),
+    ("SG", 1.28, 103.85, "Asia/Singapore"),
+    ("SH", -15.92, -5.70, "Atlantic/St_Helena"),
+    ("SI", 46.05, 14.52, "Europe/Ljubljana"),
+    ("SJ", 78.00, 16.00, "Arctic/Longyearbyen"),
+    ("SK", 48.15, 17.12, "Europe/Bratislava"),
+    ("SL", 8.50, -13.25, "Africa/Freetown"),
+    ("SM", 43.92, 12.47, "Europe/San_Marino"),
+    ("SN", 14.67, -17.43, "Africa/Dakar"),
+    ("SO", 2.07, 45.37, "Africa/Mogadishu"),
+    ("SR", 5.83, -55.17, "America/Paramaribo"),
+    ("SS", 4.85, 31.62, "Africa/Juba"),
+    ("ST", 0.33, 6.73, "Africa/Sao_Tome"),
+    ("SV", 13.70, -89.20, "America/El_Salvador"),
+    ("SX", 18.05, -63.05, "America/Lower_Princes"),
+    ("SY", 33.50, 36.30, "Asia/Damascus"),
+    ("SZ", -26.30, 31.10, "Africa/Mbabane"),
+    ("TC", 21.47, -71.13, "America/Grand_Turk"),
+    ("TD", 12.12, 15.05, "Africa/Ndjamena"),
+    ("TF", -49.35, 70.22, "Indian/Kerguelen"),
+    ("TG", 6.13, 1.22, "Africa/Lome"),
+    ("TH", 13.75, 100.52, "Asia/Bangkok"),
+    ("TJ", 38.58, 68.80, "Asia/Dushanbe"),
+    ("TK", -9.37, -171.23, "Pacific/Fakaofo"),
+    ("TL", -8.55, 125.58, "Asia/Dili"),
+    ("TM", 37.95, 58.38, "Asia/Ashgabat"),
+    ("TN", 36.80, 10.18, "Africa/Tunis"),
+    ("TO", -21.13, -175.20, "Pacific/Tongatapu"),
+    ("TR", 41.02, 28.97, "Europe/Istanbul"),
+    ("TT", 10.65, -61.52, "America/Port_of_Spain"),
+    ("TV", -8.52, 179.22, "Pacific/Funafuti"),
+    ("TW", 25.05, 121.50, "Asia/Taipei"),
+    ("TZ", -6.80, 39.28, "Africa/Dar_es_Salaam"),
+    ("UA", 50.43, 30.52, "Europe/Kyiv"),
+    ("UA", 44.95, 34.10, "Europe/Simferopol"),
+    ("UG", 0.32, 32.42, "Africa/Kampala"),
+    ("UM", 28.22, -177.37, "Pacific/Midway"),
+    ("UM", 19.28, 166.62, "Pacific/Wake"),
+    ("US", 51.88, -176.66, "America/Adak"),
+    ("US", 61.22, -149.90, "America/Anchorage"),
+    ("US", 43.61, -116.20, "America/Boise"),
+    ("US", 41.85, -87.65, "America/Chicago"),
+    ("US", 39.74, -104.98, "America/Denver"),
+    ("US", 42.33, -83.05, "America/Detroit"),
+    ("US", 39.77, -86.16, "America/Indiana/Indianapolis"),
+    ("US", 41.30, -86.62, "America/Indiana/Knox"),
+    ("US", 38.38, -86.34, "America/Indiana/Marengo"),
+    ("US", 38.49, -87.28, "America/Indiana/Petersburg"),
+    ("US", 37.95, -86.76, "America/Indiana/Tell_City"),
+    ("US", 38.75, -85.07, "America/Indiana/Vevay"),
+    ("US", 38.68, -87.53, "America/Indiana/Vincennes"),
+    ("US", 41.05, -86.60, "America/Indiana/Winamac"),
+    ("US", 58.30, -134.42, "America/Juneau"),
+    ("US", 38.25, -85.76, "America/Kentucky/Louisville"),
+    ("US", 36.83, -84.85, "America/Kentucky/Monticello"),
+    ("US", 34.05, -118.24, "America/Los_Angeles"),
+    ("US", 45.11, -87.61, "America/Menominee"),
+    ("US", 55.13, -131.58, "America/Metlakatla"),
+    ("US", 40.71, -74.01, "America/New_York"),
+    ("US", 64.50, -165.41, "America/Nome"),
+    ("US", 47.26, -101.78, "America/North_Dakota/Beulah"),
+    ("US", 47.12, -101.30, "America/North_Dakota/Center"),
+    ("US", 46.84, -101.41, "America/North_Dakota/New_Salem"),
+    ("US", 33.45, -112.07, "America/Phoenix"),
+    ("US", 57.18, -135.30, "America/Sitka"),
+    ("US", 59.55, -139.73, "America/Yakutat"),
+    ("US", 21.31, -157.86, "Pacific/Honolulu"),
+    ("UY", -34.91, -56.21, "America/Montevideo"),
+    ("UZ", 39.67, 66.80, "Asia/Samarkand"),
+    ("UZ", 41.33, 69.30, "Asia/Tashkent"),
+    ("VA", 41.90, 12.45, "Europe/Vatican"),
+    ("VC", 13.15, -61.23, "America/St_Vincent"),
+    ("VE", 10.50, -66.93, "America/Caracas"),
+    ("VG", 18.45, -64.62, "America/Tortola"),
+    ("VI", 18.35, -64.93, "America/St_Thomas"),
+    ("VN", 10.75, 106.67, "Asia/Ho_Chi_Minh"),
+    ("VU", -17.67, 168.42, "Pacific/Efate"),
+    ("WF", -13.30, -176.17, "Pacific/Wallis"),
+    ("WS", -13.83, -171.73, "Pacific/Apia"),
+    ("YE", 12.75, 45.20, "Asia/Aden"),
+    ("YT", -12.78, 45.23, "Indian/Mayotte"),
+    ("ZA", -26.25, 28.00, "Africa/Johannesburg"),
+    ("ZM", -15.42, 28.28, "Africa/Lusaka"),
+    ("ZW", -17.83, 31.05, "Africa/Harare"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(country: Option<&str>, coords: Option<(f64, f64)>) -> Location {
+        Location {
+            country: country.map(str::to_string),
+            latitude: coords.map(|c| c.0),
+            longitude: coords.map(|c| c.1),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_every_zone_is_known() {
+        for zone in ZONES {
+            assert!(zone.3.parse::<Tz>().is_ok(), "{}", zone.3);
+        }
+    }
+
+    #[test]
+    fn test_timezone() {
+        let sydney = location(Some("AU"), Some((-33.87, 151.21)));
+        assert_eq!(sydney.timezone(), Some(Tz::Australia__Sydney));
+        let perth = location(Some("au"), Some((-31.95, 115.86)));
+        assert_eq!(perth.timezone(), Some(Tz::Australia__Perth));
+        let tokyo = location(None, Some((35.68, 139.69)));
+        assert_eq!(tokyo.timezone(), Some(Tz::Asia__Tokyo));
+        assert_eq!(location(Some("JP"), None).timezone(), Some(Tz::Asia__Tokyo));
+    }
+
+    #[test]
+    fn test_unknown_timezone() {
+        assert_eq!(location(Some("US"), None).timezone(), None);
+        assert_eq!(location(Some("ZZ"), Some((0.0, 0.0))).timezone(), None);
+        assert_eq!(Location::default().timezone(), None);
+    }
+}
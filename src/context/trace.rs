@@ -0,0 +1,162 @@
+//! Structured tracing fields for IP contexts (requires the `tracing`
+//! feature).
+
+use tracing::Span;
+
+use super::fields::FieldValue;
+use super::types::IpContext;
+
+/// Names of the span fields written by [`IpContext::record_on`].
+///
+/// `tracing` only records fields a span declared when it was created, so
+/// declare these as `tracing::field::Empty` on spans that should carry
+/// Spur data.
+pub const TRACING_FIELDS: &[&str] = &[
+    "spur.infrastructure",
+    "spur.asn",
+    "spur.country",
+    "spur.tunnel_type",
+    "spur.operator",
+    "spur.anonymous",
+    "spur.risks",
+];
+
+impl IpContext {
+    /// Record a curated set of fields from this context onto `span`.
+    ///
+    /// Only low-cardinality values are recorded, so the fields can be used
+    /// for grouping and filtering in structured logs: the infrastructure
+    /// type, AS number, country code, the first tunnel's type and operator,
+    /// whether any tunnel is anonymous, and the risks joined with `,`. The
+    /// IP address itself is left to the caller. Absent values are not
+    /// recorded. See [`TRACING_FIELDS`] for the field names.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tracing::field::Empty;
+    /// use spur::test_utils::fixtures;
+    ///
+    /// let span = tracing::info_span!(
+    ///     "request",
+    ///     spur.infrastructure = Empty,
+    ///     spur.asn = Empty,
+    ///     spur.country = Empty,
+    ///     spur.tunnel_type = Empty,
+    ///     spur.operator = Empty,
+    ///     spur.anonymous = Empty,
+    ///     spur.risks = Empty,
+    /// );
+    /// fixtures::vpn_ip().record_on(&span);
+    /// ```
+    pub fn record_on(&self, span: &Span) {
+        for (name, value) in self.curated_fields() {
+            match value {
+                FieldValue::Str(value) => span.record(name, value),
+                FieldValue::String(value) => span.record(name, value.as_str()),
+                FieldValue::U64(value) => span.record(name, value),
+                FieldValue::Bool(value) => span.record(name, value),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Empty, Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use crate::test_utils::IpContextBuilder;
+    use crate::{Infrastructure, IpContext, Risk};
+
+    /// Collects every recorded span field as a string.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<BTreeMap<String, String>>>);
+
+    impl Visit for Recorder {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            let mut fields = self.0.lock().unwrap();
+            fields.insert(field.name().to_string(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            let mut fields = self.0.lock().unwrap();
+            fields.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+        fn record(&self, _: &Id, values: &Record<'_>) {
+            values.record(&mut self.clone());
+        }
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    fn recorded(context: &IpContext) -> BTreeMap<String, String> {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let span = tracing::info_span!(
+                "test",
+                spur.infrastructure = Empty,
+                spur.asn = Empty,
+                spur.country = Empty,
+                spur.tunnel_type = Empty,
+                spur.operator = Empty,
+                spur.anonymous = Empty,
+                spur.risks = Empty,
+            );
+            context.record_on(&span);
+        });
+        let fields = recorder.0.lock().unwrap().clone();
+        fields
+    }
+
+    #[test]
+    fn test_record_on() {
+        let context = IpContextBuilder::new()
+            .ip("89.39.106.191")
+            .infrastructure(Infrastructure::Datacenter)
+            .asn(9009, "M247")
+            .location("NL", None)
+            .vpn("NORD_VPN")
+            .risks(vec![Risk::Tunnel, Risk::Spam])
+            .build();
+        let fields = recorded(&context);
+        let expected = [
+            ("spur.anonymous", "true"),
+            ("spur.asn", "9009"),
+            ("spur.country", "NL"),
+            ("spur.infrastructure", "DATACENTER"),
+            ("spur.operator", "NORD_VPN"),
+            ("spur.risks", "TUNNEL,SPAM"),
+            ("spur.tunnel_type", "VPN"),
+        ];
+        let expected: BTreeMap<String, String> = expected
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(fields, expected);
+        let mut names = super::TRACING_FIELDS.to_vec();
+        names.sort_unstable();
+        assert_eq!(fields.keys().map(String::as_str).collect::<Vec<_>>(), names);
+    }
+
+    #[test]
+    fn test_absent_fields_not_recorded() {
+        assert!(recorded(&IpContext::default()).is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+//! Coarse classification of tunnels for policy rules.
+
+use super::enums::TunnelType;
+use super::types::Tunnel;
+use crate::operators;
+
+/// What kind of service a [`Tunnel`] is, as returned by
+/// [`Tunnel::class`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TunnelClass {
+    /// A Tor relay.
+    Tor,
+    /// A commercial consumer VPN service.
+    CommercialVpn,
+    /// Corporate VPN or secure web gateway egress.
+    CorporateVpn,
+    /// A proxy service, typically running on hosting infrastructure.
+    HostingProxy,
+    /// Not enough information to tell.
+    Unknown,
+}
+
+impl Tunnel {
+    /// Classify this tunnel from its type, operator and anonymity.
+    ///
+    /// Tor tunnels are [`TunnelClass::Tor`]. Operators in the
+    /// [`operators`](crate::operators) registry decide between commercial
+    /// and corporate VPNs; other VPNs count as commercial when anonymous and
+    /// as corporate when explicitly not anonymous. Proxy tunnels are
+    /// [`TunnelClass::HostingProxy`]; residential proxy networks are
+    /// reported under `client.proxies` rather than as tunnels.
+    ///
+    /// ```rust
+    /// use spur::test_utils::TunnelBuilder;
+    /// use spur::{TunnelClass, TunnelType};
+    ///
+    /// let vpn = |operator| {
+    ///     TunnelBuilder::new().tunnel_type(TunnelType::Vpn).operator(operator).build()
+    /// };
+    /// assert_eq!(vpn("NORD_VPN").class(), TunnelClass::CommercialVpn);
+    /// assert_eq!(vpn("ZSCALER").class(), TunnelClass::CorporateVpn);
+    /// ```
+    pub fn class(&self) -> TunnelClass {
+        let operator = self.operator.as_deref();
+        if self.tunnel_type == Some(TunnelType::Tor) {
+            return TunnelClass::Tor;
+        }
+        if operator.is_some_and(operators::is_corporate_vpn) {
+            return TunnelClass::CorporateVpn;
+        }
+        if operator.is_some_and(operators::is_commercial_vpn) {
+            return TunnelClass::CommercialVpn;
+        }
+        match (&self.tunnel_type, self.anonymous) {
+            (Some(TunnelType::Vpn), Some(true)) => TunnelClass::CommercialVpn,
+            (Some(TunnelType::Vpn), Some(false)) => TunnelClass::CorporateVpn,
+            (Some(TunnelType::Proxy), _) => TunnelClass::HostingProxy,
+            _ => TunnelClass::Unknown,
+        }
+    }
+
+    /// Returns `true` if this tunnel is a commercial consumer VPN.
+    pub fn is_commercial_vpn(&self) -> bool {
+        self.class() == TunnelClass::CommercialVpn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TunnelBuilder;
+
+    fn tunnel(tunnel_type: TunnelType, operator: &str, anonymous: Option<bool>) -> Tunnel {
+        let builder = TunnelBuilder::new()
+            .tunnel_type(tunnel_type)
+            .operator(operator);
+        match anonymous {
+            Some(anonymous) => builder.anonymous(anonymous).build(),
+            None => builder.build(),
+        }
+    }
+
+    #[test]
+    fn test_class() {
+        use TunnelType::{Proxy, Tor, Vpn};
+
+        let cases = [
+            (tunnel(Tor, "TOR", Some(true)), TunnelClass::Tor),
+            (tunnel(Vpn, "PROTON_VPN", None), TunnelClass::CommercialVpn),
+            (
+                tunnel(Vpn, "Zscaler", Some(true)),
+                TunnelClass::CorporateVpn,
+            ),
+            (
+                tunnel(Vpn, "SOME_VPN", Some(true)),
+                TunnelClass::CommercialVpn,
+            ),
+            (
+                tunnel(Vpn, "ACME_CORP", Some(false)),
+                TunnelClass::CorporateVpn,
+            ),
+            (tunnel(Vpn, "ACME_CORP", None), TunnelClass::Unknown),
+            (
+                tunnel(Proxy, "LUMINATI_PROXY", None),
+                TunnelClass::HostingProxy,
+            ),
+            (Tunnel::default(), TunnelClass::Unknown),
+        ];
+        for (tunnel, class) in cases {
+            assert_eq!(tunnel.class(), class, "{:?}", tunnel);
+        }
+        assert!(tunnel(Vpn, "NORD_VPN", None).is_commercial_vpn());
+        assert!(!tunnel(Tor, "NORD_VPN", None).is_commercial_vpn());
+    }
+}
@@ -1,13 +1,27 @@
 //! IP Context Object types for the Spur Context API.
 
+use std::fmt;
+#[cfg(feature = "json")]
+use std::io;
+
 use serde::{Deserialize, Deserializer, Serialize};
 
 use super::enums::{Behavior, DeviceType, Infrastructure, Risk, Service, TunnelType};
+use super::freshness::Freshness;
+#[cfg(feature = "json")]
+use crate::canonical;
+#[cfg(feature = "json")]
+use crate::hash::Fnv1a;
 
 /// The IP Context Object summarizes all available information for an IP address.
 ///
 /// All fields may be omitted if their value is null.
+///
+/// Exports that re-emit contexts with snake_case keys also parse: `as` is
+/// accepted as `autonomous_system` and a tunnel's `type` as `tunnel_type`.
+/// Serialization always uses the API names.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(default)]
 pub struct IpContext {
     /// A top-level field describing AI activity observed from this IP address.
@@ -15,13 +29,23 @@ pub struct IpContext {
     pub ai: Option<Ai>,
 
     /// BGP autonomous system information.
-    #[serde(rename = "as", skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "as",
+        alias = "autonomous_system",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub autonomous_system: Option<AutonomousSystem>,
 
     /// Descriptive data about the connecting client.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client: Option<Client>,
 
+    /// When the enrichment was produced and last observed, filled in by the
+    /// caller. Not part of the API response, so it is neither read from nor
+    /// written to the serialized form.
+    #[serde(skip)]
+    pub freshness: Option<Freshness>,
+
     /// Infrastructure type classification (datacenter, residential, mobile, etc.).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub infrastructure: Option<Infrastructure>,
@@ -39,11 +63,19 @@ pub struct IpContext {
     pub organization: Option<String>,
 
     /// List of identified risk factors or behaviors.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Also accepted as a single delimited string, e.g. `"TUNNEL,SPAM"`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_delimited"
+    )]
     pub risks: Option<Vec<Risk>>,
 
     /// List of services or protocols in use (OpenVPN, IPSec, etc.).
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Also accepted as a single delimited string.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_delimited"
+    )]
     pub services: Option<Vec<Service>>,
 
     /// Information about tunneling methods (VPN, TOR, etc.) used.
@@ -51,8 +83,112 @@ pub struct IpContext {
     pub tunnels: Option<Vec<Tunnel>>,
 }
 
+impl IpContext {
+    /// Serialize this context as compact JSON into `writer`.
+    ///
+    /// Writes directly into the destination, so a log pipeline can reuse
+    /// one buffer for every record instead of allocating a `String` each.
+    ///
+    /// ```rust
+    /// use spur::IpContext;
+    ///
+    /// let context = IpContext { ip: Some("1.2.3.4".to_string()), ..Default::default() };
+    /// let mut buf = Vec::with_capacity(4096);
+    ///
+    /// context.write_json(&mut buf).unwrap();
+    /// assert_eq!(buf, br#"{"ip":"1.2.3.4"}"#);
+    /// buf.clear(); // ready for the next record
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn write_json<W: io::Write>(&self, writer: &mut W) -> serde_json::Result<()> {
+        serde_json::to_writer(writer, self)
+    }
+
+    /// Returns a 64-bit hash of this context's content.
+    ///
+    /// The hash is computed over the canonical JSON form (object keys
+    /// sorted), so two contexts with equal content hash equally no matter
+    /// the key order of the responses they were parsed from. Array order is
+    /// significant. [`freshness`](Self::freshness) is not serialized, so the
+    /// same enrichment fetched at different times hashes equally. The hash is
+    /// stable across processes and platforms, but may change when a crate
+    /// release adds fields.
+    ///
+    /// ```rust
+    /// use spur::IpContext;
+    ///
+    /// let a: IpContext = serde_json::from_str(r#"{"ip": "1.2.3.4", "organization": "X"}"#)?;
+    /// let b: IpContext = serde_json::from_str(r#"{"organization": "X", "ip": "1.2.3.4"}"#)?;
+    /// assert_eq!(a.content_hash(), b.content_hash());
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn content_hash(&self) -> u64 {
+        let value = serde_json::to_value(self).expect("IpContext should serialize");
+        let mut hasher = Fnv1a::new();
+        serde_json::to_writer(&mut hasher, &canonical::sort_keys(value))
+            .expect("hashing cannot fail");
+        hasher.finish()
+    }
+
+    /// Write a one-line, human-readable summary into `out`.
+    ///
+    /// The summary lists the IP, infrastructure, AS number, country, tunnels
+    /// as `TYPE(operator)` and risks, skipping fields that are absent. A
+    /// missing IP is written as `-`.
+    ///
+    /// ```rust
+    /// use spur::IpContext;
+    ///
+    /// let context: IpContext = serde_json::from_str(r#"{
+    ///     "ip": "89.39.106.191",
+    ///     "infrastructure": "DATACENTER",
+    ///     "as": { "number": 49981 },
+    ///     "location": { "country": "NL" },
+    ///     "risks": ["TUNNEL", "SPAM"],
+    ///     "tunnels": [{ "type": "VPN", "operator": "NordVPN" }]
+    /// }"#).unwrap();
+    ///
+    /// let mut line = String::new();
+    /// context.write_summary(&mut line).unwrap();
+    /// assert_eq!(line, "89.39.106.191 DATACENTER AS49981 NL VPN(NordVPN) risks=TUNNEL,SPAM");
+    /// ```
+    pub fn write_summary<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        out.write_str(self.ip.as_deref().unwrap_or("-"))?;
+        if let Some(infrastructure) = &self.infrastructure {
+            write!(out, " {}", infrastructure)?;
+        }
+        if let Some(number) = self.autonomous_system.as_ref().and_then(|a| a.number) {
+            write!(out, " AS{}", number)?;
+        }
+        if let Some(country) = self.location.as_ref().and_then(|l| l.country.as_deref()) {
+            write!(out, " {}", country)?;
+        }
+        for tunnel in self.tunnels.iter().flatten() {
+            match &tunnel.tunnel_type {
+                Some(tunnel_type) => write!(out, " {}", tunnel_type)?,
+                None => out.write_str(" TUNNEL")?,
+            }
+            if let Some(operator) = &tunnel.operator {
+                write!(out, "({})", operator)?;
+            }
+        }
+        if let Some(risks) = self.risks.as_ref().filter(|r| !r.is_empty()) {
+            out.write_str(" risks=")?;
+            for (i, risk) in risks.iter().enumerate() {
+                if i > 0 {
+                    out.write_char(',')?;
+                }
+                out.write_str(risk.as_str())?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// AI activity observed from an IP address.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(default)]
 pub struct Ai {
     /// Whether AI scraper activity has been observed.
@@ -70,6 +206,7 @@ pub struct Ai {
 
 /// BGP autonomous system information.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(default)]
 pub struct AutonomousSystem {
     /// The autonomous system number.
@@ -83,6 +220,7 @@ pub struct AutonomousSystem {
 
 /// Descriptive data about the connecting client.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(default)]
 pub struct Client {
     /// Observed client behaviors (file sharing, tor usage, etc.).
@@ -105,7 +243,7 @@ pub struct Client {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub proxies: Option<Vec<String>>,
 
-    /// Geographic spread metric.
+    /// Area in square kilometers the clients were observed over.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub spread: Option<u64>,
 
@@ -116,6 +254,7 @@ pub struct Client {
 
 /// Geographic concentration of users behind an IP.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(default)]
 pub struct Concentration {
     /// City name.
@@ -126,7 +265,8 @@ pub struct Concentration {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub country: Option<String>,
 
-    /// Density metric (0.0 to 1.0).
+    /// Share of the clients observed inside the concentration area (0.0 to
+    /// 1.0); see [`Concentration::is_tight`].
     #[serde(skip_serializing_if = "Option::is_none")]
     pub density: Option<f64>,
 
@@ -134,7 +274,8 @@ pub struct Concentration {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub geohash: Option<String>,
 
-    /// Skew metric.
+    /// Distance in kilometers between the IP's geolocation and the center
+    /// of the concentration; see [`Concentration::spread_ratio`].
     #[serde(skip_serializing_if = "Option::is_none")]
     pub skew: Option<u64>,
 
@@ -145,6 +286,7 @@ pub struct Concentration {
 
 /// Spur IP Geo location information.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(default)]
 pub struct Location {
     /// City name.
@@ -170,6 +312,7 @@ pub struct Location {
 
 /// Information about tunneling methods (VPN, TOR, etc.) used.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(default)]
 pub struct Tunnel {
     /// Whether this tunnel is anonymous.
@@ -190,15 +333,28 @@ pub struct Tunnel {
     pub operator: Option<String>,
 
     /// Type of tunnel (VPN, Proxy, Tor).
-    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "type",
+        alias = "tunnel_type",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub tunnel_type: Option<TunnelType>,
 }
 
+impl Tunnel {
+    /// Returns the operator name as a [`normalize`](crate::operators::normalize)
+    /// key, for matching against operator lists.
+    pub fn operator_normalized(&self) -> Option<String> {
+        self.operator.as_deref().map(crate::operators::normalize)
+    }
+}
+
 /// A tunnel entry (ingress point).
 ///
 /// The API may return entries as simple IP strings or as detailed objects.
 /// Both formats are supported during deserialization.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(default)]
 pub struct TunnelEntry {
     /// IP address of the entry point.
@@ -210,7 +366,11 @@ pub struct TunnelEntry {
     pub location: Option<Location>,
 
     /// Autonomous system of the entry point.
-    #[serde(rename = "as", skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "as",
+        alias = "autonomous_system",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub autonomous_system: Option<AutonomousSystem>,
 }
 
@@ -230,6 +390,11 @@ impl TunnelEntry {
 /// The Spur API returns entries in two formats:
 /// - Simple: `["1.2.3.4", "5.6.7.8"]`
 /// - Detailed: `[{"ip": "1.2.3.4", "location": {...}}]`
+///
+/// Each element is dispatched on its own shape without buffering it, so no
+/// intermediate values are allocated. This works in any self-describing
+/// format, such as JSON, CBOR or MessagePack; formats that are not
+/// self-describing cannot tell the two shapes apart and report an error.
 fn deserialize_tunnel_entries<'de, D>(deserializer: D) -> Result<Option<Vec<TunnelEntry>>, D::Error>
 where
     D: Deserializer<'de>,
@@ -252,6 +417,13 @@ where
             Ok(None)
         }
 
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(None)
+        }
+
         fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
         where
             D: Deserializer<'de>,
@@ -263,23 +435,10 @@ where
         where
             A: SeqAccess<'de>,
         {
-            let mut entries = Vec::new();
-
-            while let Some(value) = seq.next_element::<serde_json::Value>()? {
-                let entry = match value {
-                    serde_json::Value::String(ip) => TunnelEntry::from_ip(ip),
-                    serde_json::Value::Object(_) => {
-                        serde_json::from_value(value).map_err(de::Error::custom)?
-                    }
-                    _ => {
-                        return Err(de::Error::custom(
-                            "expected string or object in entries array",
-                        ))
-                    }
-                };
+            let mut entries = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(64));
+            while let Some(EntryElement(entry)) = seq.next_element()? {
                 entries.push(entry);
             }
-
             Ok(Some(entries))
         }
     }
@@ -287,10 +446,156 @@ where
     deserializer.deserialize_option(TunnelEntriesVisitor)
 }
 
+/// One element of a tunnel `entries` array: an IP string or an object.
+struct EntryElement(TunnelEntry);
+
+impl<'de> Deserialize<'de> for EntryElement {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::{self, value::MapAccessDeserializer, MapAccess, Visitor};
+
+        struct EntryVisitor;
+
+        impl<'de> Visitor<'de> for EntryVisitor {
+            type Value = TunnelEntry;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("string or object in entries array")
+            }
+
+            fn visit_str<E>(self, ip: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(TunnelEntry::from_ip(ip))
+            }
+
+            fn visit_string<E>(self, ip: String) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(TunnelEntry::from_ip(ip))
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                TunnelEntry::deserialize(MapAccessDeserializer::new(map))
+            }
+        }
+
+        deserializer.deserialize_any(EntryVisitor).map(EntryElement)
+    }
+}
+
+/// Deserialize a list that may also be given as one delimited string.
+///
+/// Some export pipelines emit `"risks": "TUNNEL,SPAM"` instead of an array.
+/// The string is split on `,`, `;` or `|`, each item is trimmed, and empty
+/// items are dropped. Arrays are read as usual. Like tunnel entries, this
+/// needs a self-describing format.
+pub(crate) fn deserialize_delimited<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    use serde::de::{self, IntoDeserializer, SeqAccess, Visitor};
+    use std::marker::PhantomData;
+
+    struct DelimitedVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for DelimitedVisitor<T> {
+        type Value = Option<Vec<T>>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("an array of strings or a delimited string")
+        }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(None)
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(None)
+        }
+
+        fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_any(self)
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            value
+                .split([',', ';', '|'])
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| T::deserialize(item.into_deserializer()))
+                .collect::<Result<_, E>>()
+                .map(Some)
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(64));
+            while let Some(item) = seq.next_element()? {
+                items.push(item);
+            }
+            Ok(Some(items))
+        }
+    }
+
+    deserializer.deserialize_any(DelimitedVisitor(PhantomData))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_deserialize_snake_case_export() {
+        let json = r#"{
+            "ip": "89.39.106.191",
+            "autonomous_system": { "number": 49981, "organization": "WorldStream" },
+            "tunnels": [{
+                "tunnel_type": "VPN",
+                "operator": "NORD_VPN",
+                "entries": [{ "ip": "1.2.3.4", "autonomous_system": { "number": 9009 } }]
+            }]
+        }"#;
+        let context: IpContext = serde_json::from_str(json).unwrap();
+        let api: IpContext = serde_json::from_str(
+            &json
+                .replace("autonomous_system", "as")
+                .replace("tunnel_type", "type"),
+        )
+        .unwrap();
+        assert_eq!(context, api);
+        assert_eq!(
+            context.autonomous_system.as_ref().unwrap().number,
+            Some(49981)
+        );
+
+        let out = serde_json::to_string(&context).unwrap();
+        assert!(out.contains(r#""as":"#) && out.contains(r#""type":"VPN""#));
+        assert!(!out.contains("autonomous_system") && !out.contains("tunnel_type"));
+    }
+
     #[test]
     fn test_deserialize_full_context() {
         let json = r#"{
@@ -427,4 +732,150 @@ mod tests {
         assert_eq!(ai.bots, Some(false));
         assert_eq!(ai.services.as_ref().unwrap(), &vec!["OPENAI", "ANTHROPIC"]);
     }
+
+    #[test]
+    fn test_write_json_matches_to_string() {
+        let context: IpContext = serde_json::from_str(
+            r#"{"ip": "1.2.3.4", "risks": ["TUNNEL"], "tunnels": [{"type": "TOR"}]}"#,
+        )
+        .unwrap();
+        let mut buf = Vec::new();
+        context.write_json(&mut buf).unwrap();
+        assert_eq!(buf, serde_json::to_vec(&context).unwrap());
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let a: IpContext = serde_json::from_str(
+            r#"{"ip": "1.2.3.4", "as": {"number": 1, "organization": "X"}, "risks": ["TUNNEL"]}"#,
+        )
+        .unwrap();
+        let b: IpContext = serde_json::from_str(
+            r#"{"risks": ["TUNNEL"], "as": {"organization": "X", "number": 1}, "ip": "1.2.3.4"}"#,
+        )
+        .unwrap();
+        assert_eq!(a.content_hash(), b.content_hash());
+        assert_eq!(a.content_hash(), a.clone().content_hash());
+        // Pinned: the hash must not depend on serde_json's map ordering.
+        assert_eq!(a.content_hash(), 10_218_400_749_306_221_487);
+
+        let c = IpContext {
+            organization: Some("Y".to_string()),
+            ..a.clone()
+        };
+        assert_ne!(a.content_hash(), c.content_hash());
+        assert_ne!(IpContext::default().content_hash(), a.content_hash());
+
+        // Fetch times are not content
+        let fetched = IpContext {
+            freshness: Some(Freshness::new(std::time::SystemTime::now())),
+            ..a.clone()
+        };
+        assert_eq!(fetched.content_hash(), a.content_hash());
+    }
+
+    #[test]
+    fn test_write_summary() {
+        let mut out = String::new();
+        IpContext::default().write_summary(&mut out).unwrap();
+        assert_eq!(out, "-");
+
+        let context: IpContext = serde_json::from_str(
+            r#"{"ip": "1.2.3.4", "risks": [], "tunnels": [{"type": "TOR"}, {"operator": "X"}]}"#,
+        )
+        .unwrap();
+        out.clear();
+        context.write_summary(&mut out).unwrap();
+        assert_eq!(out, "1.2.3.4 TOR TUNNEL(X)");
+    }
+
+    #[test]
+    fn test_deserialize_mixed_tunnel_entries() {
+        let json = r#"{"entries": ["1.2.3.4", {"ip": "5.6.7.8", "location": {"country": "NL"}}]}"#;
+        let tunnel: Tunnel = serde_json::from_str(json).unwrap();
+        let entries = tunnel.entries.unwrap();
+        assert_eq!(entries[0], TunnelEntry::from_ip("1.2.3.4"));
+        assert_eq!(entries[1].ip.as_deref(), Some("5.6.7.8"));
+        assert_eq!(
+            entries[1].location.as_ref().unwrap().country.as_deref(),
+            Some("NL")
+        );
+
+        let tunnel: Tunnel = serde_json::from_str(r#"{"entries": null}"#).unwrap();
+        assert!(tunnel.entries.is_none());
+    }
+
+    #[test]
+    fn test_deserialize_invalid_tunnel_entry() {
+        let err = serde_json::from_str::<Tunnel>(r#"{"entries": [42]}"#).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("string or object in entries array"));
+    }
+
+    #[test]
+    fn test_deserialize_tunnel_entries_from_value() {
+        // An owned `Value` hands out owned strings, exercising `visit_string`.
+        let value = serde_json::json!({"entries": ["1.2.3.4"]});
+        let tunnel = Tunnel::deserialize(value).unwrap();
+        assert_eq!(
+            tunnel.entries.unwrap(),
+            vec![TunnelEntry::from_ip("1.2.3.4")]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_delimited_risks_and_services() {
+        let json = r#"{"risks": "TUNNEL, SPAM,", "services": "OPENVPN|IPSEC"}"#;
+        let context: IpContext = serde_json::from_str(json).unwrap();
+        assert_eq!(context.risks, Some(vec![Risk::Tunnel, Risk::Spam]));
+        assert_eq!(
+            context.services,
+            Some(vec![Service::OpenVpn, Service::Ipsec])
+        );
+
+        let value = serde_json::json!({"risks": "CALLBACK_PROXY;NEW_RISK", "services": null});
+        let context = IpContext::deserialize(value).unwrap();
+        assert_eq!(
+            context.risks,
+            Some(vec![
+                Risk::CallbackProxy,
+                Risk::Other("NEW_RISK".to_string())
+            ])
+        );
+        assert!(context.services.is_none());
+
+        let context: IpContext = serde_json::from_str(r#"{"risks": ""}"#).unwrap();
+        assert_eq!(context.risks, Some(vec![]));
+        assert!(serde_json::from_str::<IpContext>(r#"{"risks": 7}"#).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_lenient_fields_from_cbor() {
+        // CBOR is self-describing but not human-readable.
+        let cbor = |value: serde_json::Value| {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(&value, &mut bytes).unwrap();
+            bytes
+        };
+        let bytes = cbor(serde_json::json!({
+            "risks": "TUNNEL,SPAM",
+            "tunnels": [{"entries": ["1.2.3.4", {"ip": "5.6.7.8"}]}]
+        }));
+        let context: IpContext = ciborium::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(context.risks, Some(vec![Risk::Tunnel, Risk::Spam]));
+        assert_eq!(
+            context.tunnels.unwrap()[0].entries,
+            Some(vec![
+                TunnelEntry::from_ip("1.2.3.4"),
+                TunnelEntry::from_ip("5.6.7.8")
+            ])
+        );
+
+        let context = crate::test_utils::fixtures::vpn_ip();
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&context, &mut bytes).unwrap();
+        let parsed: IpContext = ciborium::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(parsed, context);
+    }
 }
@@ -0,0 +1,304 @@
+//! Detection and conversion of archived v1 Context API responses.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::enums::{Behavior, Infrastructure, Risk, TunnelType};
+use super::types::{AutonomousSystem, Client, IpContext, Location, Tunnel};
+
+/// Top-level keys that only appear in v1 responses.
+const V1_KEYS: &[&str] = &[
+    "anonymous",
+    "assignment",
+    "deviceBehaviors",
+    "devices",
+    "geoLite",
+    "geoPrediction",
+    "proxiedTraffic",
+    "similarIPs",
+    "vpnOperators",
+    "wifi",
+];
+
+/// Top-level keys that only appear in v2 responses.
+const V2_KEYS: &[&str] = &["ai", "client", "location", "risks", "services", "tunnels"];
+
+/// The Context API version a JSON document was produced by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContextVersion {
+    /// The v1 layout, with `vpnOperators`, `proxiedTraffic`, `geoLite` and
+    /// similar camelCase sections.
+    V1,
+    /// The current layout, deserialized directly into [`IpContext`].
+    V2,
+}
+
+impl ContextVersion {
+    /// Guess the version of a parsed response from its top-level keys.
+    ///
+    /// Documents with v1-only keys and no v2-only keys are [`V1`](Self::V1);
+    /// everything else, including documents with only the fields both
+    /// versions share (`ip`, `as`, `infrastructure`), is [`V2`](Self::V2).
+    pub fn detect(value: &Value) -> Self {
+        let Some(object) = value.as_object() else {
+            return Self::V2;
+        };
+        let has_any = |keys: &[&str]| keys.iter().any(|key| object.contains_key(*key));
+        if has_any(V1_KEYS) && !has_any(V2_KEYS) {
+            Self::V1
+        } else {
+            Self::V2
+        }
+    }
+}
+
+/// Parse a v1 or v2 response into the current [`IpContext`].
+///
+/// The version is detected with [`ContextVersion::detect`] and returned
+/// alongside the context. v1 sections are mapped onto their v2
+/// equivalents:
+///
+/// | v1 | v2 |
+/// |----|----|
+/// | `geoLite`, `geoPrediction.center` | `location` |
+/// | `devices.estimate` | `client.count` |
+/// | `deviceBehaviors.behaviors[].name` | `client.behaviors` |
+/// | `proxiedTraffic.proxies[].name` | `client.proxies`, `CALLBACK_PROXY` risk |
+/// | `vpnOperators.operators[].name` | VPN `tunnels`, `TUNNEL` risk |
+///
+/// Other v1 sections have no v2 counterpart and are dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::context::{parse_versioned, ContextVersion};
+/// use spur::TunnelType;
+///
+/// let archived = r#"{
+///     "ip": "89.39.106.191",
+///     "anonymous": true,
+///     "geoLite": { "country": "NL" },
+///     "vpnOperators": { "exists": true, "operators": [{ "name": "NORD_VPN" }] }
+/// }"#;
+///
+/// let (version, context) = parse_versioned(archived)?;
+/// assert_eq!(version, ContextVersion::V1);
+/// assert_eq!(context.tunnels.unwrap()[0].tunnel_type, Some(TunnelType::Vpn));
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn parse_versioned(json: &str) -> serde_json::Result<(ContextVersion, IpContext)> {
+    from_versioned_value(serde_json::from_str(json)?)
+}
+
+/// Like [`parse_versioned`], for an already parsed [`Value`].
+pub fn from_versioned_value(value: Value) -> serde_json::Result<(ContextVersion, IpContext)> {
+    let version = ContextVersion::detect(&value);
+    let context = match version {
+        ContextVersion::V1 => serde_json::from_value::<V1Context>(value)?.into(),
+        ContextVersion::V2 => serde_json::from_value(value)?,
+    };
+    Ok((version, context))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct V1Context {
+    ip: Option<String>,
+    #[serde(rename = "as")]
+    autonomous_system: Option<AutonomousSystem>,
+    organization: Option<String>,
+    infrastructure: Option<Infrastructure>,
+    anonymous: Option<bool>,
+    geo_lite: Option<V1Geo>,
+    geo_prediction: Option<V1GeoPrediction>,
+    devices: Option<V1Devices>,
+    device_behaviors: Option<V1Named<Behavior>>,
+    proxied_traffic: Option<V1Named<String>>,
+    vpn_operators: Option<V1Named<String>>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct V1Geo {
+    city: Option<String>,
+    country: Option<String>,
+    state: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct V1GeoPrediction {
+    center: Option<V1Point>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct V1Point {
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct V1Devices {
+    estimate: Option<u64>,
+}
+
+/// A v1 section listing named items under one of several keys.
+#[derive(Deserialize)]
+#[serde(bound = "T: Deserialize<'de>")]
+struct V1Named<T> {
+    #[serde(alias = "behaviors", alias = "proxies", alias = "operators")]
+    items: Option<Vec<V1Name<T>>>,
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "T: Deserialize<'de>")]
+struct V1Name<T> {
+    name: Option<T>,
+}
+
+impl<T> V1Named<T> {
+    fn names(section: Option<Self>) -> Option<Vec<T>> {
+        let items = section?.items?;
+        let names: Vec<T> = items.into_iter().filter_map(|item| item.name).collect();
+        (!names.is_empty()).then_some(names)
+    }
+}
+
+impl From<V1Context> for IpContext {
+    fn from(v1: V1Context) -> Self {
+        let center = v1.geo_prediction.and_then(|p| p.center);
+        let location = match (v1.geo_lite, center) {
+            (None, None) => None,
+            (geo, center) => {
+                let geo = geo.unwrap_or_default();
+                let center = center.unwrap_or_default();
+                Some(Location {
+                    city: geo.city,
+                    country: geo.country,
+                    latitude: center.latitude,
+                    longitude: center.longitude,
+                    state: geo.state,
+                })
+            }
+        };
+
+        let behaviors = V1Named::names(v1.device_behaviors);
+        let proxies = V1Named::names(v1.proxied_traffic);
+        let count = v1.devices.and_then(|d| d.estimate);
+        let client =
+            (behaviors.is_some() || proxies.is_some() || count.is_some()).then(|| Client {
+                behaviors,
+                count,
+                proxies: proxies.clone(),
+                ..Default::default()
+            });
+
+        let tunnels = V1Named::names(v1.vpn_operators).map(|operators| {
+            operators
+                .into_iter()
+                .map(|operator| Tunnel {
+                    anonymous: v1.anonymous,
+                    entries: None,
+                    operator: Some(operator),
+                    tunnel_type: Some(TunnelType::Vpn),
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let mut risks = Vec::new();
+        if tunnels.is_some() {
+            risks.push(Risk::Tunnel);
+        }
+        if proxies.is_some() {
+            risks.push(Risk::CallbackProxy);
+        }
+
+        IpContext {
+            autonomous_system: v1.autonomous_system,
+            client,
+            infrastructure: v1.infrastructure,
+            ip: v1.ip,
+            location,
+            organization: v1.organization,
+            risks: (!risks.is_empty()).then_some(risks),
+            tunnels,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixture_json, fixture_names};
+
+    const V1_RESPONSE: &str = r#"{
+        "ip": "198.51.100.23",
+        "anonymous": true,
+        "as": {"number": 9009, "organization": "M247 Ltd"},
+        "assignment": {"exists": true, "lastTurnover": "2021-03-04"},
+        "deviceBehaviors": {"exists": true, "behaviors": [{"name": "FILE_SHARING"}]},
+        "devices": {"estimate": 25},
+        "geoLite": {"city": "Amsterdam", "country": "NL", "state": "North Holland"},
+        "geoPrediction": {"exists": true, "center": {"latitude": 52.37, "longitude": 4.89}},
+        "infrastructure": "DATACENTER",
+        "proxiedTraffic": {"exists": true, "proxies": [{"name": "LUMINATI_PROXY"}]},
+        "similarIPs": {"exists": false},
+        "vpnOperators": {
+            "exists": true,
+            "operators": [{"name": "NORD_VPN"}, {"name": "SURFSHARK_VPN"}]
+        }
+    }"#;
+
+    #[test]
+    fn test_converts_v1_response() {
+        let (version, context) = parse_versioned(V1_RESPONSE).unwrap();
+        assert_eq!(version, ContextVersion::V1);
+        assert_eq!(context.ip.as_deref(), Some("198.51.100.23"));
+        assert_eq!(context.infrastructure, Some(Infrastructure::Datacenter));
+
+        let location = context.location.unwrap();
+        assert_eq!(location.country.as_deref(), Some("NL"));
+        assert_eq!(location.latitude, Some(52.37));
+
+        let client = context.client.unwrap();
+        assert_eq!(client.count, Some(25));
+        assert_eq!(client.behaviors, Some(vec![Behavior::FileSharing]));
+        assert_eq!(client.proxies, Some(vec!["LUMINATI_PROXY".to_string()]));
+
+        let tunnels = context.tunnels.unwrap();
+        assert_eq!(tunnels.len(), 2);
+        assert_eq!(tunnels[1].operator.as_deref(), Some("SURFSHARK_VPN"));
+        assert_eq!(tunnels[0].anonymous, Some(true));
+        assert_eq!(context.risks, Some(vec![Risk::Tunnel, Risk::CallbackProxy]));
+    }
+
+    #[test]
+    fn test_minimal_v1_response() {
+        let json = r#"{"ip": "1.2.3.4", "vpnOperators": {"exists": false}}"#;
+        let (version, context) = parse_versioned(json).unwrap();
+        assert_eq!(version, ContextVersion::V1);
+        assert_eq!(
+            context,
+            IpContext {
+                ip: Some("1.2.3.4".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_v2_fixtures_pass_through() {
+        for name in fixture_names() {
+            let json = fixture_json(name).unwrap();
+            let (version, context) = parse_versioned(json).unwrap();
+            assert_eq!(version, ContextVersion::V2, "{}", name);
+            assert_eq!(context, serde_json::from_str(json).unwrap(), "{}", name);
+        }
+        let shared = serde_json::json!({"ip": "1.2.3.4", "infrastructure": "MOBILE"});
+        assert_eq!(ContextVersion::detect(&shared), ContextVersion::V2);
+        assert!(parse_versioned("[1, 2]").is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Narrow, borrowed views of one concern of an [`IpContext`].
+
+use super::country::CountryCode;
+use super::enums::{Infrastructure, Risk, TunnelType};
+use super::types::{Concentration, IpContext, Location, Tunnel};
+
+/// The risk-related parts of a context, from [`IpContext::risk_view`].
+///
+/// Functions that only decide on risk can take a `RiskView` instead of the
+/// whole context. The fields are public, so tests can build a view from
+/// just the parts they need.
+///
+/// ```rust
+/// use spur::{Risk, RiskView};
+///
+/// fn should_challenge(risk: RiskView<'_>) -> bool {
+///     risk.is_anonymous() || risk.has(&Risk::CallbackProxy)
+/// }
+///
+/// let view = RiskView { risks: &[Risk::CallbackProxy], ..Default::default() };
+/// assert!(should_challenge(view));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RiskView<'a> {
+    /// The risk factors.
+    pub risks: &'a [Risk],
+    /// The tunnels the address is part of.
+    pub tunnels: &'a [Tunnel],
+    /// The infrastructure class.
+    pub infrastructure: Option<&'a Infrastructure>,
+}
+
+impl<'a> RiskView<'a> {
+    /// Returns `true` if `risk` is among the risk factors.
+    pub fn has(&self, risk: &Risk) -> bool {
+        self.risks.contains(risk)
+    }
+
+    /// Returns `true` if the address is part of any tunnel or carries the
+    /// `TUNNEL` risk.
+    pub fn is_tunneled(&self) -> bool {
+        !self.tunnels.is_empty() || self.has(&Risk::Tunnel)
+    }
+
+    /// Returns `true` if any tunnel is anonymous.
+    pub fn is_anonymous(&self) -> bool {
+        self.tunnels.iter().any(|t| t.anonymous == Some(true))
+    }
+
+    /// Returns `true` if any tunnel is a VPN.
+    pub fn is_vpn(&self) -> bool {
+        self.has_tunnel_type(TunnelType::Vpn)
+    }
+
+    /// Returns `true` if any tunnel is a Tor relay.
+    pub fn is_tor(&self) -> bool {
+        self.has_tunnel_type(TunnelType::Tor)
+    }
+
+    /// Returns `true` if the infrastructure is a datacenter.
+    pub fn is_datacenter(&self) -> bool {
+        self.infrastructure == Some(&Infrastructure::Datacenter)
+    }
+
+    /// Returns the tunnel operators, in order.
+    pub fn operators(&self) -> impl Iterator<Item = &'a str> {
+        self.tunnels.iter().filter_map(|t| t.operator.as_deref())
+    }
+
+    fn has_tunnel_type(&self, tunnel_type: TunnelType) -> bool {
+        self.tunnels
+            .iter()
+            .any(|t| t.tunnel_type.as_ref() == Some(&tunnel_type))
+    }
+}
+
+/// The geographic parts of a context, from [`IpContext::geo_view`].
+///
+/// Like [`RiskView`], the fields are public so tests can build a view
+/// directly.
+///
+/// ```rust
+/// use spur::{GeoView, Location};
+///
+/// let location = Location { country: Some("nl".into()), ..Default::default() };
+/// let geo = GeoView { location: Some(&location), ..Default::default() };
+/// assert!(geo.is_in("NL"));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GeoView<'a> {
+    /// Where the address is located.
+    pub location: Option<&'a Location>,
+    /// Where the clients behind the address are concentrated.
+    pub concentration: Option<&'a Concentration>,
+    /// The tunnels, for their entry locations.
+    pub tunnels: &'a [Tunnel],
+}
+
+impl<'a> GeoView<'a> {
+    /// Returns the country of the address as reported.
+    pub fn country(&self) -> Option<&'a str> {
+        self.location.and_then(|l| l.country.as_deref())
+    }
+
+    /// Returns the country of the address as a [`CountryCode`].
+    pub fn country_code(&self) -> Option<CountryCode> {
+        self.location.and_then(Location::country_code)
+    }
+
+    /// Returns the city of the address.
+    pub fn city(&self) -> Option<&'a str> {
+        self.location.and_then(|l| l.city.as_deref())
+    }
+
+    /// Returns the latitude and longitude of the address, if both are set.
+    pub fn coordinates(&self) -> Option<(f64, f64)> {
+        let location = self.location?;
+        Some((location.latitude?, location.longitude?))
+    }
+
+    /// Returns `true` if the address is located in `country`, compared
+    /// without regard to case.
+    pub fn is_in(&self, country: &str) -> bool {
+        self.country()
+            .is_some_and(|c| c.eq_ignore_ascii_case(country))
+    }
+
+    /// Returns `true` if the clients are concentrated in a different
+    /// country than the address is located in.
+    pub fn clients_elsewhere(&self) -> bool {
+        let concentration = self.concentration.and_then(|c| c.country.as_deref());
+        match (concentration, self.country()) {
+            (Some(a), Some(b)) => !a.eq_ignore_ascii_case(b),
+            _ => false,
+        }
+    }
+
+    /// Returns the distinct countries of the tunnel entries, in order of
+    /// first appearance.
+    pub fn entry_countries(&self) -> Vec<&'a str> {
+        let mut countries: Vec<&str> = Vec::new();
+        let entries = self.tunnels.iter().flat_map(|t| t.entries.iter().flatten());
+        for country in entries.filter_map(|e| e.location.as_ref()?.country.as_deref()) {
+            if !countries.contains(&country) {
+                countries.push(country);
+            }
+        }
+        countries
+    }
+}
+
+impl IpContext {
+    /// Returns the risk-related parts of this context.
+    pub fn risk_view(&self) -> RiskView<'_> {
+        RiskView {
+            risks: self.risks.as_deref().unwrap_or_default(),
+            tunnels: self.tunnels.as_deref().unwrap_or_default(),
+            infrastructure: self.infrastructure.as_ref(),
+        }
+    }
+
+    /// Returns the geographic parts of this context.
+    pub fn geo_view(&self) -> GeoView<'_> {
+        GeoView {
+            location: self.location.as_ref(),
+            concentration: self.client.as_ref().and_then(|c| c.concentration.as_ref()),
+            tunnels: self.tunnels.as_deref().unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::fixtures;
+
+    #[test]
+    fn test_risk_view() {
+        let tor = fixtures::tor_exit_node();
+        let risk = tor.risk_view();
+        assert!(risk.is_tunneled() && risk.is_tor() && !risk.is_vpn());
+        assert_eq!(risk.risks, tor.risks.as_deref().unwrap());
+
+        let vpn = fixtures::vpn_ip();
+        assert!(vpn.risk_view().is_vpn());
+        assert!(vpn.risk_view().operators().next().is_some());
+
+        let clean = IpContext::default();
+        assert_eq!(clean.risk_view(), RiskView::default());
+        assert!(!clean.risk_view().is_tunneled());
+    }
+
+    #[test]
+    fn test_geo_view() {
+        let proxy = fixtures::residential_proxy_ip();
+        let geo = proxy.geo_view();
+        assert!(geo.is_in("us"));
+        assert_eq!(geo.city(), Some("Seattle"));
+        assert!(geo.clients_elsewhere());
+
+        let context = crate::test_utils::IpContextBuilder::new()
+            .vpn_with_entry("PROTON_VPN", "198.51.100.20", "CH")
+            .vpn_with_entry("NORD_VPN", "198.51.100.21", "CH")
+            .build();
+        assert_eq!(context.geo_view().entry_countries(), ["CH"]);
+        assert_eq!(context.geo_view().coordinates(), None);
+    }
+}
@@ -0,0 +1,166 @@
+//! Recognizing a customer's own egress addresses.
+//!
+//! Corporate VPNs, secure web gateways and office NAT pools show up in Spur
+//! data as tunnels, so rules that challenge anonymous traffic also catch the
+//! customer's own employees. [`check`] compares a context against the
+//! networks the customer is known to egress from and says whether a
+//! tunneled-looking address is really one of them, so a policy can let it
+//! through before other rules run.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ipnet::IpNet;
+//! use spur::egress::{self, EgressCheck};
+//! use spur::test_utils::IpContextBuilder;
+//!
+//! let corporate: Vec<IpNet> = vec!["198.51.100.0/24".parse().unwrap()];
+//!
+//! let employee = IpContextBuilder::new()
+//!     .ip("198.51.100.20")
+//!     .vpn("ZSCALER")
+//!     .build();
+//! let check = egress::check(&employee, &corporate);
+//! assert!(matches!(check, EgressCheck::KnownEgress { .. }));
+//! assert_eq!(check.network(), Some(corporate[0]));
+//!
+//! let outsider = IpContextBuilder::new()
+//!     .ip("89.39.106.191")
+//!     .vpn("NORD_VPN")
+//!     .build();
+//! assert_eq!(egress::check(&outsider, &corporate), EgressCheck::Tunneled);
+//! ```
+
+use ipnet::IpNet;
+
+use crate::context::IpContext;
+use crate::ip_utils;
+#[cfg(feature = "policy")]
+use crate::policy::Decision;
+
+/// Reason code of decisions for addresses in a corporate egress range.
+pub const CORPORATE_EGRESS: &str = "CORPORATE_EGRESS";
+
+/// How a context relates to the customer's egress ranges, from [`check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EgressCheck {
+    /// The address looks tunneled but is in a corporate egress range: a
+    /// false positive of tunnel-based rules.
+    KnownEgress {
+        /// The most specific range containing the address.
+        network: IpNet,
+    },
+    /// The address is in a corporate egress range and does not look
+    /// tunneled.
+    Corporate {
+        /// The most specific range containing the address.
+        network: IpNet,
+    },
+    /// The address looks tunneled and is outside every range.
+    Tunneled,
+    /// The address is outside every range and does not look tunneled, or
+    /// the context has no address.
+    Other,
+}
+
+impl EgressCheck {
+    /// Returns the corporate range the address is in, if any.
+    pub fn network(&self) -> Option<IpNet> {
+        match self {
+            Self::KnownEgress { network } | Self::Corporate { network } => Some(*network),
+            Self::Tunneled | Self::Other => None,
+        }
+    }
+
+    /// Returns `true` if the address is in a corporate egress range.
+    pub fn is_corporate(&self) -> bool {
+        self.network().is_some()
+    }
+
+    /// Returns an `Allow` decision with reason [`CORPORATE_EGRESS`] for
+    /// addresses in a corporate range, or `None` to leave the context to
+    /// other rules (requires the `policy` feature).
+    #[cfg(feature = "policy")]
+    pub fn decide(&self) -> Option<Decision> {
+        self.is_corporate()
+            .then(|| Decision::allow().with_reason(CORPORATE_EGRESS))
+    }
+}
+
+/// Classify `context` against the `corporate_egress` networks.
+///
+/// The address checked is `context.ip`, with IPv4-mapped IPv6 addresses
+/// matched as IPv4. A context looks tunneled if it has a tunnel or the
+/// `TUNNEL` risk.
+pub fn check(context: &IpContext, corporate_egress: &[IpNet]) -> EgressCheck {
+    let network = context
+        .ip
+        .as_deref()
+        .and_then(ip_utils::parse)
+        .and_then(|ip| {
+            corporate_egress
+                .iter()
+                .filter(|net| net.contains(&ip))
+                .max_by_key(|net| net.prefix_len())
+                .copied()
+        });
+    let tunneled = context.risk_view().is_tunneled();
+
+    match (network, tunneled) {
+        (Some(network), true) => EgressCheck::KnownEgress { network },
+        (Some(network), false) => EgressCheck::Corporate { network },
+        (None, true) => EgressCheck::Tunneled,
+        (None, false) => EgressCheck::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::IpContextBuilder;
+
+    fn nets(nets: &[&str]) -> Vec<IpNet> {
+        nets.iter().map(|n| n.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_check() {
+        let corporate = nets(&["203.0.113.0/24", "203.0.113.128/25", "2001:db8::/32"]);
+        let vpn = |ip: &str| IpContextBuilder::new().ip(ip).vpn("ZSCALER").build();
+
+        assert_eq!(
+            check(&vpn("203.0.113.200"), &corporate),
+            EgressCheck::KnownEgress {
+                network: "203.0.113.128/25".parse().unwrap()
+            }
+        );
+        assert_eq!(
+            check(&vpn("::ffff:203.0.113.5"), &corporate).network(),
+            Some("203.0.113.0/24".parse().unwrap())
+        );
+        assert!(check(&vpn("2001:db8::1"), &corporate).is_corporate());
+        assert_eq!(check(&vpn("192.0.2.1"), &corporate), EgressCheck::Tunneled);
+
+        let office = IpContextBuilder::new().ip("203.0.113.9").build();
+        assert!(matches!(
+            check(&office, &corporate),
+            EgressCheck::Corporate { .. }
+        ));
+        assert_eq!(check(&IpContext::default(), &corporate), EgressCheck::Other);
+        assert_eq!(check(&vpn("203.0.113.9"), &[]), EgressCheck::Tunneled);
+    }
+
+    #[cfg(feature = "policy")]
+    #[test]
+    fn test_decide() {
+        let known = EgressCheck::KnownEgress {
+            network: "203.0.113.0/24".parse().unwrap(),
+        };
+        assert_eq!(
+            known.decide(),
+            Some(Decision::allow().with_reason(CORPORATE_EGRESS))
+        );
+        assert_eq!(EgressCheck::Tunneled.decide(), None);
+        assert_eq!(EgressCheck::Other.decide(), None);
+    }
+}
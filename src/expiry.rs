@@ -0,0 +1,126 @@
+//! Age-based expiry shared by caches and histories.
+//!
+//! Whether a context is still fresh enough to act on depends on when it was
+//! fetched and how long data of its kind stays valid. [`Expiring`] carries
+//! both next to the value, so every layer answers "is this stale?" the same
+//! way: [`CacheEntry::expiring`](crate::cache::CacheEntry::expiring) for
+//! cached entries, [`Snapshot::expiring`](crate::history::Snapshot::expiring)
+//! for recorded history, [`IpContext::expiring`](crate::IpContext::expiring)
+//! for contexts carrying [`Freshness`](crate::context::Freshness)
+//! timestamps, and [`Expiring::new`] for contexts held anywhere else, such
+//! as per-request state in a web framework.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::time::{Duration, UNIX_EPOCH};
+//! use spur::expiry::Expiring;
+//! use spur::IpContext;
+//!
+//! let fetched_at = UNIX_EPOCH + Duration::from_secs(1_000);
+//! let context = Expiring::new(IpContext::default(), fetched_at, Some(Duration::from_secs(60)));
+//!
+//! let now = fetched_at + Duration::from_secs(45);
+//! assert!(!context.is_expired(now));
+//! assert_eq!(context.remaining(now), Some(Duration::from_secs(15)));
+//! assert!(context.is_expired(now + Duration::from_secs(15)));
+//! ```
+
+use std::time::{Duration, SystemTime};
+
+/// A value together with when it was fetched and how long it stays valid.
+///
+/// A `ttl` of `None` means the value never expires. Times are passed in
+/// explicitly, so checks are deterministic in tests; a fetch time after
+/// `now` counts as age zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Expiring<T> {
+    /// The value.
+    pub value: T,
+    /// When the value was fetched.
+    pub fetched_at: SystemTime,
+    /// How long the value stays valid after `fetched_at`.
+    pub ttl: Option<Duration>,
+}
+
+impl<T> Expiring<T> {
+    /// Wrap `value`, fetched at `fetched_at` and valid for `ttl`.
+    pub fn new(value: T, fetched_at: SystemTime, ttl: Option<Duration>) -> Self {
+        Self {
+            value,
+            fetched_at,
+            ttl,
+        }
+    }
+
+    /// Returns how long ago the value was fetched.
+    pub fn age(&self, now: SystemTime) -> Duration {
+        now.duration_since(self.fetched_at).unwrap_or_default()
+    }
+
+    /// Returns when the value expires, or `None` if it never does.
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.ttl.and_then(|ttl| self.fetched_at.checked_add(ttl))
+    }
+
+    /// Returns `true` once the value's age reaches its TTL.
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.ttl.is_some_and(|ttl| self.age(now) >= ttl)
+    }
+
+    /// Returns how long the value stays valid, zero once it has expired, or
+    /// `None` if it never expires.
+    pub fn remaining(&self, now: SystemTime) -> Option<Duration> {
+        self.ttl.map(|ttl| ttl.saturating_sub(self.age(now)))
+    }
+
+    /// Borrow the value, keeping the timing.
+    pub fn as_ref(&self) -> Expiring<&T> {
+        Expiring::new(&self.value, self.fetched_at, self.ttl)
+    }
+
+    /// Transform the value, keeping the timing.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Expiring<U> {
+        Expiring::new(f(self.value), self.fetched_at, self.ttl)
+    }
+
+    /// Returns the value, dropping the timing.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_expiry() {
+        let value = Expiring::new("ctx", at(100), Some(Duration::from_secs(10)));
+        assert_eq!(value.expires_at(), Some(at(110)));
+        assert_eq!(value.age(at(104)), Duration::from_secs(4));
+        assert_eq!(value.remaining(at(104)), Some(Duration::from_secs(6)));
+        assert!(!value.is_expired(at(109)));
+        assert!(value.is_expired(at(110)));
+        assert_eq!(value.remaining(at(500)), Some(Duration::ZERO));
+
+        // Clock skew: fetched "in the future"
+        assert_eq!(value.age(at(50)), Duration::ZERO);
+        assert!(!value.is_expired(at(50)));
+    }
+
+    #[test]
+    fn test_no_ttl() {
+        let value = Expiring::new(1, at(0), None);
+        assert!(!value.is_expired(at(u32::MAX.into())));
+        assert_eq!(value.remaining(at(10)), None);
+        assert_eq!(value.expires_at(), None);
+        assert_eq!(value.map(|n| n + 1).as_ref().into_inner(), &2);
+    }
+}
@@ -0,0 +1,25 @@
+//! Geographic helpers shared by location-based features.
+
+/// Great-circle distance in kilometres between two `(latitude, longitude)`
+/// points given in degrees.
+pub(crate) fn haversine_km(a: (f64, f64), b: (f64, f64)) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+    let (lat1, lat2) = (a.0.to_radians(), b.0.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (b.1 - a.1).to_radians();
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_haversine() {
+        let paris = (48.8566, 2.3522);
+        let london = (51.5074, -0.1278);
+        assert!((haversine_km(paris, london) - 343.5).abs() < 1.0);
+        assert_eq!(haversine_km(paris, paris), 0.0);
+    }
+}
@@ -0,0 +1,323 @@
+//! Links between contexts that share infrastructure.
+//!
+//! Investigations often start from one address and ask what else runs on
+//! the same infrastructure: other exits of the same VPN operator, other
+//! addresses in the same autonomous system, or other exits reached through
+//! the same tunnel entry. [`pivot`] links every pair of contexts sharing
+//! one of those attributes, and [`PivotGraph`] answers neighbourhood
+//! queries over the result.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::graph::{pivot, Link};
+//! use spur::test_utils::IpContextBuilder;
+//!
+//! let contexts = [
+//!     IpContextBuilder::new().ip("89.39.106.191").vpn("NORD_VPN").build(),
+//!     IpContextBuilder::new().ip("89.39.107.10").vpn("NORD_VPN").build(),
+//!     IpContextBuilder::new().ip("203.0.113.9").vpn("PROTON_VPN").build(),
+//! ];
+//!
+//! let graph = pivot(&contexts);
+//! let neighbors = graph.neighbors("89.39.106.191");
+//! assert_eq!(neighbors.len(), 1);
+//! assert_eq!(neighbors[0].context.ip.as_deref(), Some("89.39.107.10"));
+//! assert_eq!(neighbors[0].via, [Link::Operator("NORD_VPN".to_string())]);
+//! ```
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+
+use crate::context::IpContext;
+use crate::ip_utils;
+
+/// An attribute shared by linked contexts.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Link {
+    /// Both contexts have a tunnel run by this operator.
+    Operator(String),
+    /// Both contexts are in this autonomous system.
+    Asn(u32),
+    /// Both contexts have a tunnel with this entry address.
+    TunnelEntry(String),
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Operator(operator) => write!(f, "operator {}", operator),
+            Self::Asn(number) => write!(f, "AS{}", number),
+            Self::TunnelEntry(ip) => write!(f, "tunnel entry {}", ip),
+        }
+    }
+}
+
+/// A context linked to the one queried.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Neighbor<'a> {
+    /// The linked context.
+    pub context: &'a IpContext,
+    /// Every attribute the two contexts share, in sorted order.
+    pub via: Vec<Link>,
+}
+
+/// Contexts linked by shared operators, autonomous systems and tunnel
+/// entries. Built by [`pivot`].
+#[derive(Debug, Clone)]
+pub struct PivotGraph<'a> {
+    contexts: &'a [IpContext],
+    by_ip: HashMap<String, usize>,
+    links: BTreeMap<Link, Vec<usize>>,
+    node_links: Vec<Vec<Link>>,
+}
+
+/// Build a [`PivotGraph`] over `contexts`.
+///
+/// Contexts are looked up by their `ip` field in canonical form (see
+/// [`ip_utils`]); if several contexts have the same address, queries by
+/// that address start from the first of them.
+pub fn pivot(contexts: &[IpContext]) -> PivotGraph<'_> {
+    let mut by_ip = HashMap::new();
+    let mut links: BTreeMap<Link, Vec<usize>> = BTreeMap::new();
+    let mut node_links = Vec::with_capacity(contexts.len());
+
+    for (index, context) in contexts.iter().enumerate() {
+        if let Some(ip) = context.ip.as_deref() {
+            by_ip.entry(ip_key(ip)).or_insert(index);
+        }
+        let mut own = links_of(context);
+        own.sort();
+        own.dedup();
+        for link in &own {
+            links.entry(link.clone()).or_default().push(index);
+        }
+        node_links.push(own);
+    }
+
+    PivotGraph {
+        contexts,
+        by_ip,
+        links,
+        node_links,
+    }
+}
+
+/// Canonical form of an address for lookups, or the text itself if it is
+/// not an address.
+fn ip_key(ip: &str) -> String {
+    ip_utils::canonicalize(ip).unwrap_or_else(|| ip.to_string())
+}
+
+fn links_of(context: &IpContext) -> Vec<Link> {
+    let tunnels = context.tunnels.iter().flatten();
+    let operators = tunnels
+        .clone()
+        .filter_map(|t| t.operator.clone())
+        .map(Link::Operator);
+    let entries = tunnels
+        .flat_map(|t| t.entries.iter().flatten())
+        .filter_map(|e| e.ip.as_deref())
+        .map(|ip| Link::TunnelEntry(ip_key(ip)));
+    let asn = context
+        .autonomous_system
+        .as_ref()
+        .and_then(|a| a.number)
+        .map(Link::Asn);
+    operators.chain(entries).chain(asn).collect()
+}
+
+impl<'a> PivotGraph<'a> {
+    fn index_of(&self, ip: &str) -> Option<usize> {
+        self.by_ip.get(&ip_key(ip)).copied()
+    }
+
+    /// Returns the number of contexts in the graph.
+    pub fn len(&self) -> usize {
+        self.contexts.len()
+    }
+
+    /// Returns `true` if the graph has no contexts.
+    pub fn is_empty(&self) -> bool {
+        self.contexts.is_empty()
+    }
+
+    /// Returns the context with address `ip`, if present.
+    pub fn get(&self, ip: &str) -> Option<&'a IpContext> {
+        self.index_of(ip).map(|index| &self.contexts[index])
+    }
+
+    /// Returns the attributes of `ip` that link it to other contexts.
+    pub fn links(&self, ip: &str) -> Vec<&Link> {
+        let Some(index) = self.index_of(ip) else {
+            return Vec::new();
+        };
+        self.node_links[index]
+            .iter()
+            .filter(|link| self.links[*link].len() > 1)
+            .collect()
+    }
+
+    /// Returns every context with attribute `link`, in input order.
+    pub fn sharing(&self, link: &Link) -> Vec<&'a IpContext> {
+        self.links
+            .get(link)
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.contexts[index])
+            .collect()
+    }
+
+    /// Returns the contexts one hop from `ip`, in input order, with the
+    /// attributes each shares with it.
+    pub fn neighbors(&self, ip: &str) -> Vec<Neighbor<'a>> {
+        let Some(start) = self.index_of(ip) else {
+            return Vec::new();
+        };
+        let mut via: BTreeMap<usize, Vec<Link>> = BTreeMap::new();
+        for link in &self.node_links[start] {
+            for &index in &self.links[link] {
+                if index != start {
+                    via.entry(index).or_default().push(link.clone());
+                }
+            }
+        }
+        via.into_iter()
+            .map(|(index, via)| Neighbor {
+                context: &self.contexts[index],
+                via,
+            })
+            .collect()
+    }
+
+    /// Returns the contexts at most `hops` links away from `ip`, excluding
+    /// the context itself, with their distance.
+    ///
+    /// Results are ordered by distance, then input order.
+    pub fn within(&self, ip: &str, hops: usize) -> Vec<(&'a IpContext, usize)> {
+        let Some(start) = self.index_of(ip) else {
+            return Vec::new();
+        };
+        let mut distance: Vec<Option<usize>> = vec![None; self.contexts.len()];
+        distance[start] = Some(0);
+        let mut queue = VecDeque::from([start]);
+        while let Some(index) = queue.pop_front() {
+            let next = distance[index].unwrap_or_default() + 1;
+            if next > hops {
+                continue;
+            }
+            for link in &self.node_links[index] {
+                for &other in &self.links[link] {
+                    if distance[other].is_none() {
+                        distance[other] = Some(next);
+                        queue.push_back(other);
+                    }
+                }
+            }
+        }
+        let mut found: Vec<(usize, usize)> = distance
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, d)| d.filter(|&d| d > 0).map(|d| (d, index)))
+            .collect();
+        found.sort_unstable();
+        found
+            .into_iter()
+            .map(|(d, index)| (&self.contexts[index], d))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::IpContextBuilder;
+
+    fn contexts() -> Vec<IpContext> {
+        vec![
+            IpContextBuilder::new()
+                .ip("10.0.0.1")
+                .vpn_with_entry("NORD_VPN", "198.51.100.1", "NL")
+                .asn(9009, "M247")
+                .build(),
+            IpContextBuilder::new()
+                .ip("10.0.0.2")
+                .vpn("NORD_VPN")
+                .build(),
+            IpContextBuilder::new()
+                .ip("10.0.0.3")
+                .vpn_with_entry("PROTON_VPN", "198.51.100.1", "CH")
+                .build(),
+            IpContextBuilder::new()
+                .ip("10.0.0.4")
+                .asn(9009, "M247")
+                .build(),
+            IpContextBuilder::new()
+                .ip("10.0.0.5")
+                .vpn("PROTON_VPN")
+                .build(),
+            IpContextBuilder::new().ip("10.0.0.6").build(),
+        ]
+    }
+
+    fn ips<'a>(found: &[(&'a IpContext, usize)]) -> Vec<(&'a str, usize)> {
+        found
+            .iter()
+            .map(|(c, d)| (c.ip.as_deref().unwrap(), *d))
+            .collect()
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let contexts = contexts();
+        let graph = pivot(&contexts);
+        assert_eq!(graph.len(), 6);
+
+        let neighbors = graph.neighbors("10.0.0.1");
+        let found: Vec<_> = neighbors
+            .iter()
+            .map(|n| (n.context.ip.as_deref().unwrap(), n.via.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("10.0.0.2", vec![Link::Operator("NORD_VPN".to_string())]),
+                (
+                    "10.0.0.3",
+                    vec![Link::TunnelEntry("198.51.100.1".to_string())]
+                ),
+                ("10.0.0.4", vec![Link::Asn(9009)]),
+            ]
+        );
+        assert!(graph.neighbors("10.0.0.6").is_empty());
+        assert!(graph.neighbors("192.0.2.1").is_empty());
+        assert!(graph.links("10.0.0.6").is_empty());
+    }
+
+    #[test]
+    fn test_within() {
+        let contexts = contexts();
+        let graph = pivot(&contexts);
+        assert_eq!(ips(&graph.within("10.0.0.2", 1)), [("10.0.0.1", 1)]);
+        assert_eq!(
+            ips(&graph.within("10.0.0.2", 3)),
+            [
+                ("10.0.0.1", 1),
+                ("10.0.0.3", 2),
+                ("10.0.0.4", 2),
+                ("10.0.0.5", 3)
+            ]
+        );
+        assert!(graph.within("10.0.0.2", 0).is_empty());
+    }
+
+    #[test]
+    fn test_sharing() {
+        let contexts = contexts();
+        let graph = pivot(&contexts);
+        let proton = graph.sharing(&Link::Operator("PROTON_VPN".to_string()));
+        assert_eq!(proton.len(), 2);
+        assert_eq!(Link::Asn(9009).to_string(), "AS9009");
+        assert_eq!(graph.get("10.0.0.4"), Some(&contexts[3]));
+    }
+}
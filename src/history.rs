@@ -0,0 +1,441 @@
+//! Timestamped snapshots of contexts, per IP address.
+//!
+//! Enrichment for an address changes over time: a hosting address becomes
+//! a VPN exit, a residential address joins a proxy network. [`ContextHistory`]
+//! keeps every snapshot recorded for an address so those transitions can be
+//! queried later, and [`changes`] describes what differs between two
+//! snapshots.
+//!
+//! Histories live in memory and can be saved to and loaded from the binary
+//! [`ContextStore`] format, which keeps every record appended to it.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::net::IpAddr;
+//! use std::time::{Duration, UNIX_EPOCH};
+//! use spur::history::{ChangeEvent, ContextHistory};
+//! use spur::test_utils::{timeline, TimelineEvent};
+//! use spur::{IpContext, TunnelType};
+//!
+//! let ip: IpAddr = "198.51.100.7".parse().unwrap();
+//! let snapshots = timeline(
+//!     "198.51.100.7",
+//!     &[TimelineEvent::Clean, TimelineEvent::Vpn("MULLVAD_VPN".to_string())],
+//! );
+//!
+//! let mut history = ContextHistory::new();
+//! for (day, context) in snapshots.into_iter().enumerate() {
+//!     let at = UNIX_EPOCH + Duration::from_secs(86_400 * day as u64);
+//!     history.record(ip, at, context);
+//! }
+//!
+//! let is_vpn = |c: &IpContext| {
+//!     c.tunnels.iter().flatten().any(|t| t.tunnel_type == Some(TunnelType::Vpn))
+//! };
+//! let first_vpn = history.first_seen(&ip, is_vpn);
+//! assert_eq!(first_vpn, Some(UNIX_EPOCH + Duration::from_secs(86_400)));
+//!
+//! let changes = history.changes(&ip);
+//! assert!(changes[0].events.contains(&ChangeEvent::TunnelAdded {
+//!     tunnel_type: Some(TunnelType::Vpn),
+//!     operator: Some("MULLVAD_VPN".to_string()),
+//! }));
+//! ```
+
+use std::collections::BTreeMap;
+#[cfg(feature = "json")]
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "json")]
+use crate::cache::{CacheEntry, ContextStore, ContextStoreWriter};
+use crate::context::{Infrastructure, IpContext, Risk, TunnelType};
+use crate::expiry::Expiring;
+use crate::ip_utils::canonical_ip;
+
+/// A context as observed at one point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// When the context was observed.
+    pub observed_at: SystemTime,
+    /// The context at that time.
+    pub context: IpContext,
+}
+
+impl Snapshot {
+    /// Returns the context with its observation time and `ttl`, for
+    /// checking whether it is still fresh enough to act on.
+    pub fn expiring(&self, ttl: Option<Duration>) -> Expiring<&IpContext> {
+        Expiring::new(&self.context, self.observed_at, ttl)
+    }
+}
+
+/// What changed between two consecutive snapshots of an address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// When the newer snapshot was observed.
+    pub observed_at: SystemTime,
+    /// The differences, in the order listed by [`changes`].
+    pub events: Vec<ChangeEvent>,
+}
+
+/// One difference between two snapshots of an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeEvent {
+    /// The infrastructure classification changed.
+    InfrastructureChanged {
+        /// The previous classification.
+        from: Option<Infrastructure>,
+        /// The new classification.
+        to: Option<Infrastructure>,
+    },
+    /// The announcing autonomous system changed.
+    AsnChanged {
+        /// The previous AS number.
+        from: Option<u32>,
+        /// The new AS number.
+        to: Option<u32>,
+    },
+    /// The location country changed.
+    CountryChanged {
+        /// The previous country code.
+        from: Option<String>,
+        /// The new country code.
+        to: Option<String>,
+    },
+    /// A tunnel appeared.
+    TunnelAdded {
+        /// The tunnel type.
+        tunnel_type: Option<TunnelType>,
+        /// The tunnel operator.
+        operator: Option<String>,
+    },
+    /// A tunnel disappeared.
+    TunnelRemoved {
+        /// The tunnel type.
+        tunnel_type: Option<TunnelType>,
+        /// The tunnel operator.
+        operator: Option<String>,
+    },
+    /// A risk was added.
+    RiskAdded(Risk),
+    /// A risk was removed.
+    RiskRemoved(Risk),
+}
+
+/// List the differences between two snapshots of the same address.
+///
+/// Events are listed in a fixed order: infrastructure, AS, country, added
+/// tunnels, removed tunnels, added risks, removed risks. Tunnels are
+/// identified by type and operator. Fields not covered by [`ChangeEvent`]
+/// are ignored.
+pub fn changes(old: &IpContext, new: &IpContext) -> Vec<ChangeEvent> {
+    let mut events = Vec::new();
+    if old.infrastructure != new.infrastructure {
+        events.push(ChangeEvent::InfrastructureChanged {
+            from: old.infrastructure.clone(),
+            to: new.infrastructure.clone(),
+        });
+    }
+    let asn = |c: &IpContext| c.autonomous_system.as_ref().and_then(|a| a.number);
+    if asn(old) != asn(new) {
+        events.push(ChangeEvent::AsnChanged {
+            from: asn(old),
+            to: asn(new),
+        });
+    }
+    let country = |c: &IpContext| c.location.as_ref().and_then(|l| l.country.clone());
+    if country(old) != country(new) {
+        events.push(ChangeEvent::CountryChanged {
+            from: country(old),
+            to: country(new),
+        });
+    }
+
+    let tunnels = |c: &IpContext| -> Vec<(Option<TunnelType>, Option<String>)> {
+        c.tunnels
+            .iter()
+            .flatten()
+            .map(|t| (t.tunnel_type.clone(), t.operator.clone()))
+            .collect()
+    };
+    let (old_tunnels, new_tunnels) = (tunnels(old), tunnels(new));
+    for (tunnel_type, operator) in new_tunnels.iter().filter(|t| !old_tunnels.contains(t)) {
+        events.push(ChangeEvent::TunnelAdded {
+            tunnel_type: tunnel_type.clone(),
+            operator: operator.clone(),
+        });
+    }
+    for (tunnel_type, operator) in old_tunnels.iter().filter(|t| !new_tunnels.contains(t)) {
+        events.push(ChangeEvent::TunnelRemoved {
+            tunnel_type: tunnel_type.clone(),
+            operator: operator.clone(),
+        });
+    }
+
+    let (old_risks, new_risks) = (old.risks.as_deref(), new.risks.as_deref());
+    let (old_risks, new_risks) = (old_risks.unwrap_or(&[]), new_risks.unwrap_or(&[]));
+    for risk in new_risks.iter().filter(|r| !old_risks.contains(r)) {
+        events.push(ChangeEvent::RiskAdded(risk.clone()));
+    }
+    for risk in old_risks.iter().filter(|r| !new_risks.contains(r)) {
+        events.push(ChangeEvent::RiskRemoved(risk.clone()));
+    }
+    events
+}
+
+/// Snapshots of contexts over time, grouped by IP address.
+///
+/// Snapshots of each address are kept ordered by observation time; they can
+/// be recorded in any order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContextHistory {
+    snapshots: BTreeMap<IpAddr, Vec<Snapshot>>,
+}
+
+impl ContextHistory {
+    /// Create an empty history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the context of `ip` as observed at `observed_at`.
+    ///
+    /// A snapshot with the same timestamp as an existing one is placed
+    /// after it.
+    pub fn record(&mut self, ip: IpAddr, observed_at: SystemTime, context: IpContext) {
+        let snapshots = self.snapshots.entry(canonical_ip(ip)).or_default();
+        let at = snapshots.partition_point(|s| s.observed_at <= observed_at);
+        snapshots.insert(
+            at,
+            Snapshot {
+                observed_at,
+                context,
+            },
+        );
+    }
+
+    /// Returns the number of addresses with at least one snapshot.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Returns `true` if nothing has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Iterate over the recorded addresses, in address order.
+    pub fn ips(&self) -> impl Iterator<Item = &IpAddr> {
+        self.snapshots.keys()
+    }
+
+    /// Returns every snapshot of `ip`, oldest first.
+    pub fn snapshots(&self, ip: &IpAddr) -> &[Snapshot] {
+        self.snapshots
+            .get(&canonical_ip(*ip))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the most recent snapshot of `ip`.
+    pub fn latest(&self, ip: &IpAddr) -> Option<&Snapshot> {
+        self.snapshots(ip).last()
+    }
+
+    /// Returns the snapshot of `ip` in effect at `time`: the last one
+    /// observed at or before it.
+    pub fn at(&self, ip: &IpAddr, time: SystemTime) -> Option<&Snapshot> {
+        let snapshots = self.snapshots(ip);
+        let after = snapshots.partition_point(|s| s.observed_at <= time);
+        after.checked_sub(1).map(|i| &snapshots[i])
+    }
+
+    /// Returns when `ip` was first observed in a state matching `predicate`.
+    pub fn first_seen<F>(&self, ip: &IpAddr, predicate: F) -> Option<SystemTime>
+    where
+        F: Fn(&IpContext) -> bool,
+    {
+        self.snapshots(ip)
+            .iter()
+            .find(|s| predicate(&s.context))
+            .map(|s| s.observed_at)
+    }
+
+    /// Describe how `ip` changed between consecutive snapshots.
+    ///
+    /// Snapshots identical in the fields tracked by [`changes`] produce no
+    /// entry.
+    pub fn changes(&self, ip: &IpAddr) -> Vec<Change> {
+        self.snapshots(ip)
+            .windows(2)
+            .filter_map(|pair| {
+                let events = changes(&pair[0].context, &pair[1].context);
+                (!events.is_empty()).then(|| Change {
+                    observed_at: pair[1].observed_at,
+                    events,
+                })
+            })
+            .collect()
+    }
+
+    /// Write every snapshot to `writer` in the [`ContextStore`] format and
+    /// return the writer.
+    ///
+    /// Observation times are stored with millisecond precision.
+    #[cfg(feature = "json")]
+    pub fn write_store<W: Write>(&self, writer: W) -> io::Result<W> {
+        let mut store = ContextStoreWriter::new(writer)?;
+        for (ip, snapshots) in &self.snapshots {
+            for snapshot in snapshots {
+                let entry = CacheEntry {
+                    context: snapshot.context.clone(),
+                    inserted_at: snapshot.observed_at,
+                };
+                store.append(*ip, &entry)?;
+            }
+        }
+        store.finish()
+    }
+
+    /// Load every record of `store` as a snapshot.
+    #[cfg(feature = "json")]
+    pub fn from_store<B: AsRef<[u8]>>(store: &ContextStore<B>) -> io::Result<Self> {
+        let mut history = Self::new();
+        for record in store.iter() {
+            let (ip, entry) = record?;
+            history.record(ip, entry.inserted_at, entry.context);
+        }
+        Ok(history)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{timeline, IpContextBuilder, TimelineEvent};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn day(n: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(86_400 * n)
+    }
+
+    fn history() -> (IpAddr, ContextHistory) {
+        let ip: IpAddr = "203.0.113.9".parse().unwrap();
+        let events = [
+            TimelineEvent::Clean,
+            TimelineEvent::Clean,
+            TimelineEvent::Vpn("NORD_VPN".to_string()),
+            TimelineEvent::Tor,
+        ];
+        let mut history = ContextHistory::new();
+        // Recorded out of order on purpose.
+        for (n, context) in timeline("203.0.113.9", &events)
+            .into_iter()
+            .enumerate()
+            .rev()
+        {
+            history.record(ip, day(n as u64), context);
+        }
+        (ip, history)
+    }
+
+    #[test]
+    fn test_queries() {
+        let (ip, history) = history();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history.snapshots(&ip).len(), 4);
+        assert_eq!(history.latest(&ip).unwrap().observed_at, day(3));
+        assert_eq!(
+            history
+                .at(&ip, day(2) + Duration::from_secs(60))
+                .unwrap()
+                .observed_at,
+            day(2)
+        );
+        assert!(history
+            .at(&ip, UNIX_EPOCH - Duration::from_secs(1))
+            .is_none());
+        assert_eq!(
+            history.first_seen(&ip, |c| {
+                c.tunnels
+                    .iter()
+                    .flatten()
+                    .any(|t| t.tunnel_type == Some(TunnelType::Tor))
+            }),
+            Some(day(3))
+        );
+        assert_eq!(history.first_seen(&ip, |c| c.organization.is_none()), None);
+        assert!(history.snapshots(&"192.0.2.1".parse().unwrap()).is_empty());
+
+        let latest = history
+            .latest(&ip)
+            .unwrap()
+            .expiring(Some(Duration::from_secs(86_400)));
+        assert!(!latest.is_expired(day(3) + Duration::from_secs(3_600)));
+        assert!(latest.is_expired(day(4)));
+    }
+
+    #[test]
+    fn test_change_events() {
+        let (ip, history) = history();
+        let changes = history.changes(&ip);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].observed_at, day(2));
+        assert_eq!(
+            changes[0].events,
+            vec![
+                ChangeEvent::TunnelAdded {
+                    tunnel_type: Some(TunnelType::Vpn),
+                    operator: Some("NORD_VPN".to_string()),
+                },
+                ChangeEvent::RiskAdded(Risk::Tunnel),
+            ]
+        );
+        assert!(changes[1].events.contains(&ChangeEvent::TunnelRemoved {
+            tunnel_type: Some(TunnelType::Vpn),
+            operator: Some("NORD_VPN".to_string()),
+        }));
+    }
+
+    #[test]
+    fn test_changes_between_contexts() {
+        let old = IpContextBuilder::new()
+            .infrastructure(Infrastructure::Datacenter)
+            .asn(9009, "M247")
+            .location("NL", None)
+            .add_risk(Risk::Spam)
+            .build();
+        let new = IpContextBuilder::new()
+            .infrastructure(Infrastructure::Residential)
+            .asn(9009, "M247")
+            .location("DE", None)
+            .build();
+
+        assert_eq!(
+            changes(&old, &new),
+            vec![
+                ChangeEvent::InfrastructureChanged {
+                    from: Some(Infrastructure::Datacenter),
+                    to: Some(Infrastructure::Residential),
+                },
+                ChangeEvent::CountryChanged {
+                    from: Some("NL".to_string()),
+                    to: Some("DE".to_string()),
+                },
+                ChangeEvent::RiskRemoved(Risk::Spam),
+            ]
+        );
+        assert!(changes(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_store_round_trip() {
+        let (_, history) = history();
+        let bytes = history.write_store(Vec::new()).unwrap();
+        let store = ContextStore::open(bytes).unwrap();
+        assert_eq!(ContextHistory::from_store(&store).unwrap(), history);
+    }
+}
@@ -0,0 +1,640 @@
+//! Reading and writing NDJSON streams of contexts.
+//!
+//! Newline-delimited JSON, one [`IpContext`] per line, is the de facto
+//! format for archived enrichments. [`ContextReader`] parses such a stream
+//! record by record, counting lines, records and skipped lines, and
+//! [`ContextWriter`] produces one. With the `gzip` feature both also read
+//! and write `.ndjson.gz` files. Archives stored as one large JSON array are
+//! read element by element with [`json_array_stream`].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::io::{ContextReader, ContextWriter};
+//! use spur::IpContext;
+//!
+//! let mut writer = ContextWriter::new(Vec::new());
+//! writer.write(&IpContext { ip: Some("1.2.3.4".into()), ..Default::default() })?;
+//! writer.write(&IpContext { ip: Some("5.6.7.8".into()), ..Default::default() })?;
+//! let mut archive = writer.into_inner();
+//! archive.extend_from_slice(b"not json\n");
+//!
+//! let mut reader = ContextReader::new(archive.as_slice()).skip_invalid();
+//! let contexts = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+//! assert_eq!(contexts.len(), 2);
+//! assert_eq!(reader.skipped(), 1);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::batch::Progress;
+use crate::context::{parse_value, IpContext, ParseError};
+
+/// Errors returned while reading a [`ContextReader`].
+#[derive(Debug)]
+pub enum ReadError {
+    /// The underlying reader failed.
+    Io(io::Error),
+    /// A line could not be parsed as an [`IpContext`].
+    Parse {
+        /// One-based line number in the stream.
+        line: u64,
+        /// The parse failure, with offsets relative to the line.
+        error: ParseError,
+    },
+    /// An element of a JSON array could not be parsed as an [`IpContext`].
+    Element {
+        /// Zero-based index of the element in the array.
+        index: u64,
+        /// The parse failure.
+        error: ParseError,
+    },
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "read error: {}", e),
+            Self::Parse { line, error } => write!(f, "line {}: {}", line, error),
+            Self::Element { index, error } => write!(f, "element {}: {}", index, error),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse { error, .. } | Self::Element { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<ReadError> for io::Error {
+    fn from(e: ReadError) -> Self {
+        match e {
+            ReadError::Io(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+/// Iterator over the contexts in an NDJSON stream.
+///
+/// Blank lines are ignored. By default a line that does not parse is
+/// returned as [`ReadError::Parse`] and reading can continue with the next
+/// line; with [`skip_invalid`](Self::skip_invalid) such lines are counted
+/// and skipped instead.
+pub struct ContextReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    skip_invalid: bool,
+    lines: u64,
+    records: u64,
+    skipped: u64,
+    total: Option<u64>,
+    progress: Option<ProgressHook>,
+}
+
+struct ProgressHook {
+    every: u64,
+    reported: u64,
+    callback: Box<dyn FnMut(Progress) + Send>,
+}
+
+impl<R: BufRead> ContextReader<R> {
+    /// Read contexts from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            skip_invalid: false,
+            lines: 0,
+            records: 0,
+            skipped: 0,
+            total: None,
+            progress: None,
+        }
+    }
+
+    /// Skip lines that do not parse instead of returning an error for them.
+    pub fn skip_invalid(mut self) -> Self {
+        self.skip_invalid = true;
+        self
+    }
+
+    /// Call `on_progress` after every `every` non-blank lines and once more
+    /// at the end of the stream, e.g. to drive a progress bar.
+    ///
+    /// [`Progress::done`] counts non-blank lines and [`Progress::errors`]
+    /// the ones that did not parse. [`Progress::total`] is `None` unless set
+    /// with [`expected`](Self::expected). An `every` of zero is treated as
+    /// one.
+    ///
+    /// ```rust
+    /// use spur::batch::Progress;
+    /// use spur::io::ContextReader;
+    ///
+    /// let feed = b"{\"ip\": \"1.2.3.4\"}\nnot json\n{\"ip\": \"5.6.7.8\"}\n";
+    /// let reader = ContextReader::new(&feed[..])
+    ///     .skip_invalid()
+    ///     .expected(3)
+    ///     .on_progress(1_000, |p: Progress| {
+    ///         eprintln!("{}/{:?} records, {} errors", p.done, p.total, p.errors)
+    ///     });
+    /// assert_eq!(reader.count(), 2);
+    /// ```
+    pub fn on_progress<F>(mut self, every: u64, on_progress: F) -> Self
+    where
+        F: FnMut(Progress) + Send + 'static,
+    {
+        self.progress = Some(ProgressHook {
+            every: every.max(1),
+            reported: 0,
+            callback: Box::new(on_progress),
+        });
+        self
+    }
+
+    /// Set the number of records the stream is expected to hold, reported
+    /// as [`Progress::total`].
+    pub fn expected(mut self, total: u64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// Returns the progress so far, as reported to
+    /// [`on_progress`](Self::on_progress).
+    pub fn progress(&self) -> Progress {
+        Progress {
+            done: self.records + self.skipped,
+            total: self.total,
+            errors: self.skipped,
+        }
+    }
+
+    /// Returns the number of lines read so far, including blank lines.
+    pub fn lines(&self) -> u64 {
+        self.lines
+    }
+
+    /// Returns the number of contexts parsed so far.
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    /// Returns the number of non-blank lines that did not parse so far,
+    /// whether they were skipped or returned as errors.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Run the progress hook if `every` lines passed since the last report,
+    /// or at the end of the stream if anything is unreported.
+    fn report(&mut self, end: bool) {
+        let progress = self.progress();
+        if let Some(hook) = &mut self.progress {
+            let due = progress.done - hook.reported;
+            if due >= hook.every || (end && due > 0) {
+                hook.reported = progress.done;
+                (hook.callback)(progress);
+            }
+        }
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for ContextReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContextReader")
+            .field("reader", &self.reader)
+            .field("skip_invalid", &self.skip_invalid)
+            .field("lines", &self.lines)
+            .field("records", &self.records)
+            .field("skipped", &self.skipped)
+            .field("total", &self.total)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<R: io::Read> ContextReader<io::BufReader<flate2::read::MultiGzDecoder<R>>> {
+    /// Read contexts from a gzip-compressed stream, such as a `.ndjson.gz`
+    /// archive or a feed download.
+    pub fn gzip(reader: R) -> Self {
+        Self::new(io::BufReader::new(flate2::read::MultiGzDecoder::new(
+            reader,
+        )))
+    }
+}
+
+impl<R: BufRead> Iterator for ContextReader<R> {
+    type Item = Result<IpContext, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buf.clear();
+            match self.reader.read_until(b'\n', &mut self.buf) {
+                Ok(0) => {
+                    self.report(true);
+                    return None;
+                }
+                Ok(_) => {}
+                Err(e) => return Some(Err(e.into())),
+            }
+            self.lines += 1;
+            if self.buf.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let parsed = IpContext::from_slice(&self.buf);
+            match &parsed {
+                Ok(_) => self.records += 1,
+                Err(_) => self.skipped += 1,
+            }
+            self.report(false);
+            match parsed {
+                Ok(context) => return Some(Ok(context)),
+                Err(error) => {
+                    if !self.skip_invalid {
+                        let line = self.lines;
+                        return Some(Err(ReadError::Parse { line, error }));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Read the contexts in a JSON array one element at a time.
+///
+/// Only one element is held in memory at a time, so archives far larger
+/// than memory can be processed. Elements must be JSON objects. Reading
+/// stops at the first element that does not parse or at malformed array
+/// syntax, since the rest of the stream cannot be located reliably after
+/// either; errors for the latter are [`ReadError::Io`] with
+/// [`io::ErrorKind::InvalidData`].
+///
+/// ```rust
+/// use spur::io::json_array_stream;
+///
+/// let archive = br#"[{"ip": "1.2.3.4"}, {"ip": "5.6.7.8"}]"#;
+/// let ips: Vec<_> = json_array_stream(&archive[..])
+///     .map(|c| c.unwrap().ip.unwrap())
+///     .collect();
+/// assert_eq!(ips, ["1.2.3.4", "5.6.7.8"]);
+/// ```
+pub fn json_array_stream<R: BufRead>(reader: R) -> JsonArrayStream<R> {
+    JsonArrayStream {
+        reader,
+        state: ArrayState::Start,
+        elements: 0,
+    }
+}
+
+/// Iterator returned by [`json_array_stream`].
+#[derive(Debug)]
+pub struct JsonArrayStream<R> {
+    reader: R,
+    state: ArrayState,
+    elements: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArrayState {
+    /// Before the opening `[`.
+    Start,
+    /// After `[`, where `]` may close an empty array.
+    First,
+    /// After an element, where `,` or `]` must follow.
+    Next,
+    /// After `]` or an error.
+    Done,
+}
+
+impl<R: BufRead> JsonArrayStream<R> {
+    /// Returns the number of elements parsed so far.
+    pub fn elements(&self) -> u64 {
+        self.elements
+    }
+
+    /// Returns the underlying reader, positioned after the last byte read.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Skips whitespace and consumes the next byte, if any.
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte = self.peek_byte()?;
+        if byte.is_some() {
+            self.reader.consume(1);
+        }
+        Ok(byte)
+    }
+
+    /// Skips whitespace and returns the next byte without consuming it.
+    fn peek_byte(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            let buf = self.reader.fill_buf()?;
+            if buf.is_empty() {
+                return Ok(None);
+            }
+            match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+                Some(i) => {
+                    let byte = buf[i];
+                    self.reader.consume(i);
+                    return Ok(Some(byte));
+                }
+                None => {
+                    let len = buf.len();
+                    self.reader.consume(len);
+                }
+            }
+        }
+    }
+
+    fn step(&mut self) -> Result<Option<IpContext>, ReadError> {
+        let invalid =
+            |message: String| ReadError::Io(io::Error::new(io::ErrorKind::InvalidData, message));
+        match self.state {
+            ArrayState::Done => return Ok(None),
+            ArrayState::Start => match self.next_byte()? {
+                Some(b'[') => self.state = ArrayState::First,
+                None => return Err(invalid("expected `[`, found end of input".to_string())),
+                Some(b) => return Err(invalid(format!("expected `[`, found {:?}", b as char))),
+            },
+            ArrayState::First => {}
+            ArrayState::Next => match self.next_byte()? {
+                Some(b',') => {}
+                Some(b']') => {
+                    self.state = ArrayState::Done;
+                    return Ok(None);
+                }
+                found => {
+                    let found =
+                        found.map_or("end of input".to_string(), |b| format!("{:?}", b as char));
+                    return Err(invalid(format!(
+                        "expected `,` or `]` after element {}, found {}",
+                        self.elements - 1,
+                        found
+                    )));
+                }
+            },
+        }
+        if self.state == ArrayState::First && self.peek_byte()? == Some(b']') {
+            self.reader.consume(1);
+            self.state = ArrayState::Done;
+            return Ok(None);
+        }
+        let context = parse_value(&mut self.reader).map_err(|error| ReadError::Element {
+            index: self.elements,
+            error,
+        })?;
+        self.elements += 1;
+        self.state = ArrayState::Next;
+        Ok(Some(context))
+    }
+}
+
+impl<R: BufRead> Iterator for JsonArrayStream<R> {
+    type Item = Result<IpContext, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.step();
+        if result.is_err() {
+            self.state = ArrayState::Done;
+        }
+        result.transpose()
+    }
+}
+
+/// Writer of contexts as an NDJSON stream.
+///
+/// Each context is written with a single `write_all` call, so wrapping a
+/// file in a `BufWriter` is still worthwhile for large exports.
+#[derive(Debug)]
+pub struct ContextWriter<W> {
+    writer: W,
+    buf: Vec<u8>,
+    written: u64,
+}
+
+impl<W: Write> ContextWriter<W> {
+    /// Write contexts to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            buf: Vec::new(),
+            written: 0,
+        }
+    }
+
+    /// Write one context as a line.
+    pub fn write(&mut self, context: &IpContext) -> io::Result<()> {
+        self.buf.clear();
+        serde_json::to_writer(&mut self.buf, context)?;
+        self.buf.push(b'\n');
+        self.writer.write_all(&self.buf)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Write every context from `contexts`, returning how many were written.
+    pub fn write_all<'a>(
+        &mut self,
+        contexts: impl IntoIterator<Item = &'a IpContext>,
+    ) -> io::Result<u64> {
+        let before = self.written;
+        for context in contexts {
+            self.write(context)?;
+        }
+        Ok(self.written - before)
+    }
+
+    /// Returns the number of contexts written so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<W: Write> ContextWriter<flate2::write::GzEncoder<W>> {
+    /// Write contexts to `writer` compressed with gzip.
+    ///
+    /// Call [`finish`](Self::finish) when done to write the gzip trailer.
+    pub fn gzip(writer: W) -> Self {
+        Self::new(flate2::write::GzEncoder::new(
+            writer,
+            flate2::Compression::default(),
+        ))
+    }
+
+    /// Complete the gzip stream and return the underlying writer.
+    pub fn finish(self) -> io::Result<W> {
+        self.writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::fixtures;
+
+    fn contexts() -> Vec<IpContext> {
+        vec![
+            fixtures::residential_ip(),
+            fixtures::tor_exit_node(),
+            fixtures::datacenter_ip(),
+        ]
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut writer = ContextWriter::new(Vec::new());
+        assert_eq!(writer.write_all(&contexts()).unwrap(), 3);
+        assert_eq!(writer.written(), 3);
+        let ndjson = writer.into_inner();
+        assert_eq!(ndjson.iter().filter(|&&b| b == b'\n').count(), 3);
+
+        let mut reader = ContextReader::new(ndjson.as_slice());
+        let read: Vec<_> = reader.by_ref().map(Result::unwrap).collect();
+        assert_eq!(read, contexts());
+        assert_eq!(
+            (reader.lines(), reader.records(), reader.skipped()),
+            (3, 3, 0)
+        );
+    }
+
+    #[test]
+    fn test_invalid_lines() {
+        let ndjson = b"{\"ip\": \"1.2.3.4\"}\n\n  \nnot json\n{\"ip\": 5}\n{\"ip\": \"5.6.7.8\"}";
+
+        let results: Vec<_> = ContextReader::new(&ndjson[..]).collect();
+        assert_eq!(results.len(), 4);
+        assert!(matches!(results[1], Err(ReadError::Parse { line: 4, .. })));
+        match &results[2] {
+            Err(e @ ReadError::Parse { line: 5, error }) => {
+                assert_eq!(error.path(), Some("ip"));
+                assert!(e.to_string().starts_with("line 5: ip: "));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(results[3].as_ref().unwrap().ip.as_deref(), Some("5.6.7.8"));
+
+        let mut reader = ContextReader::new(&ndjson[..]).skip_invalid();
+        assert_eq!(reader.by_ref().filter(Result::is_ok).count(), 2);
+        assert_eq!(
+            (reader.lines(), reader.records(), reader.skipped()),
+            (6, 2, 2)
+        );
+    }
+
+    #[test]
+    fn test_progress() {
+        use std::sync::{Arc, Mutex};
+
+        let ndjson = b"{}\n\nnot json\n{}\n{}\n{}\n{}";
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reports);
+        let mut reader = ContextReader::new(&ndjson[..])
+            .skip_invalid()
+            .expected(6)
+            .on_progress(4, move |p| sink.lock().unwrap().push(p));
+        assert_eq!(reader.by_ref().count(), 5);
+        assert_eq!(reader.next().map(|r| r.is_ok()), None);
+
+        let progress = |done, errors| Progress {
+            done,
+            total: Some(6),
+            errors,
+        };
+        assert_eq!(*reports.lock().unwrap(), [progress(4, 1), progress(6, 1)]);
+        assert_eq!(reader.progress(), progress(6, 1));
+    }
+
+    #[test]
+    fn test_json_array_stream() {
+        let archive = serde_json::to_vec_pretty(&contexts()).unwrap();
+        let mut stream = json_array_stream(archive.as_slice());
+        let read: Vec<_> = stream.by_ref().map(Result::unwrap).collect();
+        assert_eq!(read, contexts());
+        assert_eq!(stream.elements(), 3);
+        assert!(stream.next().is_none());
+
+        for empty in [&b"[]"[..], b" [ \n ] "] {
+            assert_eq!(json_array_stream(empty).count(), 0);
+        }
+    }
+
+    #[test]
+    fn test_json_array_stream_errors() {
+        let mut stream = json_array_stream(&br#"[{"ip": "1.2.3.4"}, {"ip": 5}, {}]"#[..]);
+        assert!(stream.next().unwrap().is_ok());
+        match stream.next().unwrap() {
+            Err(e @ ReadError::Element { index: 1, .. }) => {
+                assert!(e.to_string().starts_with("element 1: ip: "), "{}", e);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(stream.next().is_none());
+
+        for (input, message) in [
+            (&b"{}"[..], "expected `[`, found '{'"),
+            (b"", "expected `[`, found end of input"),
+            (b"[{} {}]", "expected `,` or `]` after element 0, found '{'"),
+            (
+                b"[{}",
+                "expected `,` or `]` after element 0, found end of input",
+            ),
+        ] {
+            let errors: Vec<_> = json_array_stream(input).filter_map(Result::err).collect();
+            assert_eq!(errors.len(), 1);
+            assert!(matches!(&errors[0], ReadError::Io(e) if e.to_string() == message));
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_round_trip() {
+        let mut writer = ContextWriter::gzip(Vec::new());
+        writer.write_all(&contexts()).unwrap();
+        let compressed = writer.finish().unwrap();
+        assert_eq!(&compressed[..2], &[0x1f, 0x8b]);
+
+        let read: Vec<_> = ContextReader::gzip(compressed.as_slice())
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(read, contexts());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_reads_fake_feed() {
+        let feed = crate::test_utils::fake_feed_gz(50, 3);
+        let mut reader = ContextReader::gzip(feed.as_slice());
+        assert_eq!(reader.by_ref().filter(Result::is_ok).count(), 50);
+        assert_eq!(reader.skipped(), 0);
+    }
+}
@@ -0,0 +1,209 @@
+//! Canonical forms of IP addresses.
+//!
+//! The same IPv6 address can be written many ways: `2001:DB8::1`,
+//! `2001:db8:0:0:0:0:0:1` and `[2001:db8::1]` are all equal, and
+//! `::ffff:192.0.2.1` is the IPv4 address `192.0.2.1` as seen by a
+//! dual-stack socket. Caches, stores, deduplication and lookups in this
+//! crate normalize addresses with these helpers so every form reaches the
+//! same entry.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::ip_utils::canonicalize;
+//!
+//! assert_eq!(canonicalize("2001:DB8:0:0:0:0:0:1").as_deref(), Some("2001:db8::1"));
+//! assert_eq!(canonicalize("::ffff:192.0.2.1").as_deref(), Some("192.0.2.1"));
+//! assert_eq!(canonicalize("not an ip"), None);
+//! ```
+//!
+//! [`is_bogon`] recognizes private, loopback, link-local, multicast,
+//! documentation and other reserved addresses, which are never worth a
+//! lookup.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Reserved IPv4 ranges, as network, prefix length and description.
+const BOGONS_V4: &[([u8; 4], u8, &str)] = &[
+    ([0, 0, 0, 0], 8, "this network"),
+    ([10, 0, 0, 0], 8, "private"),
+    ([100, 64, 0, 0], 10, "shared address space"),
+    ([127, 0, 0, 0], 8, "loopback"),
+    ([169, 254, 0, 0], 16, "link-local"),
+    ([172, 16, 0, 0], 12, "private"),
+    ([192, 0, 0, 0], 24, "IETF protocol assignments"),
+    ([192, 0, 2, 0], 24, "documentation"),
+    ([192, 168, 0, 0], 16, "private"),
+    ([198, 18, 0, 0], 15, "benchmarking"),
+    ([198, 51, 100, 0], 24, "documentation"),
+    ([203, 0, 113, 0], 24, "documentation"),
+    ([224, 0, 0, 0], 4, "multicast"),
+    ([240, 0, 0, 0], 4, "reserved"),
+];
+
+/// Reserved IPv6 ranges, as network, prefix length and description.
+const BOGONS_V6: &[([u16; 8], u8, &str)] = &[
+    ([0, 0, 0, 0, 0, 0, 0, 0], 128, "unspecified"),
+    ([0, 0, 0, 0, 0, 0, 0, 1], 128, "loopback"),
+    (
+        [0x64, 0xff9b, 1, 0, 0, 0, 0, 0],
+        48,
+        "local-use translation",
+    ),
+    ([0x100, 0, 0, 0, 0, 0, 0, 0], 64, "discard-only"),
+    ([0x2001, 0xdb8, 0, 0, 0, 0, 0, 0], 32, "documentation"),
+    ([0xfc00, 0, 0, 0, 0, 0, 0, 0], 7, "unique local"),
+    ([0xfe80, 0, 0, 0, 0, 0, 0, 0], 10, "link-local"),
+    ([0xff00, 0, 0, 0, 0, 0, 0, 0], 8, "multicast"),
+];
+
+/// Returns the canonical text form of an address: IPv4 in dotted decimal,
+/// IPv6 compressed and lowercase as in RFC 5952, with IPv4-mapped IPv6
+/// addresses written as IPv4.
+///
+/// Surrounding whitespace and square brackets are ignored. Returns `None`
+/// if `text` is not an address.
+pub fn canonicalize(text: &str) -> Option<String> {
+    parse(text).map(|ip| ip.to_string())
+}
+
+/// Parse an address, accepting the same forms as [`canonicalize`], and
+/// return it in [`canonical_ip`] form.
+pub fn parse(text: &str) -> Option<IpAddr> {
+    let text = text.trim();
+    let text = text
+        .strip_prefix('[')
+        .and_then(|t| t.strip_suffix(']'))
+        .unwrap_or(text);
+    text.parse().ok().map(canonical_ip)
+}
+
+/// Returns `ip` with IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`)
+/// converted to IPv4. Other addresses are returned unchanged.
+pub fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+/// Returns `true` if `ip` is in a private or reserved range that is not
+/// routed on the public internet (RFC 6890 and friends).
+///
+/// IPv4-mapped IPv6 addresses are checked as IPv4.
+///
+/// ```rust
+/// use spur::ip_utils::is_bogon;
+///
+/// assert!(is_bogon("10.1.2.3".parse().unwrap()));
+/// assert!(is_bogon("fe80::1".parse().unwrap()));
+/// assert!(!is_bogon("89.39.106.191".parse().unwrap()));
+/// ```
+pub fn is_bogon(ip: IpAddr) -> bool {
+    bogon_range(ip).is_some()
+}
+
+/// Returns a short description of the reserved range `ip` is in, such as
+/// `"private"` or `"link-local"`, or `None` for public addresses.
+pub fn bogon_range(ip: IpAddr) -> Option<&'static str> {
+    match canonical_ip(ip) {
+        IpAddr::V4(v4) => bogon_v4(v4),
+        IpAddr::V6(v6) => bogon_v6(v6),
+    }
+}
+
+fn bogon_v4(ip: Ipv4Addr) -> Option<&'static str> {
+    if ip == Ipv4Addr::BROADCAST {
+        return Some("broadcast");
+    }
+    let bits = u32::from(ip);
+    BOGONS_V4.iter().find_map(|&(network, len, name)| {
+        let mask = u32::MAX << (32 - u32::from(len));
+        (bits & mask == u32::from(Ipv4Addr::from(network))).then_some(name)
+    })
+}
+
+fn bogon_v6(ip: Ipv6Addr) -> Option<&'static str> {
+    let bits = u128::from(ip);
+    BOGONS_V6.iter().find_map(|&(segments, len, name)| {
+        let mask = u128::MAX.checked_shl(128 - u32::from(len)).unwrap_or(0);
+        let network = u128::from(Ipv6Addr::from(segments));
+        (bits & mask == network).then_some(name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bogons() {
+        let bogons = [
+            ("0.1.2.3", "this network"),
+            ("10.255.0.1", "private"),
+            ("100.127.255.255", "shared address space"),
+            ("127.0.0.1", "loopback"),
+            ("169.254.169.254", "link-local"),
+            ("172.31.255.255", "private"),
+            ("192.168.1.1", "private"),
+            ("198.19.0.1", "benchmarking"),
+            ("203.0.113.9", "documentation"),
+            ("239.255.255.250", "multicast"),
+            ("250.0.0.1", "reserved"),
+            ("255.255.255.255", "broadcast"),
+            ("::", "unspecified"),
+            ("::1", "loopback"),
+            ("::ffff:192.168.0.1", "private"),
+            ("2001:db8::1", "documentation"),
+            ("fd12:3456::1", "unique local"),
+            ("febf::1", "link-local"),
+            ("ff02::1", "multicast"),
+        ];
+        for (ip, range) in bogons {
+            assert_eq!(bogon_range(ip.parse().unwrap()), Some(range), "{}", ip);
+        }
+        for ip in [
+            "8.8.8.8",
+            "172.32.0.1",
+            "100.128.0.1",
+            "2606:4700::1111",
+            "fec0::1",
+        ] {
+            assert!(!is_bogon(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let forms = [
+            "2001:DB8::1",
+            "2001:db8:0:0:0:0:0:1",
+            "2001:0db8:0000::0001",
+            " [2001:db8::1] ",
+        ];
+        for form in forms {
+            assert_eq!(
+                canonicalize(form).as_deref(),
+                Some("2001:db8::1"),
+                "{}",
+                form
+            );
+        }
+        assert_eq!(
+            canonicalize("::FFFF:C000:0201").as_deref(),
+            Some("192.0.2.1")
+        );
+        assert_eq!(canonicalize("192.0.2.1").as_deref(), Some("192.0.2.1"));
+        assert_eq!(canonicalize("::1").as_deref(), Some("::1"));
+        assert_eq!(canonicalize("[2001:db8::1"), None);
+        assert_eq!(canonicalize("192.0.2.256"), None);
+    }
+
+    #[test]
+    fn test_canonical_ip() {
+        let mapped: IpAddr = "::ffff:10.0.0.1".parse().unwrap();
+        assert_eq!(canonical_ip(mapped), "10.0.0.1".parse::<IpAddr>().unwrap());
+        let compat: IpAddr = "::10.0.0.1".parse().unwrap();
+        assert_eq!(canonical_ip(compat), compat);
+    }
+}
@@ -20,6 +20,37 @@
 //! |--------|---------|
 //! | [`context`] | Context API types for IP intelligence |
 //! | [`monocle`] | Monocle API types for device-level detection |
+//! | [`cache`] | In-memory caching of contexts keyed by IP address |
+//! | [`compare`] | Agreement reports between Spur and other GeoIP sources |
+//! | [`analysis`] | Clustering and other analyses over batches of contexts |
+//! | [`batch`] | Parsing and processing many contexts at once |
+//! | [`egress`] | Telling the customer's own VPN and egress ranges apart from anonymous traffic |
+//! | [`expiry`] | Fetch time and TTL carried with a context, for staleness checks |
+//! | [`graph`] | Links between contexts sharing operators, ASNs or tunnel entries |
+//! | [`history`] | Timestamped snapshots per IP and change events |
+//! | [`io`] | Reading and writing NDJSON streams of contexts, optionally gzip-compressed |
+//! | [`ip_utils`] | Canonical text forms of IPv4 and IPv6 addresses |
+//! | [`monitor`] | Alerts when the token turns inactive or the quota crosses thresholds |
+//! | [`operators`] | Normalized keys for operator names spelled in different ways |
+//! | [`pagination`] | Typed pages and cursor iteration for paginated bulk responses |
+//! | `policy` | Declarative building blocks for risk decisions (requires the `policy` feature) |
+//! | [`provider`] | One lookup interface over Spur and other IP intelligence providers |
+//! | [`ranges`] | CIDR range sets with containment and set operations |
+//! | [`realip`] | Client IP extraction from forwarding headers |
+//! | [`sample`] | Uniform random samples of large feeds |
+//! | [`sampling`] | Per-request decisions on which traffic to enrich within quota |
+//! | [`scheduler`] | Pacing lookups by priority to fit the remaining query quota |
+//! | `signing` | Detached signatures making contexts tamper-evident (requires the `signing` feature) |
+//! | [`stats`] | Aggregate counts over batches of contexts |
+//! | `templates` | Rendering contexts through MiniJinja templates (requires the `templates` feature) |
+//! | `tor` | Cross-checks against the Tor exit list (requires the `tor` feature) |
+//! | [`velocity`] | Impossible-travel checks between observations of a session |
+//! | [`watchlist`] | Matching contexts against IPs, networks, ASNs and operators of interest |
+//! | `webhook` | Relayed event payloads and HMAC signature checks (requires the `webhook` feature) |
+//!
+//! Every module except `context`, `monocle`, `expiry`, `ip_utils`,
+//! `operators` and the ones marked with their own feature needs the
+//! `helpers` feature, which is on by default.
 //!
 //! ## Context API Types
 //!
@@ -54,6 +85,11 @@
 //! - **All fields optional** - handles partial API responses gracefully
 //! - **Efficient serialization** - `None` values are omitted
 //! - **Test utilities** - builders and fixtures for testing (via `test-utils` feature)
+//! - **Fuzzing** - `arbitrary::Arbitrary` for all API types (via `arbitrary` feature)
+//! - **Format-agnostic core** - just `serde`; JSON helpers via the `json` feature (default)
+//! - **Minimal builds** - `monocle` and `helpers` are default features and
+//!   `policy` is opt-in; with `default-features = false` only the context
+//!   types and the `expiry`, `ip_utils` and `operators` utilities are built
 //!
 //! ## Installation
 //!
@@ -210,8 +246,74 @@
 
 // API modules
 pub mod context;
+#[cfg(feature = "monocle")]
 pub mod monocle;
 
+// Integration helpers
+#[cfg(feature = "helpers")]
+pub mod analysis;
+#[cfg(feature = "json")]
+pub mod batch;
+#[cfg(feature = "helpers")]
+pub mod cache;
+#[cfg(feature = "helpers")]
+pub mod compare;
+#[cfg(feature = "helpers")]
+pub mod egress;
+pub mod expiry;
+#[cfg(feature = "helpers")]
+pub mod graph;
+#[cfg(feature = "helpers")]
+pub mod history;
+#[cfg(feature = "json")]
+pub mod io;
+pub mod ip_utils;
+#[cfg(feature = "helpers")]
+pub mod monitor;
+pub mod operators;
+#[cfg(feature = "helpers")]
+pub mod pagination;
+#[cfg(feature = "policy")]
+pub mod policy;
+#[cfg(feature = "helpers")]
+pub mod provider;
+#[cfg(feature = "helpers")]
+pub mod ranges;
+#[cfg(feature = "helpers")]
+pub mod realip;
+#[cfg(feature = "helpers")]
+pub mod sample;
+#[cfg(feature = "helpers")]
+pub mod sampling;
+#[cfg(feature = "helpers")]
+pub mod scheduler;
+#[cfg(feature = "signing")]
+pub mod signing;
+#[cfg(feature = "helpers")]
+pub mod stats;
+#[cfg(feature = "templates")]
+pub mod templates;
+#[cfg(feature = "tor")]
+pub mod tor;
+#[cfg(feature = "helpers")]
+pub mod velocity;
+#[cfg(feature = "helpers")]
+pub mod watchlist;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+
+// Internal helpers
+#[cfg(feature = "json")]
+mod canonical;
+#[cfg(any(feature = "helpers", feature = "tz"))]
+mod geo;
+#[cfg(feature = "json")]
+mod hash;
+#[cfg(any(feature = "webhook", feature = "signing"))]
+mod hex;
+#[cfg_attr(not(any(test, feature = "test-utils")), allow(dead_code))]
+mod rng;
+
 // Test utilities (optional feature)
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
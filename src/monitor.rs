@@ -0,0 +1,242 @@
+//! Alerts on changes in token status and remaining quota.
+//!
+//! A [`StatusMonitor`] keeps the latest [`ApiStatus`] and reports when the
+//! token flips inactive (or back) and when the remaining queries drop
+//! through configured thresholds. Handlers registered with
+//! [`StatusMonitor::on_alert`] run for every alert, e.g. to page someone or
+//! post to chat.
+//!
+//! The monitor does not fetch anything itself: call
+//! [`observe`](StatusMonitor::observe) with each status response, from
+//! whatever HTTP client and timer the application already uses.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::monitor::{StatusAlert, StatusMonitor};
+//! use spur::ApiStatus;
+//!
+//! let mut monitor = StatusMonitor::new()
+//!     .threshold(10_000)
+//!     .threshold(1_000)
+//!     .on_alert(|alert: &StatusAlert| eprintln!("spur: {}", alert));
+//!
+//! let status = |remaining| ApiStatus {
+//!     active: Some(true),
+//!     queries_remaining: Some(remaining),
+//!     ..Default::default()
+//! };
+//! assert!(monitor.observe(status(20_000)).is_empty());
+//! assert_eq!(
+//!     monitor.observe(status(900)),
+//!     [
+//!         StatusAlert::QuotaBelow { threshold: 10_000, remaining: 900 },
+//!         StatusAlert::QuotaBelow { threshold: 1_000, remaining: 900 },
+//!     ]
+//! );
+//! assert_eq!(monitor.latest().unwrap().queries_remaining, Some(900));
+//! ```
+
+use std::fmt;
+
+use crate::context::ApiStatus;
+
+/// A change reported by [`StatusMonitor::observe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusAlert {
+    /// The token became inactive.
+    Deactivated,
+    /// The token became active again.
+    Reactivated,
+    /// The remaining queries dropped to or below a threshold.
+    QuotaBelow {
+        /// The threshold that was crossed.
+        threshold: u64,
+        /// The remaining queries reported.
+        remaining: u64,
+    },
+}
+
+impl fmt::Display for StatusAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Deactivated => write!(f, "API token is inactive"),
+            Self::Reactivated => write!(f, "API token is active again"),
+            Self::QuotaBelow {
+                threshold,
+                remaining,
+            } => write!(
+                f,
+                "{} queries remaining, below the {} threshold",
+                remaining, threshold
+            ),
+        }
+    }
+}
+
+type Handler = Box<dyn FnMut(&StatusAlert) + Send>;
+
+/// Latest token status, with alerts on changes.
+#[derive(Default)]
+pub struct StatusMonitor {
+    latest: Option<ApiStatus>,
+    thresholds: Vec<u64>,
+    handlers: Vec<Handler>,
+}
+
+impl StatusMonitor {
+    /// Create a monitor without thresholds or handlers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Alert when the remaining queries drop to or below `threshold`.
+    ///
+    /// Each threshold alerts once per crossing: it fires again only after
+    /// the remaining queries have risen above it, e.g. with a new billing
+    /// cycle.
+    pub fn threshold(mut self, threshold: u64) -> Self {
+        self.thresholds.push(threshold);
+        self.thresholds.sort_by_key(|&t| std::cmp::Reverse(t));
+        self.thresholds.dedup();
+        self
+    }
+
+    /// Run `handler` for every alert.
+    pub fn on_alert<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&StatusAlert) + Send + 'static,
+    {
+        self.handlers.push(Box::new(handler));
+        self
+    }
+
+    /// Returns the most recently observed status.
+    pub fn latest(&self) -> Option<&ApiStatus> {
+        self.latest.as_ref()
+    }
+
+    /// Record a status response, run the handlers for any alerts and return
+    /// them.
+    ///
+    /// Quota thresholds are alerted in descending order. A first status
+    /// that is already inactive or below a threshold alerts too.
+    pub fn observe(&mut self, status: ApiStatus) -> Vec<StatusAlert> {
+        let previous = self.latest.as_ref();
+        let mut alerts = Vec::new();
+
+        let was_active = previous.and_then(|s| s.active);
+        match (was_active, status.active) {
+            (Some(false), Some(true)) => alerts.push(StatusAlert::Reactivated),
+            (Some(true) | None, Some(false)) => alerts.push(StatusAlert::Deactivated),
+            _ => {}
+        }
+
+        if let Some(remaining) = status.queries_remaining {
+            let before = previous.and_then(|s| s.queries_remaining);
+            for &threshold in &self.thresholds {
+                if remaining <= threshold && before.is_none_or(|b| b > threshold) {
+                    alerts.push(StatusAlert::QuotaBelow {
+                        threshold,
+                        remaining,
+                    });
+                }
+            }
+        }
+
+        for alert in &alerts {
+            for handler in &mut self.handlers {
+                handler(alert);
+            }
+        }
+        self.latest = Some(status);
+        alerts
+    }
+}
+
+impl fmt::Debug for StatusMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatusMonitor")
+            .field("latest", &self.latest)
+            .field("thresholds", &self.thresholds)
+            .field("handlers", &self.handlers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    fn status(active: bool, remaining: u64) -> ApiStatus {
+        ApiStatus {
+            active: Some(active),
+            queries_remaining: Some(remaining),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_active_flips() {
+        let mut monitor = StatusMonitor::new();
+        assert!(monitor.observe(status(true, 100)).is_empty());
+        assert_eq!(
+            monitor.observe(status(false, 100)),
+            [StatusAlert::Deactivated]
+        );
+        assert!(monitor.observe(status(false, 100)).is_empty());
+        assert_eq!(
+            monitor.observe(status(true, 100)),
+            [StatusAlert::Reactivated]
+        );
+
+        let mut fresh = StatusMonitor::new();
+        assert_eq!(fresh.observe(status(false, 0)), [StatusAlert::Deactivated]);
+    }
+
+    #[test]
+    fn test_thresholds_fire_once_per_crossing() {
+        let mut monitor = StatusMonitor::new()
+            .threshold(100)
+            .threshold(1000)
+            .threshold(100);
+        let below = |threshold, remaining| StatusAlert::QuotaBelow {
+            threshold,
+            remaining,
+        };
+
+        assert_eq!(monitor.observe(status(true, 500)), [below(1000, 500)]);
+        assert!(monitor.observe(status(true, 400)).is_empty());
+        assert_eq!(monitor.observe(status(true, 100)), [below(100, 100)]);
+        assert!(monitor.observe(status(true, 0)).is_empty());
+
+        // New billing cycle
+        assert!(monitor.observe(status(true, 50_000)).is_empty());
+        assert_eq!(
+            monitor.observe(status(true, 10)),
+            [below(1000, 10), below(100, 10)]
+        );
+    }
+
+    #[test]
+    fn test_handlers_run_for_each_alert() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let mut monitor = StatusMonitor::new()
+            .threshold(10)
+            .on_alert(move |alert: &StatusAlert| sink.lock().unwrap().push(alert.to_string()));
+
+        monitor.observe(status(true, 50));
+        monitor.observe(status(false, 5));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                "API token is inactive",
+                "5 queries remaining, below the 10 threshold"
+            ]
+        );
+        assert_eq!(monitor.latest(), Some(&status(false, 5)));
+    }
+}
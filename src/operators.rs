@@ -0,0 +1,148 @@
+//! Normalization of tunnel operator and proxy network names.
+//!
+//! Spur does not spell operator names consistently across products and
+//! over time: the same service may appear as `PROTON_VPN`, `ProtonVPN` or
+//! `Proton VPN`. [`normalize`] maps all of these to one key, so operator
+//! allow and deny lists can be joined against contexts without missing
+//! matches.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::operators;
+//!
+//! assert_eq!(operators::normalize("PROTON_VPN"), "PROTONVPN");
+//! assert_eq!(operators::normalize("Proton VPN"), "PROTONVPN");
+//! assert!(operators::same("ProtonVPN", "proton-vpn"));
+//! ```
+//!
+//! A small built-in registry tells commercial consumer VPN services
+//! ([`is_commercial_vpn`]) from corporate VPN and secure web gateway egress
+//! ([`is_corporate_vpn`]).
+
+/// Normalized names of commercial consumer VPN services, without a
+/// trailing `VPN`.
+pub const COMMERCIAL_VPNS: &[&str] = &[
+    "ATLAS",
+    "CYBERGHOST",
+    "EXPRESS",
+    "HIDEME",
+    "HIDEMYASS",
+    "HOTSPOTSHIELD",
+    "IPVANISH",
+    "MULLVAD",
+    "NORD",
+    "PRIVADO",
+    "PRIVATEINTERNETACCESS",
+    "PROTON",
+    "PURE",
+    "STRONG",
+    "SURFSHARK",
+    "TUNNELBEAR",
+    "VYPR",
+    "WINDSCRIBE",
+    "ZENMATE",
+];
+
+/// Normalized names of corporate VPN and secure web gateway services,
+/// without a trailing `VPN`.
+pub const CORPORATE_VPNS: &[&str] = &[
+    "CISCOANYCONNECT",
+    "CISCOUMBRELLA",
+    "FORTICLIENT",
+    "GLOBALPROTECT",
+    "IBOSS",
+    "MENLOSECURITY",
+    "NETSKOPE",
+    "PRISMAACCESS",
+    "ZSCALER",
+];
+
+/// Returns the normalized key of an operator name.
+///
+/// Letters are uppercased and everything that is not a letter or digit
+/// (spaces, underscores, hyphens, dots) is dropped. The key is meant for
+/// comparisons and map keys, not for display.
+pub fn normalize(operator: &str) -> String {
+    operator
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Returns `true` if two operator names normalize to the same key.
+pub fn same(a: &str, b: &str) -> bool {
+    normalize(a) == normalize(b)
+}
+
+/// Returns `true` if `operator` is a known commercial consumer VPN, in any
+/// spelling and with or without a `VPN` suffix.
+pub fn is_commercial_vpn(operator: &str) -> bool {
+    in_registry(COMMERCIAL_VPNS, operator)
+}
+
+/// Returns `true` if `operator` is a known corporate VPN or secure web
+/// gateway, in any spelling and with or without a `VPN` suffix.
+pub fn is_corporate_vpn(operator: &str) -> bool {
+    in_registry(CORPORATE_VPNS, operator)
+}
+
+fn in_registry(registry: &[&str], operator: &str) -> bool {
+    let key = normalize(operator);
+    let key = key
+        .strip_suffix("VPN")
+        .filter(|k| !k.is_empty())
+        .unwrap_or(&key);
+    registry.contains(&key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Tunnel;
+
+    #[test]
+    fn test_variants_normalize_alike() {
+        for name in [
+            "PROTON_VPN",
+            "ProtonVPN",
+            "Proton VPN",
+            "proton-vpn",
+            " Proton.VPN ",
+        ] {
+            assert_eq!(normalize(name), "PROTONVPN", "{}", name);
+            assert!(same(name, "PROTON_VPN"));
+        }
+        assert!(!same("PROTON_VPN", "NORD_VPN"));
+        assert_eq!(normalize("Mullvad VPN 2"), "MULLVADVPN2");
+        assert_eq!(normalize("___"), "");
+    }
+
+    #[test]
+    fn test_registry() {
+        for name in [
+            "NORD_VPN",
+            "NordVPN",
+            "Mullvad",
+            "PRIVATE_INTERNET_ACCESS_VPN",
+        ] {
+            assert!(is_commercial_vpn(name), "{}", name);
+            assert!(!is_corporate_vpn(name), "{}", name);
+        }
+        assert!(is_corporate_vpn("ZSCALER"));
+        assert!(is_corporate_vpn("Cisco AnyConnect"));
+        assert!(!is_commercial_vpn("VPN"));
+        assert!(!is_commercial_vpn("OXYLABS_PROXY"));
+    }
+
+    #[test]
+    fn test_tunnel_operator_normalized() {
+        let tunnel = Tunnel {
+            operator: Some("Nord VPN".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(tunnel.operator_normalized().as_deref(), Some("NORDVPN"));
+        assert_eq!(Tunnel::default().operator_normalized(), None);
+    }
+}
@@ -0,0 +1,251 @@
+//! Typed pages and cursors for paginated responses.
+//!
+//! Bulk endpoints return large result sets a page at a time, each page
+//! carrying an opaque cursor for the next. [`Page`] deserializes one page,
+//! and [`paginate`] turns a function that fetches a page into an iterator
+//! over every item, so callers do not write the cursor loop themselves.
+//!
+//! This crate has no HTTP client; the fetch function sends the request
+//! with the service's own client, passing the cursor along.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::pagination::{paginate, Cursor, Page};
+//! use spur::IpContext;
+//!
+//! // Stands in for an HTTP request to a bulk endpoint
+//! fn fetch(cursor: Option<&Cursor>) -> Result<Page<IpContext>, serde_json::Error> {
+//!     let body = match cursor.map(Cursor::as_str) {
+//!         None => r#"{"items": [{"ip": "1.1.1.1"}, {"ip": "2.2.2.2"}], "next": "p2"}"#,
+//!         Some("p2") => r#"{"items": [{"ip": "3.3.3.3"}]}"#,
+//!         Some(_) => unreachable!(),
+//!     };
+//!     serde_json::from_str(body)
+//! }
+//!
+//! let ips: Vec<String> = paginate(fetch)
+//!     .map(|context| context.unwrap().ip.unwrap())
+//!     .collect();
+//! assert_eq!(ips, ["1.1.1.1", "2.2.2.2", "3.3.3.3"]);
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// An opaque position in a paginated result set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Cursor(String);
+
+impl Cursor {
+    /// Wrap a cursor value.
+    pub fn new(cursor: impl Into<String>) -> Self {
+        Self(cursor.into())
+    }
+
+    /// Returns the cursor value, to send with the next request.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// One page of a paginated response.
+///
+/// Deserializes from `{"items": [...], "next": "..."}`; `data` and
+/// `results` are accepted for the items, and `next_cursor` and `cursor`
+/// for the cursor. A missing, null or empty cursor marks the last page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    /// The items on this page.
+    #[serde(alias = "data", alias = "results")]
+    pub items: Vec<T>,
+
+    /// The cursor of the next page, or `None` on the last page.
+    #[serde(
+        default,
+        rename = "next",
+        alias = "next_cursor",
+        alias = "cursor",
+        deserialize_with = "deserialize_cursor",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub next: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+    /// Create a page of `items` followed by the page at `next`.
+    pub fn new(items: Vec<T>, next: Option<Cursor>) -> Self {
+        Self { items, next }
+    }
+
+    /// Returns `true` if no page follows this one.
+    pub fn is_last(&self) -> bool {
+        self.next.is_none()
+    }
+}
+
+fn deserialize_cursor<'de, D>(deserializer: D) -> Result<Option<Cursor>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let cursor = Option::<String>::deserialize(deserializer)?;
+    Ok(cursor.filter(|c| !c.is_empty()).map(Cursor))
+}
+
+/// Iterate over the items of every page that `fetch` returns.
+///
+/// `fetch` is called with `None` for the first page and with the previous
+/// page's cursor after that, only once the items fetched so far are used
+/// up. Iteration ends after the last page, or after the first error, which
+/// is yielded as an item.
+pub fn paginate<T, E, F>(fetch: F) -> Paginate<T, F>
+where
+    F: FnMut(Option<&Cursor>) -> Result<Page<T>, E>,
+{
+    Paginate {
+        fetch,
+        items: Vec::new().into_iter(),
+        next: None,
+        pages: 0,
+        done: false,
+    }
+}
+
+/// Iterator over the items of a paginated result set, from [`paginate`].
+pub struct Paginate<T, F> {
+    fetch: F,
+    items: std::vec::IntoIter<T>,
+    next: Option<Cursor>,
+    pages: u64,
+    done: bool,
+}
+
+impl<T, F> Paginate<T, F> {
+    /// Returns the number of pages fetched so far.
+    pub fn pages(&self) -> u64 {
+        self.pages
+    }
+
+    /// Returns the cursor of the next page to fetch, for resuming later.
+    ///
+    /// `None` before the first page and after the last.
+    pub fn cursor(&self) -> Option<&Cursor> {
+        self.next.as_ref()
+    }
+}
+
+impl<T, F> fmt::Debug for Paginate<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Paginate")
+            .field("pages", &self.pages)
+            .field("next", &self.next)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, E, F> Iterator for Paginate<T, F>
+where
+    F: FnMut(Option<&Cursor>) -> Result<Page<T>, E>,
+{
+    type Item = Result<T, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.items.next() {
+                return Some(Ok(item));
+            }
+            if self.done {
+                return None;
+            }
+            match (self.fetch)(self.next.as_ref()) {
+                Ok(page) => {
+                    self.pages += 1;
+                    self.items = page.items.into_iter();
+                    self.done = page.next.is_none();
+                    self.next = page.next;
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered(pages: &[(&[u32], Option<&str>)]) -> Vec<Page<u32>> {
+        pages
+            .iter()
+            .map(|(items, next)| Page::new(items.to_vec(), next.map(Cursor::new)))
+            .collect()
+    }
+
+    #[test]
+    fn test_walks_pages_lazily() {
+        let pages = numbered(&[(&[1, 2], Some("a")), (&[], Some("b")), (&[3], None)]);
+        let mut seen = Vec::new();
+        let mut iter = paginate(|cursor: Option<&Cursor>| {
+            seen.push(cursor.map(|c| c.to_string()));
+            Ok::<_, String>(pages[seen.len() - 1].clone())
+        });
+
+        assert_eq!(iter.next(), Some(Ok(1)));
+        assert_eq!(iter.pages(), 1);
+        assert_eq!(iter.cursor(), Some(&Cursor::new("a")));
+        let rest: Vec<u32> = iter.by_ref().map(Result::unwrap).collect();
+        assert_eq!(rest, [2, 3]);
+        assert_eq!((iter.pages(), iter.cursor()), (3, None));
+        drop(iter);
+        assert_eq!(seen, [None, Some("a".into()), Some("b".into())]);
+    }
+
+    #[test]
+    fn test_stops_after_error() {
+        let mut calls = 0;
+        let results: Vec<Result<u32, String>> = paginate(|_: Option<&Cursor>| {
+            calls += 1;
+            match calls {
+                1 => Ok(Page::new(vec![7], Some(Cursor::new("next")))),
+                _ => Err("timeout".to_string()),
+            }
+        })
+        .collect();
+        assert_eq!(results, [Ok(7), Err("timeout".to_string())]);
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_serde() {
+        for body in [
+            r#"{"items": [1, 2], "next": "abc"}"#,
+            r#"{"data": [1, 2], "next_cursor": "abc"}"#,
+            r#"{"results": [1, 2], "cursor": "abc", "total": 40}"#,
+        ] {
+            let page: Page<u32> = serde_json::from_str(body).unwrap();
+            assert_eq!(page, Page::new(vec![1, 2], Some(Cursor::new("abc"))));
+        }
+        for body in [
+            r#"{"items": []}"#,
+            r#"{"items": [], "next": null}"#,
+            r#"{"items": [], "next": ""}"#,
+        ] {
+            assert!(serde_json::from_str::<Page<u32>>(body).unwrap().is_last());
+        }
+
+        let page = Page::new(vec![1u32], None);
+        assert_eq!(serde_json::to_string(&page).unwrap(), r#"{"items":[1]}"#);
+    }
+}
@@ -0,0 +1,262 @@
+//! The shared decision vocabulary.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// The outcome of a risk decision about a request.
+///
+/// Every variant carries optional reason codes explaining the outcome and an
+/// optional TTL for how long the outcome may be reused for the same
+/// address. Serializes with an `action` tag and the TTL in whole seconds:
+///
+/// ```json
+/// {"action": "BLOCK", "reasons": ["TOR_EXIT"], "ttl": 3600}
+/// ```
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use spur::policy::Decision;
+///
+/// let decision = Decision::block()
+///     .with_reason("TOR_EXIT")
+///     .with_ttl(Duration::from_secs(3600));
+///
+/// assert!(decision.is_block());
+/// assert_eq!(decision.reasons(), ["TOR_EXIT"]);
+/// assert_eq!(
+///     serde_json::to_string(&decision).unwrap(),
+///     r#"{"action":"BLOCK","reasons":["TOR_EXIT"],"ttl":3600}"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Decision {
+    /// Let the request through.
+    Allow {
+        /// Reason codes for the outcome.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        reasons: Vec<String>,
+        /// How long the outcome may be reused.
+        #[serde(default, skip_serializing_if = "Option::is_none", with = "ttl_secs")]
+        ttl: Option<Duration>,
+    },
+    /// Require additional verification, such as a CAPTCHA or step-up
+    /// authentication.
+    Challenge {
+        /// Reason codes for the outcome.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        reasons: Vec<String>,
+        /// How long the outcome may be reused.
+        #[serde(default, skip_serializing_if = "Option::is_none", with = "ttl_secs")]
+        ttl: Option<Duration>,
+    },
+    /// Reject the request.
+    Block {
+        /// Reason codes for the outcome.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        reasons: Vec<String>,
+        /// How long the outcome may be reused.
+        #[serde(default, skip_serializing_if = "Option::is_none", with = "ttl_secs")]
+        ttl: Option<Duration>,
+    },
+}
+
+impl Decision {
+    /// An `Allow` decision with no reasons or TTL.
+    pub fn allow() -> Self {
+        Self::Allow {
+            reasons: Vec::new(),
+            ttl: None,
+        }
+    }
+
+    /// A `Challenge` decision with no reasons or TTL.
+    pub fn challenge() -> Self {
+        Self::Challenge {
+            reasons: Vec::new(),
+            ttl: None,
+        }
+    }
+
+    /// A `Block` decision with no reasons or TTL.
+    pub fn block() -> Self {
+        Self::Block {
+            reasons: Vec::new(),
+            ttl: None,
+        }
+    }
+
+    /// Add a reason code.
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.parts_mut().0.push(reason.into());
+        self
+    }
+
+    /// Set the TTL.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        *self.parts_mut().1 = Some(ttl);
+        self
+    }
+
+    /// Returns the reason codes.
+    pub fn reasons(&self) -> &[String] {
+        match self {
+            Self::Allow { reasons, .. }
+            | Self::Challenge { reasons, .. }
+            | Self::Block { reasons, .. } => reasons,
+        }
+    }
+
+    /// Returns the TTL, if set.
+    pub fn ttl(&self) -> Option<Duration> {
+        match self {
+            Self::Allow { ttl, .. } | Self::Challenge { ttl, .. } | Self::Block { ttl, .. } => *ttl,
+        }
+    }
+
+    /// Returns the action as its serialized string: `"ALLOW"`,
+    /// `"CHALLENGE"` or `"BLOCK"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow { .. } => "ALLOW",
+            Self::Challenge { .. } => "CHALLENGE",
+            Self::Block { .. } => "BLOCK",
+        }
+    }
+
+    /// Returns true if this is an `Allow` decision.
+    pub fn is_allow(&self) -> bool {
+        matches!(self, Self::Allow { .. })
+    }
+
+    /// Returns true if this is a `Challenge` decision.
+    pub fn is_challenge(&self) -> bool {
+        matches!(self, Self::Challenge { .. })
+    }
+
+    /// Returns true if this is a `Block` decision.
+    pub fn is_block(&self) -> bool {
+        matches!(self, Self::Block { .. })
+    }
+
+    /// Combine two decisions about the same request.
+    ///
+    /// The stricter action wins (`Block` over `Challenge` over `Allow`).
+    /// Reasons of both decisions with that action are kept, and the shorter
+    /// TTL applies.
+    pub fn strictest(self, other: Decision) -> Decision {
+        let (mut winner, loser) = match self.rank().cmp(&other.rank()) {
+            std::cmp::Ordering::Less => (other, self),
+            _ => (self, other),
+        };
+        if winner.rank() == loser.rank() {
+            for reason in loser.reasons() {
+                if !winner.reasons().contains(reason) {
+                    winner.parts_mut().0.push(reason.clone());
+                }
+            }
+            let ttl = match (winner.ttl(), loser.ttl()) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+            *winner.parts_mut().1 = ttl;
+        }
+        winner
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Allow { .. } => 0,
+            Self::Challenge { .. } => 1,
+            Self::Block { .. } => 2,
+        }
+    }
+
+    fn parts_mut(&mut self) -> (&mut Vec<String>, &mut Option<Duration>) {
+        match self {
+            Self::Allow { reasons, ttl }
+            | Self::Challenge { reasons, ttl }
+            | Self::Block { reasons, ttl } => (reasons, ttl),
+        }
+    }
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())?;
+        if !self.reasons().is_empty() {
+            write!(f, " ({})", self.reasons().join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Serializes an optional TTL as whole seconds.
+mod ttl_secs {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        ttl: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match ttl {
+            Some(ttl) => serializer.serialize_u64(ttl.as_secs()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<u64>::deserialize(deserializer).map(|secs| secs.map(Duration::from_secs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serde_round_trip() {
+        for decision in [
+            Decision::allow(),
+            Decision::challenge().with_reason("VPN"),
+            Decision::block()
+                .with_reason("TOR_EXIT")
+                .with_ttl(Duration::from_secs(60)),
+        ] {
+            let json = serde_json::to_string(&decision).unwrap();
+            assert_eq!(serde_json::from_str::<Decision>(&json).unwrap(), decision);
+        }
+        assert_eq!(
+            serde_json::to_string(&Decision::allow()).unwrap(),
+            r#"{"action":"ALLOW"}"#
+        );
+        assert!(serde_json::from_str::<Decision>(r#"{"action":"MAYBE"}"#).is_err());
+    }
+
+    #[test]
+    fn test_strictest() {
+        let challenge = Decision::challenge()
+            .with_reason("VPN")
+            .with_ttl(Duration::from_secs(300));
+        let block = Decision::block().with_reason("TOR_EXIT");
+        assert_eq!(challenge.clone().strictest(block.clone()), block);
+        assert_eq!(Decision::allow().strictest(challenge.clone()), challenge);
+
+        let merged = challenge.strictest(
+            Decision::challenge()
+                .with_reason("GEO_MISMATCH")
+                .with_reason("VPN")
+                .with_ttl(Duration::from_secs(60)),
+        );
+        assert_eq!(merged.reasons(), ["VPN", "GEO_MISMATCH"]);
+        assert_eq!(merged.ttl(), Some(Duration::from_secs(60)));
+        assert_eq!(merged.to_string(), "CHALLENGE (VPN, GEO_MISMATCH)");
+    }
+}
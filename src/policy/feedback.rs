@@ -0,0 +1,193 @@
+//! Training data export from decision records.
+
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+use super::record::to_millis;
+use super::DecisionRecord;
+
+/// Fixed leading columns of [`write_training_csv`] output.
+const COLUMNS: &[&str] = &[
+    "decided_at",
+    "policy_version",
+    "ip",
+    "infrastructure",
+    "asn",
+    "country",
+    "risks",
+    "tunnel_operators",
+    "matched_rules",
+    "decision",
+    "total_score",
+];
+
+/// Join decision records with their later-known outcomes and write them as
+/// a flat CSV training dataset.
+///
+/// `outcome` returns the label for a record (for example `"fraud"` or
+/// `"legitimate"`), or `None` if the outcome is not known yet; unlabelled
+/// records are skipped. Each score component becomes its own
+/// `score.<name>` column, empty for records without it. List-valued columns
+/// are joined with `;`, and `decided_at` is milliseconds since the Unix
+/// epoch. The last column is `outcome`.
+///
+/// Returns the number of rows written, excluding the header.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::policy::{write_training_csv, DecisionRecord};
+/// use spur::test_utils::fixtures;
+///
+/// let records = [
+///     DecisionRecord::new(&fixtures::vpn_ip(), "v3").score("tunnel", 40.0),
+///     DecisionRecord::new(&fixtures::residential_ip(), "v3"),
+/// ];
+///
+/// let mut csv = Vec::new();
+/// let rows = write_training_csv(&mut csv, &records, |record| {
+///     record.input.infrastructure.as_ref().map(|_| "legitimate".to_string())
+/// })
+/// .unwrap();
+///
+/// assert_eq!(rows, 2);
+/// assert!(String::from_utf8(csv).unwrap().starts_with("decided_at,"));
+/// ```
+pub fn write_training_csv<W, F>(
+    mut writer: W,
+    records: &[DecisionRecord],
+    mut outcome: F,
+) -> io::Result<usize>
+where
+    W: Write,
+    F: FnMut(&DecisionRecord) -> Option<String>,
+{
+    let labelled: Vec<(&DecisionRecord, String)> = records
+        .iter()
+        .filter_map(|record| outcome(record).map(|label| (record, label)))
+        .collect();
+    let components: BTreeSet<&str> = labelled
+        .iter()
+        .flat_map(|(record, _)| record.score.iter().map(|c| c.name.as_str()))
+        .collect();
+
+    let header = COLUMNS
+        .iter()
+        .map(|column| column.to_string())
+        .chain(components.iter().map(|name| format!("score.{}", name)))
+        .chain(["outcome".to_string()]);
+    write_row(&mut writer, header)?;
+
+    for (record, label) in &labelled {
+        let input = &record.input;
+        let mut row = vec![
+            to_millis(record.decided_at).to_string(),
+            record.policy_version.clone(),
+            input.ip.clone().unwrap_or_default(),
+            input
+                .infrastructure
+                .as_ref()
+                .map(|i| i.to_string())
+                .unwrap_or_default(),
+            input.asn.map(|n| n.to_string()).unwrap_or_default(),
+            input.country.clone().unwrap_or_default(),
+            join(input.risks.iter().map(|r| r.as_str())),
+            join(input.tunnel_operators.iter().map(String::as_str)),
+            join(record.matched_rules.iter().map(String::as_str)),
+            record
+                .decision
+                .as_ref()
+                .map(|d| d.as_str().to_string())
+                .unwrap_or_default(),
+            record.total_score().to_string(),
+        ];
+        for name in &components {
+            let value: Option<f64> = record
+                .score
+                .iter()
+                .filter(|c| c.name == *name)
+                .map(|c| c.value)
+                .reduce(|a, b| a + b);
+            row.push(value.map(|v| v.to_string()).unwrap_or_default());
+        }
+        row.push(label.clone());
+        write_row(&mut writer, row)?;
+    }
+    writer.flush()?;
+    Ok(labelled.len())
+}
+
+fn join<'a>(values: impl Iterator<Item = &'a str>) -> String {
+    values.collect::<Vec<_>>().join(";")
+}
+
+/// Write one CSV record, quoting fields as described in RFC 4180.
+fn write_row<W: Write>(writer: &mut W, fields: impl IntoIterator<Item = String>) -> io::Result<()> {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(field.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::Decision;
+    use crate::test_utils::fixtures;
+    use crate::IpContext;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_training_csv() {
+        let at = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+        let records = [
+            DecisionRecord::new(&fixtures::vpn_ip(), "v1")
+                .matched_rule("vpn")
+                .matched_rule("datacenter")
+                .score("tunnel", 40.0)
+                .score("tunnel", 5.0)
+                .decision(Decision::block())
+                .decided_at(at),
+            DecisionRecord::new(&IpContext::default(), "v1").decided_at(at),
+            DecisionRecord::new(&fixtures::residential_ip(), "v2")
+                .score("geo", 1.5)
+                .decided_at(at),
+        ];
+        let mut csv = Vec::new();
+        let rows = write_training_csv(&mut csv, &records, |record| {
+            (record.policy_version == "v1").then(|| "fraud".to_string())
+        })
+        .unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(rows, 2);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with(",total_score,score.tunnel,outcome"));
+        assert!(lines[1].starts_with("1700000000000,v1,"));
+        assert!(lines[1].ends_with(",vpn;datacenter,BLOCK,45,45,fraud"));
+        assert!(lines[2].ends_with(",,,,0,,fraud"));
+    }
+
+    #[test]
+    fn test_quoting() {
+        let mut out = Vec::new();
+        write_row(
+            &mut out,
+            [
+                "plain".to_string(),
+                "a,b".to_string(),
+                "say \"hi\"".to_string(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(out, b"plain,\"a,b\",\"say \"\"hi\"\"\"\r\n");
+    }
+}
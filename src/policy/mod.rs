@@ -0,0 +1,36 @@
+//! # Policy Building Blocks
+//!
+//! Types for turning Spur data into risk decisions declaratively. This crate
+//! does not ship a policy engine; these are the shared pieces such engines
+//! load from configuration and consume.
+//!
+//! ## Key Types
+//!
+//! | Type | Purpose |
+//! |------|---------|
+//! | [`Decision`] | Allow / Challenge / Block with reason codes and TTL |
+//! | [`CountryWeights`] | Geo-based risk multipliers keyed by country code |
+//! | [`OperatorRules`] | Tunnel operator allow and deny lists |
+//! | [`DecisionRecord`] | Audit trail of one decision: input, rules, score breakdown |
+//! | [`write_training_csv`] | Decision records joined with outcomes as a CSV dataset |
+//! | [`Notifier`] | Alerting hook for Block and Challenge decisions |
+
+mod decision;
+#[cfg(feature = "json")]
+mod feedback;
+#[cfg(feature = "json")]
+mod notify;
+mod operators;
+#[cfg(feature = "json")]
+mod record;
+mod weights;
+
+pub use decision::*;
+#[cfg(feature = "json")]
+pub use feedback::*;
+#[cfg(feature = "json")]
+pub use notify::*;
+pub use operators::*;
+#[cfg(feature = "json")]
+pub use record::*;
+pub use weights::*;
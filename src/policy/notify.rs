@@ -0,0 +1,254 @@
+//! Notification hooks for decisions that block or challenge.
+
+use std::io;
+
+use serde::Serialize;
+
+use super::{DecisionRecord, InputSummary};
+
+/// Receives decision records worth alerting on.
+///
+/// Implement this for a service's alerting channel, or use one of the
+/// built-in payload formats, [`WebhookNotifier`] and [`SlackNotifier`].
+/// Closures taking a `&DecisionRecord` implement it too. Policy engines
+/// call [`notify_triggered`] after every decision.
+pub trait Notifier {
+    /// Deliver a notification about `record`.
+    fn notify(&self, record: &DecisionRecord) -> io::Result<()>;
+}
+
+impl<F> Notifier for F
+where
+    F: Fn(&DecisionRecord) -> io::Result<()>,
+{
+    fn notify(&self, record: &DecisionRecord) -> io::Result<()> {
+        self(record)
+    }
+}
+
+/// Call `notifier` if `record` holds a `Block` or `Challenge` decision.
+///
+/// Returns whether the notifier was called.
+///
+/// # Example
+///
+/// ```rust
+/// use std::cell::RefCell;
+/// use spur::policy::{notify_triggered, Decision, DecisionRecord, SlackNotifier};
+/// use spur::test_utils::fixtures;
+///
+/// let sent = RefCell::new(Vec::new());
+/// let slack = SlackNotifier::new(|body: &[u8]| {
+///     sent.borrow_mut().push(body.to_vec());
+///     Ok(())
+/// });
+///
+/// let record = DecisionRecord::new(&fixtures::tor_exit_node(), "v3")
+///     .decision(Decision::block().with_reason("TOR_EXIT"));
+/// assert!(notify_triggered(&slack, &record).unwrap());
+///
+/// let allowed = DecisionRecord::new(&fixtures::residential_ip(), "v3")
+///     .decision(Decision::allow());
+/// assert!(!notify_triggered(&slack, &allowed).unwrap());
+/// assert_eq!(sent.borrow().len(), 1);
+/// ```
+pub fn notify_triggered<N>(notifier: &N, record: &DecisionRecord) -> io::Result<bool>
+where
+    N: Notifier + ?Sized,
+{
+    let triggered = record
+        .decision
+        .as_ref()
+        .is_some_and(|d| d.is_block() || d.is_challenge());
+    if triggered {
+        notifier.notify(record)?;
+    }
+    Ok(triggered)
+}
+
+/// Sends a generic JSON payload for each notification.
+///
+/// The payload wraps the full record:
+///
+/// ```json
+/// {"event": "spur.decision", "action": "BLOCK", "record": { ... }}
+/// ```
+///
+/// Delivery is up to `send`, which receives the serialized body; this
+/// crate has no HTTP client, so `send` typically posts it with the
+/// service's own.
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier<F> {
+    send: F,
+}
+
+impl<F> WebhookNotifier<F>
+where
+    F: Fn(&[u8]) -> io::Result<()>,
+{
+    /// Create a notifier delivering payloads with `send`.
+    pub fn new(send: F) -> Self {
+        Self { send }
+    }
+
+    /// Returns the JSON payload for `record`.
+    pub fn payload(record: &DecisionRecord) -> io::Result<Vec<u8>> {
+        #[derive(Serialize)]
+        struct Payload<'a> {
+            event: &'static str,
+            action: Option<&'static str>,
+            record: &'a DecisionRecord,
+        }
+
+        let payload = Payload {
+            event: "spur.decision",
+            action: record.decision.as_ref().map(|d| d.as_str()),
+            record,
+        };
+        Ok(serde_json::to_vec(&payload)?)
+    }
+}
+
+impl<F> Notifier for WebhookNotifier<F>
+where
+    F: Fn(&[u8]) -> io::Result<()>,
+{
+    fn notify(&self, record: &DecisionRecord) -> io::Result<()> {
+        (self.send)(&Self::payload(record)?)
+    }
+}
+
+/// Sends a Slack-style `{"text": "..."}` payload for each notification,
+/// as accepted by Slack incoming webhooks and compatible chat tools.
+///
+/// The text is one line such as
+/// `BLOCK 185.220.101.1 (TOR_EXIT) · DATACENTER · AS60729 · DE · policy v3`.
+/// Delivery is up to `send`, as with [`WebhookNotifier`].
+#[derive(Debug, Clone)]
+pub struct SlackNotifier<F> {
+    send: F,
+}
+
+impl<F> SlackNotifier<F>
+where
+    F: Fn(&[u8]) -> io::Result<()>,
+{
+    /// Create a notifier delivering payloads with `send`.
+    pub fn new(send: F) -> Self {
+        Self { send }
+    }
+
+    /// Returns the message text for `record`.
+    pub fn text(record: &DecisionRecord) -> String {
+        let InputSummary {
+            ip,
+            infrastructure,
+            asn,
+            country,
+            ..
+        } = &record.input;
+        let mut text = match &record.decision {
+            Some(decision) => decision.as_str().to_string(),
+            None => "UNDECIDED".to_string(),
+        };
+        text.push(' ');
+        text.push_str(ip.as_deref().unwrap_or("unknown ip"));
+        if let Some(decision) = record.decision.as_ref().filter(|d| !d.reasons().is_empty()) {
+            text.push_str(&format!(" ({})", decision.reasons().join(", ")));
+        }
+        let details = [
+            infrastructure.as_ref().map(|i| i.to_string()),
+            asn.map(|n| format!("AS{}", n)),
+            country.clone(),
+            Some(format!("policy {}", record.policy_version)),
+        ];
+        for detail in details.into_iter().flatten() {
+            text.push_str(" · ");
+            text.push_str(&detail);
+        }
+        text
+    }
+
+    /// Returns the JSON payload for `record`.
+    pub fn payload(record: &DecisionRecord) -> io::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(
+            &serde_json::json!({ "text": Self::text(record) }),
+        )?)
+    }
+}
+
+impl<F> Notifier for SlackNotifier<F>
+where
+    F: Fn(&[u8]) -> io::Result<()>,
+{
+    fn notify(&self, record: &DecisionRecord) -> io::Result<()> {
+        (self.send)(&Self::payload(record)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::Decision;
+    use crate::test_utils::IpContextBuilder;
+    use crate::Infrastructure;
+
+    type Sink = fn(&[u8]) -> io::Result<()>;
+
+    fn record() -> DecisionRecord {
+        let context = IpContextBuilder::new()
+            .ip("185.220.101.1")
+            .infrastructure(Infrastructure::Datacenter)
+            .asn(60729, "Tor Exit")
+            .location("DE", None)
+            .tor()
+            .build();
+        DecisionRecord::new(&context, "v3").decision(
+            Decision::block()
+                .with_reason("TOR_EXIT")
+                .with_reason("DATACENTER"),
+        )
+    }
+
+    #[test]
+    fn test_slack_text() {
+        assert_eq!(
+            SlackNotifier::<Sink>::text(&record()),
+            "BLOCK 185.220.101.1 (TOR_EXIT, DATACENTER) · DATACENTER · AS60729 · DE · policy v3"
+        );
+        let payload: serde_json::Value =
+            serde_json::from_slice(&SlackNotifier::<Sink>::payload(&record()).unwrap()).unwrap();
+        assert!(payload["text"].as_str().unwrap().starts_with("BLOCK "));
+        let bare = DecisionRecord::new(&crate::IpContext::default(), "v1");
+        assert_eq!(
+            SlackNotifier::<Sink>::text(&bare),
+            "UNDECIDED unknown ip · policy v1"
+        );
+    }
+
+    #[test]
+    fn test_webhook_payload() {
+        let body = WebhookNotifier::<Sink>::payload(&record()).unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["event"], "spur.decision");
+        assert_eq!(payload["action"], "BLOCK");
+        assert_eq!(payload["record"]["input"]["ip"], "185.220.101.1");
+    }
+
+    #[test]
+    fn test_triggers_and_errors() {
+        let failing = |_: &DecisionRecord| Err(io::Error::other("down"));
+        assert!(notify_triggered(&failing, &record()).is_err());
+
+        let challenge = record().decision(Decision::challenge());
+        let undecided = DecisionRecord::new(&crate::IpContext::default(), "v1");
+        let called = std::cell::Cell::new(0);
+        let count = |_: &DecisionRecord| {
+            called.set(called.get() + 1);
+            Ok(())
+        };
+        assert!(notify_triggered(&count, &challenge).unwrap());
+        assert!(!notify_triggered(&count, &undecided).unwrap());
+        assert_eq!(called.get(), 1);
+    }
+}
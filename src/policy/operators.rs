@@ -0,0 +1,219 @@
+//! Tunnel operator allow and deny lists.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::decision::Decision;
+use crate::context::IpContext;
+use crate::operators::normalize;
+
+/// Reason code of decisions for addresses behind a denylisted operator.
+pub const OPERATOR_DENIED: &str = "OPERATOR_DENIED";
+
+/// Reason code of decisions for addresses behind allowlisted operators only.
+pub const OPERATOR_ALLOWED: &str = "OPERATOR_ALLOWED";
+
+/// Operator allowlists and denylists, with a fallback for other anonymous
+/// traffic.
+///
+/// Operators are stored and matched as [`normalize`] keys, so `Zscaler`,
+/// `ZSCALER` and `z-scaler` name the same entry. [`decide`](Self::decide)
+/// applies the lists to a context:
+///
+/// 1. If any tunnel operator is denylisted, the address is blocked.
+/// 2. If every tunnel is run by an allowlisted operator, it is allowed.
+/// 3. Otherwise, if any tunnel is anonymous, the
+///    [`unlisted_anonymous`](Self::unlisted_anonymous) decision applies.
+///
+/// A tunnel without an operator counts as unlisted.
+///
+/// # Example
+///
+/// Allow the corporate VPN, block all other anonymous traffic:
+///
+/// ```rust
+/// use spur::policy::{Decision, OperatorRules};
+/// use spur::test_utils::IpContextBuilder;
+///
+/// let rules: OperatorRules = serde_json::from_str(r#"{
+///     "allow": ["Zscaler"],
+///     "unlisted_anonymous": {"action": "BLOCK", "reasons": ["ANONYMOUS"]}
+/// }"#).unwrap();
+///
+/// let corporate = IpContextBuilder::new().vpn("ZSCALER").build();
+/// assert_eq!(rules.decide(&corporate), Some(Decision::allow().with_reason("OPERATOR_ALLOWED")));
+///
+/// let consumer = IpContextBuilder::new().vpn("NORD_VPN").build();
+/// assert!(rules.decide(&consumer).unwrap().is_block());
+///
+/// let direct = IpContextBuilder::new().build();
+/// assert_eq!(rules.decide(&direct), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OperatorRules {
+    /// Normalized keys of operators to let through.
+    #[serde(deserialize_with = "deserialize_operators")]
+    pub allow: BTreeSet<String>,
+
+    /// Normalized keys of operators to block. Wins over `allow`.
+    #[serde(deserialize_with = "deserialize_operators")]
+    pub deny: BTreeSet<String>,
+
+    /// Decision for anonymous addresses not settled by either list, or
+    /// `None` to leave them to other rules.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unlisted_anonymous: Option<Decision>,
+}
+
+/// How an operator name matches [`OperatorRules`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperatorMatch {
+    /// The operator is allowlisted.
+    Allowed,
+    /// The operator is denylisted.
+    Denied,
+    /// The operator is on neither list.
+    Unlisted,
+}
+
+impl OperatorRules {
+    /// Create empty lists with no fallback.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `operator` to the allowlist.
+    pub fn allow(mut self, operator: &str) -> Self {
+        self.allow.insert(normalize(operator));
+        self
+    }
+
+    /// Add `operator` to the denylist.
+    pub fn deny(mut self, operator: &str) -> Self {
+        self.deny.insert(normalize(operator));
+        self
+    }
+
+    /// Set the decision for anonymous addresses behind unlisted operators.
+    pub fn unlisted_anonymous(mut self, decision: Decision) -> Self {
+        self.unlisted_anonymous = Some(decision);
+        self
+    }
+
+    /// Returns how `operator` matches the lists, in any spelling.
+    pub fn check(&self, operator: &str) -> OperatorMatch {
+        let key = normalize(operator);
+        if self.deny.contains(&key) {
+            OperatorMatch::Denied
+        } else if self.allow.contains(&key) {
+            OperatorMatch::Allowed
+        } else {
+            OperatorMatch::Unlisted
+        }
+    }
+
+    /// Returns the decision the lists make for `context`, or `None` if they
+    /// do not apply to it.
+    pub fn decide(&self, context: &IpContext) -> Option<Decision> {
+        let tunnels = context.tunnels.as_deref().unwrap_or_default();
+        let matches: Vec<OperatorMatch> = tunnels
+            .iter()
+            .map(|t| {
+                t.operator
+                    .as_deref()
+                    .map_or(OperatorMatch::Unlisted, |o| self.check(o))
+            })
+            .collect();
+
+        if matches.contains(&OperatorMatch::Denied) {
+            Some(Decision::block().with_reason(OPERATOR_DENIED))
+        } else if !matches.is_empty() && matches.iter().all(|m| *m == OperatorMatch::Allowed) {
+            Some(Decision::allow().with_reason(OPERATOR_ALLOWED))
+        } else if context.risk_view().is_anonymous() {
+            self.unlisted_anonymous.clone()
+        } else {
+            None
+        }
+    }
+}
+
+fn deserialize_operators<'de, D>(deserializer: D) -> Result<BTreeSet<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let operators = Vec::<String>::deserialize(deserializer)?;
+    Ok(operators.iter().map(|o| normalize(o)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::IpContextBuilder;
+
+    #[test]
+    fn test_check_normalizes() {
+        let rules = OperatorRules::new()
+            .allow("Zscaler")
+            .deny("PROTON_VPN")
+            .deny("zscaler");
+
+        assert_eq!(rules.check("Proton VPN"), OperatorMatch::Denied);
+        assert_eq!(rules.check("ZSCALER"), OperatorMatch::Denied);
+        assert_eq!(rules.check("NORD_VPN"), OperatorMatch::Unlisted);
+        assert_eq!(
+            OperatorRules::new()
+                .allow("Cisco AnyConnect")
+                .check("CISCO_ANYCONNECT"),
+            OperatorMatch::Allowed
+        );
+    }
+
+    #[test]
+    fn test_decide() {
+        let rules = OperatorRules::new()
+            .allow("ZSCALER")
+            .deny("Tor Project")
+            .unlisted_anonymous(Decision::challenge());
+
+        let tor = IpContextBuilder::new().vpn("Zscaler").tor().build();
+        assert_eq!(
+            rules.decide(&tor),
+            Some(Decision::block().with_reason(OPERATOR_DENIED))
+        );
+
+        // One allowlisted tunnel does not vouch for another
+        let mixed = IpContextBuilder::new()
+            .vpn("Zscaler")
+            .vpn("NORD_VPN")
+            .build();
+        assert_eq!(rules.decide(&mixed), Some(Decision::challenge()));
+
+        // Non-anonymous unlisted tunnels are left to other rules
+        let proxy = IpContextBuilder::new().proxy("SOME_PROXY").build();
+        assert_eq!(rules.decide(&proxy), None);
+        assert_eq!(rules.decide(&IpContext::default()), None);
+
+        let without_fallback = OperatorRules::new().allow("ZSCALER");
+        let nord = IpContextBuilder::new().vpn("NORD_VPN").build();
+        assert_eq!(without_fallback.decide(&nord), None);
+    }
+
+    #[test]
+    fn test_serde() {
+        let rules: OperatorRules =
+            serde_json::from_str(r#"{"allow": ["Global Protect"], "deny": ["nord_vpn"]}"#).unwrap();
+        assert_eq!(
+            rules,
+            OperatorRules::new().allow("GLOBALPROTECT").deny("NORDVPN")
+        );
+
+        let json = serde_json::to_string(&rules).unwrap();
+        assert_eq!(json, r#"{"allow":["GLOBALPROTECT"],"deny":["NORDVPN"]}"#);
+        assert_eq!(
+            serde_json::from_str::<OperatorRules>("{}").unwrap(),
+            OperatorRules::new()
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! Audit records of risk decisions.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::Decision;
+use crate::context::{Infrastructure, IpContext, Risk};
+
+/// An auditable record of one risk decision.
+///
+/// Captures what the decision was based on, which policy version and rules
+/// produced it, and how the score was put together, so the decision can be
+/// explained and reproduced later. Timestamps serialize as milliseconds
+/// since the Unix epoch.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::policy::DecisionRecord;
+/// use spur::test_utils::fixtures;
+///
+/// let context = fixtures::vpn_ip();
+/// let record = DecisionRecord::new(&context, "2024-06-01")
+///     .matched_rule("anonymous-vpn")
+///     .score("tunnel", 40.0)
+///     .score("datacenter", 15.0);
+///
+/// assert_eq!(record.total_score(), 55.0);
+/// assert!(record.matches_input(&context));
+///
+/// let json = serde_json::to_string(&record).unwrap();
+/// assert!(json.contains(r#""policy_version":"2024-06-01""#));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionRecord {
+    /// The parts of the context the decision was based on.
+    pub input: InputSummary,
+
+    /// Version of the policy that produced the decision.
+    pub policy_version: String,
+
+    /// Names of the rules that matched, in evaluation order.
+    #[serde(default)]
+    pub matched_rules: Vec<String>,
+
+    /// Contributions to the score, in the order they were applied.
+    #[serde(default)]
+    pub score: Vec<ScoreComponent>,
+
+    /// The outcome, once decided.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<Decision>,
+
+    /// When the decision was made.
+    #[serde(with = "unix_millis")]
+    pub decided_at: SystemTime,
+}
+
+impl DecisionRecord {
+    /// Start a record for a decision about `context`, stamped with the
+    /// current time.
+    pub fn new(context: &IpContext, policy_version: impl Into<String>) -> Self {
+        Self {
+            input: InputSummary::from(context),
+            policy_version: policy_version.into(),
+            matched_rules: Vec::new(),
+            score: Vec::new(),
+            decision: None,
+            decided_at: SystemTime::now(),
+        }
+    }
+
+    /// Record a matched rule.
+    pub fn matched_rule(mut self, rule: impl Into<String>) -> Self {
+        self.matched_rules.push(rule.into());
+        self
+    }
+
+    /// Record a score contribution.
+    pub fn score(mut self, name: impl Into<String>, value: f64) -> Self {
+        self.score.push(ScoreComponent {
+            name: name.into(),
+            value,
+        });
+        self
+    }
+
+    /// Record the outcome.
+    pub fn decision(mut self, decision: Decision) -> Self {
+        self.decision = Some(decision);
+        self
+    }
+
+    /// Set the decision time.
+    pub fn decided_at(mut self, time: SystemTime) -> Self {
+        self.decided_at = time;
+        self
+    }
+
+    /// Returns the sum of all score contributions.
+    pub fn total_score(&self) -> f64 {
+        self.score.iter().fold(0.0, |total, c| total + c.value)
+    }
+
+    /// Returns `true` if `context` is the same input the decision was made
+    /// on, as judged by [`IpContext::content_hash`].
+    pub fn matches_input(&self, context: &IpContext) -> bool {
+        self.input.content_hash == format_hash(context.content_hash())
+    }
+}
+
+/// The parts of an [`IpContext`] a decision is usually explained by.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputSummary {
+    /// The address decided on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+
+    /// Infrastructure classification.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub infrastructure: Option<Infrastructure>,
+
+    /// Autonomous system number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asn: Option<u32>,
+
+    /// Location country code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+
+    /// Risks listed on the context.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub risks: Vec<Risk>,
+
+    /// Operators of the context's tunnels.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tunnel_operators: Vec<String>,
+
+    /// [`IpContext::content_hash`] of the full context, as 16 hex digits.
+    pub content_hash: String,
+}
+
+impl From<&IpContext> for InputSummary {
+    fn from(context: &IpContext) -> Self {
+        Self {
+            ip: context.ip.clone(),
+            infrastructure: context.infrastructure.clone(),
+            asn: context.autonomous_system.as_ref().and_then(|a| a.number),
+            country: context.location.as_ref().and_then(|l| l.country.clone()),
+            risks: context.risks.clone().unwrap_or_default(),
+            tunnel_operators: context
+                .tunnels
+                .iter()
+                .flatten()
+                .filter_map(|t| t.operator.clone())
+                .collect(),
+            content_hash: format_hash(context.content_hash()),
+        }
+    }
+}
+
+/// One named contribution to a decision score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreComponent {
+    /// What contributed, e.g. a rule or signal name.
+    pub name: String,
+    /// The amount added to the score.
+    pub value: f64,
+}
+
+fn format_hash(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+/// Converts a timestamp to milliseconds since the Unix epoch.
+pub(super) fn to_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Serializes a `SystemTime` as milliseconds since the Unix epoch.
+mod unix_millis {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(to_millis(*time))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        u64::deserialize(deserializer).map(|millis| UNIX_EPOCH + Duration::from_millis(millis))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::fixtures;
+
+    #[test]
+    fn test_record_round_trip() {
+        let context = fixtures::vpn_ip();
+        let record = DecisionRecord::new(&context, "v7")
+            .matched_rule("vpn")
+            .score("tunnel", 40.0)
+            .score("allowlisted-asn", -10.0)
+            .decision(Decision::challenge().with_reason("VPN"))
+            .decided_at(UNIX_EPOCH + Duration::from_millis(1_717_200_000_123));
+
+        assert_eq!(record.total_score(), 30.0);
+        assert_eq!(record.input.ip, context.ip);
+        assert!(!record.input.tunnel_operators.is_empty());
+
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["decided_at"], 1_717_200_000_123u64);
+        assert_eq!(json["decision"]["action"], "CHALLENGE");
+        assert_eq!(json["input"]["content_hash"].as_str().unwrap().len(), 16);
+        let back: DecisionRecord = serde_json::from_value(json).unwrap();
+        assert_eq!(back, record);
+    }
+
+    #[test]
+    fn test_matches_input() {
+        let context = fixtures::vpn_ip();
+        let record = DecisionRecord::new(&context, "v1");
+        assert!(record.matches_input(&context));
+        assert!(!record.matches_input(&fixtures::residential_ip()));
+    }
+}
@@ -0,0 +1,138 @@
+//! Country risk weighting.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::context::IpContext;
+
+/// Risk multipliers keyed by ISO 3166-1 alpha-2 country code.
+///
+/// A weight of `1.0` is neutral; larger weights raise the risk attributed to
+/// a country (sanctioned regions, high-fraud corridors) and smaller ones
+/// lower it. Countries not listed get [`default`](Self::default).
+///
+/// Country codes are matched without regard to case.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::policy::CountryWeights;
+/// use spur::test_utils::IpContextBuilder;
+///
+/// let weights: CountryWeights = serde_json::from_str(r#"{
+///     "default": 1.0,
+///     "countries": { "kp": 10.0, "RU": 2.5, "NL": 0.8 }
+/// }"#).unwrap();
+///
+/// assert_eq!(weights.weight("KP"), 10.0);
+/// assert_eq!(weights.weight("US"), 1.0);
+///
+/// let context = IpContextBuilder::new().location("RU", None).build();
+/// assert_eq!(weights.weight_for(&context), 2.5);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CountryWeights {
+    /// Weight for countries that are not listed and for contexts without a
+    /// location.
+    pub default: f64,
+
+    /// Weights keyed by uppercase country code.
+    #[serde(deserialize_with = "deserialize_countries")]
+    pub countries: BTreeMap<String, f64>,
+}
+
+impl Default for CountryWeights {
+    fn default() -> Self {
+        Self {
+            default: 1.0,
+            countries: BTreeMap::new(),
+        }
+    }
+}
+
+impl CountryWeights {
+    /// Create a table where every country has weight `1.0`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the weight for countries that are not listed.
+    pub fn default_weight(mut self, weight: f64) -> Self {
+        self.default = weight;
+        self
+    }
+
+    /// Set the weight of `country`.
+    pub fn with(mut self, country: &str, weight: f64) -> Self {
+        self.countries.insert(country.to_ascii_uppercase(), weight);
+        self
+    }
+
+    /// Returns the weight of `country`.
+    pub fn weight(&self, country: &str) -> f64 {
+        let weight = match self.countries.get(country) {
+            Some(weight) => Some(weight),
+            None => self.countries.get(&country.to_ascii_uppercase()),
+        };
+        weight.copied().unwrap_or(self.default)
+    }
+
+    /// Returns the weight for the location country of `context`.
+    pub fn weight_for(&self, context: &IpContext) -> f64 {
+        context
+            .location
+            .as_ref()
+            .and_then(|l| l.country.as_deref())
+            .map_or(self.default, |country| self.weight(country))
+    }
+}
+
+fn deserialize_countries<'de, D>(deserializer: D) -> Result<BTreeMap<String, f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let countries = BTreeMap::<String, f64>::deserialize(deserializer)?;
+    Ok(countries
+        .into_iter()
+        .map(|(country, weight)| (country.to_ascii_uppercase(), weight))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::IpContextBuilder;
+
+    #[test]
+    fn test_builder_and_lookup() {
+        let weights = CountryWeights::new()
+            .default_weight(0.5)
+            .with("ir", 4.0)
+            .with("CN", 1.5);
+
+        assert_eq!(weights.weight("IR"), 4.0);
+        assert_eq!(weights.weight("cn"), 1.5);
+        assert_eq!(weights.weight("DE"), 0.5);
+        assert_eq!(weights.weight_for(&IpContext::default()), 0.5);
+        assert_eq!(
+            weights.weight_for(&IpContextBuilder::new().location("IR", None).build()),
+            4.0
+        );
+    }
+
+    #[test]
+    fn test_serde() {
+        let weights: CountryWeights =
+            serde_json::from_str(r#"{"countries": {"ru": 2.0}}"#).unwrap();
+        assert_eq!(weights, CountryWeights::new().with("RU", 2.0));
+
+        let json = serde_json::to_string(&weights).unwrap();
+        assert_eq!(json, r#"{"default":1.0,"countries":{"RU":2.0}}"#);
+        assert_eq!(
+            serde_json::from_str::<CountryWeights>("{}").unwrap(),
+            CountryWeights::new()
+        );
+    }
+}
@@ -22,8 +22,8 @@
 use proptest::prelude::*;
 
 use crate::context::{
-    Ai, AutonomousSystem, Behavior, Client, Concentration, DeviceType, Infrastructure, IpContext,
-    Location, Risk, Service, Tunnel, TunnelEntry, TunnelType,
+    Ai, ApiStatus, AutonomousSystem, Behavior, Client, Concentration, DeviceType, Infrastructure,
+    IpContext, Location, Risk, Service, TagMetadata, TagMetrics, Tunnel, TunnelEntry, TunnelType,
 };
 use crate::monocle::Assessment;
 
@@ -252,6 +252,7 @@ pub fn arb_ip_context() -> impl Strategy<Value = IpContext> {
                     ai,
                     autonomous_system,
                     client,
+                    freshness: None,
                     infrastructure,
                     ip,
                     location,
@@ -294,6 +295,517 @@ pub fn arb_vpn_context() -> impl Strategy<Value = IpContext> {
         })
 }
 
+/// Strategy for the string-encoded booleans used in tag metadata.
+fn arb_flag() -> impl Strategy<Value = String> {
+    prop_oneof![Just("true".to_string()), Just("false".to_string())]
+}
+
+/// Strategy for generating arbitrary TagMetrics values.
+///
+/// Metrics are string-encoded numbers, as returned by the API.
+pub fn arb_tag_metrics() -> impl Strategy<Value = TagMetrics> {
+    (
+        proptest::option::of("[0-9]{1,3}\\.[0-9]{1,14}"),
+        proptest::option::of("0\\.[0-9]{1,17}"),
+        proptest::option::of("[0-9]{1,6}"),
+        proptest::option::of("[0-9]{1,3}"),
+        proptest::option::of("[0-9]{1,9}"),
+        proptest::option::of("[0-9]{1,6}"),
+    )
+        .prop_map(
+            |(
+                average_device_count,
+                churn_rate,
+                distinct_asns,
+                distinct_countries,
+                distinct_ips,
+                distinct_isps,
+            )| TagMetrics {
+                average_device_count,
+                churn_rate,
+                distinct_asns,
+                distinct_countries,
+                distinct_ips,
+                distinct_isps,
+            },
+        )
+}
+
+/// Strategy for generating arbitrary TagMetadata values.
+pub fn arb_tag_metadata() -> impl Strategy<Value = TagMetadata> {
+    let flags = (
+        proptest::option::of(arb_flag()),
+        proptest::option::of(arb_flag()),
+        proptest::option::of(arb_flag()),
+        proptest::option::of(arb_flag()),
+        proptest::option::of(arb_flag()),
+        proptest::option::of(arb_flag()),
+        proptest::option::of(arb_flag()),
+        proptest::option::of(arb_flag()),
+        proptest::option::of(arb_flag()),
+        proptest::option::of(arb_flag()),
+    );
+    let details = (
+        proptest::option::of(proptest::collection::vec("[A-Z_]{3,20}", 0..5)),
+        proptest::option::of("[A-Za-z0-9 .,]{0,80}"),
+        proptest::option::of(arb_tag_metrics()),
+        proptest::option::of("[A-Za-z0-9 ]{2,30}"),
+        proptest::option::of(proptest::collection::vec("[A-Z]{3,10}", 0..4)),
+        proptest::option::of(proptest::collection::vec("[A-Z]{3,10}", 0..4)),
+        proptest::option::of("[A-Z0-9_]{3,30}"),
+        proptest::option::of(proptest::collection::vec("[A-Z]{3,8}", 0..4)),
+        proptest::option::of("https://[a-z]{3,12}\\.[a-z]{2,3}"),
+    );
+    (flags, details).prop_map(
+        |(
+            (
+                allows_crypto,
+                allows_free_access,
+                allows_multihop,
+                allows_torrents,
+                allows_white_label,
+                is_anonymous,
+                is_callback_proxy,
+                is_enterprise,
+                is_inactive,
+                is_no_log,
+            ),
+            (
+                categories,
+                description,
+                metrics,
+                name,
+                platforms,
+                protocols,
+                tag,
+                targeting_types,
+                website,
+            ),
+        )| TagMetadata {
+            allows_crypto,
+            allows_free_access,
+            allows_multihop,
+            allows_torrents,
+            allows_white_label,
+            categories,
+            description,
+            is_anonymous,
+            is_callback_proxy,
+            is_enterprise,
+            is_inactive,
+            is_no_log,
+            metrics,
+            name,
+            platforms,
+            protocols,
+            tag,
+            targeting_types,
+            website,
+        },
+    )
+}
+
+/// Strategy for generating arbitrary ApiStatus values.
+pub fn arb_api_status() -> impl Strategy<Value = ApiStatus> {
+    (
+        proptest::option::of(proptest::bool::ANY),
+        proptest::option::of(0u64..10_000_000),
+        proptest::option::of("[a-z]{3,12}"),
+    )
+        .prop_map(|(active, queries_remaining, service_tier)| ApiStatus {
+            active,
+            queries_remaining,
+            service_tier,
+        })
+}
+
+/// Country codes used by the realistic strategies.
+const COUNTRIES: &[&str] = &["US", "DE", "NL", "GB", "FR", "JP", "BR", "IN", "CA", "RU"];
+
+fn arb_country() -> impl Strategy<Value = String> {
+    proptest::sample::select(COUNTRIES).prop_map(str::to_string)
+}
+
+/// Strategy for a public-looking IPv4 address string.
+fn arb_public_ipv4() -> impl Strategy<Value = String> {
+    (1u8..=223, any::<u8>(), any::<u8>(), 1u8..=254)
+        .prop_filter("private or loopback first octet", |(a, _, _, _)| {
+            *a != 10 && *a != 127
+        })
+        .prop_map(|(a, b, c, d)| format!("{}.{}.{}.{}", a, b, c, d))
+}
+
+/// Strategy for a location in `country`, with a city and integer
+/// coordinates.
+fn arb_location_in(country: String) -> impl Strategy<Value = Location> {
+    ("[A-Z][a-z]{3,12}", -60i32..70, -180i32..180).prop_map(move |(city, lat, lon)| Location {
+        country: Some(country.clone()),
+        state: None,
+        city: Some(city),
+        latitude: Some(lat as f64),
+        longitude: Some(lon as f64),
+    })
+}
+
+/// Strategy for an autonomous system whose organization also names the
+/// context's `organization`.
+fn arb_network() -> impl Strategy<Value = (AutonomousSystem, String)> {
+    (1u32..400_000, "[A-Z][a-z]{3,12}( [A-Z][a-z]{2,8})?").prop_map(|(number, org)| {
+        (
+            AutonomousSystem {
+                number: Some(number),
+                organization: Some(org.clone()),
+            },
+            org,
+        )
+    })
+}
+
+/// Fields shared by every realistic context.
+fn arb_base(infrastructure: Infrastructure) -> impl Strategy<Value = IpContext> {
+    (arb_public_ipv4(), arb_network(), arb_country())
+        .prop_flat_map(|(ip, network, country)| (Just(ip), Just(network), arb_location_in(country)))
+        .prop_map(
+            move |(ip, (autonomous_system, organization), location)| IpContext {
+                ip: Some(ip),
+                infrastructure: Some(infrastructure.clone()),
+                organization: Some(organization),
+                autonomous_system: Some(autonomous_system),
+                location: Some(location),
+                ..Default::default()
+            },
+        )
+}
+
+fn arb_household_client() -> impl Strategy<Value = Client> {
+    (
+        1u64..8,
+        proptest::collection::vec(arb_known_device_type(), 1..3),
+        proptest::bool::weighted(0.1),
+    )
+        .prop_map(|(count, types, file_sharing)| Client {
+            count: Some(count),
+            countries: Some(1),
+            types: Some(types),
+            behaviors: file_sharing.then(|| vec![Behavior::FileSharing]),
+            ..Default::default()
+        })
+}
+
+fn arb_known_device_type() -> impl Strategy<Value = DeviceType> {
+    prop_oneof![Just(DeviceType::Mobile), Just(DeviceType::Desktop)]
+}
+
+fn arb_realistic_residential() -> impl Strategy<Value = IpContext> {
+    (
+        arb_base(Infrastructure::Residential),
+        arb_household_client(),
+    )
+        .prop_map(|(ctx, client)| IpContext {
+            client: Some(client),
+            ..ctx
+        })
+}
+
+fn arb_realistic_mobile() -> impl Strategy<Value = IpContext> {
+    (arb_base(Infrastructure::Mobile), 10u64..500).prop_map(|(ctx, count)| IpContext {
+        client: Some(Client {
+            count: Some(count),
+            countries: Some(1),
+            types: Some(vec![DeviceType::Mobile]),
+            ..Default::default()
+        }),
+        ..ctx
+    })
+}
+
+fn arb_realistic_datacenter() -> impl Strategy<Value = IpContext> {
+    (
+        arb_base(Infrastructure::Datacenter),
+        proptest::bool::weighted(0.2),
+    )
+        .prop_map(|(ctx, ssh)| IpContext {
+            services: ssh.then(|| vec![Service::Ssh]),
+            ..ctx
+        })
+}
+
+fn arb_realistic_vpn() -> impl Strategy<Value = IpContext> {
+    (
+        arb_base(Infrastructure::Datacenter),
+        "[A-Z]{3,10}_VPN",
+        proptest::collection::vec(
+            prop_oneof![
+                Just(Service::OpenVpn),
+                Just(Service::Wireguard),
+                Just(Service::Ipsec),
+            ],
+            1..3,
+        ),
+        10u64..1000,
+        1u32..50,
+    )
+        .prop_map(|(ctx, operator, services, count, countries)| IpContext {
+            tunnels: Some(vec![Tunnel {
+                tunnel_type: Some(TunnelType::Vpn),
+                operator: Some(operator),
+                anonymous: Some(true),
+                entries: None,
+            }]),
+            risks: Some(vec![Risk::Tunnel]),
+            services: Some(services),
+            client: Some(Client {
+                count: Some(count),
+                countries: Some(countries),
+                types: Some(vec![DeviceType::Desktop, DeviceType::Mobile]),
+                ..Default::default()
+            }),
+            ..ctx
+        })
+}
+
+fn arb_realistic_tor() -> impl Strategy<Value = IpContext> {
+    (
+        arb_base(Infrastructure::Datacenter),
+        100u64..2000,
+        20u32..150,
+    )
+        .prop_map(|(ctx, count, countries)| IpContext {
+            tunnels: Some(vec![Tunnel {
+                tunnel_type: Some(TunnelType::Tor),
+                operator: Some("TOR_PROXY".to_string()),
+                anonymous: Some(true),
+                entries: None,
+            }]),
+            risks: Some(vec![Risk::Tunnel]),
+            client: Some(Client {
+                count: Some(count),
+                countries: Some(countries),
+                behaviors: Some(vec![Behavior::TorProxyUser]),
+                ..Default::default()
+            }),
+            ..ctx
+        })
+}
+
+fn arb_realistic_residential_proxy() -> impl Strategy<Value = IpContext> {
+    (
+        arb_base(Infrastructure::Residential),
+        proptest::collection::btree_set("[A-Z]{3,10}_PROXY", 1..4),
+        5u64..80,
+        proptest::option::of((arb_country(), 50u32..100)),
+    )
+        .prop_map(|(mut ctx, proxies, count, concentration)| {
+            let home = ctx.location.as_ref().and_then(|l| l.country.clone());
+            let mut risks = vec![Risk::CallbackProxy];
+            let concentration = concentration.map(|(country, density)| {
+                if Some(&country) != home.as_ref() {
+                    risks.push(Risk::GeoMismatch);
+                }
+                Concentration {
+                    country: Some(country),
+                    density: Some(density as f64 / 100.0),
+                    ..Default::default()
+                }
+            });
+            ctx.client = Some(Client {
+                count: Some(count),
+                countries: Some(1),
+                proxies: Some(proxies.into_iter().collect()),
+                types: Some(vec![DeviceType::Desktop, DeviceType::Mobile]),
+                concentration,
+                ..Default::default()
+            });
+            ctx.risks = Some(risks);
+            ctx
+        })
+}
+
+/// Strategy for internally consistent contexts, for testing business logic
+/// rather than serde.
+///
+/// Unlike [`arb_ip_context`], the generated values respect the invariants
+/// real API responses do:
+///
+/// - every context has an IP, infrastructure, AS, organization and location
+/// - tunnels always come with the `TUNNEL` risk, and VPN and Tor exits are
+///   datacenter addresses
+/// - VPN operators end in `_VPN` and proxy networks in `_PROXY`
+/// - residential and mobile addresses never carry tunnels; residential
+///   proxies show up as `client.proxies` plus the `CALLBACK_PROXY` risk
+/// - `GEO_MISMATCH` appears only when the client concentration country
+///   differs from the location country
+/// - only known enum variants are used
+pub fn arb_realistic_context() -> impl Strategy<Value = IpContext> {
+    prop_oneof![
+        3 => arb_realistic_residential(),
+        1 => arb_realistic_mobile(),
+        2 => arb_realistic_datacenter(),
+        2 => arb_realistic_vpn(),
+        1 => arb_realistic_tor(),
+        1 => arb_realistic_residential_proxy(),
+    ]
+}
+
+/// Strategy for enum strings no current variant uses (`NEW_...`).
+///
+/// The prefix guarantees the value can never collide with a known variant,
+/// so it must always deserialize to `Other`.
+pub fn arb_novel_enum_string() -> impl Strategy<Value = String> {
+    "NEW_[A-Z_]{3,16}"
+}
+
+/// A valid context together with JSON for it that carries extra data a
+/// future API version might send.
+#[derive(Debug, Clone)]
+pub struct ForwardCompatCase {
+    /// The context the JSON must deserialize to.
+    pub expected: IpContext,
+    /// `expected` serialized, with unknown keys added to nested objects.
+    pub json: String,
+}
+
+/// Strategy for JSON values placed under unknown keys.
+fn arb_unknown_value() -> impl Strategy<Value = serde_json::Value> {
+    use serde_json::{json, Value};
+    prop_oneof![
+        Just(Value::Null),
+        proptest::bool::ANY.prop_map(Value::Bool),
+        proptest::num::i64::ANY.prop_map(|n| json!(n)),
+        "[A-Za-z0-9 _-]{0,20}".prop_map(Value::String),
+        ("[a-z]{1,8}", proptest::collection::vec(0u32..1000, 0..3))
+            .prop_map(|(key, items)| json!({ key: items })),
+    ]
+}
+
+/// Strategy exercising the crate's forward-compatibility guarantees.
+///
+/// Starts from [`arb_ip_context`], replaces some enum values with novel
+/// strings, then serializes it and injects unknown keys (`unknown...`) into
+/// randomly chosen objects at any depth. The injected keys also include
+/// `as_of`, `last_seen` and `freshness`, which name the caller-set
+/// [`Freshness`](crate::context::Freshness) but are never parsed. Deserializing
+/// [`ForwardCompatCase::json`] must succeed and yield
+/// [`ForwardCompatCase::expected`].
+///
+/// ```rust,ignore
+/// proptest! {
+///     #[test]
+///     fn tolerates_future_api(case in arb_forward_compat_case()) {
+///         let parsed: IpContext = serde_json::from_str(&case.json).unwrap();
+///         prop_assert_eq!(parsed, case.expected);
+///     }
+/// }
+/// ```
+pub fn arb_forward_compat_case() -> impl Strategy<Value = ForwardCompatCase> {
+    let novel = (
+        proptest::option::of(arb_novel_enum_string()),
+        proptest::option::of(arb_novel_enum_string()),
+        proptest::option::of(arb_novel_enum_string()),
+        proptest::option::of(arb_novel_enum_string()),
+        proptest::option::of(arb_novel_enum_string()),
+        proptest::option::of(arb_novel_enum_string()),
+    );
+    let extra = proptest::collection::vec(
+        (
+            proptest::num::usize::ANY,
+            prop_oneof![
+                4 => "unknown[A-Z][A-Za-z]{2,10}",
+                1 => prop_oneof![Just("as_of"), Just("last_seen"), Just("freshness")]
+                    .prop_map(String::from),
+            ],
+            arb_unknown_value(),
+        ),
+        0..8,
+    );
+    (arb_ip_context(), novel, extra).prop_map(
+        |(mut context, (infra, risk, service, tunnel_type, behavior, device), extra)| {
+            if let Some(s) = infra {
+                context.infrastructure = Some(Infrastructure::Other(s));
+            }
+            if let Some(s) = risk {
+                context
+                    .risks
+                    .get_or_insert_with(Vec::new)
+                    .push(Risk::Other(s));
+            }
+            if let Some(s) = service {
+                context
+                    .services
+                    .get_or_insert_with(Vec::new)
+                    .push(Service::Other(s));
+            }
+            if let Some(s) = tunnel_type {
+                if let Some(tunnel) = context.tunnels.as_mut().and_then(|t| t.first_mut()) {
+                    tunnel.tunnel_type = Some(TunnelType::Other(s));
+                }
+            }
+            if let Some(client) = context.client.as_mut() {
+                if let Some(s) = behavior {
+                    client
+                        .behaviors
+                        .get_or_insert_with(Vec::new)
+                        .push(Behavior::Other(s));
+                }
+                if let Some(s) = device {
+                    client
+                        .types
+                        .get_or_insert_with(Vec::new)
+                        .push(DeviceType::Other(s));
+                }
+            }
+
+            let mut value = serde_json::to_value(&context).expect("IpContext should serialize");
+            for (selector, key, unknown) in extra {
+                let mut n = selector % count_objects(&value);
+                insert_into_nth_object(&mut value, &mut n, key, unknown);
+            }
+
+            ForwardCompatCase {
+                expected: context,
+                json: value.to_string(),
+            }
+        },
+    )
+}
+
+/// Count the JSON objects in `value`, including nested ones.
+fn count_objects(value: &serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::Object(map) => 1 + map.values().map(count_objects).sum::<usize>(),
+        serde_json::Value::Array(items) => items.iter().map(count_objects).sum(),
+        _ => 0,
+    }
+}
+
+/// Insert `key` into the `n`th object of `value` in pre-order, unless that
+/// object already has it. Returns `true` once the object has been visited.
+fn insert_into_nth_object(
+    value: &mut serde_json::Value,
+    n: &mut usize,
+    key: String,
+    unknown: serde_json::Value,
+) -> bool {
+    let children: Vec<&mut serde_json::Value> = match value {
+        serde_json::Value::Object(map) => {
+            if *n == 0 {
+                map.entry(key).or_insert(unknown);
+                return true;
+            }
+            *n -= 1;
+            map.values_mut().collect()
+        }
+        serde_json::Value::Array(items) => items.iter_mut().collect(),
+        _ => return false,
+    };
+    for child in children {
+        if insert_into_nth_object(child, n, key.clone(), unknown.clone()) {
+            return true;
+        }
+    }
+    false
+}
+
 // =============================================================================
 // Monocle API Strategies
 // =============================================================================
@@ -437,6 +949,71 @@ mod tests {
             assert_eq!(display, serialized);
         }
 
+        #[test]
+        fn unknown_fields_and_variants_are_tolerated(case in arb_forward_compat_case()) {
+            let parsed: IpContext = serde_json::from_str(&case.json).unwrap();
+            assert_eq!(parsed, case.expected);
+        }
+
+        #[test]
+        fn novel_enum_strings_are_other(s in arb_novel_enum_string()) {
+            let risk: Risk = serde_json::from_str(&format!("\"{}\"", s)).unwrap();
+            assert_eq!(risk, Risk::Other(s));
+        }
+
+        #[test]
+        fn realistic_context_is_consistent(ctx in arb_realistic_context()) {
+            assert!(ctx.ip.is_some() && ctx.infrastructure.is_some());
+            let risks = ctx.risks.clone().unwrap_or_default();
+            let tunnels = ctx.tunnels.clone().unwrap_or_default();
+
+            assert_eq!(!tunnels.is_empty(), risks.contains(&Risk::Tunnel));
+            if !tunnels.is_empty() {
+                assert_eq!(ctx.infrastructure, Some(Infrastructure::Datacenter));
+            }
+            for tunnel in &tunnels {
+                if tunnel.tunnel_type == Some(TunnelType::Vpn) {
+                    assert!(tunnel.operator.as_ref().unwrap().ends_with("_VPN"));
+                }
+            }
+
+            let proxies = ctx.client.as_ref().and_then(|c| c.proxies.clone()).unwrap_or_default();
+            assert_eq!(!proxies.is_empty(), risks.contains(&Risk::CallbackProxy));
+
+            let concentration_country = ctx
+                .client
+                .as_ref()
+                .and_then(|c| c.concentration.as_ref())
+                .and_then(|c| c.country.clone());
+            let mismatch = concentration_country.is_some()
+                && concentration_country != ctx.location.as_ref().unwrap().country;
+            assert_eq!(mismatch, risks.contains(&Risk::GeoMismatch));
+
+            let json = serde_json::to_string(&ctx).unwrap();
+            assert_eq!(serde_json::from_str::<IpContext>(&json).unwrap(), ctx);
+        }
+
+        #[test]
+        fn tag_metrics_roundtrip(metrics in arb_tag_metrics()) {
+            let json = serde_json::to_string(&metrics).unwrap();
+            let parsed: TagMetrics = serde_json::from_str(&json).unwrap();
+            assert_eq!(metrics, parsed);
+        }
+
+        #[test]
+        fn tag_metadata_roundtrip(meta in arb_tag_metadata()) {
+            let json = serde_json::to_string(&meta).unwrap();
+            let parsed: TagMetadata = serde_json::from_str(&json).unwrap();
+            assert_eq!(meta, parsed);
+        }
+
+        #[test]
+        fn api_status_roundtrip(status in arb_api_status()) {
+            let json = serde_json::to_string(&status).unwrap();
+            let parsed: ApiStatus = serde_json::from_str(&json).unwrap();
+            assert_eq!(status, parsed);
+        }
+
         // Monocle API tests
         #[test]
         fn assessment_roundtrip(assessment in arb_assessment()) {
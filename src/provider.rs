@@ -0,0 +1,376 @@
+//! One interface over Spur and other IP intelligence providers.
+//!
+//! Applications that evaluate another provider next to Spur, or keep one
+//! as a fallback, want a single lookup interface so the rest of the code
+//! does not care where an answer came from. [`IpIntelProvider`] is that
+//! interface: a lookup returns the subset of [`IpContext`] that
+//! [`common_subset`] keeps, which other providers can map their data onto.
+//!
+//! This crate has no HTTP client. [`CacheProvider`] serves Spur data from
+//! any [`ContextCache`], such as one filled from a feed or kept up to date
+//! by the service's own client, and is the reference implementation.
+//! [`Failover`] and [`Split`] combine providers for fail-over and A/B
+//! comparisons.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::net::IpAddr;
+//! use spur::cache::{ContextCache, LruContextCache};
+//! use spur::provider::{CacheProvider, Failover, IpIntelProvider, ProviderError};
+//! use spur::test_utils::fixtures;
+//! use spur::IpContext;
+//!
+//! struct Unreachable;
+//!
+//! impl IpIntelProvider for Unreachable {
+//!     fn name(&self) -> &str {
+//!         "other"
+//!     }
+//!
+//!     fn lookup(&self, _ip: IpAddr) -> Result<IpContext, ProviderError> {
+//!         Err(ProviderError::Unavailable("connection refused".to_string()))
+//!     }
+//! }
+//!
+//! let cache = LruContextCache::new(100);
+//! let ip: IpAddr = "89.39.106.191".parse().unwrap();
+//! cache.insert(ip, fixtures::datacenter_ip());
+//!
+//! let provider = Failover::new(Unreachable, CacheProvider::new("spur", cache));
+//! assert!(provider.lookup(ip).unwrap().infrastructure.is_some());
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::net::IpAddr;
+
+use crate::cache::ContextCache;
+use crate::context::IpContext;
+use crate::ip_utils::canonical_ip;
+use crate::rng::SplitMix64;
+
+/// Why a provider could not answer a lookup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderError {
+    /// The provider has no data for the address.
+    NotFound,
+    /// The provider refused the lookup because a quota or rate limit was
+    /// reached.
+    RateLimited,
+    /// The provider could not be reached or returned an unusable answer.
+    Unavailable(String),
+}
+
+impl ProviderError {
+    /// Returns `true` if another provider might answer instead: the
+    /// provider was rate limited or unavailable, rather than having no
+    /// data.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, Self::NotFound)
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => f.write_str("no data for address"),
+            Self::RateLimited => f.write_str("rate limited"),
+            Self::Unavailable(reason) => write!(f, "provider unavailable: {}", reason),
+        }
+    }
+}
+
+impl Error for ProviderError {}
+
+/// A source of IP intelligence.
+///
+/// Lookups return the fields [`common_subset`] keeps; implementations for
+/// providers other than Spur map their data onto those fields, using the
+/// Spur names for infrastructure, tunnel types and risks where they can.
+pub trait IpIntelProvider {
+    /// Returns a short, stable name for metrics and logs, such as `spur`.
+    fn name(&self) -> &str;
+
+    /// Look up `ip`.
+    fn lookup(&self, ip: IpAddr) -> Result<IpContext, ProviderError>;
+}
+
+impl<P: IpIntelProvider + ?Sized> IpIntelProvider for &P {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<IpContext, ProviderError> {
+        (**self).lookup(ip)
+    }
+}
+
+impl<P: IpIntelProvider + ?Sized> IpIntelProvider for Box<P> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<IpContext, ProviderError> {
+        (**self).lookup(ip)
+    }
+}
+
+/// Returns the part of `context` every provider is expected to fill in.
+///
+/// Kept are the address, infrastructure, autonomous system, organization,
+/// location, tunnels and risks. Client behaviour, services, AI activity and
+/// other Spur-specific fields are dropped, so answers from different
+/// providers compare field by field.
+pub fn common_subset(context: IpContext) -> IpContext {
+    IpContext {
+        ip: context.ip,
+        infrastructure: context.infrastructure,
+        autonomous_system: context.autonomous_system,
+        organization: context.organization,
+        location: context.location,
+        tunnels: context.tunnels,
+        risks: context.risks,
+        ..Default::default()
+    }
+}
+
+/// Serves contexts held in a [`ContextCache`].
+///
+/// Lookups return the [`common_subset`] of the cached context, or
+/// [`ProviderError::NotFound`] on a miss.
+#[derive(Debug)]
+pub struct CacheProvider<C> {
+    name: String,
+    cache: C,
+}
+
+impl<C: ContextCache> CacheProvider<C> {
+    /// Serve the contexts in `cache` under `name`.
+    pub fn new(name: &str, cache: C) -> Self {
+        Self {
+            name: name.to_string(),
+            cache,
+        }
+    }
+
+    /// Returns the cache, for filling it.
+    pub fn cache(&self) -> &C {
+        &self.cache
+    }
+}
+
+impl<C: ContextCache> IpIntelProvider for CacheProvider<C> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<IpContext, ProviderError> {
+        self.cache
+            .get(&ip)
+            .map(common_subset)
+            .ok_or(ProviderError::NotFound)
+    }
+}
+
+/// Asks a primary provider and falls back to a secondary one.
+///
+/// The secondary is only asked when the primary's error
+/// [`is_retryable`](ProviderError::is_retryable); an address the primary
+/// has no data for is reported as not found.
+#[derive(Debug)]
+pub struct Failover<P, S> {
+    primary: P,
+    secondary: S,
+}
+
+impl<P: IpIntelProvider, S: IpIntelProvider> Failover<P, S> {
+    /// Combine `primary` with the `secondary` fallback.
+    pub fn new(primary: P, secondary: S) -> Self {
+        Self { primary, secondary }
+    }
+}
+
+impl<P: IpIntelProvider, S: IpIntelProvider> IpIntelProvider for Failover<P, S> {
+    fn name(&self) -> &str {
+        self.primary.name()
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<IpContext, ProviderError> {
+        match self.primary.lookup(ip) {
+            Err(err) if err.is_retryable() => self.secondary.lookup(ip),
+            result => result,
+        }
+    }
+}
+
+/// Sends a fixed share of addresses to a second provider, for A/B
+/// comparisons.
+///
+/// Which provider an address goes to depends only on the address and the
+/// seed, so repeated lookups of one address always hit the same provider.
+/// [`provider_for`](Self::provider_for) tells which one that is, for
+/// tagging metrics.
+#[derive(Debug)]
+pub struct Split<A, B> {
+    a: A,
+    b: B,
+    share_b: f64,
+    seed: u64,
+}
+
+impl<A: IpIntelProvider, B: IpIntelProvider> Split<A, B> {
+    /// Send about `share_b` (between 0 and 1) of addresses to `b` and the
+    /// rest to `a`.
+    pub fn new(a: A, b: B, share_b: f64) -> Self {
+        Self {
+            a,
+            b,
+            share_b,
+            seed: 0,
+        }
+    }
+
+    /// Set the seed that assigns addresses, to reshuffle the groups.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Returns the name of the provider `ip` is sent to.
+    pub fn provider_for(&self, ip: IpAddr) -> &str {
+        if self.goes_to_b(ip) {
+            self.b.name()
+        } else {
+            self.a.name()
+        }
+    }
+
+    fn goes_to_b(&self, ip: IpAddr) -> bool {
+        let key = match canonical_ip(ip) {
+            IpAddr::V4(v4) => u64::from(u32::from(v4)),
+            IpAddr::V6(v6) => {
+                let bits = u128::from(v6);
+                (bits >> 64) as u64 ^ bits as u64
+            }
+        };
+        SplitMix64::new(self.seed ^ key).chance(self.share_b)
+    }
+}
+
+impl<A: IpIntelProvider, B: IpIntelProvider> IpIntelProvider for Split<A, B> {
+    fn name(&self) -> &str {
+        self.a.name()
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<IpContext, ProviderError> {
+        if self.goes_to_b(ip) {
+            self.b.lookup(ip)
+        } else {
+            self.a.lookup(ip)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::cache::LruContextCache;
+    use crate::test_utils::fixtures;
+
+    struct Fixed {
+        name: &'static str,
+        result: Result<IpContext, ProviderError>,
+        calls: Cell<usize>,
+    }
+
+    impl Fixed {
+        fn new(name: &'static str, result: Result<IpContext, ProviderError>) -> Self {
+            Self {
+                name,
+                result,
+                calls: Cell::new(0),
+            }
+        }
+    }
+
+    impl IpIntelProvider for Fixed {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn lookup(&self, _ip: IpAddr) -> Result<IpContext, ProviderError> {
+            self.calls.set(self.calls.get() + 1);
+            self.result.clone()
+        }
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_cache_provider() {
+        let provider = CacheProvider::new("spur", LruContextCache::new(10));
+        let full = fixtures::vpn_ip();
+        provider.cache().insert(ip("1.2.3.4"), full.clone());
+
+        let context = provider.lookup(ip("::ffff:1.2.3.4")).unwrap();
+        assert_eq!(context, common_subset(full));
+        assert!(context.client.is_none());
+        assert!(context.tunnels.is_some());
+        assert_eq!(provider.lookup(ip("5.6.7.8")), Err(ProviderError::NotFound));
+        assert_eq!(provider.name(), "spur");
+    }
+
+    #[test]
+    fn test_failover() {
+        let down = Fixed::new("a", Err(ProviderError::RateLimited));
+        let up = Fixed::new("b", Ok(fixtures::residential_ip()));
+        let failover = Failover::new(&down, &up);
+        assert!(failover.lookup(ip("1.2.3.4")).is_ok());
+        assert_eq!((down.calls.get(), up.calls.get()), (1, 1));
+
+        let missing = Fixed::new("a", Err(ProviderError::NotFound));
+        let failover = Failover::new(&missing, &up);
+        assert_eq!(failover.lookup(ip("1.2.3.4")), Err(ProviderError::NotFound));
+        assert_eq!(up.calls.get(), 1);
+        assert_eq!(failover.name(), "a");
+    }
+
+    #[test]
+    fn test_split() {
+        let a = Fixed::new("a", Err(ProviderError::NotFound));
+        let b = Fixed::new("b", Ok(IpContext::default()));
+        let split = Split::new(&a, &b, 0.25).seed(7);
+
+        let ips: Vec<IpAddr> = (0..1000u32)
+            .map(|n| IpAddr::V4(n.wrapping_mul(2_654_435_761).into()))
+            .collect();
+        let to_b = ips
+            .iter()
+            .filter(|ip| split.provider_for(**ip) == "b")
+            .count();
+        assert!((200..300).contains(&to_b), "{}", to_b);
+
+        for ip in ips.iter().take(50) {
+            let routed = split.provider_for(*ip);
+            assert_eq!(split.lookup(*ip).is_ok(), routed == "b");
+            assert_eq!(split.provider_for(*ip), routed);
+        }
+        assert_eq!(Split::new(&a, &b, 0.0).provider_for(ips[0]), "a");
+        assert_eq!(Split::new(&a, &b, 1.0).provider_for(ips[0]), "b");
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            ProviderError::Unavailable("timeout".to_string()).to_string(),
+            "provider unavailable: timeout"
+        );
+        assert!(!ProviderError::NotFound.is_retryable());
+        assert!(ProviderError::RateLimited.is_retryable());
+    }
+}
@@ -0,0 +1,391 @@
+//! Sets of address ranges for containment queries.
+//!
+//! Firewall-like consumers want to ask whether an address falls in any of
+//! a set of anonymization ranges, often after adding their own ranges or
+//! carving out an allowlist. [`IpRangeSet`] keeps IPv4 and IPv6 ranges
+//! merged and sorted, answers [`contains`](IpRangeSet::contains) with a
+//! binary search, supports union, intersection and subtraction, and turns
+//! the result back into a minimal list of CIDR blocks.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ipnet::IpNet;
+//! use spur::ranges::IpRangeSet;
+//!
+//! let vpn: IpRangeSet = ["89.39.104.0/22", "2001:db8::/32"]
+//!     .iter()
+//!     .map(|net| net.parse::<IpNet>().unwrap())
+//!     .collect();
+//! let allowlist: IpRangeSet = ["89.39.106.0/24".parse::<IpNet>().unwrap()]
+//!     .into_iter()
+//!     .collect();
+//!
+//! let blocked = vpn.subtract(&allowlist);
+//! assert!(blocked.contains("89.39.105.1".parse().unwrap()));
+//! assert!(!blocked.contains("89.39.106.191".parse().unwrap()));
+//! assert_eq!(blocked.to_cidrs().len(), 3);
+//! ```
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+
+use crate::context::IpContext;
+use crate::ip_utils::{self, canonical_ip};
+
+/// A set of IPv4 and IPv6 addresses, stored as merged ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IpRangeSet {
+    v4: Ranges,
+    v6: Ranges,
+}
+
+impl IpRangeSet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect the addresses of contexts matching `predicate`, together
+    /// with the entry addresses of their tunnels.
+    ///
+    /// ```rust
+    /// use spur::ranges::IpRangeSet;
+    /// use spur::test_utils::fixtures;
+    ///
+    /// let contexts = [fixtures::vpn_ip(), fixtures::residential_ip()];
+    /// let set = IpRangeSet::from_contexts(&contexts, |c| c.tunnels.is_some());
+    ///
+    /// assert!(set.contains(fixtures::vpn_ip().ip.unwrap().parse().unwrap()));
+    /// ```
+    pub fn from_contexts<'a, I, F>(contexts: I, predicate: F) -> Self
+    where
+        I: IntoIterator<Item = &'a IpContext>,
+        F: Fn(&IpContext) -> bool,
+    {
+        let mut set = Self::new();
+        for context in contexts.into_iter().filter(|c| predicate(c)) {
+            let entries = context
+                .tunnels
+                .iter()
+                .flatten()
+                .flat_map(|t| t.entries.iter().flatten())
+                .filter_map(|e| e.ip.as_deref());
+            for ip in context.ip.as_deref().into_iter().chain(entries) {
+                if let Some(ip) = ip_utils::parse(ip) {
+                    set.insert(ip);
+                }
+            }
+        }
+        set
+    }
+
+    /// Add a single address.
+    pub fn insert(&mut self, ip: IpAddr) {
+        match canonical_ip(ip) {
+            IpAddr::V4(v4) => self.v4.insert(u32::from(v4).into(), u32::from(v4).into()),
+            IpAddr::V6(v6) => self.v6.insert(v6.into(), v6.into()),
+        }
+    }
+
+    /// Add every address in a network.
+    pub fn insert_net(&mut self, net: IpNet) {
+        match net {
+            IpNet::V4(net) => self.v4.insert(
+                u32::from(net.network()).into(),
+                u32::from(net.broadcast()).into(),
+            ),
+            IpNet::V6(net) => self.v6.insert(net.network().into(), net.broadcast().into()),
+        }
+    }
+
+    /// Returns `true` if `ip` is in the set.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match canonical_ip(ip) {
+            IpAddr::V4(v4) => self.v4.contains(u32::from(v4).into()),
+            IpAddr::V6(v6) => self.v6.contains(v6.into()),
+        }
+    }
+
+    /// Returns `true` if the set has no addresses.
+    pub fn is_empty(&self) -> bool {
+        self.v4.0.is_empty() && self.v6.0.is_empty()
+    }
+
+    /// Returns the addresses in either set.
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            v4: self.v4.union(&other.v4),
+            v6: self.v6.union(&other.v6),
+        }
+    }
+
+    /// Returns the addresses in both sets.
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self {
+            v4: self.v4.intersect(&other.v4),
+            v6: self.v6.intersect(&other.v6),
+        }
+    }
+
+    /// Returns the addresses in this set but not in `other`.
+    pub fn subtract(&self, other: &Self) -> Self {
+        Self {
+            v4: self.v4.subtract(&other.v4),
+            v6: self.v6.subtract(&other.v6),
+        }
+    }
+
+    /// Returns the smallest list of CIDR blocks covering exactly this set,
+    /// IPv4 first, each family in address order.
+    pub fn to_cidrs(&self) -> Vec<IpNet> {
+        let v4 = self.v4.cidrs(32).into_iter().map(|(start, prefix)| {
+            let net = Ipv4Net::new(Ipv4Addr::from(start as u32), prefix);
+            IpNet::V4(net.expect("prefix is at most 32"))
+        });
+        let v6 = self.v6.cidrs(128).into_iter().map(|(start, prefix)| {
+            let net = Ipv6Net::new(Ipv6Addr::from(start), prefix);
+            IpNet::V6(net.expect("prefix is at most 128"))
+        });
+        v4.chain(v6).collect()
+    }
+}
+
+impl FromIterator<IpNet> for IpRangeSet {
+    fn from_iter<I: IntoIterator<Item = IpNet>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<IpNet> for IpRangeSet {
+    fn extend<I: IntoIterator<Item = IpNet>>(&mut self, iter: I) {
+        for net in iter {
+            self.insert_net(net);
+        }
+    }
+}
+
+impl FromIterator<IpAddr> for IpRangeSet {
+    fn from_iter<I: IntoIterator<Item = IpAddr>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<IpAddr> for IpRangeSet {
+    fn extend<I: IntoIterator<Item = IpAddr>>(&mut self, iter: I) {
+        for ip in iter {
+            self.insert(ip);
+        }
+    }
+}
+
+/// Sorted, non-overlapping, non-adjacent inclusive ranges of one address
+/// family.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+struct Ranges(Vec<(u128, u128)>);
+
+impl Ranges {
+    fn insert(&mut self, start: u128, end: u128) {
+        // Ranges overlapping or touching the new one are folded into it.
+        let first = self
+            .0
+            .partition_point(|&(_, e)| e.saturating_add(1) < start);
+        let mut last = first;
+        let (mut start, mut end) = (start, end);
+        while last < self.0.len() && self.0[last].0 <= end.saturating_add(1) {
+            start = start.min(self.0[last].0);
+            end = end.max(self.0[last].1);
+            last += 1;
+        }
+        self.0.splice(first..last, [(start, end)]);
+    }
+
+    fn contains(&self, value: u128) -> bool {
+        let index = self.0.partition_point(|&(_, end)| end < value);
+        self.0.get(index).is_some_and(|&(start, _)| start <= value)
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        for &(start, end) in &other.0 {
+            union.insert(start, end);
+        }
+        union
+    }
+
+    fn intersect(&self, other: &Self) -> Self {
+        let (mut i, mut j) = (0, 0);
+        let mut out = Vec::new();
+        while i < self.0.len() && j < other.0.len() {
+            let (a, b) = (self.0[i], other.0[j]);
+            let start = a.0.max(b.0);
+            let end = a.1.min(b.1);
+            if start <= end {
+                out.push((start, end));
+            }
+            if a.1 < b.1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self(out)
+    }
+
+    fn subtract(&self, other: &Self) -> Self {
+        let mut out = Vec::new();
+        let mut j = 0;
+        for &(start, end) in &self.0 {
+            while j < other.0.len() && other.0[j].1 < start {
+                j += 1;
+            }
+            // `from` is the start of the part not yet cut or kept.
+            let mut from = Some(start);
+            for &(cut_start, cut_end) in other.0[j..].iter().take_while(|c| c.0 <= end) {
+                let Some(f) = from else { break };
+                if cut_start > f {
+                    out.push((f, cut_start - 1));
+                }
+                from = cut_end.checked_add(1).filter(|&next| next <= end);
+            }
+            if let Some(f) = from {
+                out.push((f, end));
+            }
+        }
+        Self(out)
+    }
+
+    /// Split into `(network, prefix length)` CIDR blocks for an address
+    /// width of `bits`.
+    fn cidrs(&self, bits: u8) -> Vec<(u128, u8)> {
+        // Offset of the last address in a block of 2^size addresses.
+        let span = |size: u32| {
+            if size >= 128 {
+                u128::MAX
+            } else {
+                (1u128 << size) - 1
+            }
+        };
+        let mut out = Vec::new();
+        for &(start, end) in &self.0 {
+            let mut start = start;
+            loop {
+                // Largest block aligned at `start` that does not pass `end`.
+                let mut size = start.trailing_zeros().min(u32::from(bits));
+                while end - start < span(size) {
+                    size -= 1;
+                }
+                out.push((start, bits - size as u8));
+                let last = start + span(size);
+                if last >= end {
+                    break;
+                }
+                start = last + 1;
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(nets: &[&str]) -> IpRangeSet {
+        nets.iter()
+            .map(|net| net.parse::<IpNet>().unwrap())
+            .collect()
+    }
+
+    fn cidrs(set: &IpRangeSet) -> Vec<String> {
+        set.to_cidrs().iter().map(ToString::to_string).collect()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_merge_and_contains() {
+        let set = set(&[
+            "10.0.0.0/25",
+            "10.0.0.128/25",
+            "10.0.2.0/24",
+            "2001:db8::/127",
+        ]);
+        assert_eq!(
+            cidrs(&set),
+            ["10.0.0.0/24", "10.0.2.0/24", "2001:db8::/127"]
+        );
+        assert!(set.contains(ip("10.0.0.200")));
+        assert!(!set.contains(ip("10.0.1.1")));
+        assert!(set.contains(ip("2001:db8::1")));
+        assert!(!set.contains(ip("2001:db8::2")));
+        assert!(set.contains(ip("::ffff:10.0.2.9")));
+        assert!(IpRangeSet::new().is_empty());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&["10.0.0.0/24", "192.0.2.0/24"]);
+        let b = set(&["10.0.0.128/25", "198.51.100.0/24"]);
+
+        assert_eq!(
+            cidrs(&a.union(&b)),
+            ["10.0.0.0/24", "192.0.2.0/24", "198.51.100.0/24"]
+        );
+        assert_eq!(cidrs(&a.intersect(&b)), ["10.0.0.128/25"]);
+        assert_eq!(cidrs(&a.subtract(&b)), ["10.0.0.0/25", "192.0.2.0/24"]);
+
+        let holes = set(&["10.0.0.0/32", "10.0.0.5/32", "10.0.0.255/32"]);
+        assert_eq!(
+            cidrs(&set(&["10.0.0.0/24"]).subtract(&holes)),
+            [
+                "10.0.0.1/32",
+                "10.0.0.2/31",
+                "10.0.0.4/32",
+                "10.0.0.6/31",
+                "10.0.0.8/29",
+                "10.0.0.16/28",
+                "10.0.0.32/27",
+                "10.0.0.64/26",
+                "10.0.0.128/26",
+                "10.0.0.192/27",
+                "10.0.0.224/28",
+                "10.0.0.240/29",
+                "10.0.0.248/30",
+                "10.0.0.252/31",
+                "10.0.0.254/32",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_full_ranges() {
+        let all = set(&["0.0.0.0/0", "::/0"]);
+        assert_eq!(cidrs(&all), ["0.0.0.0/0", "::/0"]);
+        assert!(all.subtract(&all).is_empty());
+        let rest = all.subtract(&set(&["::/1"]));
+        assert_eq!(cidrs(&rest), ["0.0.0.0/0", "8000::/1"]);
+    }
+
+    #[test]
+    fn test_from_contexts() {
+        use crate::test_utils::IpContextBuilder;
+
+        let contexts = [
+            IpContextBuilder::new()
+                .ip("203.0.113.5")
+                .vpn_with_entry("PROTON_VPN", "198.51.100.20", "CH")
+                .build(),
+            IpContextBuilder::new().ip("192.0.2.1").build(),
+        ];
+        let set = IpRangeSet::from_contexts(&contexts, |c| c.tunnels.is_some());
+        assert_eq!(cidrs(&set), ["198.51.100.20/32", "203.0.113.5/32"]);
+    }
+}
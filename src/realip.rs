@@ -0,0 +1,346 @@
+//! Client IP extraction utilities.
+//!
+//! Before looking up an IP in Spur, a server behind load balancers or CDNs
+//! has to work out which address actually belongs to the client. The socket
+//! peer is usually the nearest proxy, and the forwarding headers are only
+//! trustworthy when they were written by a proxy you control.
+//!
+//! [`ClientIpResolver`] walks the forwarding chain from the socket peer
+//! outwards, skipping hops that belong to a trusted proxy network, and
+//! returns the first address that does not. Headers supplied by untrusted
+//! peers are ignored entirely.
+//!
+//! Only the one header your proxies write is read, chosen with
+//! [`ClientIpResolver::header`]. Proxies usually pass other forwarding
+//! headers through unchanged, so a client could set them to anything.
+//!
+//! | [`TrustedHeader`] | Header | Notes |
+//! |-------------------|--------|-------|
+//! | `XForwardedFor` (default) | `X-Forwarded-For` | Comma-separated list, optional ports |
+//! | `Forwarded` | `Forwarded` | RFC 7239 `for=` parameters |
+//! | `CfConnectingIp` | `CF-Connecting-IP` | Single address set by Cloudflare |
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::net::IpAddr;
+//! use spur::realip::ClientIpResolver;
+//!
+//! let resolver = ClientIpResolver::new()
+//!     .trust("10.0.0.0/8".parse().unwrap());
+//!
+//! let peer: IpAddr = "10.0.0.5".parse().unwrap();
+//! let headers = [("X-Forwarded-For", "89.39.106.191, 10.0.0.7")];
+//!
+//! let client = resolver.resolve(peer, headers);
+//! assert_eq!(client, "89.39.106.191".parse::<IpAddr>().unwrap());
+//! ```
+//!
+//! Headers are accepted as `(name, value)` string pairs so any HTTP stack can
+//! be used. For `http::HeaderMap`, something like
+//! `map.iter().filter_map(|(k, v)| Some((k.as_str(), v.to_str().ok()?)))`
+//! does the conversion.
+
+use std::net::{IpAddr, SocketAddr};
+
+pub use ipnet::IpNet;
+
+use crate::ip_utils;
+
+/// The forwarding header a [`ClientIpResolver`] reads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TrustedHeader {
+    /// `X-Forwarded-For`, appended to by each proxy.
+    #[default]
+    XForwardedFor,
+    /// RFC 7239 `Forwarded`, appended to by each proxy.
+    Forwarded,
+    /// `CF-Connecting-IP`, set by Cloudflare to the address that connected
+    /// to it. The trusted networks should be Cloudflare's only.
+    CfConnectingIp,
+}
+
+impl TrustedHeader {
+    /// Returns the header name, in its usual spelling.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::XForwardedFor => "X-Forwarded-For",
+            Self::Forwarded => "Forwarded",
+            Self::CfConnectingIp => "CF-Connecting-IP",
+        }
+    }
+}
+
+/// Resolves the effective client IP from a socket peer and forwarding headers.
+///
+/// With no trusted networks configured, the socket peer is always returned.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientIpResolver {
+    trusted: Vec<IpNet>,
+    header: TrustedHeader,
+}
+
+impl ClientIpResolver {
+    /// Create a resolver that trusts no proxies.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust a proxy network; hops inside it are skipped.
+    pub fn trust(mut self, net: IpNet) -> Self {
+        self.trusted.push(net);
+        self
+    }
+
+    /// Trust several proxy networks at once.
+    pub fn trust_all<I>(mut self, nets: I) -> Self
+    where
+        I: IntoIterator<Item = IpNet>,
+    {
+        self.trusted.extend(nets);
+        self
+    }
+
+    /// Set the header the trusted proxies write; every other forwarding
+    /// header is ignored. Defaults to [`TrustedHeader::XForwardedFor`].
+    pub fn header(mut self, header: TrustedHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Returns `true` if `ip` falls inside a trusted proxy network.
+    pub fn is_trusted(&self, ip: &IpAddr) -> bool {
+        let ip = ip_utils::canonical_ip(*ip);
+        self.trusted.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Resolve the client IP for a request.
+    ///
+    /// `headers` is every `(name, value)` pair on the request; names are
+    /// matched case-insensitively and repeated headers are joined in order.
+    /// Only the configured [`TrustedHeader`] is read.
+    pub fn resolve<I, K, V>(&self, peer: IpAddr, headers: I) -> IpAddr
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let peer = ip_utils::canonical_ip(peer);
+        if !self.is_trusted(&peer) {
+            return peer;
+        }
+
+        let mut chain = Vec::new();
+        for (name, value) in headers {
+            if !name.as_ref().eq_ignore_ascii_case(self.header.name()) {
+                continue;
+            }
+            let value = value.as_ref();
+            match self.header {
+                TrustedHeader::XForwardedFor => chain.extend(value.split(',').map(parse_node)),
+                TrustedHeader::Forwarded => chain.extend(parse_forwarded(value)),
+                // Cloudflare sets a single address; use the first copy.
+                TrustedHeader::CfConnectingIp => {
+                    return parse_node(value).unwrap_or(peer);
+                }
+            }
+        }
+        self.walk(peer, &chain)
+    }
+
+    /// Walk the hop chain right to left while the current hop is trusted.
+    fn walk(&self, peer: IpAddr, chain: &[Option<IpAddr>]) -> IpAddr {
+        let mut current = peer;
+        for hop in chain.iter().rev() {
+            if !self.is_trusted(&current) {
+                break;
+            }
+            match hop {
+                Some(ip) => current = *ip,
+                // An obfuscated or malformed hop ends what we can verify.
+                None => break,
+            }
+        }
+        current
+    }
+}
+
+/// Resolve the client IP using a plain trusted-proxy list.
+///
+/// Shorthand for a [`ClientIpResolver`] reading `X-Forwarded-For`.
+pub fn resolve<I, K, V>(peer: IpAddr, headers: I, trusted: &[IpNet]) -> IpAddr
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    ClientIpResolver::new()
+        .trust_all(trusted.iter().copied())
+        .resolve(peer, headers)
+}
+
+/// Parse the `for=` parameters of an RFC 7239 `Forwarded` header value.
+fn parse_forwarded(value: &str) -> Vec<Option<IpAddr>> {
+    value
+        .split(',')
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let (key, node) = pair.split_once('=')?;
+                key.trim()
+                    .eq_ignore_ascii_case("for")
+                    .then(|| parse_node(node))
+            })
+        })
+        .collect()
+}
+
+/// Parse a single node: a bare IP, `ip:port`, `[v6]` or `[v6]:port`,
+/// optionally wrapped in double quotes.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Some(rest) = node.strip_prefix('[') {
+        let (addr, _) = rest.split_once(']')?;
+        return addr.parse().ok().map(ip_utils::canonical_ip);
+    }
+    if let Ok(ip) = node.parse::<IpAddr>() {
+        return Some(ip_utils::canonical_ip(ip));
+    }
+    node.parse::<SocketAddr>()
+        .ok()
+        .map(|s| ip_utils::canonical_ip(s.ip()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn resolver() -> ClientIpResolver {
+        ClientIpResolver::new()
+            .trust("10.0.0.0/8".parse().unwrap())
+            .trust("fd00::/8".parse().unwrap())
+    }
+
+    #[test]
+    fn test_untrusted_peer_ignores_headers() {
+        let headers = [("X-Forwarded-For", "1.2.3.4")];
+        assert_eq!(
+            resolver().resolve(ip("203.0.113.9"), headers),
+            ip("203.0.113.9")
+        );
+    }
+
+    #[test]
+    fn test_x_forwarded_for_skips_trusted_hops() {
+        let headers = [("x-forwarded-for", "6.6.6.6, 89.39.106.191, 10.1.1.1")];
+        assert_eq!(
+            resolver().resolve(ip("10.0.0.5"), headers),
+            ip("89.39.106.191")
+        );
+    }
+
+    #[test]
+    fn test_repeated_headers_are_joined() {
+        let headers = [
+            ("X-Forwarded-For", "89.39.106.191"),
+            ("X-Forwarded-For", "10.1.1.1:8080"),
+        ];
+        assert_eq!(
+            resolver().resolve(ip("10.0.0.5"), headers),
+            ip("89.39.106.191")
+        );
+    }
+
+    #[test]
+    fn test_forwarded_header() {
+        let headers = [
+            ("X-Forwarded-For", "1.1.1.1"),
+            (
+                "Forwarded",
+                r#"for=192.0.2.60;proto=http, for="[2001:db8:cafe::17]:4711""#,
+            ),
+        ];
+        assert_eq!(
+            resolver()
+                .header(TrustedHeader::Forwarded)
+                .resolve(ip("10.0.0.5"), headers),
+            ip("2001:db8:cafe::17")
+        );
+    }
+
+    #[test]
+    fn test_client_supplied_headers_are_ignored() {
+        // The proxy appends to X-Forwarded-For and passes the client's own
+        // Forwarded and CF-Connecting-IP headers through untouched.
+        let headers = [
+            ("Forwarded", "for=1.2.3.4"),
+            ("CF-Connecting-IP", "5.6.7.8"),
+            ("X-Forwarded-For", "89.39.106.191"),
+        ];
+        assert_eq!(
+            resolver().resolve(ip("10.0.0.5"), headers),
+            ip("89.39.106.191")
+        );
+    }
+
+    #[test]
+    fn test_obfuscated_hop_stops_walk() {
+        let headers = [("Forwarded", "for=1.2.3.4, for=_hidden")];
+        assert_eq!(
+            resolver()
+                .header(TrustedHeader::Forwarded)
+                .resolve(ip("10.0.0.5"), headers),
+            ip("10.0.0.5")
+        );
+    }
+
+    #[test]
+    fn test_all_hops_trusted_returns_leftmost() {
+        let headers = [("X-Forwarded-For", "10.9.9.9, 10.1.1.1")];
+        assert_eq!(resolver().resolve(ip("10.0.0.5"), headers), ip("10.9.9.9"));
+    }
+
+    #[test]
+    fn test_cf_connecting_ip() {
+        let headers = [
+            ("CF-Connecting-IP", "89.39.106.191"),
+            ("X-Forwarded-For", "1.1.1.1"),
+        ];
+        let cloudflare = ClientIpResolver::new()
+            .trust("173.245.48.0/20".parse().unwrap())
+            .header(TrustedHeader::CfConnectingIp);
+        assert_eq!(resolver().resolve(ip("10.0.0.5"), headers), ip("1.1.1.1"));
+        assert_eq!(
+            cloudflare.resolve(ip("173.245.48.1"), headers),
+            ip("89.39.106.191")
+        );
+        // Not honored from a peer outside the trusted networks
+        assert_eq!(cloudflare.resolve(ip("10.0.0.5"), headers), ip("10.0.0.5"));
+        assert_eq!(
+            cloudflare.resolve(ip("173.245.48.1"), [("X-Forwarded-For", "1.1.1.1")]),
+            ip("173.245.48.1")
+        );
+    }
+
+    #[test]
+    fn test_ipv4_mapped_peer_is_unmapped() {
+        let headers = [("X-Forwarded-For", "89.39.106.191")];
+        assert_eq!(
+            resolver().resolve(ip("::ffff:10.0.0.5"), headers),
+            ip("89.39.106.191")
+        );
+    }
+
+    #[test]
+    fn test_resolve_shorthand() {
+        let trusted = ["10.0.0.0/8".parse().unwrap()];
+        let headers = [("X-Forwarded-For", "89.39.106.191")];
+        assert_eq!(
+            resolve(ip("10.0.0.5"), headers, &trusted),
+            ip("89.39.106.191")
+        );
+    }
+}
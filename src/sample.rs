@@ -0,0 +1,159 @@
+//! Random samples of large context feeds.
+//!
+//! Exploratory analysis and fixture harvesting rarely need a whole feed,
+//! but a `head` of it is biased towards whatever the producer wrote first.
+//! [`reservoir`] draws a uniform sample of `k` records in a single pass
+//! without holding more than `k` of them in memory.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::io::Cursor;
+//!
+//! let feed: String = (0..1000)
+//!     .map(|i| format!("{{\"ip\": \"10.0.{}.{}\"}}\n", i / 256, i % 256))
+//!     .collect();
+//!
+//! let sample = spur::sample::reservoir(Cursor::new(feed), 10, 42).unwrap();
+//! assert_eq!(sample.len(), 10);
+//! ```
+
+#[cfg(feature = "json")]
+use std::io::{self, BufRead};
+
+#[cfg(feature = "json")]
+use crate::context::IpContext;
+use crate::rng::SplitMix64;
+
+/// Draw a uniform random sample of up to `k` contexts from a
+/// newline-delimited JSON feed.
+///
+/// Every valid record has the same chance of being selected. Blank lines
+/// and lines that do not parse as an [`IpContext`] are skipped. The same
+/// feed and `seed` always give the same sample. The order of the returned
+/// contexts is unspecified.
+#[cfg(feature = "json")]
+pub fn reservoir<R: BufRead>(reader: R, k: usize, seed: u64) -> io::Result<Vec<IpContext>> {
+    let mut sample = Reservoir::new(k, seed);
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Ok(context) = serde_json::from_str::<IpContext>(&line) {
+            sample.offer(context);
+        }
+    }
+    Ok(sample.into_vec())
+}
+
+/// Uniform sample of up to `k` items from a stream of unknown length.
+///
+/// Implements Algorithm R: the first `k` items are kept, and the `n`-th item
+/// after that replaces a random kept item with probability `k / n`.
+///
+/// ```rust
+/// use spur::sample::Reservoir;
+///
+/// let mut reservoir = Reservoir::new(3, 7);
+/// for n in 0..100 {
+///     reservoir.offer(n);
+/// }
+///
+/// assert_eq!(reservoir.seen(), 100);
+/// assert_eq!(reservoir.items().len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Reservoir<T> {
+    k: usize,
+    seen: u64,
+    items: Vec<T>,
+    rng: SplitMix64,
+}
+
+impl<T> Reservoir<T> {
+    /// Create an empty reservoir keeping up to `k` items.
+    pub fn new(k: usize, seed: u64) -> Self {
+        Self {
+            k,
+            seen: 0,
+            items: Vec::with_capacity(k.min(1024)),
+            rng: SplitMix64::new(seed),
+        }
+    }
+
+    /// Offer the next item of the stream.
+    pub fn offer(&mut self, item: T) {
+        self.seen += 1;
+        if self.items.len() < self.k {
+            self.items.push(item);
+            return;
+        }
+        let slot = self.rng.below(self.seen) as usize;
+        if slot < self.k {
+            self.items[slot] = item;
+        }
+    }
+
+    /// Returns the number of items offered so far.
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    /// Returns the items sampled so far.
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Consume the reservoir, returning the sample.
+    pub fn into_vec(self) -> Vec<T> {
+        self.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_short_feed_is_kept_whole() {
+        let feed = "{\"ip\": \"1.2.3.4\"}\n\nnot json\n{\"ip\": \"5.6.7.8\"}\n";
+        let sample = reservoir(Cursor::new(feed), 10, 1).unwrap();
+        let mut ips: Vec<_> = sample.iter().filter_map(|c| c.ip.as_deref()).collect();
+        ips.sort_unstable();
+        assert_eq!(ips, ["1.2.3.4", "5.6.7.8"]);
+        assert!(reservoir(Cursor::new(feed), 0, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_deterministic_per_seed() {
+        let feed: String = (0..500)
+            .map(|i| format!("{{\"ip\": \"10.0.{}.{}\"}}\n", i / 256, i % 256))
+            .collect();
+        let a = reservoir(Cursor::new(&feed), 5, 9).unwrap();
+        let b = reservoir(Cursor::new(&feed), 5, 9).unwrap();
+        let c = reservoir(Cursor::new(&feed), 5, 10).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_roughly_uniform() {
+        // Each of 10 items should land in a 2-item sample about 20% of the
+        // time.
+        let mut hits = [0u32; 10];
+        for seed in 0..5000 {
+            let mut reservoir = Reservoir::new(2, seed);
+            for n in 0..10 {
+                reservoir.offer(n);
+            }
+            for &n in reservoir.items() {
+                hits[n] += 1;
+            }
+        }
+        for count in hits {
+            assert!((850..1150).contains(&count), "{:?}", hits);
+        }
+    }
+}
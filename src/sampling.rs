@@ -0,0 +1,221 @@
+//! Deciding which requests to enrich.
+//!
+//! High-volume sites rarely have the query quota to look up every request.
+//! A [`Sampler`] decides per request whether to enrich it, at rates set per
+//! endpoint, per infrastructure guess and globally, so sensitive endpoints
+//! are always covered and the rest of the traffic still yields a
+//! statistically useful slice. Each [`SampleDecision`] carries the rate it
+//! was drawn at, so aggregates over enriched requests can be weighted back
+//! up to the whole traffic.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::sampling::Sampler;
+//! use spur::Infrastructure;
+//!
+//! let sampler = Sampler::new(0.05)
+//!     .endpoint("login", 1.0)
+//!     .infrastructure(Infrastructure::Datacenter, 0.5)
+//!     .seed(42);
+//!
+//! // Every login is enriched
+//! assert!(sampler.decide(Some("login"), None).enrich);
+//!
+//! // Other requests from a likely datacenter address are enriched half the time
+//! let decision = sampler.decide(Some("search"), Some(&Infrastructure::Datacenter));
+//! assert_eq!(decision.rate, 0.5);
+//! ```
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+
+use crate::context::Infrastructure;
+use crate::rng::SplitMix64;
+
+/// The outcome of [`Sampler::decide`] for one request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleDecision {
+    /// Whether to enrich the request.
+    pub enrich: bool,
+    /// The probability the request was enriched with.
+    pub rate: f64,
+}
+
+impl SampleDecision {
+    /// Returns how many requests an enriched request stands for, `1 / rate`.
+    ///
+    /// Weighting enriched requests by this gives unbiased estimates of
+    /// totals over all traffic. Zero for a rate of zero.
+    pub fn weight(&self) -> f64 {
+        if self.rate > 0.0 {
+            1.0 / self.rate
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Per-request enrichment sampling.
+///
+/// The rate of a request is the first of these that is set:
+///
+/// 1. the rate of its endpoint,
+/// 2. the rate of the infrastructure it is guessed to come from, for
+///    example from an earlier lookup or a local ASN table,
+/// 3. the global rate.
+///
+/// Rates are clamped to `[0, 1]`. Decisions are drawn from a generator
+/// seeded from process entropy, so separate samplers pick different
+/// requests; set [`seed`](Self::seed) to make a given sequence of requests
+/// always give the same decisions. Methods take `&self`, so a sampler can
+/// be shared between request handlers.
+#[derive(Debug)]
+pub struct Sampler {
+    global: f64,
+    endpoints: HashMap<String, f64>,
+    infrastructures: HashMap<Infrastructure, f64>,
+    rng: Mutex<SplitMix64>,
+}
+
+impl Sampler {
+    /// Create a sampler enriching `global_rate` of requests.
+    pub fn new(global_rate: f64) -> Self {
+        Self {
+            global: clamp(global_rate),
+            endpoints: HashMap::new(),
+            infrastructures: HashMap::new(),
+            rng: Mutex::new(SplitMix64::new(entropy_seed())),
+        }
+    }
+
+    /// Set the rate for requests to `endpoint`, matched exactly.
+    pub fn endpoint(mut self, endpoint: &str, rate: f64) -> Self {
+        self.endpoints.insert(endpoint.to_string(), clamp(rate));
+        self
+    }
+
+    /// Set the rate for requests guessed to come from `infrastructure`.
+    pub fn infrastructure(mut self, infrastructure: Infrastructure, rate: f64) -> Self {
+        self.infrastructures.insert(infrastructure, clamp(rate));
+        self
+    }
+
+    /// Set the seed of the generator decisions are drawn from, for
+    /// reproducible runs.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Mutex::new(SplitMix64::new(seed));
+        self
+    }
+
+    /// Returns the rate for a request to `endpoint` from `infrastructure`.
+    pub fn rate(&self, endpoint: Option<&str>, infrastructure: Option<&Infrastructure>) -> f64 {
+        endpoint
+            .and_then(|e| self.endpoints.get(e))
+            .or_else(|| infrastructure.and_then(|i| self.infrastructures.get(i)))
+            .copied()
+            .unwrap_or(self.global)
+    }
+
+    /// Decide whether to enrich a request to `endpoint` from
+    /// `infrastructure`.
+    pub fn decide(
+        &self,
+        endpoint: Option<&str>,
+        infrastructure: Option<&Infrastructure>,
+    ) -> SampleDecision {
+        let rate = self.rate(endpoint, infrastructure);
+        let enrich = match rate {
+            r if r >= 1.0 => true,
+            r if r <= 0.0 => false,
+            r => self.rng.lock().unwrap_or_else(|e| e.into_inner()).chance(r),
+        };
+        SampleDecision { enrich, rate }
+    }
+}
+
+/// A seed from the random keys the standard library gives each
+/// `RandomState`.
+fn entropy_seed() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+fn clamp(rate: f64) -> f64 {
+    if rate.is_nan() {
+        0.0
+    } else {
+        rate.clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_precedence() {
+        let sampler = Sampler::new(0.1)
+            .endpoint("checkout", 1.0)
+            .infrastructure(Infrastructure::Mobile, 0.01);
+
+        assert_eq!(
+            sampler.rate(Some("checkout"), Some(&Infrastructure::Mobile)),
+            1.0
+        );
+        assert_eq!(
+            sampler.rate(Some("search"), Some(&Infrastructure::Mobile)),
+            0.01
+        );
+        assert_eq!(
+            sampler.rate(Some("search"), Some(&Infrastructure::Datacenter)),
+            0.1
+        );
+        assert_eq!(sampler.rate(None, None), 0.1);
+    }
+
+    #[test]
+    fn test_decisions() {
+        let sampler = Sampler::new(0.2).endpoint("health", 0.0).seed(7);
+        let enriched = (0..10_000)
+            .filter(|_| sampler.decide(None, None).enrich)
+            .count();
+        assert!((1800..2200).contains(&enriched), "{}", enriched);
+        assert!((0..100).all(|_| !sampler.decide(Some("health"), None).enrich));
+
+        // The same seed gives the same decisions
+        let draw = |seed| -> Vec<bool> {
+            let sampler = Sampler::new(0.5).seed(seed);
+            (0..64).map(|_| sampler.decide(None, None).enrich).collect()
+        };
+        assert_eq!(draw(3), draw(3));
+        assert_ne!(draw(3), draw(4));
+    }
+
+    #[test]
+    fn test_unseeded_samplers_differ() {
+        let draw = || -> Vec<bool> {
+            let sampler = Sampler::new(0.5);
+            (0..64).map(|_| sampler.decide(None, None).enrich).collect()
+        };
+        assert_ne!(draw(), draw());
+    }
+
+    #[test]
+    fn test_weight_and_clamp() {
+        let sampler = Sampler::new(4.0)
+            .endpoint("a", -1.0)
+            .endpoint("b", f64::NAN);
+        assert_eq!(sampler.rate(None, None), 1.0);
+        assert_eq!(sampler.rate(Some("a"), None), 0.0);
+        assert_eq!(sampler.rate(Some("b"), None), 0.0);
+
+        let decision = SampleDecision {
+            enrich: true,
+            rate: 0.25,
+        };
+        assert_eq!(decision.weight(), 4.0);
+        assert_eq!(sampler.decide(Some("a"), None).weight(), 0.0);
+    }
+}
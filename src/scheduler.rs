@@ -0,0 +1,326 @@
+//! Spreading lookups over a query quota.
+//!
+//! Spur tokens come with a fixed number of queries per billing cycle,
+//! reported as [`ApiStatus::queries_remaining`]. A [`Scheduler`] queues
+//! lookup requests by [`Priority`] and hands them out at a pace that makes
+//! the remaining queries last until the end of the cycle: high-priority
+//! lookups go out at once, normal ones are paced, and low-priority
+//! enrichment is dropped once the remaining budget falls below a reserve.
+//!
+//! The scheduler does no I/O and never sleeps. The caller performs the
+//! lookups, passes in the current time and feeds back fresh `ApiStatus`
+//! responses, so it fits both blocking and async clients.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::time::{Duration, SystemTime};
+//! use spur::scheduler::{Priority, Scheduled, Scheduler};
+//! use spur::ApiStatus;
+//!
+//! let now = SystemTime::now();
+//! let mut scheduler = Scheduler::new(now + Duration::from_secs(86_400));
+//! scheduler.update_status(
+//!     &ApiStatus { queries_remaining: Some(8_640), ..Default::default() },
+//!     now,
+//! );
+//!
+//! scheduler.submit("1.2.3.4", Priority::High);
+//! scheduler.submit("5.6.7.8", Priority::Normal);
+//!
+//! assert_eq!(scheduler.poll(now), Scheduled::Send("1.2.3.4"));
+//! // 8,639 queries left for a day: about one every ten seconds
+//! assert!(matches!(scheduler.poll(now), Scheduled::Wait(_)));
+//! assert_eq!(scheduler.poll(now + Duration::from_secs(10)), Scheduled::Send("5.6.7.8"));
+//! ```
+
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+use crate::context::ApiStatus;
+
+/// How important a lookup is when the quota runs low.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Priority {
+    /// Background enrichment; dropped when the budget is tight.
+    Low,
+    /// Paced to fit the quota.
+    Normal,
+    /// Sent immediately while any queries remain.
+    High,
+}
+
+impl Priority {
+    const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+}
+
+/// What the caller should do next, as returned by [`Scheduler::poll`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scheduled<T> {
+    /// Perform this lookup now.
+    Send(T),
+    /// This low-priority lookup will not fit the budget; discard it.
+    Drop(T),
+    /// Nothing may be sent yet; poll again after this long.
+    Wait(Duration),
+    /// The queue is empty.
+    Idle,
+}
+
+/// Queue of lookup requests paced to fit a query quota.
+///
+/// Until [`update_status`](Self::update_status) reports a number of
+/// remaining queries, and again once the cycle end has passed, requests are
+/// not paced. Each request handed out by [`poll`](Self::poll) counts as one
+/// query against the remaining budget.
+#[derive(Debug)]
+pub struct Scheduler<T> {
+    queues: [VecDeque<T>; 3],
+    cycle_end: SystemTime,
+    remaining: Option<u64>,
+    reserve: u64,
+    burst: f64,
+    tokens: f64,
+    refilled_at: Option<SystemTime>,
+}
+
+impl<T> Scheduler<T> {
+    /// Create a scheduler for a billing cycle ending at `cycle_end`.
+    ///
+    /// Defaults to a burst of 10 paced requests and a low-priority reserve
+    /// of 0 queries.
+    pub fn new(cycle_end: SystemTime) -> Self {
+        Self {
+            queues: Default::default(),
+            cycle_end,
+            remaining: None,
+            reserve: 0,
+            burst: 10.0,
+            tokens: 0.0,
+            refilled_at: None,
+        }
+    }
+
+    /// Set how many paced requests may go out back to back after a quiet
+    /// period.
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = f64::from(burst.max(1));
+        self
+    }
+
+    /// Keep this many queries for normal and high priority lookups; once no
+    /// more than `reserve` remain, low priority requests are dropped.
+    pub fn low_priority_reserve(mut self, reserve: u64) -> Self {
+        self.reserve = reserve;
+        self
+    }
+
+    /// Set the end of the current billing cycle, e.g. after a quota reset.
+    pub fn set_cycle_end(&mut self, cycle_end: SystemTime) {
+        self.cycle_end = cycle_end;
+    }
+
+    /// Record a fresh status response.
+    ///
+    /// An inactive token counts as having no queries left.
+    pub fn update_status(&mut self, status: &ApiStatus, now: SystemTime) {
+        self.refill(now);
+        self.remaining = match status.active {
+            Some(false) => Some(0),
+            _ => status.queries_remaining,
+        };
+    }
+
+    /// Returns the remaining query budget, if known.
+    pub fn remaining(&self) -> Option<u64> {
+        self.remaining
+    }
+
+    /// Queue a lookup request.
+    pub fn submit(&mut self, request: T, priority: Priority) {
+        self.queue(priority).push_back(request);
+    }
+
+    /// Returns the number of queued requests.
+    pub fn pending(&self) -> usize {
+        self.queues.iter().map(VecDeque::len).sum()
+    }
+
+    /// Returns the next action for the caller at time `now`.
+    ///
+    /// Requests come out highest priority first and in submission order
+    /// within a priority.
+    pub fn poll(&mut self, now: SystemTime) -> Scheduled<T> {
+        if now >= self.cycle_end {
+            self.remaining = None;
+        }
+        self.refill(now);
+
+        let Some(priority) = Priority::ALL
+            .into_iter()
+            .find(|&p| !self.queue(p).is_empty())
+        else {
+            return Scheduled::Idle;
+        };
+
+        let Some(remaining) = self.remaining else {
+            return self.send(priority);
+        };
+        if priority == Priority::Low && remaining <= self.reserve {
+            let request = self
+                .queue(priority)
+                .pop_front()
+                .expect("queue is not empty");
+            return Scheduled::Drop(request);
+        }
+        if remaining == 0 {
+            let until_reset = self.cycle_end.duration_since(now).unwrap_or_default();
+            return Scheduled::Wait(until_reset);
+        }
+        if priority == Priority::High {
+            return self.send(priority);
+        }
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return self.send(priority);
+        }
+        let rate = self.rate(now);
+        Scheduled::Wait(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+    }
+
+    fn queue(&mut self, priority: Priority) -> &mut VecDeque<T> {
+        &mut self.queues[priority as usize]
+    }
+
+    fn send(&mut self, priority: Priority) -> Scheduled<T> {
+        if let Some(remaining) = &mut self.remaining {
+            *remaining = remaining.saturating_sub(1);
+        }
+        Scheduled::Send(
+            self.queue(priority)
+                .pop_front()
+                .expect("queue is not empty"),
+        )
+    }
+
+    /// Returns the paced rate in queries per second that spreads the
+    /// remaining budget evenly until the end of the cycle.
+    fn rate(&self, now: SystemTime) -> f64 {
+        let left = self.cycle_end.duration_since(now).unwrap_or_default();
+        let remaining = self.remaining.unwrap_or(0) as f64;
+        remaining.max(1.0) / left.as_secs_f64().max(1.0)
+    }
+
+    /// Accrue paced tokens for the time since the last refill.
+    fn refill(&mut self, now: SystemTime) {
+        if let Some(refilled_at) = self.refilled_at {
+            let elapsed = now.duration_since(refilled_at).unwrap_or_default();
+            self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate(now)).min(self.burst);
+        }
+        self.refilled_at = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(86_400);
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs)
+    }
+
+    fn status(remaining: u64) -> ApiStatus {
+        ApiStatus {
+            active: Some(true),
+            queries_remaining: Some(remaining),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_unpaced_without_status() {
+        let mut scheduler = Scheduler::new(at(0) + DAY);
+        for i in 0..5 {
+            scheduler.submit(i, Priority::Low);
+        }
+        assert_eq!(scheduler.pending(), 5);
+        for i in 0..5 {
+            assert_eq!(scheduler.poll(at(0)), Scheduled::Send(i));
+        }
+        assert_eq!(scheduler.poll(at(0)), Scheduled::Idle);
+    }
+
+    #[test]
+    fn test_priority_order() {
+        let mut scheduler = Scheduler::new(at(0) + DAY);
+        scheduler.submit("low", Priority::Low);
+        scheduler.submit("normal", Priority::Normal);
+        scheduler.submit("high 1", Priority::High);
+        scheduler.submit("high 2", Priority::High);
+
+        let order: Vec<_> = std::iter::from_fn(|| match scheduler.poll(at(0)) {
+            Scheduled::Send(r) => Some(r),
+            _ => None,
+        })
+        .collect();
+        assert_eq!(order, ["high 1", "high 2", "normal", "low"]);
+    }
+
+    #[test]
+    fn test_paces_normal_requests_over_cycle() {
+        // 864 queries for a day: one every 100 seconds, bursts of 2
+        let mut scheduler = Scheduler::new(at(0) + DAY).burst(2);
+        scheduler.update_status(&status(864), at(0));
+        for i in 0..4 {
+            scheduler.submit(i, Priority::Normal);
+        }
+
+        match scheduler.poll(at(0)) {
+            Scheduled::Wait(wait) => assert_eq!(wait.as_secs(), 100),
+            other => panic!("unexpected {:?}", other),
+        }
+        // A quiet hour only builds up a burst of 2
+        assert_eq!(scheduler.poll(at(3600)), Scheduled::Send(0));
+        assert_eq!(scheduler.poll(at(3600)), Scheduled::Send(1));
+        assert!(matches!(scheduler.poll(at(3600)), Scheduled::Wait(_)));
+        assert_eq!(scheduler.poll(at(3700)), Scheduled::Send(2));
+        assert_eq!(scheduler.remaining(), Some(861));
+    }
+
+    #[test]
+    fn test_high_priority_ignores_pacing() {
+        let mut scheduler = Scheduler::new(at(0) + DAY);
+        scheduler.update_status(&status(2), at(0));
+        for i in 0..3 {
+            scheduler.submit(i, Priority::High);
+        }
+        assert_eq!(scheduler.poll(at(0)), Scheduled::Send(0));
+        assert_eq!(scheduler.poll(at(0)), Scheduled::Send(1));
+        assert_eq!(scheduler.poll(at(0)), Scheduled::Wait(DAY));
+        // After the cycle ends the budget is unknown until the next status
+        assert_eq!(scheduler.poll(at(0) + DAY), Scheduled::Send(2));
+    }
+
+    #[test]
+    fn test_drops_low_priority_within_reserve() {
+        let mut scheduler = Scheduler::new(at(0) + DAY).low_priority_reserve(100);
+        scheduler.update_status(&status(100), at(0));
+        scheduler.submit("background", Priority::Low);
+        scheduler.submit("user", Priority::High);
+        assert_eq!(scheduler.poll(at(0)), Scheduled::Send("user"));
+        assert_eq!(scheduler.poll(at(0)), Scheduled::Drop("background"));
+
+        scheduler.update_status(
+            &ApiStatus {
+                active: Some(false),
+                ..Default::default()
+            },
+            at(1),
+        );
+        scheduler.submit("normal", Priority::Normal);
+        assert_eq!(scheduler.remaining(), Some(0));
+        assert!(matches!(scheduler.poll(at(1)), Scheduled::Wait(_)));
+    }
+}
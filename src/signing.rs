@@ -0,0 +1,228 @@
+//! Tamper-evident contexts.
+//!
+//! Enrichment results passed between internal services, or archived for
+//! later review, are only as trustworthy as every hop they went through.
+//! [`sign`] computes a detached HMAC-SHA256 [`Signature`] over the
+//! canonical JSON form of a context, and [`verify`] checks a
+//! [`SignedContext`] against it, so any change to the content is caught.
+//!
+//! The canonical form sorts object keys, as
+//! [`IpContext::content_hash`] does, so a context that was re-serialized
+//! by another service with a different key order still verifies. Fields
+//! this crate does not know are not part of a parsed context, so they are
+//! neither signed nor checked.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::signing::{sign, verify, SignedContext};
+//! use spur::test_utils::fixtures;
+//!
+//! let key = b"shared secret of the enrichment service";
+//! let signed = SignedContext::new(fixtures::vpn_ip(), key);
+//! let json = serde_json::to_string(&signed).unwrap();
+//!
+//! // ...later, in another service
+//! let mut received: SignedContext = serde_json::from_str(&json).unwrap();
+//! assert!(verify(&received, key).is_ok());
+//!
+//! received.context.tunnels = None;
+//! assert!(verify(&received, key).is_err());
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::Sha256;
+
+use crate::canonical;
+use crate::context::IpContext;
+use crate::hex;
+
+/// A detached HMAC-SHA256 signature over a context, from [`sign`].
+///
+/// Displays and serializes as 64 lowercase hex digits.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Signature([u8; 32]);
+
+impl Signature {
+    /// Returns the raw signature bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Signature({})", self)
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(&self.0))
+    }
+}
+
+impl FromStr for Signature {
+    type Err = SignatureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).ok_or(SignatureError::Malformed)?;
+        let bytes = bytes.try_into().map_err(|_| SignatureError::Malformed)?;
+        Ok(Self(bytes))
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A context together with its detached signature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedContext {
+    /// The signed context.
+    pub context: IpContext,
+    /// The signature over `context`.
+    pub signature: Signature,
+}
+
+impl SignedContext {
+    /// Sign `context` with `key`.
+    pub fn new(context: IpContext, key: &[u8]) -> Self {
+        let signature = sign(&context, key);
+        Self { context, signature }
+    }
+}
+
+/// Errors returned by [`verify`] and when parsing a [`Signature`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureError {
+    /// The signature is not 64 hex digits.
+    Malformed,
+    /// The signature does not match the context under the key.
+    Mismatch,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed context signature"),
+            Self::Mismatch => write!(f, "context signature mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// Returns the canonical JSON form of `context` that signatures cover:
+/// compact, with object keys sorted.
+pub fn canonical_json(context: &IpContext) -> Vec<u8> {
+    let value = serde_json::to_value(context).expect("IpContext should serialize");
+    let value = canonical::sort_keys(value);
+    serde_json::to_vec(&value).expect("Value should serialize")
+}
+
+/// Returns the signature of `context` under `key`.
+pub fn sign(context: &IpContext, key: &[u8]) -> Signature {
+    Signature(mac(context, key).finalize().into_bytes().into())
+}
+
+/// Check that `signed.signature` is the signature of `signed.context`
+/// under `key`.
+///
+/// The signature is compared in constant time.
+pub fn verify(signed: &SignedContext, key: &[u8]) -> Result<(), SignatureError> {
+    mac(&signed.context, key)
+        .verify_slice(signed.signature.as_bytes())
+        .map_err(|_| SignatureError::Mismatch)
+}
+
+fn mac(context: &IpContext, key: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(&canonical_json(context));
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::fixtures;
+
+    const KEY: &[u8] = b"test key";
+
+    #[test]
+    fn test_key_order_does_not_matter() {
+        let a: IpContext =
+            serde_json::from_str(r#"{"ip": "1.2.3.4", "organization": "X"}"#).unwrap();
+        let b: IpContext =
+            serde_json::from_str(r#"{"organization": "X", "ip": "1.2.3.4"}"#).unwrap();
+        assert_eq!(sign(&a, KEY), sign(&b, KEY));
+        assert_eq!(
+            canonical_json(&a),
+            br#"{"ip":"1.2.3.4","organization":"X"}"#
+        );
+    }
+
+    #[test]
+    fn test_pinned_output() {
+        // Signatures must agree between builds with different dependency
+        // graphs, so the exact bytes are pinned.
+        let context: IpContext = serde_json::from_str(
+            r#"{"tunnels": [{"type": "VPN", "operator": "X", "anonymous": true}],
+                "location": {"country": "NL", "city": "Amsterdam"}, "ip": "1.2.3.4"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(canonical_json(&context)).unwrap(),
+            concat!(
+                r#"{"ip":"1.2.3.4","location":{"city":"Amsterdam","country":"NL"},"#,
+                r#""tunnels":[{"anonymous":true,"operator":"X","type":"VPN"}]}"#
+            )
+        );
+        assert_eq!(
+            sign(&context, KEY).to_string(),
+            "ba48fbfec87d430571ddfe90d8961447ef59eb408784ff5c097710e9f0c5a439"
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let signed = SignedContext::new(fixtures::residential_ip(), KEY);
+        assert_eq!(verify(&signed, KEY), Ok(()));
+        assert_eq!(verify(&signed, b"other key"), Err(SignatureError::Mismatch));
+
+        let mut tampered = signed.clone();
+        tampered.context.organization = Some("Someone Else".to_string());
+        assert_eq!(verify(&tampered, KEY), Err(SignatureError::Mismatch));
+    }
+
+    #[test]
+    fn test_signature_text() {
+        let signature = sign(&IpContext::default(), KEY);
+        let text = signature.to_string();
+        assert_eq!(text.len(), 64);
+        assert_eq!(text.parse::<Signature>(), Ok(signature.clone()));
+        assert_eq!(
+            text.to_uppercase().parse::<Signature>(),
+            Ok(signature.clone())
+        );
+        assert_eq!("abcd".parse::<Signature>(), Err(SignatureError::Malformed));
+        assert_eq!(
+            serde_json::to_string(&signature).unwrap(),
+            format!("\"{}\"", text)
+        );
+        assert!(serde_json::from_str::<Signature>(r#""zz""#).is_err());
+    }
+}
@@ -0,0 +1,434 @@
+//! Aggregate statistics over batches of contexts.
+//!
+//! [`Aggregator`] counts what a batch of enrichments contains: how many
+//! contexts per infrastructure type, which tunnel operators and autonomous
+//! systems are most common, how often each risk appears, and where the
+//! addresses are located. [`Aggregator::summary`] turns the counts into a
+//! [`Summary`] that serializes to JSON for dashboards.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::stats::Aggregator;
+//! use spur::test_utils::fixtures;
+//!
+//! let contexts = [fixtures::vpn_ip(), fixtures::tor_exit_node(), fixtures::residential_ip()];
+//! let summary = contexts.iter().collect::<Aggregator>().summary(10);
+//!
+//! assert_eq!(summary.total, 3);
+//! println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+//! ```
+//!
+//! For dashboards tracking traffic over time, aggregate each time window
+//! separately and pass the windows to [`dashboard_json`].
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::context::IpContext;
+
+/// Accumulates counts over a stream of contexts.
+///
+/// Feed contexts with [`add`](Self::add), [`Extend`] or [`FromIterator`].
+/// Aggregators built on separate threads can be combined with
+/// [`merge`](Self::merge).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Aggregator {
+    total: u64,
+    infrastructure: BTreeMap<String, u64>,
+    operators: BTreeMap<String, u64>,
+    asns: BTreeMap<u32, (u64, Option<String>)>,
+    risks: BTreeMap<String, u64>,
+    countries: BTreeMap<String, u64>,
+}
+
+impl Aggregator {
+    /// Create an empty aggregator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one context.
+    ///
+    /// Operators and risks are counted once per context, even if a context
+    /// lists the same one several times.
+    pub fn add(&mut self, context: &IpContext) {
+        self.total += 1;
+        if let Some(infrastructure) = &context.infrastructure {
+            bump(&mut self.infrastructure, infrastructure.as_str());
+        }
+        let operators: HashSet<&str> = context
+            .tunnels
+            .iter()
+            .flatten()
+            .filter_map(|t| t.operator.as_deref())
+            .collect();
+        for operator in operators {
+            bump(&mut self.operators, operator);
+        }
+        if let Some(asn) = &context.autonomous_system {
+            if let Some(number) = asn.number {
+                let (count, organization) = self.asns.entry(number).or_default();
+                *count += 1;
+                if organization.is_none() {
+                    organization.clone_from(&asn.organization);
+                }
+            }
+        }
+        let risks: HashSet<&str> = context.risks.iter().flatten().map(|r| r.as_str()).collect();
+        for risk in risks {
+            bump(&mut self.risks, risk);
+        }
+        if let Some(country) = context.location.as_ref().and_then(|l| l.country.as_deref()) {
+            bump(&mut self.countries, country);
+        }
+    }
+
+    /// Add the counts of `other` to this aggregator.
+    pub fn merge(&mut self, other: Aggregator) {
+        self.total += other.total;
+        merge_counts(&mut self.infrastructure, other.infrastructure);
+        merge_counts(&mut self.operators, other.operators);
+        merge_counts(&mut self.risks, other.risks);
+        merge_counts(&mut self.countries, other.countries);
+        for (number, (count, organization)) in other.asns {
+            let entry = self.asns.entry(number).or_default();
+            entry.0 += count;
+            if entry.1.is_none() {
+                entry.1 = organization;
+            }
+        }
+    }
+
+    /// Returns the number of contexts counted.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Produce a summary, keeping the `top_n` most common operators and
+    /// autonomous systems.
+    ///
+    /// Ties are broken by name or AS number so the output is stable.
+    pub fn summary(&self, top_n: usize) -> Summary {
+        let mut operators: Vec<Ranked> = self
+            .operators
+            .iter()
+            .map(|(name, &count)| Ranked {
+                name: name.clone(),
+                count,
+            })
+            .collect();
+        // BTreeMap iteration is already ordered by name, so a stable sort
+        // by count keeps ties alphabetical.
+        operators.sort_by_key(|r| Reverse(r.count));
+        operators.truncate(top_n);
+
+        let mut asns: Vec<AsnCount> = self
+            .asns
+            .iter()
+            .map(|(&number, (count, organization))| AsnCount {
+                number,
+                organization: organization.clone(),
+                count: *count,
+            })
+            .collect();
+        asns.sort_by_key(|a| Reverse(a.count));
+        asns.truncate(top_n);
+
+        Summary {
+            total: self.total,
+            infrastructure: self.infrastructure.clone(),
+            top_operators: operators,
+            top_asns: asns,
+            risks: self.risks.clone(),
+            countries: self.countries.clone(),
+        }
+    }
+}
+
+impl<'a> Extend<&'a IpContext> for Aggregator {
+    fn extend<I: IntoIterator<Item = &'a IpContext>>(&mut self, contexts: I) {
+        for context in contexts {
+            self.add(context);
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a IpContext> for Aggregator {
+    fn from_iter<I: IntoIterator<Item = &'a IpContext>>(contexts: I) -> Self {
+        let mut aggregator = Self::new();
+        aggregator.extend(contexts);
+        aggregator
+    }
+}
+
+/// Counts produced by [`Aggregator::summary`].
+///
+/// Enum values are keyed by their API strings, so unknown values from newer
+/// API versions are counted under their own names.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Summary {
+    /// Number of contexts counted.
+    pub total: u64,
+    /// Contexts per infrastructure type.
+    pub infrastructure: BTreeMap<String, u64>,
+    /// Most common tunnel operators, most frequent first.
+    pub top_operators: Vec<Ranked>,
+    /// Most common autonomous systems, most frequent first.
+    pub top_asns: Vec<AsnCount>,
+    /// Contexts per risk.
+    pub risks: BTreeMap<String, u64>,
+    /// Contexts per location country code.
+    pub countries: BTreeMap<String, u64>,
+}
+
+/// A named value and how many contexts it appeared in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ranked {
+    /// The value, e.g. an operator name.
+    pub name: String,
+    /// Number of contexts.
+    pub count: u64,
+}
+
+/// An autonomous system and how many contexts it appeared in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AsnCount {
+    /// The autonomous system number.
+    pub number: u32,
+    /// The first organization name seen for this AS.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    /// Number of contexts.
+    pub count: u64,
+}
+
+/// Version of the [`Dashboard`] document layout.
+pub const DASHBOARD_SCHEMA_VERSION: u32 = 1;
+
+/// Traffic-mix document produced by [`dashboard`] and [`dashboard_json`].
+///
+/// The layout is versioned by `schema_version`; fields are only added
+/// within a version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dashboard {
+    /// Layout version, [`DASHBOARD_SCHEMA_VERSION`].
+    pub schema_version: u32,
+    /// Contexts counted across all windows.
+    pub total: u64,
+    /// Contexts counted in the latest window.
+    pub latest_total: u64,
+    /// Relative change of the latest window's total against the previous
+    /// window (`0.25` is +25%). `None` with fewer than two windows or an
+    /// empty previous window.
+    pub total_change: Option<f64>,
+    /// One point per window, oldest first.
+    pub trend: Vec<TrendPoint>,
+    /// Most common tunnel operators in the latest window.
+    pub top_operators: Vec<Ranked>,
+    /// Share of contexts in the latest window listing each risk (0.0 to 1.0).
+    pub risk_mix: BTreeMap<String, f64>,
+    /// Share of contexts in the latest window per infrastructure type.
+    pub infrastructure_mix: BTreeMap<String, f64>,
+}
+
+/// Counts for one window of a [`Dashboard`] trend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrendPoint {
+    /// The window label, e.g. its start time.
+    pub window: String,
+    /// Contexts counted in the window.
+    pub total: u64,
+    /// Contexts per risk in the window.
+    pub risks: BTreeMap<String, u64>,
+}
+
+/// Build a [`Dashboard`] from per-window aggregates, oldest first.
+///
+/// The top operators and mixes describe the last window; the trend covers
+/// all of them.
+pub fn dashboard(windows: &[(String, Aggregator)], top_n: usize) -> Dashboard {
+    let latest = windows.last().map(|(_, a)| a);
+    let previous = windows.len().checked_sub(2).map(|i| &windows[i].1);
+    let latest_total = latest.map_or(0, |a| a.total);
+    let share = |counts: &BTreeMap<String, u64>| -> BTreeMap<String, f64> {
+        counts
+            .iter()
+            .map(|(key, &count)| (key.clone(), count as f64 / latest_total as f64))
+            .collect()
+    };
+
+    Dashboard {
+        schema_version: DASHBOARD_SCHEMA_VERSION,
+        total: windows.iter().map(|(_, a)| a.total).sum(),
+        latest_total,
+        total_change: previous
+            .filter(|p| p.total > 0)
+            .map(|p| (latest_total as f64 - p.total as f64) / p.total as f64),
+        trend: windows
+            .iter()
+            .map(|(window, a)| TrendPoint {
+                window: window.clone(),
+                total: a.total,
+                risks: a.risks.clone(),
+            })
+            .collect(),
+        top_operators: latest.map_or_else(Vec::new, |a| a.summary(top_n).top_operators),
+        risk_mix: latest.map_or_else(BTreeMap::new, |a| share(&a.risks)),
+        infrastructure_mix: latest.map_or_else(BTreeMap::new, |a| share(&a.infrastructure)),
+    }
+}
+
+/// Render [`dashboard`] as pretty-printed JSON.
+///
+/// Map keys are sorted, so the same input always produces the same
+/// document.
+///
+/// ```rust
+/// use spur::stats::{dashboard_json, Aggregator};
+/// use spur::test_utils::{generate_many, Profile};
+///
+/// let windows: Vec<(String, Aggregator)> = ["10:00", "11:00"]
+///     .iter()
+///     .zip([40, 50])
+///     .map(|(label, n)| {
+///         let contexts = generate_many(n, Profile::Vpn, n as usize);
+///         (label.to_string(), contexts.iter().collect())
+///     })
+///     .collect();
+///
+/// let json = dashboard_json(&windows, 5);
+/// assert!(json.contains(r#""total_change": 0.25"#));
+/// ```
+#[cfg(feature = "json")]
+pub fn dashboard_json(windows: &[(String, Aggregator)], top_n: usize) -> String {
+    serde_json::to_string_pretty(&dashboard(windows, top_n)).expect("dashboard should serialize")
+}
+
+fn bump(counts: &mut BTreeMap<String, u64>, key: &str) {
+    match counts.get_mut(key) {
+        Some(count) => *count += 1,
+        None => {
+            counts.insert(key.to_string(), 1);
+        }
+    }
+}
+
+fn merge_counts(into: &mut BTreeMap<String, u64>, from: BTreeMap<String, u64>) {
+    for (key, count) in from {
+        *into.entry(key).or_default() += count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{generate_many, IpContextBuilder, Profile};
+    use crate::{Infrastructure, Risk};
+
+    fn vpn(operator: &str, asn: u32) -> IpContext {
+        IpContextBuilder::new()
+            .infrastructure(Infrastructure::Datacenter)
+            .add_risk(Risk::Tunnel)
+            .add_risk(Risk::Tunnel)
+            .vpn(operator)
+            .proxy(operator)
+            .asn(asn, "Hosting")
+            .location("NL", None)
+            .build()
+    }
+
+    #[test]
+    fn test_summary_counts() {
+        let contexts = [
+            vpn("NORD_VPN", 9009),
+            vpn("MULLVAD_VPN", 9009),
+            vpn("NORD_VPN", 16509),
+            IpContext::default(),
+        ];
+        let summary = contexts.iter().collect::<Aggregator>().summary(1);
+
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.infrastructure["DATACENTER"], 3);
+        assert_eq!(summary.risks["TUNNEL"], 3);
+        assert_eq!(summary.countries["NL"], 3);
+        assert_eq!(
+            summary.top_operators,
+            vec![Ranked {
+                name: "NORD_VPN".to_string(),
+                count: 2
+            }]
+        );
+        assert_eq!(summary.top_asns.len(), 1);
+        assert_eq!(summary.top_asns[0].number, 9009);
+        assert_eq!(summary.top_asns[0].organization.as_deref(), Some("Hosting"));
+    }
+
+    #[test]
+    fn test_merge_matches_single_pass() {
+        let contexts = generate_many(5, Profile::Vpn, 300);
+        let whole: Aggregator = contexts.iter().collect();
+        let mut left: Aggregator = contexts[..120].iter().collect();
+        left.merge(contexts[120..].iter().collect());
+
+        assert_eq!(left, whole);
+        assert_eq!(left.total(), 300);
+    }
+
+    #[test]
+    fn test_dashboard() {
+        let window = |contexts: &[IpContext]| contexts.iter().collect::<Aggregator>();
+        let windows = vec![
+            (
+                "mon".to_string(),
+                window(&[vpn("NORD_VPN", 1), IpContext::default()]),
+            ),
+            (
+                "tue".to_string(),
+                window(&[
+                    vpn("NORD_VPN", 1),
+                    vpn("MULLVAD_VPN", 2),
+                    vpn("NORD_VPN", 3),
+                ]),
+            ),
+        ];
+        let dashboard = dashboard(&windows, 1);
+
+        assert_eq!(dashboard.total, 5);
+        assert_eq!(dashboard.latest_total, 3);
+        assert_eq!(dashboard.total_change, Some(0.5));
+        assert_eq!(dashboard.trend[0].window, "mon");
+        assert_eq!(dashboard.trend[0].risks["TUNNEL"], 1);
+        assert_eq!(dashboard.top_operators[0].name, "NORD_VPN");
+        assert_eq!(dashboard.risk_mix["TUNNEL"], 1.0);
+        assert_eq!(dashboard.infrastructure_mix["DATACENTER"], 1.0);
+
+        let json = dashboard_json(&windows, 1);
+        assert_eq!(json, dashboard_json(&windows, 1));
+        let parsed: Dashboard = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, dashboard);
+    }
+
+    #[test]
+    fn test_empty_dashboard() {
+        let dashboard = dashboard(&[], 5);
+        assert_eq!(dashboard.total, 0);
+        assert_eq!(dashboard.total_change, None);
+        assert!(dashboard.trend.is_empty() && dashboard.risk_mix.is_empty());
+    }
+
+    #[test]
+    fn test_summary_serializes() {
+        let summary = [vpn("NORD_VPN", 9009)]
+            .iter()
+            .collect::<Aggregator>()
+            .summary(5);
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["top_asns"][0]["number"], 9009);
+        assert_eq!(json["top_operators"][0]["name"], "NORD_VPN");
+        let back: Summary = serde_json::from_value(json).unwrap();
+        assert_eq!(back, summary);
+    }
+}
@@ -0,0 +1,196 @@
+//! Rendering contexts through user-supplied templates (requires the
+//! `templates` feature).
+//!
+//! Alert lines, ticket bodies and chat messages often need a different
+//! layout per team. [`render_template`] renders a
+//! [MiniJinja](https://docs.rs/minijinja) (Jinja2 syntax) template against a
+//! context, so those formats can live in configuration instead of code.
+//!
+//! Templates see a flat set of variables, each absent (undefined) when the
+//! context lacks the value:
+//!
+//! | Variable | Value |
+//! |----------|-------|
+//! | `ip` | The IP address |
+//! | `infrastructure` | Infrastructure type, e.g. `DATACENTER` |
+//! | `asn`, `as_organization` | Autonomous system number and organization |
+//! | `organization` | Organization name |
+//! | `country`, `state`, `city` | Location |
+//! | `tunnel_type`, `operator` | Type and operator of the first tunnel |
+//! | `anonymous` | Whether any tunnel is anonymous |
+//! | `risks`, `services` | Lists of API strings, e.g. `["TUNNEL", "SPAM"]` |
+//! | `client_count`, `client_countries` | Client counts |
+//! | `proxies` | Proxy networks observed among the clients |
+//!
+//! The whole context is also available as `context`, with the API field
+//! names, for anything the flat set does not cover.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::templates::render_template;
+//! use spur::test_utils::fixtures;
+//!
+//! let template = "{{ ip }} is {{ infrastructure | lower }} \
+//!     {%- if operator %} via {{ operator }}{% endif %} \
+//!     [{{ risks | join(', ') }}]";
+//! let line = render_template(&fixtures::vpn_ip(), template).unwrap();
+//! assert!(line.contains(" via "));
+//! ```
+
+use minijinja::{Environment, Error, Value};
+use serde::Serialize;
+
+use crate::context::IpContext;
+
+/// Render `template` against `context`.
+///
+/// The template is compiled on every call. To render many contexts with the
+/// same template, add it to a [`minijinja::Environment`] once and render it
+/// with [`template_context`].
+///
+/// # Errors
+///
+/// Returns the MiniJinja error if the template has a syntax error or fails
+/// to render, e.g. because a filter was given a value it does not accept.
+pub fn render_template(context: &IpContext, template: &str) -> Result<String, Error> {
+    Environment::new().render_str(template, template_context(context))
+}
+
+/// Returns the variables [`render_template`] exposes, for rendering with a
+/// caller-managed [`minijinja::Environment`].
+pub fn template_context(context: &IpContext) -> Value {
+    Value::from_serialize(Vars::new(context))
+}
+
+/// The template variables; `None` fields are left out so they are undefined.
+#[derive(Serialize)]
+struct Vars<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ip: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    infrastructure: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    asn: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    as_organization: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    organization: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    country: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    city: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tunnel_type: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operator: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    anonymous: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    risks: Option<Vec<&'a str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    services: Option<Vec<&'a str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_countries: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proxies: Option<&'a [String]>,
+    context: &'a IpContext,
+}
+
+impl<'a> Vars<'a> {
+    fn new(context: &'a IpContext) -> Self {
+        let autonomous_system = context.autonomous_system.as_ref();
+        let location = context.location.as_ref();
+        let tunnels = context.tunnels.as_deref().unwrap_or_default();
+        let tunnel = tunnels.first();
+        let client = context.client.as_ref();
+        Self {
+            ip: context.ip.as_deref(),
+            infrastructure: context.infrastructure.as_ref().map(|i| i.as_str()),
+            asn: autonomous_system.and_then(|a| a.number),
+            as_organization: autonomous_system.and_then(|a| a.organization.as_deref()),
+            organization: context.organization.as_deref(),
+            country: location.and_then(|l| l.country.as_deref()),
+            state: location.and_then(|l| l.state.as_deref()),
+            city: location.and_then(|l| l.city.as_deref()),
+            tunnel_type: tunnel
+                .and_then(|t| t.tunnel_type.as_ref())
+                .map(|t| t.as_str()),
+            operator: tunnel.and_then(|t| t.operator.as_deref()),
+            anonymous: tunnels
+                .iter()
+                .any(|t| t.anonymous.is_some())
+                .then(|| tunnels.iter().any(|t| t.anonymous == Some(true))),
+            risks: context
+                .risks
+                .as_deref()
+                .map(|r| r.iter().map(|r| r.as_str()).collect()),
+            services: context
+                .services
+                .as_deref()
+                .map(|s| s.iter().map(|s| s.as_str()).collect()),
+            client_count: client.and_then(|c| c.count),
+            client_countries: client.and_then(|c| c.countries),
+            proxies: client.and_then(|c| c.proxies.as_deref()),
+            context,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{Infrastructure, Risk};
+    use crate::test_utils::{fixtures, IpContextBuilder};
+
+    #[test]
+    fn test_flat_fields() {
+        let context = IpContextBuilder::new()
+            .ip("89.39.106.191")
+            .infrastructure(Infrastructure::Datacenter)
+            .asn(9009, "M247")
+            .location("NL", Some("Amsterdam"))
+            .vpn("NORD_VPN")
+            .risks(vec![Risk::Tunnel, Risk::Spam])
+            .build();
+        let rendered = render_template(
+            &context,
+            "{{ ip }}|{{ infrastructure }}|AS{{ asn }} {{ as_organization }}|\
+             {{ city }}, {{ country }}|{{ tunnel_type }} {{ operator }}|\
+             {{ anonymous }}|{{ risks | join(',') }}",
+        )
+        .unwrap();
+        assert_eq!(
+            rendered,
+            "89.39.106.191|DATACENTER|AS9009 M247|Amsterdam, NL|VPN NORD_VPN|True|TUNNEL,SPAM"
+        );
+    }
+
+    #[test]
+    fn test_absent_fields_are_undefined() {
+        let template = "{% if operator is defined %}{{ operator }}{% else %}none{% endif %}";
+        assert_eq!(
+            render_template(&IpContext::default(), template).unwrap(),
+            "none"
+        );
+        assert_eq!(
+            render_template(&IpContext::default(), "[{{ city }}]").unwrap(),
+            "[]"
+        );
+    }
+
+    #[test]
+    fn test_full_context_and_errors() {
+        let context = fixtures::callback_proxy_ip();
+        let rendered = render_template(&context, "{{ context.client.proxies[0] }}").unwrap();
+        assert_eq!(
+            Some(rendered.as_str()),
+            context.client.unwrap().proxies.unwrap()[0].as_str().into()
+        );
+        assert!(render_template(&IpContext::default(), "{% if %}").is_err());
+    }
+}
@@ -33,6 +33,55 @@
 //! let tor = fixtures::tor_exit_node();
 //! ```
 //!
+//! Nested values have their own builders ([`TunnelBuilder`],
+//! [`TunnelEntryBuilder`], [`ClientBuilder`], [`LocationBuilder`] and
+//! [`ConcentrationBuilder`]) for tests that need a precise shape, and
+//! [`TagMetadataBuilder`] and [`ApiStatusBuilder`] cover tag metadata and
+//! token status responses.
+//!
+//! ### Bundled API responses
+//!
+//! ```rust
+//! use spur::test_utils::{load_all_fixtures, load_fixture};
+//!
+//! let mullvad = load_fixture("mullvad_vpn");
+//! for (name, context) in load_all_fixtures() {
+//!     // ... run every recorded response through your pipeline ...
+//! }
+//! ```
+//!
+//! Responses captured from production can be scrubbed with [`anonymize`]
+//! before being committed as fixtures, and [`coverage`] reports which
+//! fields and enum variants a fixture set leaves untested.
+//!
+//! ### Assertions
+//!
+//! ```rust
+//! use spur::test_utils::fixtures;
+//! use spur::test_utils::matchers::{assert_that, infrastructure_is, tunnel_operated_by};
+//! use spur::Infrastructure;
+//!
+//! assert_that(
+//!     &fixtures::vpn_ip(),
+//!     infrastructure_is(Infrastructure::Datacenter).and(tunnel_operated_by("NordVPN")),
+//! );
+//! ```
+//!
+//! ### Generated datasets
+//!
+//! ```rust
+//! use spur::test_utils::{generate_many, Profile};
+//!
+//! // Reproducible: the same seed always yields the same contexts
+//! let vpns = generate_many(42, Profile::Vpn, 500);
+//! ```
+//!
+//! [`fake_feed`] and [`write_fake_feed`] render a mix of profiles as an
+//! NDJSON feed for testing feed ingestion; with the `gzip` feature,
+//! `fake_feed_gz` produces the compressed form. [`timeline`] builds
+//! successive snapshots of one address (clean, then a VPN exit, then Tor,
+//! ...) for diffing and alerting code.
+//!
 //! ### Monocle API
 //!
 //! ```rust
@@ -48,13 +97,38 @@
 //! let vpn_user = monocle_fixtures::vpn_assessment();
 //! let clean_user = monocle_fixtures::clean_assessment();
 //! ```
+//!
+//! [`monocle_bundle`] and [`encrypted_monocle_bundle`] wrap an assessment in
+//! an opaque bundle string for end-to-end tests of Monocle handling, and
+//! [`open_test_bundle`] decodes it again in a mock Decryption API.
 
 use crate::context::{
-    Ai, AutonomousSystem, Behavior, Client, Concentration, DeviceType, Infrastructure, IpContext,
-    Location, Risk, Service, Tunnel, TunnelEntry, TunnelType,
+    Ai, ApiStatus, AutonomousSystem, Behavior, Client, Concentration, DeviceType, Infrastructure,
+    IpContext, Location, Risk, Service, TagMetadata, TagMetrics, Tunnel, TunnelEntry, TunnelType,
 };
 use crate::monocle::Assessment;
 
+mod anonymize;
+mod bundled;
+mod coverage;
+mod feed;
+mod generate;
+pub mod matchers;
+mod monocle_bundle;
+mod snapshot;
+mod timeline;
+
+pub use anonymize::anonymize;
+pub use bundled::{fixture_json, fixture_names, load_all_fixtures, load_fixture};
+pub use coverage::{coverage, Coverage};
+#[cfg(feature = "gzip")]
+pub use feed::fake_feed_gz;
+pub use feed::{fake_feed, write_fake_feed};
+pub use generate::{generate, generate_many, Profile};
+pub use monocle_bundle::{encrypted_monocle_bundle, monocle_bundle, open_test_bundle, TestBundle};
+pub use snapshot::{assert_matches_snapshot, to_canonical_json, UPDATE_SNAPSHOTS_ENV};
+pub use timeline::{timeline, TimelineEvent};
+
 // =============================================================================
 // Context API Test Utilities
 // =============================================================================
@@ -269,12 +343,577 @@ impl IpContextBuilder {
         self
     }
 
+    /// Add a fully specified tunnel, e.g. one made with [`TunnelBuilder`].
+    pub fn add_tunnel(mut self, tunnel: Tunnel) -> Self {
+        let tunnels = self.context.tunnels.get_or_insert_with(Vec::new);
+        tunnels.push(tunnel);
+        self
+    }
+
+    /// Set the location, e.g. one made with [`LocationBuilder`].
+    pub fn with_location(mut self, location: Location) -> Self {
+        self.context.location = Some(location);
+        self
+    }
+
+    /// Set the client information, e.g. one made with [`ClientBuilder`].
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.context.client = Some(client);
+        self
+    }
+
     /// Build the final [`IpContext`].
     pub fn build(self) -> IpContext {
         self.context
     }
 }
 
+/// Builder for creating [`Tunnel`] instances in tests.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::test_utils::{IpContextBuilder, TunnelBuilder, TunnelEntryBuilder};
+/// use spur::TunnelType;
+///
+/// let tunnel = TunnelBuilder::new()
+///     .tunnel_type(TunnelType::Vpn)
+///     .operator("NORD_VPN")
+///     .anonymous(true)
+///     .add_entry(TunnelEntryBuilder::new().ip("89.39.106.82").country("NL").build())
+///     .build();
+///
+/// let context = IpContextBuilder::new().add_tunnel(tunnel).build();
+/// assert_eq!(context.tunnels.unwrap()[0].entries.as_ref().unwrap().len(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TunnelBuilder {
+    tunnel: Tunnel,
+}
+
+impl TunnelBuilder {
+    /// Create a new empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the tunnel type.
+    pub fn tunnel_type(mut self, tunnel_type: TunnelType) -> Self {
+        self.tunnel.tunnel_type = Some(tunnel_type);
+        self
+    }
+
+    /// Set the operator name.
+    pub fn operator(mut self, operator: &str) -> Self {
+        self.tunnel.operator = Some(operator.to_string());
+        self
+    }
+
+    /// Set the anonymous flag.
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.tunnel.anonymous = Some(anonymous);
+        self
+    }
+
+    /// Add an entry point.
+    pub fn add_entry(mut self, entry: TunnelEntry) -> Self {
+        let entries = self.tunnel.entries.get_or_insert_with(Vec::new);
+        entries.push(entry);
+        self
+    }
+
+    /// Set all entry points.
+    pub fn entries(mut self, entries: Vec<TunnelEntry>) -> Self {
+        self.tunnel.entries = Some(entries);
+        self
+    }
+
+    /// Build the final [`Tunnel`].
+    pub fn build(self) -> Tunnel {
+        self.tunnel
+    }
+}
+
+/// Builder for creating [`TunnelEntry`] instances in tests.
+#[derive(Debug, Clone, Default)]
+pub struct TunnelEntryBuilder {
+    entry: TunnelEntry,
+}
+
+impl TunnelEntryBuilder {
+    /// Create a new empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the entry IP address.
+    pub fn ip(mut self, ip: &str) -> Self {
+        self.entry.ip = Some(ip.to_string());
+        self
+    }
+
+    /// Set the entry country, keeping any other location fields.
+    pub fn country(mut self, country: &str) -> Self {
+        let location = self.entry.location.get_or_insert_with(Location::default);
+        location.country = Some(country.to_string());
+        self
+    }
+
+    /// Set the full entry location.
+    pub fn location(mut self, location: Location) -> Self {
+        self.entry.location = Some(location);
+        self
+    }
+
+    /// Set autonomous system information.
+    pub fn asn(mut self, number: u32, organization: &str) -> Self {
+        self.entry.autonomous_system = Some(AutonomousSystem {
+            number: Some(number),
+            organization: Some(organization.to_string()),
+        });
+        self
+    }
+
+    /// Build the final [`TunnelEntry`].
+    pub fn build(self) -> TunnelEntry {
+        self.entry
+    }
+}
+
+/// Builder for creating [`Client`] instances in tests.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::test_utils::{ClientBuilder, ConcentrationBuilder};
+/// use spur::{Behavior, DeviceType};
+///
+/// let client = ClientBuilder::new()
+///     .count(12)
+///     .countries(3)
+///     .add_behavior(Behavior::FileSharing)
+///     .add_type(DeviceType::Mobile)
+///     .concentration(ConcentrationBuilder::new().country("US").density(0.4).build())
+///     .build();
+///
+/// assert_eq!(client.count, Some(12));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClientBuilder {
+    client: Client,
+}
+
+impl ClientBuilder {
+    /// Create a new empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of observed clients.
+    pub fn count(mut self, count: u64) -> Self {
+        self.client.count = Some(count);
+        self
+    }
+
+    /// Set the number of countries clients were observed in.
+    pub fn countries(mut self, countries: u32) -> Self {
+        self.client.countries = Some(countries);
+        self
+    }
+
+    /// Set the geographic spread.
+    pub fn spread(mut self, spread: u64) -> Self {
+        self.client.spread = Some(spread);
+        self
+    }
+
+    /// Add a client behavior.
+    pub fn add_behavior(mut self, behavior: Behavior) -> Self {
+        let behaviors = self.client.behaviors.get_or_insert_with(Vec::new);
+        behaviors.push(behavior);
+        self
+    }
+
+    /// Set all client behaviors.
+    pub fn behaviors(mut self, behaviors: Vec<Behavior>) -> Self {
+        self.client.behaviors = Some(behaviors);
+        self
+    }
+
+    /// Add a device type.
+    pub fn add_type(mut self, device_type: DeviceType) -> Self {
+        let types = self.client.types.get_or_insert_with(Vec::new);
+        types.push(device_type);
+        self
+    }
+
+    /// Set all device types.
+    pub fn types(mut self, types: Vec<DeviceType>) -> Self {
+        self.client.types = Some(types);
+        self
+    }
+
+    /// Add a proxy service observed on the clients.
+    pub fn add_proxy(mut self, proxy: &str) -> Self {
+        let proxies = self.client.proxies.get_or_insert_with(Vec::new);
+        proxies.push(proxy.to_string());
+        self
+    }
+
+    /// Set the geographic concentration.
+    pub fn concentration(mut self, concentration: Concentration) -> Self {
+        self.client.concentration = Some(concentration);
+        self
+    }
+
+    /// Build the final [`Client`].
+    pub fn build(self) -> Client {
+        self.client
+    }
+}
+
+/// Builder for creating [`Location`] instances in tests.
+#[derive(Debug, Clone, Default)]
+pub struct LocationBuilder {
+    location: Location,
+}
+
+impl LocationBuilder {
+    /// Create a new empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the country code.
+    pub fn country(mut self, country: &str) -> Self {
+        self.location.country = Some(country.to_string());
+        self
+    }
+
+    /// Set the state or region.
+    pub fn state(mut self, state: &str) -> Self {
+        self.location.state = Some(state.to_string());
+        self
+    }
+
+    /// Set the city.
+    pub fn city(mut self, city: &str) -> Self {
+        self.location.city = Some(city.to_string());
+        self
+    }
+
+    /// Set latitude and longitude.
+    pub fn coordinates(mut self, latitude: f64, longitude: f64) -> Self {
+        self.location.latitude = Some(latitude);
+        self.location.longitude = Some(longitude);
+        self
+    }
+
+    /// Build the final [`Location`].
+    pub fn build(self) -> Location {
+        self.location
+    }
+}
+
+/// Builder for creating [`Concentration`] instances in tests.
+#[derive(Debug, Clone, Default)]
+pub struct ConcentrationBuilder {
+    concentration: Concentration,
+}
+
+impl ConcentrationBuilder {
+    /// Create a new empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the country code.
+    pub fn country(mut self, country: &str) -> Self {
+        self.concentration.country = Some(country.to_string());
+        self
+    }
+
+    /// Set the state or region.
+    pub fn state(mut self, state: &str) -> Self {
+        self.concentration.state = Some(state.to_string());
+        self
+    }
+
+    /// Set the city.
+    pub fn city(mut self, city: &str) -> Self {
+        self.concentration.city = Some(city.to_string());
+        self
+    }
+
+    /// Set the density (0.0 to 1.0).
+    pub fn density(mut self, density: f64) -> Self {
+        self.concentration.density = Some(density);
+        self
+    }
+
+    /// Set the geohash.
+    pub fn geohash(mut self, geohash: &str) -> Self {
+        self.concentration.geohash = Some(geohash.to_string());
+        self
+    }
+
+    /// Set the skew.
+    pub fn skew(mut self, skew: u64) -> Self {
+        self.concentration.skew = Some(skew);
+        self
+    }
+
+    /// Build the final [`Concentration`].
+    pub fn build(self) -> Concentration {
+        self.concentration
+    }
+}
+
+/// Builder for creating [`TagMetadata`] instances in tests.
+///
+/// The API encodes flags and metrics as strings; the builder takes native
+/// values and formats them the same way.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::test_utils::TagMetadataBuilder;
+///
+/// let meta = TagMetadataBuilder::new()
+///     .tag("EXAMPLE_PROXY")
+///     .name("Example Proxy")
+///     .add_category("RESIDENTIAL_PROXY")
+///     .anonymous(true)
+///     .distinct_ips(250_000)
+///     .build();
+///
+/// assert_eq!(meta.is_anonymous.as_deref(), Some("true"));
+/// assert_eq!(meta.metrics.unwrap().distinct_ips.as_deref(), Some("250000"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TagMetadataBuilder {
+    metadata: TagMetadata,
+}
+
+impl TagMetadataBuilder {
+    /// Create a new empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the service tag (e.g., "OXYLABS_PROXY").
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.metadata.tag = Some(tag.to_string());
+        self
+    }
+
+    /// Set the human-readable name.
+    pub fn name(mut self, name: &str) -> Self {
+        self.metadata.name = Some(name.to_string());
+        self
+    }
+
+    /// Set the description.
+    pub fn description(mut self, description: &str) -> Self {
+        self.metadata.description = Some(description.to_string());
+        self
+    }
+
+    /// Set the website.
+    pub fn website(mut self, website: &str) -> Self {
+        self.metadata.website = Some(website.to_string());
+        self
+    }
+
+    /// Add a product category (e.g., "RESIDENTIAL_PROXY").
+    pub fn add_category(mut self, category: &str) -> Self {
+        let categories = self.metadata.categories.get_or_insert_with(Vec::new);
+        categories.push(category.to_string());
+        self
+    }
+
+    /// Set the supported platforms.
+    pub fn platforms(mut self, platforms: &[&str]) -> Self {
+        self.metadata.platforms = Some(platforms.iter().map(|s| s.to_string()).collect());
+        self
+    }
+
+    /// Set the protocols.
+    pub fn protocols(mut self, protocols: &[&str]) -> Self {
+        self.metadata.protocols = Some(protocols.iter().map(|s| s.to_string()).collect());
+        self
+    }
+
+    /// Set the targeting granularities (e.g., "CITY", "ASN").
+    pub fn targeting_types(mut self, types: &[&str]) -> Self {
+        self.metadata.targeting_types = Some(types.iter().map(|s| s.to_string()).collect());
+        self
+    }
+
+    /// Set whether crypto payments are accepted.
+    pub fn allows_crypto(mut self, value: bool) -> Self {
+        self.metadata.allows_crypto = Some(value.to_string());
+        self
+    }
+
+    /// Set whether free access is available.
+    pub fn allows_free_access(mut self, value: bool) -> Self {
+        self.metadata.allows_free_access = Some(value.to_string());
+        self
+    }
+
+    /// Set whether multi-hop is offered.
+    pub fn allows_multihop(mut self, value: bool) -> Self {
+        self.metadata.allows_multihop = Some(value.to_string());
+        self
+    }
+
+    /// Set whether torrent traffic is permitted.
+    pub fn allows_torrents(mut self, value: bool) -> Self {
+        self.metadata.allows_torrents = Some(value.to_string());
+        self
+    }
+
+    /// Set whether white-label versions exist.
+    pub fn allows_white_label(mut self, value: bool) -> Self {
+        self.metadata.allows_white_label = Some(value.to_string());
+        self
+    }
+
+    /// Set whether the service anonymizes traffic.
+    pub fn anonymous(mut self, value: bool) -> Self {
+        self.metadata.is_anonymous = Some(value.to_string());
+        self
+    }
+
+    /// Set whether the service offers callback proxies.
+    pub fn callback_proxy(mut self, value: bool) -> Self {
+        self.metadata.is_callback_proxy = Some(value.to_string());
+        self
+    }
+
+    /// Set whether the service targets enterprises.
+    pub fn enterprise(mut self, value: bool) -> Self {
+        self.metadata.is_enterprise = Some(value.to_string());
+        self
+    }
+
+    /// Set whether the service is inactive or defunct.
+    pub fn inactive(mut self, value: bool) -> Self {
+        self.metadata.is_inactive = Some(value.to_string());
+        self
+    }
+
+    /// Set whether the service claims a no-log policy.
+    pub fn no_log(mut self, value: bool) -> Self {
+        self.metadata.is_no_log = Some(value.to_string());
+        self
+    }
+
+    /// Set all metrics at once.
+    pub fn metrics(mut self, metrics: TagMetrics) -> Self {
+        self.metadata.metrics = Some(metrics);
+        self
+    }
+
+    /// Set the average device count metric.
+    pub fn average_device_count(mut self, value: f64) -> Self {
+        self.metrics_mut().average_device_count = Some(value.to_string());
+        self
+    }
+
+    /// Set the churn rate metric.
+    pub fn churn_rate(mut self, value: f64) -> Self {
+        self.metrics_mut().churn_rate = Some(value.to_string());
+        self
+    }
+
+    /// Set the distinct ASN count metric.
+    pub fn distinct_asns(mut self, value: u64) -> Self {
+        self.metrics_mut().distinct_asns = Some(value.to_string());
+        self
+    }
+
+    /// Set the distinct country count metric.
+    pub fn distinct_countries(mut self, value: u64) -> Self {
+        self.metrics_mut().distinct_countries = Some(value.to_string());
+        self
+    }
+
+    /// Set the distinct IP count metric.
+    pub fn distinct_ips(mut self, value: u64) -> Self {
+        self.metrics_mut().distinct_ips = Some(value.to_string());
+        self
+    }
+
+    /// Set the distinct ISP count metric.
+    pub fn distinct_isps(mut self, value: u64) -> Self {
+        self.metrics_mut().distinct_isps = Some(value.to_string());
+        self
+    }
+
+    fn metrics_mut(&mut self) -> &mut TagMetrics {
+        self.metadata
+            .metrics
+            .get_or_insert_with(TagMetrics::default)
+    }
+
+    /// Build the final [`TagMetadata`].
+    pub fn build(self) -> TagMetadata {
+        self.metadata
+    }
+}
+
+/// Builder for creating [`ApiStatus`] instances in tests.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::test_utils::ApiStatusBuilder;
+///
+/// let status = ApiStatusBuilder::new()
+///     .active(true)
+///     .queries_remaining(25)
+///     .service_tier("online")
+///     .build();
+///
+/// assert_eq!(status.queries_remaining, Some(25));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ApiStatusBuilder {
+    status: ApiStatus,
+}
+
+impl ApiStatusBuilder {
+    /// Create a new empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether the token is active.
+    pub fn active(mut self, active: bool) -> Self {
+        self.status.active = Some(active);
+        self
+    }
+
+    /// Set the number of queries remaining.
+    pub fn queries_remaining(mut self, remaining: u64) -> Self {
+        self.status.queries_remaining = Some(remaining);
+        self
+    }
+
+    /// Set the service tier.
+    pub fn service_tier(mut self, tier: &str) -> Self {
+        self.status.service_tier = Some(tier.to_string());
+        self
+    }
+
+    /// Build the final [`ApiStatus`].
+    pub fn build(self) -> ApiStatus {
+        self.status
+    }
+}
+
 /// Pre-built test fixtures for common Context API scenarios.
 ///
 /// These fixtures represent typical IP contexts that you might encounter
@@ -436,6 +1075,202 @@ pub mod fixtures {
             ])
             .build()
     }
+
+    /// A mobile carrier IPv6 address.
+    ///
+    /// Matches `tests/fixtures/mobile_ipv6.json`.
+    pub fn ipv6_ip() -> IpContext {
+        IpContextBuilder::new()
+            .ip("2607:fb90:1c2e:4a61::1")
+            .infrastructure(Infrastructure::Mobile)
+            .asn(21928, "T-Mobile USA, Inc.")
+            .organization("T-Mobile USA, Inc.")
+            .with_location(us_location("Washington", "Seattle"))
+            .client(12, 1)
+            .client_behaviors(vec![Behavior::FileSharing])
+            .client_types(vec![DeviceType::Mobile])
+            .build()
+    }
+
+    /// A Starlink satellite connection.
+    ///
+    /// `SATELLITE` is not a named [`Infrastructure`] variant, so this
+    /// exercises the `Other` fallback. Matches
+    /// `tests/fixtures/satellite_starlink.json`.
+    pub fn satellite_ip() -> IpContext {
+        IpContextBuilder::new()
+            .ip("203.0.113.88")
+            .infrastructure(Infrastructure::Other("SATELLITE".to_string()))
+            .asn(14593, "Space Exploration Technologies Corporation")
+            .organization("SpaceX Services, Inc.")
+            .with_location(us_location("Colorado", "Denver"))
+            .client(3, 1)
+            .client_types(vec![DeviceType::Desktop, DeviceType::Mobile])
+            .build()
+    }
+
+    /// A residential IP flagged as a callback proxy exit.
+    ///
+    /// Matches `tests/fixtures/residential_callback_proxy.json`.
+    pub fn callback_proxy_ip() -> IpContext {
+        IpContextBuilder::new()
+            .ip("198.51.100.23")
+            .infrastructure(Infrastructure::Residential)
+            .asn(20115, "Charter Communications LLC")
+            .organization("Charter Communications")
+            .with_location(us_location("Texas", "Austin"))
+            .with_client(
+                ClientBuilder::new()
+                    .count(18)
+                    .countries(1)
+                    .add_proxy("OXYLABS_PROXY")
+                    .add_proxy("IPROYAL_PROXY")
+                    .add_type(DeviceType::Desktop)
+                    .build(),
+            )
+            .add_risk(Risk::CallbackProxy)
+            .build()
+    }
+
+    /// A residential IP whose clients are concentrated far from its
+    /// geolocation.
+    ///
+    /// Matches `tests/fixtures/residential_geo_mismatch.json`.
+    pub fn geo_mismatch_ip() -> IpContext {
+        IpContextBuilder::new()
+            .ip("203.0.113.201")
+            .infrastructure(Infrastructure::Residential)
+            .asn(3320, "Deutsche Telekom AG")
+            .organization("Deutsche Telekom AG")
+            .with_location(
+                LocationBuilder::new()
+                    .country("DE")
+                    .state("Berlin")
+                    .city("Berlin")
+                    .build(),
+            )
+            .with_client(
+                ClientBuilder::new()
+                    .count(6)
+                    .countries(2)
+                    .add_type(DeviceType::Mobile)
+                    .concentration(
+                        ConcentrationBuilder::new()
+                            .country("VN")
+                            .city("Hanoi")
+                            .density(0.92)
+                            .geohash("w7er8")
+                            .skew(8900)
+                            .build(),
+                    )
+                    .build(),
+            )
+            .add_risk(Risk::GeoMismatch)
+            .build()
+    }
+
+    fn us_location(state: &str, city: &str) -> Location {
+        LocationBuilder::new()
+            .country("US")
+            .state(state)
+            .city(city)
+            .build()
+    }
+
+    /// Metadata for a large residential proxy network, modeled on the
+    /// Oxylabs tag.
+    pub fn oxylabs_like() -> TagMetadata {
+        TagMetadataBuilder::new()
+            .tag("OXYLABS_PROXY")
+            .name("Oxylabs")
+            .description("OxyLabs is the second largest proxy provider tracked.")
+            .website("https://oxylabs.io")
+            .add_category("RESIDENTIAL_PROXY")
+            .add_category("DATACENTER_PROXY")
+            .add_category("MOBILE_PROXY")
+            .add_category("ISP_PROXY")
+            .platforms(&["ROUTER"])
+            .protocols(&[])
+            .targeting_types(&["CITY", "STATE", "COUNTRY", "ASN"])
+            .allows_crypto(false)
+            .allows_free_access(false)
+            .allows_multihop(false)
+            .allows_torrents(false)
+            .allows_white_label(true)
+            .anonymous(true)
+            .callback_proxy(true)
+            .enterprise(false)
+            .inactive(false)
+            .no_log(true)
+            .average_device_count(37.20332478669546)
+            .churn_rate(0.08675012801772562)
+            .distinct_asns(25334)
+            .distinct_countries(235)
+            .distinct_ips(6367903)
+            .distinct_isps(67413)
+            .build()
+    }
+
+    /// Metadata for a consumer VPN service.
+    pub fn consumer_vpn() -> TagMetadata {
+        TagMetadataBuilder::new()
+            .tag("NORD_VPN")
+            .name("NordVPN")
+            .description("A popular consumer VPN service.")
+            .website("https://nordvpn.com")
+            .platforms(&["WINDOWS", "MACOS", "LINUX", "ANDROID", "IOS"])
+            .protocols(&["OPENVPN", "WIREGUARD", "IPSEC"])
+            .allows_crypto(true)
+            .allows_free_access(false)
+            .allows_multihop(true)
+            .allows_torrents(true)
+            .anonymous(true)
+            .callback_proxy(false)
+            .enterprise(false)
+            .inactive(false)
+            .no_log(true)
+            .distinct_countries(111)
+            .distinct_ips(5400)
+            .build()
+    }
+
+    /// Metadata for a service that has shut down.
+    pub fn defunct_service() -> TagMetadata {
+        TagMetadataBuilder::new()
+            .tag("DEFUNCT_VPN")
+            .name("Defunct VPN")
+            .description("A VPN service that is no longer operating.")
+            .anonymous(true)
+            .inactive(true)
+            .distinct_ips(0)
+            .build()
+    }
+
+    /// An active token with plenty of quota left.
+    pub fn active_status() -> ApiStatus {
+        ApiStatusBuilder::new()
+            .active(true)
+            .queries_remaining(49283)
+            .service_tier("online")
+            .build()
+    }
+
+    /// An active token that has used up its quota for the billing cycle.
+    pub fn exhausted_status() -> ApiStatus {
+        ApiStatusBuilder::new()
+            .active(true)
+            .queries_remaining(0)
+            .service_tier("online")
+            .build()
+    }
+
+    /// A token that has been deactivated.
+    pub fn inactive_token() -> ApiStatus {
+        ApiStatusBuilder::new()
+            .active(false)
+            .queries_remaining(0)
+            .build()
+    }
 }
 
 /// Convert an [`IpContext`] to JSON for testing.
@@ -676,6 +1511,54 @@ mod tests {
         assert_eq!(tunnels.len(), 3);
     }
 
+    #[test]
+    fn test_nested_builders() {
+        let context = IpContextBuilder::new()
+            .add_tunnel(
+                TunnelBuilder::new()
+                    .tunnel_type(TunnelType::Proxy)
+                    .operator("OXYLABS_PROXY")
+                    .add_entry(
+                        TunnelEntryBuilder::new()
+                            .ip("5.6.7.8")
+                            .country("DE")
+                            .asn(3320, "Deutsche Telekom AG")
+                            .build(),
+                    )
+                    .build(),
+            )
+            .with_location(
+                LocationBuilder::new()
+                    .country("US")
+                    .city("Dallas")
+                    .coordinates(32.78, -96.8)
+                    .build(),
+            )
+            .with_client(
+                ClientBuilder::new()
+                    .count(3)
+                    .add_type(DeviceType::Desktop)
+                    .add_proxy("OXYLABS_PROXY")
+                    .concentration(ConcentrationBuilder::new().geohash("9vg").skew(12).build())
+                    .build(),
+            )
+            .build();
+
+        let tunnel = &context.tunnels.as_ref().unwrap()[0];
+        assert_eq!(tunnel.anonymous, None);
+        let entry = &tunnel.entries.as_ref().unwrap()[0];
+        assert_eq!(
+            entry.location.as_ref().unwrap().country.as_deref(),
+            Some("DE")
+        );
+        assert_eq!(entry.autonomous_system.as_ref().unwrap().number, Some(3320));
+        assert_eq!(context.location.unwrap().longitude, Some(-96.8));
+
+        let client = context.client.unwrap();
+        assert_eq!(client.proxies, Some(vec!["OXYLABS_PROXY".to_string()]));
+        assert_eq!(client.concentration.unwrap().skew, Some(12));
+    }
+
     #[test]
     fn test_fixtures_residential() {
         let ctx = fixtures::residential_ip();
@@ -702,6 +1585,73 @@ mod tests {
             .any(|t| t.tunnel_type == Some(TunnelType::Tor)));
     }
 
+    #[test]
+    fn test_uncommon_shape_fixtures() {
+        let ipv6 = fixtures::ipv6_ip();
+        assert!(ipv6
+            .ip
+            .as_deref()
+            .unwrap()
+            .parse::<std::net::Ipv6Addr>()
+            .is_ok());
+
+        assert_eq!(
+            fixtures::satellite_ip().infrastructure,
+            Some(Infrastructure::Other("SATELLITE".to_string()))
+        );
+        assert!(fixtures::callback_proxy_ip()
+            .risks
+            .unwrap()
+            .contains(&Risk::CallbackProxy));
+        assert!(fixtures::geo_mismatch_ip()
+            .risks
+            .unwrap()
+            .contains(&Risk::GeoMismatch));
+    }
+
+    #[test]
+    fn test_uncommon_shape_fixtures_match_json() {
+        assert_eq!(fixtures::ipv6_ip(), load_fixture("mobile_ipv6"));
+        assert_eq!(fixtures::satellite_ip(), load_fixture("satellite_starlink"));
+        assert_eq!(
+            fixtures::callback_proxy_ip(),
+            load_fixture("residential_callback_proxy")
+        );
+        assert_eq!(
+            fixtures::geo_mismatch_ip(),
+            load_fixture("residential_geo_mismatch")
+        );
+    }
+
+    #[test]
+    fn test_tag_metadata_fixtures() {
+        let oxylabs = fixtures::oxylabs_like();
+        assert_eq!(oxylabs.categories.as_ref().unwrap().len(), 4);
+        assert_eq!(oxylabs.is_callback_proxy.as_deref(), Some("true"));
+        let metrics = oxylabs.metrics.unwrap();
+        assert_eq!(metrics.distinct_ips.as_deref(), Some("6367903"));
+        assert_eq!(metrics.churn_rate.as_deref(), Some("0.08675012801772562"));
+
+        assert_eq!(
+            fixtures::consumer_vpn().is_anonymous.as_deref(),
+            Some("true")
+        );
+        assert_eq!(
+            fixtures::defunct_service().is_inactive.as_deref(),
+            Some("true")
+        );
+    }
+
+    #[test]
+    fn test_api_status_fixtures() {
+        assert_eq!(fixtures::active_status().active, Some(true));
+        assert_eq!(fixtures::exhausted_status().queries_remaining, Some(0));
+
+        let inactive = fixtures::inactive_token();
+        assert_eq!(inactive.active, Some(false));
+        assert!(inactive.service_tier.is_none());
+    }
+
     #[test]
     fn test_json_roundtrip() {
         let original = fixtures::high_risk_ip();
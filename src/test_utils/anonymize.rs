@@ -0,0 +1,270 @@
+//! Scrubbing of captured API responses before they are committed as fixtures.
+
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::context::{Concentration, IpContext, Location};
+use crate::hash::fnv1a;
+use crate::rng::SplitMix64;
+
+/// The three IPv4 documentation blocks (RFC 5737), as their first three octets.
+const TEST_NETS: [[u8; 3]; 3] = [[192, 0, 2], [198, 51, 100], [203, 0, 113]];
+
+/// Number of IPv4 documentation addresses: 254 usable hosts per block.
+const TEST_NET_HOSTS: u64 = 3 * 254;
+
+/// Largest distance, in degrees, coordinates are moved by.
+const COORDINATE_JITTER: f64 = 0.25;
+
+/// Characters of a concentration geohash that are kept, a cell of roughly
+/// 156 km by 156 km.
+const GEOHASH_PRECISION: usize = 3;
+
+/// Replace identifying details of a captured context with safe stand-ins.
+///
+/// - every IP address (the context's own and tunnel entry addresses) is
+///   mapped to a documentation address: TEST-NET-1/2/3 for IPv4 and
+///   `2001:db8::/32` for IPv6
+/// - latitude and longitude are rounded to one decimal place and shifted by
+///   up to a quarter of a degree
+/// - the client concentration geohash is truncated to three characters and
+///   its city is removed
+/// - organization names, including those of autonomous systems, are removed
+///
+/// Everything else (risks, tunnel operators, client statistics, ...) is kept,
+/// since that is usually what a fixture is meant to exercise. The mapping is
+/// deterministic: anonymizing the same response twice yields the same
+/// fixture, and an address that appears more than once in a context is
+/// always replaced by the same documentation address.
+///
+/// There are only 762 IPv4 documentation addresses, so two different
+/// inputs can hash to the same one. Within one context, later addresses are
+/// moved to the next free documentation address so that distinct addresses
+/// stay distinct; a context with more than 762 distinct IPv4 addresses
+/// cannot be kept apart and reuses addresses. Contexts anonymized separately
+/// may share addresses.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::test_utils::{anonymize, load_fixture};
+///
+/// let captured = load_fixture("mullvad_vpn");
+/// let safe = anonymize(&captured);
+///
+/// assert_ne!(safe.ip, captured.ip);
+/// assert!(safe.organization.is_none());
+/// assert_eq!(safe.tunnels, anonymize(&captured).tunnels);
+/// ```
+pub fn anonymize(context: &IpContext) -> IpContext {
+    let mut context = context.clone();
+    let mut rng = SplitMix64::new(fnv1a(context.ip.as_deref().unwrap_or_default().as_bytes()));
+    let mut addresses = AddressMap::default();
+
+    context.ip = context.ip.as_deref().map(|ip| addresses.map(ip));
+    context.organization = None;
+    if let Some(asys) = context.autonomous_system.as_mut() {
+        asys.organization = None;
+    }
+    if let Some(location) = context.location.as_mut() {
+        fuzz_location(location, &mut rng);
+    }
+    if let Some(concentration) = context
+        .client
+        .as_mut()
+        .and_then(|client| client.concentration.as_mut())
+    {
+        coarsen_concentration(concentration);
+    }
+
+    for tunnel in context.tunnels.iter_mut().flatten() {
+        for entry in tunnel.entries.iter_mut().flatten() {
+            entry.ip = entry.ip.as_deref().map(|ip| addresses.map(ip));
+            if let Some(asys) = entry.autonomous_system.as_mut() {
+                asys.organization = None;
+            }
+            if let Some(location) = entry.location.as_mut() {
+                fuzz_location(location, &mut rng);
+            }
+        }
+    }
+
+    context
+}
+
+/// The documentation addresses assigned within one context.
+#[derive(Default)]
+struct AddressMap {
+    assigned: HashMap<String, String>,
+    used: HashSet<String>,
+}
+
+impl AddressMap {
+    /// Returns the documentation address for `ip`, assigning the first
+    /// unused candidate the first time `ip` is seen.
+    fn map(&mut self, ip: &str) -> String {
+        if let Some(mapped) = self.assigned.get(ip) {
+            return mapped.clone();
+        }
+        let mapped = (0..TEST_NET_HOSTS)
+            .map(|attempt| documentation_ip(ip, attempt))
+            .find(|candidate| !self.used.contains(candidate))
+            .unwrap_or_else(|| documentation_ip(ip, 0));
+        self.used.insert(mapped.clone());
+        self.assigned.insert(ip.to_string(), mapped.clone());
+        mapped
+    }
+}
+
+/// Map an address to a documentation address derived from its hash, moved
+/// `attempt` places along to step past collisions.
+///
+/// Strings that are not IPv6 addresses, including malformed ones, become
+/// IPv4 documentation addresses.
+fn documentation_ip(ip: &str, attempt: u64) -> String {
+    let hash = fnv1a(ip.as_bytes());
+    let mapped = match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V6(_)) => {
+            let hash = hash.wrapping_add(attempt);
+            IpAddr::V6(Ipv6Addr::new(
+                0x2001,
+                0xdb8,
+                (hash >> 48) as u16,
+                0,
+                (hash >> 32) as u16,
+                (hash >> 16) as u16,
+                hash as u16,
+                1,
+            ))
+        }
+        _ => {
+            let host = (hash % TEST_NET_HOSTS + attempt) % TEST_NET_HOSTS;
+            let [a, b, c] = TEST_NETS[(host / 254) as usize];
+            IpAddr::V4(Ipv4Addr::new(a, b, c, 1 + (host % 254) as u8))
+        }
+    };
+    mapped.to_string()
+}
+
+fn coarsen_concentration(concentration: &mut Concentration) {
+    concentration.city = None;
+    if let Some(geohash) = concentration.geohash.as_mut() {
+        *geohash = geohash.chars().take(GEOHASH_PRECISION).collect();
+    }
+}
+
+fn fuzz_location(location: &mut Location, rng: &mut SplitMix64) {
+    location.latitude = location.latitude.map(|lat| fuzz_coordinate(lat, 90.0, rng));
+    location.longitude = location
+        .longitude
+        .map(|lon| fuzz_coordinate(lon, 180.0, rng));
+}
+
+fn fuzz_coordinate(value: f64, limit: f64, rng: &mut SplitMix64) -> f64 {
+    let jitter = (rng.next_f64() * 2.0 - 1.0) * COORDINATE_JITTER;
+    let fuzzed = ((value + jitter) * 10.0).round() / 10.0;
+    fuzzed.clamp(-limit, limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::TunnelEntry;
+    use crate::test_utils::{load_all_fixtures, IpContextBuilder, TunnelBuilder};
+
+    fn is_documentation(ip: &str) -> bool {
+        match ip.parse::<IpAddr>().unwrap() {
+            IpAddr::V4(v4) => {
+                let [a, b, c, _] = v4.octets();
+                TEST_NETS.contains(&[a, b, c])
+            }
+            IpAddr::V6(v6) => v6.segments()[..2] == [0x2001, 0xdb8],
+        }
+    }
+
+    #[test]
+    fn test_anonymize_fixtures() {
+        for (name, original) in load_all_fixtures() {
+            let safe = anonymize(&original);
+            assert_eq!(safe, anonymize(&original), "{} not deterministic", name);
+            assert!(safe.organization.is_none(), "{}", name);
+            assert_eq!(safe.risks, original.risks, "{}", name);
+            if let Some(ip) = safe.ip.as_deref() {
+                assert!(is_documentation(ip), "{}: {}", name, ip);
+            }
+            for entry in safe
+                .tunnels
+                .iter()
+                .flatten()
+                .flat_map(|t| t.entries.iter().flatten())
+            {
+                assert!(is_documentation(entry.ip.as_deref().unwrap()), "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_anonymize_keeps_address_family() {
+        let v6 = IpContextBuilder::new().ip("2a01:4f8::1").build();
+        assert!(anonymize(&v6).ip.unwrap().starts_with("2001:db8:"));
+
+        let v4 = IpContextBuilder::new().ip("89.39.106.191").build();
+        let ip = anonymize(&v4).ip.unwrap();
+        assert!(ip.parse::<Ipv4Addr>().is_ok() && is_documentation(&ip));
+    }
+
+    #[test]
+    fn test_anonymize_fuzzes_coordinates() {
+        let context = IpContextBuilder::new()
+            .ip("1.2.3.4")
+            .asn(7922, "Comcast")
+            .location_full("US", Some("PA"), Some("Philadelphia"), 39.9526, -75.1652)
+            .build();
+        let safe = anonymize(&context);
+        let location = safe.location.unwrap();
+        let (lat, lon) = (location.latitude.unwrap(), location.longitude.unwrap());
+        assert!((lat - 39.9526).abs() <= 0.3 && lat != 39.9526);
+        assert!((lon + 75.1652).abs() <= 0.3);
+        assert_eq!(location.city.as_deref(), Some("Philadelphia"));
+        assert_eq!(safe.autonomous_system.unwrap().number, Some(7922));
+    }
+
+    #[test]
+    fn test_anonymize_coarsens_concentration() {
+        let safe = anonymize(&crate::test_utils::fixtures::geo_mismatch_ip());
+        let concentration = safe.client.unwrap().concentration.unwrap();
+        assert_eq!(concentration.geohash.as_deref(), Some("w7e"));
+        assert_eq!(concentration.city, None);
+        assert_eq!(concentration.country.as_deref(), Some("VN"));
+    }
+
+    #[test]
+    fn test_anonymize_keeps_addresses_distinct() {
+        // More addresses than fit without a hash collision.
+        let ips: Vec<String> = (0..500)
+            .map(|i| format!("10.0.{}.{}", i / 256, i % 256))
+            .collect();
+        let tunnel = TunnelBuilder::new()
+            .entries(
+                ips.iter()
+                    .map(|ip| TunnelEntry::from_ip(ip.clone()))
+                    .collect(),
+            )
+            .build();
+        let context = IpContextBuilder::new()
+            .ip(&ips[0])
+            .add_tunnel(tunnel)
+            .build();
+
+        let safe = anonymize(&context);
+        let mapped: Vec<&str> = safe.tunnels.as_ref().unwrap()[0]
+            .entries
+            .iter()
+            .flatten()
+            .map(|entry| entry.ip.as_deref().unwrap())
+            .collect();
+        let distinct: HashSet<&str> = mapped.iter().copied().collect();
+        assert_eq!(distinct.len(), ips.len());
+        assert_eq!(safe.ip.as_deref(), Some(mapped[0]));
+    }
+}
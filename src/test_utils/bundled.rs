@@ -0,0 +1,149 @@
+//! JSON fixtures bundled into the crate.
+
+use crate::context::IpContext;
+
+/// Every file in `tests/fixtures`, keyed by file stem.
+const BUNDLED: &[(&str, &str)] = &[
+    (
+        "ai_scraper_response",
+        include_str!("../../tests/fixtures/ai_scraper_response.json"),
+    ),
+    ("ipv6", include_str!("../../tests/fixtures/ipv6.json")),
+    (
+        "library_of_congress",
+        include_str!("../../tests/fixtures/library_of_congress.json"),
+    ),
+    (
+        "mobile_ipv6",
+        include_str!("../../tests/fixtures/mobile_ipv6.json"),
+    ),
+    (
+        "mullvad_vpn",
+        include_str!("../../tests/fixtures/mullvad_vpn.json"),
+    ),
+    (
+        "proton_vpn",
+        include_str!("../../tests/fixtures/proton_vpn.json"),
+    ),
+    (
+        "residential_callback_proxy",
+        include_str!("../../tests/fixtures/residential_callback_proxy.json"),
+    ),
+    (
+        "residential_geo_mismatch",
+        include_str!("../../tests/fixtures/residential_geo_mismatch.json"),
+    ),
+    (
+        "residential_response",
+        include_str!("../../tests/fixtures/residential_response.json"),
+    ),
+    (
+        "rvpn_dataimpulse",
+        include_str!("../../tests/fixtures/rvpn_dataimpulse.json"),
+    ),
+    (
+        "rvpn_geonode_proxy",
+        include_str!("../../tests/fixtures/rvpn_geonode_proxy.json"),
+    ),
+    (
+        "rvpn_infatica",
+        include_str!("../../tests/fixtures/rvpn_infatica.json"),
+    ),
+    (
+        "rvpn_ipidea",
+        include_str!("../../tests/fixtures/rvpn_ipidea.json"),
+    ),
+    (
+        "rvpn_massive_proxy",
+        include_str!("../../tests/fixtures/rvpn_massive_proxy.json"),
+    ),
+    (
+        "rvpn_nodemaven",
+        include_str!("../../tests/fixtures/rvpn_nodemaven.json"),
+    ),
+    (
+        "rvpn_oxylabs_proxy",
+        include_str!("../../tests/fixtures/rvpn_oxylabs_proxy.json"),
+    ),
+    (
+        "rvpn_soax_proxy",
+        include_str!("../../tests/fixtures/rvpn_soax_proxy.json"),
+    ),
+    (
+        "rvpn_virginia_beach",
+        include_str!("../../tests/fixtures/rvpn_virginia_beach.json"),
+    ),
+    (
+        "rvrpn_earnfm",
+        include_str!("../../tests/fixtures/rvrpn_earnfm.json"),
+    ),
+    (
+        "satellite_starlink",
+        include_str!("../../tests/fixtures/satellite_starlink.json"),
+    ),
+    (
+        "tor_response",
+        include_str!("../../tests/fixtures/tor_response.json"),
+    ),
+    (
+        "vpn_abcproxy",
+        include_str!("../../tests/fixtures/vpn_abcproxy.json"),
+    ),
+    (
+        "vpn_response",
+        include_str!("../../tests/fixtures/vpn_response.json"),
+    ),
+];
+
+/// Returns the names of the bundled fixtures, without the `.json` suffix.
+pub fn fixture_names() -> impl Iterator<Item = &'static str> {
+    BUNDLED.iter().map(|(name, _)| *name)
+}
+
+/// Returns the raw JSON of a bundled fixture.
+///
+/// `name` is the file name in the crate's `tests/fixtures` directory, with or
+/// without the `.json` suffix.
+pub fn fixture_json(name: &str) -> Option<&'static str> {
+    let name = name.strip_suffix(".json").unwrap_or(name);
+    BUNDLED
+        .iter()
+        .find(|(stem, _)| *stem == name)
+        .map(|(_, json)| *json)
+}
+
+/// Load one of the crate's bundled fixtures as an [`IpContext`].
+///
+/// The fixtures are real (or realistic) API responses embedded at compile
+/// time, so downstream crates can reuse them without copying JSON into their
+/// own test trees. See [`fixture_names`] for what is available.
+///
+/// Panics if no fixture has that name.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::test_utils::load_fixture;
+/// use spur::TunnelType;
+///
+/// let context = load_fixture("tor_response");
+/// assert_eq!(context.tunnels.unwrap()[0].tunnel_type, Some(TunnelType::Tor));
+/// ```
+pub fn load_fixture(name: &str) -> IpContext {
+    let json = fixture_json(name).unwrap_or_else(|| {
+        panic!(
+            "no bundled fixture named {:?}; available: {}",
+            name,
+            fixture_names().collect::<Vec<_>>().join(", ")
+        )
+    });
+    serde_json::from_str(json)
+        .unwrap_or_else(|e| panic!("bundled fixture {} should parse: {}", name, e))
+}
+
+/// Load every bundled fixture, paired with its name.
+pub fn load_all_fixtures() -> Vec<(&'static str, IpContext)> {
+    fixture_names()
+        .map(|name| (name, load_fixture(name)))
+        .collect()
+}
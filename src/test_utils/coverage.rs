@@ -0,0 +1,371 @@
+//! Reports of which API shapes a set of fixtures exercises.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::context::{Behavior, DeviceType, Infrastructure, IpContext, Risk, Service, TunnelType};
+
+/// Every field of an [`IpContext`], as a path of JSON keys. Array elements
+/// are marked with `[]`.
+const FIELDS: &[&str] = &[
+    "ai",
+    "ai.bots",
+    "ai.scrapers",
+    "ai.services",
+    "as",
+    "as.number",
+    "as.organization",
+    "client",
+    "client.behaviors",
+    "client.concentration",
+    "client.concentration.city",
+    "client.concentration.country",
+    "client.concentration.density",
+    "client.concentration.geohash",
+    "client.concentration.skew",
+    "client.concentration.state",
+    "client.count",
+    "client.countries",
+    "client.proxies",
+    "client.spread",
+    "client.types",
+    "infrastructure",
+    "ip",
+    "location",
+    "location.city",
+    "location.country",
+    "location.latitude",
+    "location.longitude",
+    "location.state",
+    "organization",
+    "risks",
+    "services",
+    "tunnels",
+    "tunnels[].anonymous",
+    "tunnels[].entries",
+    "tunnels[].entries[].as",
+    "tunnels[].entries[].as.number",
+    "tunnels[].entries[].as.organization",
+    "tunnels[].entries[].ip",
+    "tunnels[].entries[].location",
+    "tunnels[].entries[].location.city",
+    "tunnels[].entries[].location.country",
+    "tunnels[].entries[].location.latitude",
+    "tunnels[].entries[].location.longitude",
+    "tunnels[].entries[].location.state",
+    "tunnels[].operator",
+    "tunnels[].type",
+];
+
+/// The enums checked for variant coverage, keyed by type name.
+const ENUMS: &[(&str, &[&str])] = &[
+    ("Behavior", Behavior::KNOWN_VALUES),
+    ("DeviceType", DeviceType::KNOWN_VALUES),
+    ("Infrastructure", Infrastructure::KNOWN_VALUES),
+    ("Risk", Risk::KNOWN_VALUES),
+    ("Service", Service::KNOWN_VALUES),
+    ("TunnelType", TunnelType::KNOWN_VALUES),
+];
+
+/// Which fields and enum variants a set of contexts exercises.
+///
+/// Produced by [`coverage`]. The [`Display`](fmt::Display) implementation
+/// renders a short report listing what is missing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    contexts: usize,
+    fields: BTreeMap<&'static str, usize>,
+    variants: BTreeMap<(&'static str, &'static str), usize>,
+    other_values: BTreeMap<(&'static str, String), usize>,
+}
+
+impl Coverage {
+    /// Returns the number of contexts examined.
+    pub fn contexts(&self) -> usize {
+        self.contexts
+    }
+
+    /// Returns how many contexts set the field at `path` (e.g.
+    /// `"client.concentration.geohash"` or `"tunnels[].type"`).
+    ///
+    /// Fields inside arrays are counted once per element.
+    pub fn field_count(&self, path: &str) -> usize {
+        self.fields.get(path).copied().unwrap_or(0)
+    }
+
+    /// Returns how many times the known variant `value` of `enum_name`
+    /// (e.g. `("Risk", "TUNNEL")`) was seen.
+    pub fn variant_count(&self, enum_name: &str, value: &str) -> usize {
+        self.variants
+            .iter()
+            .find(|((e, v), _)| *e == enum_name && *v == value)
+            .map_or(0, |(_, &count)| count)
+    }
+
+    /// Returns the field paths no context sets.
+    pub fn missing_fields(&self) -> Vec<&'static str> {
+        self.fields
+            .iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(&path, _)| path)
+            .collect()
+    }
+
+    /// Returns the known enum variants no context uses, as
+    /// `(enum name, API string)` pairs.
+    pub fn missing_variants(&self) -> Vec<(&'static str, &'static str)> {
+        self.variants
+            .iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(&key, _)| key)
+            .collect()
+    }
+
+    /// Returns the values that deserialized to an `Other` variant, as
+    /// `(enum name, value)` pairs.
+    pub fn other_values(&self) -> Vec<(&'static str, &str)> {
+        self.other_values
+            .keys()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect()
+    }
+
+    /// Returns `true` if every field and known variant is exercised.
+    pub fn is_complete(&self) -> bool {
+        self.missing_fields().is_empty() && self.missing_variants().is_empty()
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = self.fields.values().filter(|&&c| c > 0).count();
+        let variants = self.variants.values().filter(|&&c| c > 0).count();
+        writeln!(f, "contexts: {}", self.contexts)?;
+        writeln!(f, "fields:   {}/{}", fields, self.fields.len())?;
+        writeln!(f, "variants: {}/{}", variants, self.variants.len())?;
+        for path in self.missing_fields() {
+            writeln!(f, "  missing field {}", path)?;
+        }
+        for (name, value) in self.missing_variants() {
+            writeln!(f, "  missing variant {}::{}", name, value)?;
+        }
+        for (name, value) in self.other_values() {
+            writeln!(f, "  unknown value {}::Other({:?})", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Report which fields and enum variants `contexts` exercise.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::test_utils::{coverage, load_all_fixtures};
+///
+/// let fixtures: Vec<_> = load_all_fixtures().into_iter().map(|(_, c)| c).collect();
+/// let report = coverage(&fixtures);
+///
+/// assert!(report.field_count("tunnels[].operator") > 0);
+/// println!("{}", report);
+/// ```
+pub fn coverage<'a, I>(contexts: I) -> Coverage
+where
+    I: IntoIterator<Item = &'a IpContext>,
+{
+    let mut report = Coverage {
+        contexts: 0,
+        fields: FIELDS.iter().map(|&path| (path, 0)).collect(),
+        variants: ENUMS
+            .iter()
+            .flat_map(|&(name, values)| values.iter().map(move |&v| ((name, v), 0)))
+            .collect(),
+        other_values: BTreeMap::new(),
+    };
+
+    for context in contexts {
+        report.contexts += 1;
+        let json = serde_json::to_value(context).expect("IpContext should serialize");
+        report.visit(&json, String::new());
+
+        let client = context.client.as_ref();
+        let tunnel_types = context.tunnels.iter().flatten();
+        report.record(
+            "Infrastructure",
+            context.infrastructure.iter().map(|v| v.as_str()),
+        );
+        report.record("Risk", context.risks.iter().flatten().map(|v| v.as_str()));
+        report.record(
+            "Service",
+            context.services.iter().flatten().map(|v| v.as_str()),
+        );
+        report.record(
+            "TunnelType",
+            tunnel_types
+                .filter_map(|t| t.tunnel_type.as_ref())
+                .map(|v| v.as_str()),
+        );
+        report.record(
+            "Behavior",
+            client
+                .and_then(|c| c.behaviors.as_ref())
+                .into_iter()
+                .flatten()
+                .map(|v| v.as_str()),
+        );
+        report.record(
+            "DeviceType",
+            client
+                .and_then(|c| c.types.as_ref())
+                .into_iter()
+                .flatten()
+                .map(|v| v.as_str()),
+        );
+    }
+    report
+}
+
+impl Coverage {
+    fn visit(&mut self, value: &Value, path: String) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", path, key)
+                    };
+                    if let Some(count) = self.fields.get_mut(path.as_str()) {
+                        *count += 1;
+                    }
+                    self.visit(value, path);
+                }
+            }
+            Value::Array(items) => {
+                for item in items.iter().filter(|item| item.is_object()) {
+                    self.visit(item, format!("{}[]", path));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn record<'v>(&mut self, name: &'static str, values: impl Iterator<Item = &'v str>) {
+        for value in values {
+            let known = self
+                .variants
+                .iter_mut()
+                .find(|((e, v), _)| *e == name && *v == value);
+            match known {
+                Some((_, count)) => *count += 1,
+                None => {
+                    *self
+                        .other_values
+                        .entry((name, value.to_string()))
+                        .or_default() += 1
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixtures, load_all_fixtures};
+
+    /// A context setting every field, with each known variant used once.
+    fn everything() -> IpContext {
+        let mut json = serde_json::json!({
+            "ai": {"bots": true, "scrapers": true, "services": ["OPENAI"]},
+            "as": {"number": 1, "organization": "Org"},
+            "client": {
+                "behaviors": Behavior::KNOWN_VALUES,
+                "concentration": {
+                    "city": "C", "country": "US", "density": 0.5,
+                    "geohash": "dr4", "skew": 1, "state": "S"
+                },
+                "count": 1, "countries": 1, "proxies": ["P"], "spread": 1,
+                "types": DeviceType::KNOWN_VALUES
+            },
+            "infrastructure": "DATACENTER",
+            "ip": "192.0.2.1",
+            "location": {
+                "city": "C", "country": "US", "latitude": 1.0, "longitude": 1.0, "state": "S"
+            },
+            "organization": "Org",
+            "risks": Risk::KNOWN_VALUES,
+            "services": Service::KNOWN_VALUES,
+            "tunnels": []
+        });
+        for tunnel_type in TunnelType::KNOWN_VALUES {
+            json["tunnels"]
+                .as_array_mut()
+                .unwrap()
+                .push(serde_json::json!({
+                    "anonymous": true,
+                    "entries": [{
+                        "as": {"number": 2, "organization": "Entry"},
+                        "ip": "192.0.2.2",
+                        "location": {
+                            "city": "C", "country": "NL", "latitude": 1.0,
+                            "longitude": 1.0, "state": "S"
+                        }
+                    }],
+                    "operator": "OP",
+                    "type": tunnel_type
+                }));
+        }
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_fully_populated_context_is_complete() {
+        let mut contexts = vec![everything()];
+        for value in &Infrastructure::KNOWN_VALUES[1..] {
+            contexts.push(
+                serde_json::from_value(serde_json::json!({ "infrastructure": value })).unwrap(),
+            );
+        }
+        let report = coverage(&contexts);
+        assert!(report.is_complete(), "{}", report);
+        assert!(report.other_values().is_empty());
+        assert_eq!(
+            report.field_count("tunnels[].type"),
+            TunnelType::KNOWN_VALUES.len()
+        );
+    }
+
+    #[test]
+    fn test_reports_missing_and_unknown() {
+        let mut vpn = fixtures::vpn_ip();
+        vpn.risks = Some(vec![Risk::Other("NEW_RISK".to_string())]);
+        let report = coverage([&vpn]);
+
+        assert_eq!(report.contexts(), 1);
+        assert_eq!(report.field_count("tunnels[].operator"), 1);
+        assert!(report
+            .missing_fields()
+            .contains(&"client.concentration.geohash"));
+        assert!(report.missing_variants().contains(&("Risk", "TUNNEL")));
+        assert_eq!(report.variant_count("TunnelType", "VPN"), 1);
+        assert_eq!(report.other_values(), vec![("Risk", "NEW_RISK")]);
+
+        let text = report.to_string();
+        assert!(text.contains("missing variant Risk::SPAM"));
+        assert!(text.contains("unknown value Risk::Other(\"NEW_RISK\")"));
+    }
+
+    #[test]
+    fn test_bundled_fixture_coverage() {
+        let fixtures: Vec<_> = load_all_fixtures().into_iter().map(|(_, c)| c).collect();
+        let report = coverage(&fixtures);
+        assert_eq!(report.contexts(), fixtures.len());
+        assert!(report
+            .missing_variants()
+            .iter()
+            .all(|(name, _)| *name != "TunnelType"));
+    }
+}
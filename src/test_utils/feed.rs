@@ -0,0 +1,104 @@
+//! Fake NDJSON feeds built from the generator profiles.
+
+use std::io::{self, Write};
+
+use super::generate::{generate_with, Profile};
+use crate::rng::SplitMix64;
+
+/// Write a feed of `n` generated contexts as NDJSON to `writer`.
+///
+/// Each line is one [`IpContext`](crate::IpContext), with the profile of
+/// every record drawn from [`Profile::ALL`]. Records are produced one at a
+/// time, so arbitrarily large feeds can be streamed to a file or pipe.
+pub fn write_fake_feed<W: Write>(mut writer: W, n: usize, seed: u64) -> io::Result<()> {
+    let mut rng = SplitMix64::new(seed);
+    for _ in 0..n {
+        let profile = *rng.pick(&Profile::ALL);
+        serde_json::to_writer(&mut writer, &generate_with(&mut rng, profile))?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/// Build an in-memory NDJSON feed of `n` generated contexts.
+///
+/// The same `n` and `seed` always produce the same bytes, so feed-ingestion
+/// code can be tested without downloading a real feed.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::test_utils::fake_feed;
+/// use spur::IpContext;
+///
+/// let feed = fake_feed(100, 42);
+/// let records: Vec<IpContext> = feed
+///     .split(|&b| b == b'\n')
+///     .filter(|line| !line.is_empty())
+///     .map(|line| serde_json::from_slice(line).unwrap())
+///     .collect();
+/// assert_eq!(records.len(), 100);
+/// ```
+pub fn fake_feed(n: usize, seed: u64) -> Vec<u8> {
+    let mut feed = Vec::new();
+    write_fake_feed(&mut feed, n, seed).expect("writing to a Vec cannot fail");
+    feed
+}
+
+/// Build a gzip-compressed NDJSON feed, as served by the Spur feed API.
+///
+/// Decompresses to exactly [`fake_feed`]`(n, seed)`.
+#[cfg(feature = "gzip")]
+pub fn fake_feed_gz(n: usize, seed: u64) -> Vec<u8> {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    write_fake_feed(&mut encoder, n, seed).expect("writing to a Vec cannot fail");
+    encoder.finish().expect("writing to a Vec cannot fail")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::IpContext;
+
+    fn parse(feed: &[u8]) -> Vec<IpContext> {
+        feed.split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_fake_feed_is_deterministic_ndjson() {
+        let feed = fake_feed(300, 5);
+        assert_eq!(feed, fake_feed(300, 5));
+        assert_ne!(feed, fake_feed(300, 6));
+        assert_eq!(feed.iter().filter(|&&b| b == b'\n').count(), 300);
+
+        let records = parse(&feed);
+        assert_eq!(records.len(), 300);
+        assert!(records.iter().any(|r| r.tunnels.is_some()));
+        assert!(records.iter().any(|r| r.tunnels.is_none()));
+    }
+
+    #[test]
+    fn test_empty_feed() {
+        assert!(fake_feed(0, 1).is_empty());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_fake_feed_gz_round_trip() {
+        use std::io::Read;
+
+        let compressed = fake_feed_gz(200, 9);
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, fake_feed(200, 9));
+        assert!(compressed.len() < decompressed.len());
+    }
+}
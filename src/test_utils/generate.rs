@@ -0,0 +1,371 @@
+//! Deterministic generation of realistic-but-fake contexts.
+
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::str::FromStr;
+
+use super::{ClientBuilder, ConcentrationBuilder, IpContextBuilder, LocationBuilder};
+use super::{TunnelBuilder, TunnelEntryBuilder};
+use crate::context::{Behavior, DeviceType, Infrastructure, IpContext, Risk, Service, TunnelType};
+use crate::rng::SplitMix64;
+
+/// The kind of context produced by [`generate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// A home broadband connection with a handful of devices.
+    Residential,
+    /// A commercial VPN exit in a datacenter.
+    Vpn,
+    /// A Tor exit relay.
+    Tor,
+    /// A cloud or hosting address with no tunnel.
+    Datacenter,
+    /// A residential address enrolled in one or more proxy networks.
+    ResidentialProxy,
+}
+
+impl Profile {
+    /// Every profile, in declaration order.
+    pub const ALL: [Profile; 5] = [
+        Profile::Residential,
+        Profile::Vpn,
+        Profile::Tor,
+        Profile::Datacenter,
+        Profile::ResidentialProxy,
+    ];
+
+    /// Returns the kebab-case name accepted by [`FromStr`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Profile::Residential => "residential",
+            Profile::Vpn => "vpn",
+            Profile::Tor => "tor",
+            Profile::Datacenter => "datacenter",
+            Profile::ResidentialProxy => "residential-proxy",
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Profile::ALL
+            .into_iter()
+            .find(|p| p.as_str() == s)
+            .ok_or_else(|| format!("unknown profile: {}", s))
+    }
+}
+
+/// A network a generated address can come from.
+struct Network {
+    asn: u32,
+    organization: &'static str,
+    country: &'static str,
+    state: &'static str,
+    city: &'static str,
+    /// First two octets of the generated addresses.
+    prefix: [u8; 2],
+}
+
+const fn net(
+    asn: u32,
+    organization: &'static str,
+    country: &'static str,
+    state: &'static str,
+    city: &'static str,
+    prefix: [u8; 2],
+) -> Network {
+    Network {
+        asn,
+        organization,
+        country,
+        state,
+        city,
+        prefix,
+    }
+}
+
+#[rustfmt::skip]
+const RESIDENTIAL: &[Network] = &[
+    net(7922, "Comcast Cable Communications, LLC", "US", "Pennsylvania", "Philadelphia", [73, 165]),
+    net(7922, "Comcast Cable Communications, LLC", "US", "Washington", "Seattle", [76, 104]),
+    net(701, "Verizon Business", "US", "New York", "Brooklyn", [108, 46]),
+    net(20115, "Charter Communications LLC", "US", "Texas", "Austin", [70, 123]),
+    net(3320, "Deutsche Telekom AG", "DE", "Berlin", "Berlin", [79, 199]),
+    net(2856, "British Telecommunications PLC", "GB", "England", "Manchester", [86, 146]),
+    net(3215, "Orange S.A.", "FR", "Ile-de-France", "Paris", [90, 12]),
+    net(4766, "Korea Telecom", "KR", "Seoul", "Seoul", [121, 134]),
+];
+
+#[rustfmt::skip]
+const DATACENTER: &[Network] = &[
+    net(16509, "Amazon.com, Inc.", "US", "Virginia", "Ashburn", [52, 4]),
+    net(14061, "DigitalOcean, LLC", "US", "New Jersey", "Clifton", [159, 65]),
+    net(24940, "Hetzner Online GmbH", "DE", "Saxony", "Falkenstein", [88, 198]),
+    net(16276, "OVH SAS", "FR", "Hauts-de-France", "Roubaix", [51, 77]),
+    net(49981, "WorldStream B.V.", "NL", "North Holland", "Amsterdam", [89, 39]),
+    net(9009, "M247 Europe SRL", "RO", "Bucharest", "Bucharest", [185, 156]),
+];
+
+#[rustfmt::skip]
+const TOR: &[Network] = &[
+    net(60729, "ZWIEBELFREUNDE", "DE", "Hesse", "Frankfurt", [185, 220]),
+    net(208294, "Foundation for Applied Privacy", "AT", "Vienna", "Vienna", [109, 70]),
+    net(53667, "FranTech Solutions", "LU", "Luxembourg", "Luxembourg", [209, 141]),
+];
+
+const VPN_OPERATORS: &[&str] = &[
+    "NORD_VPN",
+    "EXPRESS_VPN",
+    "MULLVAD_VPN",
+    "PROTON_VPN",
+    "SURFSHARK_VPN",
+    "PRIVATE_INTERNET_ACCESS_VPN",
+];
+
+const PROXY_NETWORKS: &[&str] = &[
+    "OXYLABS_PROXY",
+    "IPROYAL_PROXY",
+    "SOAX_PROXY",
+    "NETNUT_PROXY",
+    "INFATICA_PROXY",
+    "DATAIMPULSE_PROXY",
+    "NODEMAVEN_PROXY",
+    "EARNFM_PROXY",
+];
+
+const VPN_SERVICES: &[Service] = &[Service::OpenVpn, Service::Wireguard, Service::Ipsec];
+
+/// Generate a realistic-but-fake context for `profile` from `seed`.
+///
+/// The same seed and profile always produce the same context, so large
+/// reproducible datasets can be built outside of proptest. Addresses, ASNs
+/// and operators are drawn from small tables of plausible values; they are
+/// not real Spur observations.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::test_utils::{generate, Profile};
+///
+/// let a = generate(42, Profile::Vpn);
+/// let b = generate(42, Profile::Vpn);
+/// assert_eq!(a, b);
+/// assert!(a.tunnels.is_some());
+/// ```
+pub fn generate(seed: u64, profile: Profile) -> IpContext {
+    generate_with(&mut SplitMix64::new(seed), profile)
+}
+
+/// Generate `count` contexts for `profile` from a single seed.
+///
+/// ```rust
+/// use spur::test_utils::{generate_many, Profile};
+///
+/// let dataset = generate_many(7, Profile::ResidentialProxy, 1000);
+/// assert_eq!(dataset.len(), 1000);
+/// assert_eq!(dataset, generate_many(7, Profile::ResidentialProxy, 1000));
+/// ```
+pub fn generate_many(seed: u64, profile: Profile, count: usize) -> Vec<IpContext> {
+    let mut rng = SplitMix64::new(seed);
+    (0..count)
+        .map(|_| generate_with(&mut rng, profile))
+        .collect()
+}
+
+pub(super) fn generate_with(rng: &mut SplitMix64, profile: Profile) -> IpContext {
+    match profile {
+        Profile::Residential => residential(rng),
+        Profile::Vpn => vpn(rng),
+        Profile::Tor => tor(rng),
+        Profile::Datacenter => datacenter(rng),
+        Profile::ResidentialProxy => residential_proxy(rng),
+    }
+}
+
+fn base(
+    rng: &mut SplitMix64,
+    network: &Network,
+    infrastructure: Infrastructure,
+) -> IpContextBuilder {
+    let ip = Ipv4Addr::new(
+        network.prefix[0],
+        network.prefix[1],
+        rng.below(256) as u8,
+        1 + rng.below(254) as u8,
+    );
+    IpContextBuilder::new()
+        .ip(&ip.to_string())
+        .infrastructure(infrastructure)
+        .organization(network.organization)
+        .asn(network.asn, network.organization)
+        .with_location(
+            LocationBuilder::new()
+                .country(network.country)
+                .state(network.state)
+                .city(network.city)
+                .build(),
+        )
+}
+
+fn residential(rng: &mut SplitMix64) -> IpContext {
+    let network = rng.pick(RESIDENTIAL);
+    let mut client = ClientBuilder::new()
+        .count(1 + rng.below(6))
+        .countries(1)
+        .add_type(DeviceType::Desktop);
+    if rng.chance(0.7) {
+        client = client.add_type(DeviceType::Mobile);
+    }
+    if rng.chance(0.1) {
+        client = client.add_behavior(Behavior::FileSharing);
+    }
+    base(rng, network, Infrastructure::Residential)
+        .with_client(client.build())
+        .build()
+}
+
+fn vpn(rng: &mut SplitMix64) -> IpContext {
+    let network = rng.pick(DATACENTER);
+    let operator = *rng.pick(VPN_OPERATORS);
+    let mut tunnel = TunnelBuilder::new()
+        .tunnel_type(TunnelType::Vpn)
+        .operator(operator)
+        .anonymous(true);
+    if rng.chance(0.3) {
+        let entry = rng.pick(DATACENTER);
+        tunnel = tunnel.add_entry(
+            TunnelEntryBuilder::new()
+                .ip(&Ipv4Addr::new(entry.prefix[0], entry.prefix[1], 0, 1).to_string())
+                .country(entry.country)
+                .asn(entry.asn, entry.organization)
+                .build(),
+        );
+    }
+    base(rng, network, Infrastructure::Datacenter)
+        .add_tunnel(tunnel.build())
+        .add_service(rng.pick(VPN_SERVICES).clone())
+        .add_risk(Risk::Tunnel)
+        .with_client(
+            ClientBuilder::new()
+                .count(10 + rng.below(500))
+                .countries(1 + rng.below(40) as u32)
+                .add_type(DeviceType::Desktop)
+                .add_type(DeviceType::Mobile)
+                .build(),
+        )
+        .build()
+}
+
+fn tor(rng: &mut SplitMix64) -> IpContext {
+    let network = rng.pick(TOR);
+    base(rng, network, Infrastructure::Datacenter)
+        .tor()
+        .add_risk(Risk::Tunnel)
+        .with_client(
+            ClientBuilder::new()
+                .count(100 + rng.below(900))
+                .countries(20 + rng.below(120) as u32)
+                .add_behavior(Behavior::TorProxyUser)
+                .build(),
+        )
+        .build()
+}
+
+fn datacenter(rng: &mut SplitMix64) -> IpContext {
+    let network = rng.pick(DATACENTER);
+    let mut builder = base(rng, network, Infrastructure::Datacenter);
+    if rng.chance(0.2) {
+        builder = builder.add_service(Service::Ssh);
+    }
+    builder.build()
+}
+
+fn residential_proxy(rng: &mut SplitMix64) -> IpContext {
+    let network = rng.pick(RESIDENTIAL);
+    let mut client = ClientBuilder::new()
+        .count(5 + rng.below(60))
+        .countries(1)
+        .add_type(DeviceType::Desktop)
+        .add_type(DeviceType::Mobile);
+    let proxies = 1 + rng.below(4) as usize;
+    let start = rng.below(PROXY_NETWORKS.len() as u64) as usize;
+    for i in 0..proxies {
+        client = client.add_proxy(PROXY_NETWORKS[(start + i) % PROXY_NETWORKS.len()]);
+    }
+    let mut builder = base(rng, network, Infrastructure::Residential).add_risk(Risk::CallbackProxy);
+    if rng.chance(0.25) {
+        let far = rng.pick(RESIDENTIAL);
+        client = client.concentration(
+            ConcentrationBuilder::new()
+                .country(far.country)
+                .city(far.city)
+                .density(0.5 + rng.next_f64() / 2.0)
+                .build(),
+        );
+        if far.country != network.country {
+            builder = builder.add_risk(Risk::GeoMismatch);
+        }
+    }
+    builder.with_client(client.build()).build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic_per_seed() {
+        for profile in Profile::ALL {
+            assert_eq!(generate(1, profile), generate(1, profile));
+        }
+        assert_ne!(
+            generate(1, Profile::Residential).ip,
+            generate(2, Profile::Residential).ip
+        );
+    }
+
+    #[test]
+    fn test_profiles_have_expected_shape() {
+        for seed in 0..50 {
+            let residential = generate(seed, Profile::Residential);
+            assert_eq!(
+                residential.infrastructure,
+                Some(Infrastructure::Residential)
+            );
+            assert!(residential.tunnels.is_none());
+
+            let vpn = generate(seed, Profile::Vpn);
+            assert_eq!(vpn.tunnels.unwrap()[0].tunnel_type, Some(TunnelType::Vpn));
+
+            let tor = generate(seed, Profile::Tor);
+            assert_eq!(tor.tunnels.unwrap()[0].tunnel_type, Some(TunnelType::Tor));
+
+            let proxy = generate(seed, Profile::ResidentialProxy);
+            assert!(!proxy.client.unwrap().proxies.unwrap().is_empty());
+            assert!(proxy.risks.unwrap().contains(&Risk::CallbackProxy));
+        }
+    }
+
+    #[test]
+    fn test_generated_ips_parse() {
+        for ctx in generate_many(9, Profile::Datacenter, 200) {
+            ctx.ip.unwrap().parse::<Ipv4Addr>().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_profile_from_str() {
+        for profile in Profile::ALL {
+            assert_eq!(profile.as_str().parse::<Profile>(), Ok(profile));
+        }
+        assert!("satellite".parse::<Profile>().is_err());
+    }
+}
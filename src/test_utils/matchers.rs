@@ -0,0 +1,326 @@
+//! Composable assertions on [`IpContext`] values.
+//!
+//! Each matcher describes what it expects and, on failure, what it actually
+//! found, so a failing test reads like a sentence instead of an `unwrap` on
+//! `None` deep inside a chain of `as_ref()` calls.
+//!
+//! ```rust
+//! use spur::test_utils::matchers::{assert_that, has_risk, infrastructure_is, tunnel_operated_by};
+//! use spur::test_utils::IpContextBuilder;
+//! use spur::{Infrastructure, Risk};
+//!
+//! let vpn = IpContextBuilder::new()
+//!     .ip("1.2.3.4")
+//!     .infrastructure(Infrastructure::Datacenter)
+//!     .vpn("NordVPN")
+//!     .add_risk(Risk::Tunnel)
+//!     .build();
+//!
+//! assert_that(
+//!     &vpn,
+//!     has_risk(Risk::Tunnel)
+//!         .and(infrastructure_is(Infrastructure::Datacenter))
+//!         .and(tunnel_operated_by("NordVPN")),
+//! );
+//! ```
+//!
+//! A failing assertion panics with a message such as:
+//!
+//! ```text
+//! expected context for 1.2.3.4 to have risk SPAM, but risks were [TUNNEL]
+//! ```
+
+use std::fmt;
+
+use crate::context::{Behavior, Infrastructure, IpContext, Risk, Service, TunnelType};
+
+type Check = Box<dyn Fn(&IpContext) -> Result<(), String>>;
+
+/// A named predicate on an [`IpContext`].
+///
+/// Build matchers with the functions in this module and combine them with
+/// [`Matcher::and`], [`Matcher::or`] and [`not`].
+pub struct Matcher {
+    description: String,
+    check: Check,
+}
+
+impl Matcher {
+    /// Create a matcher from a description and a check.
+    ///
+    /// The description completes the sentence "expected context to ...".
+    /// The check returns `Err` with a description of what was found instead.
+    pub fn new<F>(description: impl Into<String>, check: F) -> Self
+    where
+        F: Fn(&IpContext) -> Result<(), String> + 'static,
+    {
+        Self {
+            description: description.into(),
+            check: Box::new(check),
+        }
+    }
+
+    /// Returns what this matcher expects.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns `true` if `context` satisfies this matcher.
+    pub fn matches(&self, context: &IpContext) -> bool {
+        (self.check)(context).is_ok()
+    }
+
+    /// Check `context`, returning a readable failure message on mismatch.
+    pub fn check(&self, context: &IpContext) -> Result<(), String> {
+        (self.check)(context).map_err(|found| {
+            format!(
+                "expected context for {} to {}, but {}",
+                context.ip.as_deref().unwrap_or("<no ip>"),
+                self.description,
+                found
+            )
+        })
+    }
+
+    /// Require both this matcher and `other`.
+    pub fn and(self, other: Matcher) -> Matcher {
+        let description = format!("{} and {}", self.description, other.description);
+        Matcher::new(description, move |ctx| {
+            (self.check)(ctx)?;
+            (other.check)(ctx)
+        })
+    }
+
+    /// Require this matcher or `other`.
+    pub fn or(self, other: Matcher) -> Matcher {
+        let description = format!("{} or {}", self.description, other.description);
+        Matcher::new(description, move |ctx| match (self.check)(ctx) {
+            Ok(()) => Ok(()),
+            Err(first) => (other.check)(ctx).map_err(|second| format!("{} and {}", first, second)),
+        })
+    }
+}
+
+impl fmt::Debug for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Matcher")
+            .field("description", &self.description)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Assert that `context` satisfies `matcher`, panicking with the matcher's
+/// failure message otherwise.
+#[track_caller]
+pub fn assert_that(context: &IpContext, matcher: Matcher) {
+    if let Err(message) = matcher.check(context) {
+        panic!("{}", message);
+    }
+}
+
+/// Invert a matcher.
+pub fn not(matcher: Matcher) -> Matcher {
+    let description = format!("not {}", matcher.description);
+    Matcher::new(description, move |ctx| match (matcher.check)(ctx) {
+        Ok(()) => Err(format!("it did {}", matcher.description)),
+        Err(_) => Ok(()),
+    })
+}
+
+/// Matches contexts whose `risks` contain `risk`.
+pub fn has_risk(risk: Risk) -> Matcher {
+    Matcher::new(format!("have risk {}", risk), move |ctx| {
+        contains(ctx.risks.as_deref(), &risk, "risks")
+    })
+}
+
+/// Matches contexts whose `services` contain `service`.
+pub fn has_service(service: Service) -> Matcher {
+    Matcher::new(format!("have service {}", service), move |ctx| {
+        contains(ctx.services.as_deref(), &service, "services")
+    })
+}
+
+/// Matches contexts whose client `behaviors` contain `behavior`.
+pub fn has_behavior(behavior: Behavior) -> Matcher {
+    Matcher::new(format!("have client behavior {}", behavior), move |ctx| {
+        let behaviors = ctx.client.as_ref().and_then(|c| c.behaviors.as_deref());
+        contains(behaviors, &behavior, "client behaviors")
+    })
+}
+
+/// Matches contexts whose client `proxies` contain `proxy`.
+pub fn has_proxy(proxy: &str) -> Matcher {
+    let proxy = proxy.to_string();
+    Matcher::new(format!("have client proxy {}", proxy), move |ctx| {
+        let proxies = ctx.client.as_ref().and_then(|c| c.proxies.as_deref());
+        contains(proxies, &proxy, "client proxies")
+    })
+}
+
+/// Matches contexts with the given infrastructure.
+pub fn infrastructure_is(infrastructure: Infrastructure) -> Matcher {
+    Matcher::new(
+        format!("have infrastructure {}", infrastructure),
+        move |ctx| match &ctx.infrastructure {
+            Some(found) if *found == infrastructure => Ok(()),
+            Some(found) => Err(format!("infrastructure was {}", found)),
+            None => Err("infrastructure was absent".to_string()),
+        },
+    )
+}
+
+/// Matches contexts located in `country` (ISO 3166-1 alpha-2).
+pub fn country_is(country: &str) -> Matcher {
+    let country = country.to_string();
+    Matcher::new(format!("be located in {}", country), move |ctx| {
+        match ctx.location.as_ref().and_then(|l| l.country.as_deref()) {
+            Some(found) if found == country => Ok(()),
+            Some(found) => Err(format!("country was {}", found)),
+            None => Err("country was absent".to_string()),
+        }
+    })
+}
+
+/// Matches contexts announced by autonomous system `number`.
+pub fn asn_is(number: u32) -> Matcher {
+    Matcher::new(format!("be in AS{}", number), move |ctx| {
+        match ctx.autonomous_system.as_ref().and_then(|a| a.number) {
+            Some(found) if found == number => Ok(()),
+            Some(found) => Err(format!("AS was AS{}", found)),
+            None => Err("AS was absent".to_string()),
+        }
+    })
+}
+
+/// Matches contexts with at least one tunnel of `tunnel_type`.
+pub fn has_tunnel_type(tunnel_type: TunnelType) -> Matcher {
+    Matcher::new(format!("have a {} tunnel", tunnel_type), move |ctx| {
+        let types: Vec<_> = ctx
+            .tunnels
+            .iter()
+            .flatten()
+            .filter_map(|t| t.tunnel_type.as_ref())
+            .collect();
+        if types.contains(&&tunnel_type) {
+            Ok(())
+        } else {
+            Err(format!("tunnel types were {}", list(&types)))
+        }
+    })
+}
+
+/// Matches contexts with at least one tunnel run by `operator`.
+pub fn tunnel_operated_by(operator: &str) -> Matcher {
+    let operator = operator.to_string();
+    Matcher::new(
+        format!("have a tunnel operated by {}", operator),
+        move |ctx| {
+            let operators: Vec<_> = ctx
+                .tunnels
+                .iter()
+                .flatten()
+                .filter_map(|t| t.operator.as_deref())
+                .collect();
+            if operators.contains(&operator.as_str()) {
+                Ok(())
+            } else {
+                Err(format!("tunnel operators were {}", list(&operators)))
+            }
+        },
+    )
+}
+
+/// Matches contexts with at least one anonymous tunnel.
+pub fn is_anonymous() -> Matcher {
+    Matcher::new("have an anonymous tunnel", |ctx| {
+        let tunnels = ctx.tunnels.as_deref().unwrap_or_default();
+        if tunnels.iter().any(|t| t.anonymous == Some(true)) {
+            Ok(())
+        } else {
+            Err(format!(
+                "it had {} tunnel(s), none anonymous",
+                tunnels.len()
+            ))
+        }
+    })
+}
+
+fn contains<T: PartialEq + fmt::Display>(
+    values: Option<&[T]>,
+    wanted: &T,
+    field: &str,
+) -> Result<(), String> {
+    match values {
+        Some(values) if values.contains(wanted) => Ok(()),
+        Some(values) => Err(format!("{} were {}", field, list(values))),
+        None => Err(format!("{} were absent", field)),
+    }
+}
+
+fn list<T: fmt::Display>(values: &[T]) -> String {
+    let items: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    format!("[{}]", items.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixtures, IpContextBuilder};
+
+    #[test]
+    fn test_matchers_pass_on_fixtures() {
+        assert_that(
+            &fixtures::vpn_ip(),
+            has_service(Service::OpenVpn)
+                .and(infrastructure_is(Infrastructure::Datacenter))
+                .and(tunnel_operated_by("NordVPN"))
+                .and(has_tunnel_type(TunnelType::Vpn))
+                .and(is_anonymous()),
+        );
+        assert_that(
+            &fixtures::residential_ip(),
+            not(has_tunnel_type(TunnelType::Vpn))
+                .and(infrastructure_is(Infrastructure::Residential)),
+        );
+    }
+
+    #[test]
+    fn test_failure_messages() {
+        let context = IpContextBuilder::new()
+            .ip("1.2.3.4")
+            .add_risk(Risk::Tunnel)
+            .vpn("NordVPN")
+            .build();
+
+        assert_eq!(
+            has_risk(Risk::Spam).check(&context).unwrap_err(),
+            "expected context for 1.2.3.4 to have risk SPAM, but risks were [TUNNEL]"
+        );
+        assert_eq!(
+            infrastructure_is(Infrastructure::Mobile)
+                .check(&context)
+                .unwrap_err(),
+            "expected context for 1.2.3.4 to have infrastructure MOBILE, \
+             but infrastructure was absent"
+        );
+        assert_eq!(
+            tunnel_operated_by("MULLVAD_VPN")
+                .or(has_service(Service::Ssh))
+                .check(&context)
+                .unwrap_err(),
+            "expected context for 1.2.3.4 to have a tunnel operated by MULLVAD_VPN \
+             or have service SSH, but tunnel operators were [NordVPN] and services were absent"
+        );
+        assert_eq!(
+            not(has_risk(Risk::Tunnel)).check(&context).unwrap_err(),
+            "expected context for 1.2.3.4 to not have risk TUNNEL, but it did have risk TUNNEL"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "to be located in DE, but country was US")]
+    fn test_assert_that_panics() {
+        assert_that(&fixtures::residential_ip(), country_is("DE"));
+    }
+}
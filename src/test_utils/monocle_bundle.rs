@@ -0,0 +1,168 @@
+//! Offline stand-ins for encrypted Monocle bundles.
+//!
+//! Real bundles are encrypted by the Monocle JavaScript with key material
+//! only Spur holds, so they cannot be produced in a test. The bundles here
+//! have the same shape as far as integration code is concerned (an opaque,
+//! URL-safe string posted to a decryption endpoint) and can be opened again
+//! with [`open_test_bundle`], e.g. from a mock of the Decryption API.
+
+use crate::hash::fnv1a;
+use crate::monocle::Assessment;
+use crate::rng::SplitMix64;
+
+const PLAIN_PREFIX: &str = "mcl-test.v1.";
+const ENCRYPTED_PREFIX: &str = "mcl-test.v1e.";
+
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// A test bundle paired with the assessment it decodes to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestBundle {
+    /// The bundle string, as the Monocle JavaScript would submit it.
+    pub bundle: String,
+    /// The assessment a decryption of `bundle` must yield.
+    pub expected: Assessment,
+}
+
+/// Build an unencrypted test bundle for `assessment`.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::test_utils::{monocle_bundle, monocle_fixtures, open_test_bundle};
+///
+/// let case = monocle_bundle(monocle_fixtures::vpn_assessment());
+///
+/// // A mock Decryption API would do this with the posted body:
+/// let decrypted = open_test_bundle(&case.bundle, None).unwrap();
+/// assert_eq!(decrypted, case.expected);
+/// ```
+pub fn monocle_bundle(assessment: Assessment) -> TestBundle {
+    let json = serde_json::to_vec(&assessment).expect("Assessment should serialize");
+    TestBundle {
+        bundle: format!("{}{}", PLAIN_PREFIX, encode(&json)),
+        expected: assessment,
+    }
+}
+
+/// Build a test bundle for `assessment` encrypted with a test `key`.
+///
+/// The cipher is a keyed stream for exercising key handling and rejection
+/// of bundles encrypted with another key. It is not the Monocle cipher and
+/// offers no real confidentiality.
+pub fn encrypted_monocle_bundle(assessment: Assessment, key: &[u8]) -> TestBundle {
+    let mut data = serde_json::to_vec(&assessment).expect("Assessment should serialize");
+    apply_keystream(&mut data, key);
+    TestBundle {
+        bundle: format!("{}{}", ENCRYPTED_PREFIX, encode(&data)),
+        expected: assessment,
+    }
+}
+
+/// Decode a bundle produced by [`monocle_bundle`] or
+/// [`encrypted_monocle_bundle`].
+///
+/// Encrypted bundles need the `key` they were built with; a missing or
+/// wrong key is an error, as is a bundle that was not produced by this
+/// module.
+pub fn open_test_bundle(bundle: &str, key: Option<&[u8]>) -> Result<Assessment, String> {
+    let bundle = bundle.trim();
+    let data = if let Some(body) = bundle.strip_prefix(ENCRYPTED_PREFIX) {
+        let key = key.ok_or("bundle is encrypted but no key was given")?;
+        let mut data = decode(body)?;
+        apply_keystream(&mut data, key);
+        data
+    } else if let Some(body) = bundle.strip_prefix(PLAIN_PREFIX) {
+        decode(body)?
+    } else {
+        return Err("not a test bundle".to_string());
+    };
+    serde_json::from_slice(&data)
+        .map_err(|e| format!("bundle does not decrypt to an assessment: {}", e))
+}
+
+fn apply_keystream(data: &mut [u8], key: &[u8]) {
+    let mut rng = SplitMix64::new(fnv1a(key));
+    for chunk in data.chunks_mut(8) {
+        let stream = rng.next_u64().to_le_bytes();
+        for (byte, k) in chunk.iter_mut().zip(stream) {
+            *byte ^= k;
+        }
+    }
+}
+
+/// Unpadded base64url encoding.
+fn encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len() * 4 / 3 + 3);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | ((b as u32) << (16 - 8 * i)));
+        for i in 0..=chunk.len() {
+            out.push(BASE64URL[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+fn decode(text: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut n) = (0u32, 0u32);
+    for c in text.bytes() {
+        let value = BASE64URL
+            .iter()
+            .position(|&b| b == c)
+            .ok_or_else(|| format!("invalid bundle character {:?}", c as char))?;
+        n = (n << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((n >> bits) as u8);
+            n &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::monocle_fixtures;
+
+    #[test]
+    fn test_base64url_round_trip() {
+        assert_eq!(encode(b"Man"), "TWFu");
+        assert_eq!(encode(b"Ma"), "TWE");
+        assert_eq!(encode(b"\xfb\xff"), "-_8");
+        for len in 0..20 {
+            let data: Vec<u8> = (0..len).map(|i| (i * 37) as u8).collect();
+            assert_eq!(decode(&encode(&data)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_plain_bundle_round_trip() {
+        let case = monocle_bundle(monocle_fixtures::proxy_assessment());
+        assert!(case.bundle.starts_with(PLAIN_PREFIX));
+        assert_eq!(open_test_bundle(&case.bundle, None).unwrap(), case.expected);
+    }
+
+    #[test]
+    fn test_encrypted_bundle_needs_key() {
+        let case = encrypted_monocle_bundle(monocle_fixtures::vpn_assessment(), b"secret");
+        assert_eq!(
+            open_test_bundle(&case.bundle, Some(b"secret")).unwrap(),
+            case.expected
+        );
+        assert!(open_test_bundle(&case.bundle, None).is_err());
+        assert!(open_test_bundle(&case.bundle, Some(b"other")).is_err());
+        assert!(!case.bundle.contains("vpn"));
+    }
+
+    #[test]
+    fn test_rejects_foreign_bundles() {
+        assert!(open_test_bundle("eyJhbGciOi...", None).is_err());
+        assert!(open_test_bundle("mcl-test.v1.!!", None).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//! Golden-file snapshot assertions.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::canonical;
+
+/// Environment variable that makes [`assert_matches_snapshot`] rewrite
+/// snapshots instead of comparing against them.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "SPUR_UPDATE_SNAPSHOTS";
+
+/// Lines of unchanged context shown around each difference.
+const CONTEXT_LINES: usize = 2;
+
+/// Serialize `value` as canonical JSON: object keys sorted, two-space
+/// indentation and a trailing newline.
+///
+/// Two values that serialize to the same JSON document always produce the
+/// same string, regardless of struct field order.
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> String {
+    let value = serde_json::to_value(value).expect("value should serialize to JSON");
+    let value = canonical::sort_keys(value);
+    let mut json = serde_json::to_string_pretty(&value).expect("JSON value should serialize");
+    json.push('\n');
+    json
+}
+
+/// Assert that `value` serializes to the canonical JSON stored at `path`.
+///
+/// Relative paths are resolved against the current directory, which for
+/// `cargo test` is the package root. If the file does not exist it is
+/// created and the assertion passes; set `SPUR_UPDATE_SNAPSHOTS=1` to
+/// overwrite existing snapshots after an intended change. On mismatch the
+/// panic message contains a line diff of the expected and actual JSON.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use spur::test_utils::{assert_matches_snapshot, fixtures};
+///
+/// let enriched = fixtures::vpn_ip();
+/// assert_matches_snapshot(&enriched, "tests/snapshots/vpn_ip.json");
+/// ```
+#[track_caller]
+pub fn assert_matches_snapshot<T: Serialize + ?Sized>(value: &T, path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    let actual = to_canonical_json(value);
+    let update = std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some_and(|v| v != "0" && !v.is_empty());
+
+    if update || !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .unwrap_or_else(|e| panic!("failed to create {}: {}", parent.display(), e));
+        }
+        fs::write(path, &actual)
+            .unwrap_or_else(|e| panic!("failed to write snapshot {}: {}", path.display(), e));
+        return;
+    }
+
+    let stored = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("failed to read snapshot {}: {}", path.display(), e));
+    // Re-canonicalize so hand-edited snapshots only differ in content.
+    let expected = match serde_json::from_str::<serde_json::Value>(&stored) {
+        Ok(value) => to_canonical_json(&value),
+        Err(_) => stored,
+    };
+
+    if expected != actual {
+        panic!(
+            "snapshot {} does not match (- expected, + actual); \
+             rerun with {}=1 to update\n{}",
+            path.display(),
+            UPDATE_SNAPSHOTS_ENV,
+            line_diff(&expected, &actual)
+        );
+    }
+}
+
+/// Render a line diff of `expected` and `actual`, showing only changed
+/// lines and a little surrounding context.
+fn line_diff(expected: &str, actual: &str) -> String {
+    let a: Vec<&str> = expected.lines().collect();
+    let b: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence table, filled from the end.
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            ops.push((' ', a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(('-', a[i]));
+            i += 1;
+        } else {
+            ops.push(('+', b[j]));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = (0..ops.len()).filter(|&k| ops[k].0 != ' ').collect();
+    let mut out = String::new();
+    let mut last_shown = None;
+    for (k, (tag, line)) in ops.iter().enumerate() {
+        let near_change = changed
+            .iter()
+            .any(|&c| k + CONTEXT_LINES >= c && k <= c + CONTEXT_LINES);
+        if !near_change {
+            continue;
+        }
+        if last_shown.is_some_and(|prev: usize| k > prev + 1) {
+            out.push_str("  ...\n");
+        }
+        let _ = writeln!(out, "{} {}", tag, line);
+        last_shown = Some(k);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::fixtures;
+
+    #[test]
+    fn test_canonical_json_sorts_keys() {
+        let json = to_canonical_json(&serde_json::json!({"b": 1, "a": {"d": 2, "c": 3}}));
+        assert_eq!(
+            json,
+            "{\n  \"a\": {\n    \"c\": 3,\n    \"d\": 2\n  },\n  \"b\": 1\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_snapshot_created_then_matched() {
+        let path = std::env::temp_dir().join(format!("spur-snapshot-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let context = fixtures::vpn_ip();
+        assert_matches_snapshot(&context, &path);
+        assert!(path.exists());
+        assert_matches_snapshot(&context, &path);
+
+        let changed = crate::IpContext {
+            organization: Some("Changed".to_string()),
+            ..context
+        };
+        let result = std::panic::catch_unwind(|| assert_matches_snapshot(&changed, &path));
+        let _ = fs::remove_file(&path);
+
+        let message = result.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(message.contains("+   \"organization\": \"Changed\""));
+    }
+
+    #[test]
+    fn test_line_diff_shows_context() {
+        let expected = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let actual = "1\n2\n3\n4\nfive\n6\n7\n8\n";
+        assert_eq!(
+            line_diff(expected, actual),
+            "  3\n  4\n- 5\n+ five\n  6\n  7\n"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Sequences of snapshots of one address changing over time.
+
+use super::generate::{generate, Profile};
+use super::{ClientBuilder, TunnelBuilder};
+use crate::context::{Behavior, DeviceType, Infrastructure, IpContext, Risk, TunnelType};
+use crate::hash::fnv1a;
+
+/// What an address is doing at one point of a [`timeline`].
+///
+/// Each event describes the complete state of the address at that
+/// snapshot, not a change relative to the previous one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TimelineEvent {
+    /// Hosting address with no tunnels or risks.
+    Clean,
+    /// Exit of a VPN run by the given operator.
+    Vpn(String),
+    /// Tor exit relay.
+    Tor,
+    /// Exit of the given residential proxy network.
+    ResidentialProxy(String),
+}
+
+/// Build one snapshot of `ip` per event, in order.
+///
+/// The address keeps the same autonomous system, organization and location
+/// across the timeline, derived from `ip`, so only the fields an event
+/// affects differ between consecutive snapshots. This makes the output
+/// suitable for testing diffing, alerting and time-series storage.
+///
+/// # Example
+///
+/// ```rust
+/// use spur::test_utils::{timeline, TimelineEvent};
+/// use spur::Risk;
+///
+/// let history = timeline(
+///     "198.51.100.7",
+///     &[
+///         TimelineEvent::Clean,
+///         TimelineEvent::Vpn("MULLVAD_VPN".to_string()),
+///         TimelineEvent::Tor,
+///     ],
+/// );
+///
+/// assert_eq!(history.len(), 3);
+/// assert!(history[0].risks.is_none());
+/// assert_eq!(history[1].autonomous_system, history[2].autonomous_system);
+/// assert!(history[2].risks.as_ref().unwrap().contains(&Risk::Tunnel));
+/// ```
+pub fn timeline(ip: &str, events: &[TimelineEvent]) -> Vec<IpContext> {
+    let base = IpContext {
+        ip: Some(ip.to_string()),
+        services: None,
+        ..generate(fnv1a(ip.as_bytes()), Profile::Datacenter)
+    };
+    events.iter().map(|event| snapshot(&base, event)).collect()
+}
+
+fn snapshot(base: &IpContext, event: &TimelineEvent) -> IpContext {
+    let mut context = base.clone();
+    match event {
+        TimelineEvent::Clean => {}
+        TimelineEvent::Vpn(operator) => {
+            context.tunnels = Some(vec![TunnelBuilder::new()
+                .tunnel_type(TunnelType::Vpn)
+                .operator(operator)
+                .anonymous(true)
+                .build()]);
+            context.risks = Some(vec![Risk::Tunnel]);
+            context.client = Some(
+                ClientBuilder::new()
+                    .count(120)
+                    .countries(8)
+                    .add_type(DeviceType::Desktop)
+                    .add_type(DeviceType::Mobile)
+                    .build(),
+            );
+        }
+        TimelineEvent::Tor => {
+            context.tunnels = Some(vec![TunnelBuilder::new()
+                .tunnel_type(TunnelType::Tor)
+                .operator("Tor Project")
+                .anonymous(true)
+                .build()]);
+            context.risks = Some(vec![Risk::Tunnel]);
+            context.client = Some(
+                ClientBuilder::new()
+                    .count(600)
+                    .countries(70)
+                    .add_behavior(Behavior::TorProxyUser)
+                    .build(),
+            );
+        }
+        TimelineEvent::ResidentialProxy(network) => {
+            context.infrastructure = Some(Infrastructure::Residential);
+            context.risks = Some(vec![Risk::CallbackProxy]);
+            context.client = Some(
+                ClientBuilder::new()
+                    .count(12)
+                    .countries(1)
+                    .add_type(DeviceType::Desktop)
+                    .add_proxy(network)
+                    .build(),
+            );
+        }
+    }
+    context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_clean_vpn_tor() {
+        let history = timeline(
+            "203.0.113.9",
+            &[
+                TimelineEvent::Clean,
+                TimelineEvent::Vpn("NORD_VPN".to_string()),
+                TimelineEvent::Tor,
+                TimelineEvent::Clean,
+            ],
+        );
+
+        assert!(history
+            .iter()
+            .all(|c| c.ip.as_deref() == Some("203.0.113.9")));
+        assert!(history[0].tunnels.is_none() && history[0].risks.is_none());
+        assert_eq!(
+            history[1].tunnels.as_ref().unwrap()[0].operator.as_deref(),
+            Some("NORD_VPN")
+        );
+        assert_eq!(
+            history[2].tunnels.as_ref().unwrap()[0].tunnel_type,
+            Some(TunnelType::Tor)
+        );
+        assert_eq!(history[0], history[3]);
+        assert_eq!(history[0].location, history[2].location);
+    }
+
+    #[test]
+    fn test_timeline_residential_proxy() {
+        let history = timeline(
+            "192.0.2.44",
+            &[TimelineEvent::ResidentialProxy("IPROYAL_PROXY".to_string())],
+        );
+        let client = history[0].client.as_ref().unwrap();
+        assert_eq!(client.proxies, Some(vec!["IPROYAL_PROXY".to_string()]));
+        assert_eq!(history[0].risks, Some(vec![Risk::CallbackProxy]));
+    }
+
+    #[test]
+    fn test_timeline_is_deterministic() {
+        let events = [TimelineEvent::Clean, TimelineEvent::Tor];
+        assert_eq!(timeline("1.2.3.4", &events), timeline("1.2.3.4", &events));
+        assert!(timeline("1.2.3.4", &[]).is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+//! Cross-checking contexts against the public Tor exit list (requires the
+//! `tor` feature).
+//!
+//! The Tor Project publishes the addresses of exit relays, both as a bulk
+//! list with one address per line and in the `exit-addresses` format with
+//! `ExitAddress` records. [`TorExitList`] reads either format, answers
+//! membership queries, and checks whether a context's Tor tunnel tagging
+//! agrees with the list. Fetching the list is left to the caller's HTTP
+//! stack.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::test_utils::fixtures;
+//! use spur::tor::{TorCheck, TorExitList};
+//!
+//! let list = TorExitList::from_reader("185.220.101.1\n".as_bytes()).unwrap();
+//!
+//! assert!(list.is_known_tor_exit(&"185.220.101.1".parse().unwrap()));
+//! assert_eq!(list.check(&fixtures::tor_exit_node()), TorCheck::Confirmed);
+//! ```
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::net::IpAddr;
+use std::path::Path;
+
+use crate::context::{IpContext, TunnelType};
+use crate::ip_utils::{self, canonical_ip};
+
+/// A set of known Tor exit addresses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TorExitList {
+    exits: HashSet<IpAddr>,
+}
+
+impl TorExitList {
+    /// Create an empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a bulk exit list or an `exit-addresses` document.
+    ///
+    /// Lines holding a bare address and `ExitAddress <ip> ...` records are
+    /// read; comments and every other line are ignored.
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut list = Self::new();
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+            let address = match line.strip_prefix("ExitAddress ") {
+                Some(record) => record.split_whitespace().next(),
+                None => Some(line),
+            };
+            if let Some(ip) = address.and_then(ip_utils::parse) {
+                list.insert(ip);
+            }
+        }
+        Ok(list)
+    }
+
+    /// Read a list from a file, as with [`from_reader`](Self::from_reader).
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Add an exit address.
+    pub fn insert(&mut self, ip: IpAddr) {
+        self.exits.insert(canonical_ip(ip));
+    }
+
+    /// Returns the number of exit addresses.
+    pub fn len(&self) -> usize {
+        self.exits.len()
+    }
+
+    /// Returns `true` if the list has no addresses.
+    pub fn is_empty(&self) -> bool {
+        self.exits.is_empty()
+    }
+
+    /// Returns `true` if `ip` is a listed exit.
+    pub fn is_known_tor_exit(&self, ip: &IpAddr) -> bool {
+        self.exits.contains(&canonical_ip(*ip))
+    }
+
+    /// Check whether the context's Tor tagging agrees with the list.
+    pub fn check(&self, context: &IpContext) -> TorCheck {
+        let Some(ip) = context.ip.as_deref().and_then(ip_utils::parse) else {
+            return TorCheck::NoIp;
+        };
+        let tagged = context
+            .tunnels
+            .iter()
+            .flatten()
+            .any(|t| t.tunnel_type == Some(TunnelType::Tor));
+        match (self.is_known_tor_exit(&ip), tagged) {
+            (true, true) => TorCheck::Confirmed,
+            (false, false) => TorCheck::NotTor,
+            (true, false) => TorCheck::MissingTorTag,
+            (false, true) => TorCheck::NotOnExitList,
+        }
+    }
+}
+
+impl FromIterator<IpAddr> for TorExitList {
+    fn from_iter<I: IntoIterator<Item = IpAddr>>(iter: I) -> Self {
+        Self {
+            exits: iter.into_iter().map(canonical_ip).collect(),
+        }
+    }
+}
+
+/// Result of [`TorExitList::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TorCheck {
+    /// Listed as an exit and tagged with a Tor tunnel.
+    Confirmed,
+    /// Neither listed nor tagged.
+    NotTor,
+    /// Listed as an exit, but the context has no Tor tunnel.
+    MissingTorTag,
+    /// Tagged with a Tor tunnel, but not on the list. Expected for a while
+    /// after a relay stops being an exit, or if the list is outdated.
+    NotOnExitList,
+    /// The context has no parseable IP address.
+    NoIp,
+}
+
+impl TorCheck {
+    /// Returns `true` if the context and the list agree.
+    pub fn is_consistent(&self) -> bool {
+        matches!(self, Self::Confirmed | Self::NotTor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixtures, IpContextBuilder};
+
+    const EXIT_ADDRESSES: &str = "\
+ExitNode 0011BD2485AD45D984EC4159C88FC066E5E3300E
+Published 2024-06-01 10:15:02
+LastStatus 2024-06-01 11:00:00
+ExitAddress 162.247.74.201 2024-06-01 11:08:22
+ExitNode 0091174DE56EE1A8C1ED5EF31B1AB4D06AB2F1BA
+ExitAddress 2001:db8::dead 2024-06-01 11:08:22
+";
+
+    #[test]
+    fn test_parse_formats() {
+        let list = TorExitList::from_reader(EXIT_ADDRESSES.as_bytes()).unwrap();
+        assert_eq!(list.len(), 2);
+        assert!(list.is_known_tor_exit(&"162.247.74.201".parse().unwrap()));
+        assert!(list.is_known_tor_exit(&"2001:db8::dead".parse().unwrap()));
+
+        let bulk = "# exits\n185.220.101.1\n\n  185.220.101.2  \nnot an ip\n";
+        let list = TorExitList::from_reader(bulk.as_bytes()).unwrap();
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn test_check() {
+        let list: TorExitList = ["185.220.101.1", "192.0.2.7"]
+            .iter()
+            .map(|ip| ip.parse().unwrap())
+            .collect();
+
+        assert_eq!(list.check(&fixtures::tor_exit_node()), TorCheck::Confirmed);
+        assert_eq!(list.check(&fixtures::vpn_ip()), TorCheck::NotTor);
+        let untagged = IpContextBuilder::new().ip("192.0.2.7").build();
+        assert_eq!(list.check(&untagged), TorCheck::MissingTorTag);
+        let stale = IpContextBuilder::new().ip("192.0.2.8").tor().build();
+        assert_eq!(list.check(&stale), TorCheck::NotOnExitList);
+        assert!(!list.check(&stale).is_consistent());
+        assert_eq!(list.check(&IpContext::default()), TorCheck::NoIp);
+    }
+}
@@ -0,0 +1,237 @@
+//! Geo-velocity checks between sessions.
+//!
+//! A login from Amsterdam followed twenty minutes later by one from Sydney
+//! is a classic sign of a shared or stolen credential. [`impossible_travel`]
+//! compares two [`SessionObservation`]s and reports how fast the user would
+//! have had to travel between the locations Spur reports for them.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::time::{Duration, UNIX_EPOCH};
+//! use spur::velocity::{impossible_travel, SessionObservation};
+//! use spur::{IpContext, Location};
+//!
+//! fn at(lat: f64, lon: f64) -> IpContext {
+//!     IpContext {
+//!         location: Some(Location {
+//!             latitude: Some(lat),
+//!             longitude: Some(lon),
+//!             ..Default::default()
+//!         }),
+//!         ..Default::default()
+//!     }
+//! }
+//!
+//! let login = SessionObservation::new(
+//!     "198.51.100.7".parse().unwrap(),
+//!     UNIX_EPOCH,
+//!     at(52.37, 4.90),
+//! );
+//! let next = SessionObservation::new(
+//!     "203.0.113.9".parse().unwrap(),
+//!     UNIX_EPOCH + Duration::from_secs(20 * 60),
+//!     at(-33.87, 151.21),
+//! );
+//!
+//! let travel = impossible_travel(&login, &next).unwrap();
+//! assert!(travel.impossible);
+//! assert!(travel.distance_km > 16_000.0);
+//! ```
+
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime};
+
+use crate::context::IpContext;
+use crate::geo::haversine_km;
+
+/// Speed above which travel counts as impossible, about that of a
+/// commercial airliner.
+pub const MAX_SPEED_KMH: f64 = 1000.0;
+
+/// Distance below which travel never counts as impossible.
+///
+/// IP geolocation is at best city-level and often off by a few hundred
+/// kilometres, so nearby locations are not evidence of travel at all.
+pub const MIN_DISTANCE_KM: f64 = 500.0;
+
+/// A request from a user session: where it came from, when, and what Spur
+/// knows about the address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionObservation {
+    /// The client address.
+    pub ip: IpAddr,
+    /// When the request was seen.
+    pub timestamp: SystemTime,
+    /// The context of `ip`.
+    pub context: IpContext,
+}
+
+impl SessionObservation {
+    /// Create an observation.
+    pub fn new(ip: IpAddr, timestamp: SystemTime, context: IpContext) -> Self {
+        Self {
+            ip,
+            timestamp,
+            context,
+        }
+    }
+
+    /// Returns the latitude and longitude of the address, if both are set.
+    pub fn coordinates(&self) -> Option<(f64, f64)> {
+        self.context.geo_view().coordinates()
+    }
+
+    /// Returns the observation time in the time zone of the address, from
+    /// [`Location::timezone`](crate::Location::timezone) (requires the `tz`
+    /// feature).
+    ///
+    /// Useful for spotting logins at unusual local hours.
+    #[cfg(feature = "tz")]
+    pub fn local_time(&self) -> Option<chrono::DateTime<chrono_tz::Tz>> {
+        let timezone = self.context.location.as_ref()?.timezone()?;
+        let utc: chrono::DateTime<chrono::Utc> = self.timestamp.into();
+        Some(utc.with_timezone(&timezone))
+    }
+}
+
+/// The travel implied by two observations, from [`impossible_travel`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Travel {
+    /// Great-circle distance between the two locations.
+    pub distance_km: f64,
+    /// Time between the two observations.
+    pub elapsed: Duration,
+    /// Speed needed to cover the distance in time; infinite for a non-zero
+    /// distance covered in no time.
+    pub speed_kmh: f64,
+    /// Whether the distance is at least [`MIN_DISTANCE_KM`] and the speed
+    /// above [`MAX_SPEED_KMH`].
+    pub impossible: bool,
+    /// Whether either address is part of a tunnel. Switching VPN exits
+    /// explains any apparent travel, so callers usually discount these.
+    pub tunneled: bool,
+}
+
+/// Compare the locations of two observations of the same session.
+///
+/// The observations may be given in either order. Returns `None` if either
+/// address has no coordinates.
+pub fn impossible_travel(a: &SessionObservation, b: &SessionObservation) -> Option<Travel> {
+    let distance_km = haversine_km(a.coordinates()?, b.coordinates()?);
+    let elapsed = match b.timestamp.duration_since(a.timestamp) {
+        Ok(elapsed) => elapsed,
+        Err(err) => err.duration(),
+    };
+    let speed_kmh = if distance_km == 0.0 {
+        0.0
+    } else {
+        distance_km / (elapsed.as_secs_f64() / 3600.0)
+    };
+
+    Some(Travel {
+        distance_km,
+        elapsed,
+        speed_kmh,
+        impossible: distance_km >= MIN_DISTANCE_KM && speed_kmh > MAX_SPEED_KMH,
+        tunneled: a.context.risk_view().is_tunneled() || b.context.risk_view().is_tunneled(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+    use crate::test_utils::IpContextBuilder;
+
+    const HOUR: u64 = 3600;
+
+    fn observation(secs: u64, coords: Option<(f64, f64)>) -> SessionObservation {
+        let mut context = IpContextBuilder::new().location("NL", None).build();
+        if let (Some(location), Some((lat, lon))) = (context.location.as_mut(), coords) {
+            location.latitude = Some(lat);
+            location.longitude = Some(lon);
+        }
+        SessionObservation::new(
+            "198.51.100.7".parse().unwrap(),
+            UNIX_EPOCH + Duration::from_secs(secs),
+            context,
+        )
+    }
+
+    const PARIS: (f64, f64) = (48.8566, 2.3522);
+    const LONDON: (f64, f64) = (51.5074, -0.1278);
+    const NEW_YORK: (f64, f64) = (40.7128, -74.0060);
+
+    #[test]
+    fn test_speeds() {
+        // Paris to New York in 2 hours is too fast, in 9 hours it is a flight
+        let paris = observation(0, Some(PARIS));
+        let fast = impossible_travel(&paris, &observation(2 * HOUR, Some(NEW_YORK))).unwrap();
+        assert!(fast.impossible);
+        assert!((fast.speed_kmh - fast.distance_km / 2.0).abs() < 1e-9);
+        let flight = impossible_travel(&paris, &observation(9 * HOUR, Some(NEW_YORK))).unwrap();
+        assert!(!flight.impossible);
+
+        // Order does not matter
+        let reversed = impossible_travel(&observation(2 * HOUR, Some(NEW_YORK)), &paris).unwrap();
+        assert_eq!(reversed, fast);
+    }
+
+    #[test]
+    fn test_noise_and_missing_coordinates() {
+        // Paris to London in a minute is within geolocation noise
+        let nearby =
+            impossible_travel(&observation(0, Some(PARIS)), &observation(60, Some(LONDON)))
+                .unwrap();
+        assert!(nearby.speed_kmh > MAX_SPEED_KMH);
+        assert!(!nearby.impossible);
+
+        let same = impossible_travel(&observation(0, Some(PARIS)), &observation(0, Some(PARIS)));
+        assert_eq!(same.unwrap().speed_kmh, 0.0);
+
+        let instant = impossible_travel(
+            &observation(0, Some(PARIS)),
+            &observation(0, Some(NEW_YORK)),
+        );
+        assert_eq!(instant.unwrap().speed_kmh, f64::INFINITY);
+
+        assert!(impossible_travel(&observation(0, Some(PARIS)), &observation(0, None)).is_none());
+    }
+
+    #[test]
+    fn test_tunneled() {
+        let mut vpn = observation(HOUR, Some(NEW_YORK));
+        vpn.context.tunnels = IpContextBuilder::new().vpn("NORD_VPN").build().tunnels;
+        let travel = impossible_travel(&observation(0, Some(PARIS)), &vpn).unwrap();
+        assert!(travel.impossible && travel.tunneled);
+        assert!(
+            !impossible_travel(&observation(0, Some(PARIS)), &observation(0, Some(PARIS)))
+                .unwrap()
+                .tunneled
+        );
+    }
+
+    #[cfg(feature = "tz")]
+    #[test]
+    fn test_local_time() {
+        use chrono::Timelike;
+
+        use crate::Location;
+
+        let mut noon_utc = observation(12 * HOUR, Some(NEW_YORK));
+        noon_utc.context.location = Some(Location {
+            country: Some("US".to_string()),
+            latitude: Some(NEW_YORK.0),
+            longitude: Some(NEW_YORK.1),
+            ..Default::default()
+        });
+        // 1970-01-01 was in winter, so New York was at UTC-5
+        assert_eq!(noon_utc.local_time().unwrap().hour(), 7);
+
+        let mut unknown = noon_utc;
+        unknown.context.location = None;
+        assert!(unknown.local_time().is_none());
+    }
+}
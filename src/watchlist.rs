@@ -0,0 +1,334 @@
+//! Matching contexts against a list of attributes of interest.
+//!
+//! Alerting pipelines often care about a fixed set of addresses, networks,
+//! autonomous systems, tunnel operators or countries. A [`Watchlist`] holds
+//! those entries and [`Watchlist::check`] reports which of them an enriched
+//! context matches, with a short reason for each.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use spur::test_utils::IpContextBuilder;
+//! use spur::watchlist::Watchlist;
+//!
+//! let watchlist = Watchlist::new()
+//!     .network("89.39.104.0/22".parse().unwrap())
+//!     .operator("NORD_VPN")
+//!     .country("RU");
+//!
+//! let context = IpContextBuilder::new()
+//!     .ip("89.39.106.191")
+//!     .vpn("NORD_VPN")
+//!     .build();
+//!
+//! let matches = watchlist.check(&context);
+//! assert_eq!(matches.len(), 2);
+//! assert_eq!(matches[0].reason, "ip 89.39.106.191 is in 89.39.104.0/22");
+//! ```
+
+use std::fmt;
+use std::net::IpAddr;
+
+use ipnet::IpNet;
+
+use crate::context::{IpContext, TunnelEntry};
+use crate::ip_utils::{self, canonical_ip};
+use crate::operators;
+
+/// One attribute of interest on a [`Watchlist`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WatchEntry {
+    /// A single address.
+    Ip(IpAddr),
+    /// A network in CIDR notation.
+    Network(IpNet),
+    /// An autonomous system number.
+    Asn(u32),
+    /// A tunnel operator or residential proxy network, matched by
+    /// [`operators::same`], so `Proton VPN` matches `PROTON_VPN`.
+    Operator(String),
+    /// An ISO 3166-1 alpha-2 country code, matched without regard to case.
+    Country(String),
+}
+
+impl fmt::Display for WatchEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ip(ip) => write!(f, "ip {}", ip),
+            Self::Network(net) => write!(f, "network {}", net),
+            Self::Asn(number) => write!(f, "AS{}", number),
+            Self::Operator(operator) => write!(f, "operator {}", operator),
+            Self::Country(country) => write!(f, "country {}", country),
+        }
+    }
+}
+
+/// A watchlist entry matched by a context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchMatch<'a> {
+    /// The entry that matched.
+    pub entry: &'a WatchEntry,
+    /// Which part of the context matched, e.g.
+    /// `"tunnel entry 1.2.3.4 is in 1.2.3.0/24"`.
+    pub reason: String,
+}
+
+/// A set of IPs, networks, ASNs, operators and countries to watch for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Watchlist {
+    entries: Vec<WatchEntry>,
+}
+
+impl Watchlist {
+    /// Create an empty watchlist.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch a single address.
+    pub fn ip(self, ip: IpAddr) -> Self {
+        self.watch(WatchEntry::Ip(ip))
+    }
+
+    /// Watch every address in a network.
+    pub fn network(self, net: IpNet) -> Self {
+        self.watch(WatchEntry::Network(net))
+    }
+
+    /// Watch an autonomous system.
+    pub fn asn(self, number: u32) -> Self {
+        self.watch(WatchEntry::Asn(number))
+    }
+
+    /// Watch a tunnel operator or proxy network.
+    pub fn operator(self, operator: &str) -> Self {
+        self.watch(WatchEntry::Operator(operator.to_string()))
+    }
+
+    /// Watch a country code.
+    pub fn country(self, country: &str) -> Self {
+        self.watch(WatchEntry::Country(country.to_string()))
+    }
+
+    /// Add an entry.
+    pub fn watch(mut self, entry: WatchEntry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Add several entries.
+    pub fn watch_all<I>(mut self, entries: I) -> Self
+    where
+        I: IntoIterator<Item = WatchEntry>,
+    {
+        self.entries.extend(entries);
+        self
+    }
+
+    /// Returns the entries, in the order they were added.
+    pub fn entries(&self) -> &[WatchEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entries `context` matches, in watchlist order.
+    ///
+    /// Addresses, ASNs and countries are checked on the context itself and
+    /// on its tunnel entry points. Operators are checked against tunnel
+    /// operators and client proxy networks. Each entry is reported at most
+    /// once, with the first reason found.
+    pub fn check(&self, context: &IpContext) -> Vec<WatchMatch<'_>> {
+        let entries: Vec<&TunnelEntry> = context
+            .tunnels
+            .iter()
+            .flatten()
+            .flat_map(|t| t.entries.iter().flatten())
+            .collect();
+        self.entries
+            .iter()
+            .filter_map(|entry| {
+                let reason = match entry {
+                    WatchEntry::Ip(ip) => {
+                        match_ip(context, &entries, |addr| addr == canonical_ip(*ip))
+                            .map(|(what, addr)| format!("{} {}", what, addr))
+                    }
+                    WatchEntry::Network(net) => {
+                        match_ip(context, &entries, |addr| net.contains(&addr))
+                            .map(|(what, addr)| format!("{} {} is in {}", what, addr, net))
+                    }
+                    WatchEntry::Asn(number) => match_asn(context, &entries, *number),
+                    WatchEntry::Operator(operator) => match_operator(context, operator),
+                    WatchEntry::Country(country) => match_country(context, &entries, country),
+                }?;
+                Some(WatchMatch { entry, reason })
+            })
+            .collect()
+    }
+
+    /// Returns `true` if `context` matches any entry.
+    pub fn matches(&self, context: &IpContext) -> bool {
+        !self.check(context).is_empty()
+    }
+}
+
+fn match_ip(
+    context: &IpContext,
+    entries: &[&TunnelEntry],
+    pred: impl Fn(IpAddr) -> bool,
+) -> Option<(&'static str, IpAddr)> {
+    let parse = |ip: &Option<String>| ip.as_deref().and_then(ip_utils::parse);
+    if let Some(ip) = parse(&context.ip).filter(|&ip| pred(ip)) {
+        return Some(("ip", ip));
+    }
+    entries
+        .iter()
+        .filter_map(|entry| parse(&entry.ip))
+        .find(|&ip| pred(ip))
+        .map(|ip| ("tunnel entry", ip))
+}
+
+fn match_asn(context: &IpContext, entries: &[&TunnelEntry], number: u32) -> Option<String> {
+    let asn = |a: &Option<crate::AutonomousSystem>| a.as_ref().and_then(|a| a.number);
+    if asn(&context.autonomous_system) == Some(number) {
+        return Some(format!("AS{}", number));
+    }
+    entries
+        .iter()
+        .any(|entry| asn(&entry.autonomous_system) == Some(number))
+        .then(|| format!("tunnel entry in AS{}", number))
+}
+
+fn match_operator(context: &IpContext, operator: &str) -> Option<String> {
+    let tunnel = context
+        .tunnels
+        .iter()
+        .flatten()
+        .filter_map(|t| t.operator.as_deref())
+        .find(|o| operators::same(o, operator));
+    if let Some(found) = tunnel {
+        return Some(format!("tunnel operator {}", found));
+    }
+    context
+        .client
+        .as_ref()
+        .and_then(|c| c.proxies.as_ref())
+        .into_iter()
+        .flatten()
+        .find(|p| operators::same(p, operator))
+        .map(|found| format!("client proxy {}", found))
+}
+
+fn match_country(context: &IpContext, entries: &[&TunnelEntry], country: &str) -> Option<String> {
+    let is_match = |l: &Option<crate::Location>| {
+        l.as_ref()
+            .and_then(|l| l.country.as_deref())
+            .is_some_and(|c| c.eq_ignore_ascii_case(country))
+    };
+    if is_match(&context.location) {
+        return Some(format!("located in {}", country));
+    }
+    entries
+        .iter()
+        .any(|entry| is_match(&entry.location))
+        .then(|| format!("tunnel entry located in {}", country))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{fixtures, ClientBuilder, IpContextBuilder};
+
+    #[test]
+    fn test_no_matches() {
+        let watchlist = Watchlist::new()
+            .ip("192.0.2.1".parse().unwrap())
+            .asn(1)
+            .country("AQ")
+            .operator("NOBODY_VPN");
+        assert_eq!(watchlist.len(), 4);
+        assert!(!watchlist.matches(&fixtures::vpn_ip()));
+        assert!(Watchlist::new().check(&fixtures::vpn_ip()).is_empty());
+    }
+
+    #[test]
+    fn test_matches_context_fields() {
+        let context = IpContextBuilder::new()
+            .ip("2001:db8::7")
+            .asn(9009, "M247")
+            .location("nl", None)
+            .vpn("Nord_VPN")
+            .build();
+        let watchlist = Watchlist::new()
+            .ip("2001:db8::7".parse().unwrap())
+            .network("2001:db8::/32".parse().unwrap())
+            .asn(9009)
+            .operator("NORD_VPN")
+            .country("NL");
+
+        let reasons: Vec<_> = watchlist
+            .check(&context)
+            .into_iter()
+            .map(|m| m.reason)
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                "ip 2001:db8::7",
+                "ip 2001:db8::7 is in 2001:db8::/32",
+                "AS9009",
+                "tunnel operator Nord_VPN",
+                "located in NL",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matches_tunnel_entries_and_proxies() {
+        let context = IpContextBuilder::new()
+            .ip("203.0.113.5")
+            .vpn_with_entry("PROTON_VPN", "198.51.100.20", "CH")
+            .with_client(ClientBuilder::new().add_proxy("OXYLABS_PROXY").build())
+            .build();
+        let watchlist = Watchlist::new()
+            .network("198.51.100.0/24".parse().unwrap())
+            .country("CH")
+            .operator("oxylabs_proxy");
+
+        let matches = watchlist.check(&context);
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[0].entry, &watchlist.entries()[0]);
+        assert_eq!(
+            matches[0].reason,
+            "tunnel entry 198.51.100.20 is in 198.51.100.0/24"
+        );
+        assert_eq!(matches[1].reason, "tunnel entry located in CH");
+        assert_eq!(matches[2].reason, "client proxy OXYLABS_PROXY");
+    }
+
+    #[test]
+    fn test_operator_spellings() {
+        let context = IpContextBuilder::new().vpn("PROTON_VPN").build();
+        for operator in ["ProtonVPN", "Proton VPN", "proton_vpn"] {
+            assert!(Watchlist::new().operator(operator).matches(&context));
+        }
+        assert!(!Watchlist::new().operator("Proton").matches(&context));
+    }
+
+    #[test]
+    fn test_entry_display() {
+        assert_eq!(WatchEntry::Asn(13335).to_string(), "AS13335");
+        assert_eq!(
+            WatchEntry::Network("10.0.0.0/8".parse().unwrap()).to_string(),
+            "network 10.0.0.0/8"
+        );
+    }
+}
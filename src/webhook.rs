@@ -0,0 +1,354 @@
+//! Event payloads and HMAC signatures for relaying Spur-related events.
+//!
+//! Spur does not publish a webhook format. This module defines this
+//! crate's own envelope for passing events such as quota alerts (see
+//! [`monitor`](crate::monitor)) and new feed generations between your own
+//! services; it does not parse deliveries sent by Spur.
+//!
+//! A [`WebhookEvent`] is a JSON envelope with an event `type` and a `data`
+//! object. Unknown types keep their raw data so a receiver can log them and
+//! move on.
+//!
+//! [`sign_hmac`] and [`verify_hmac`] are a generic timestamped HMAC-SHA256
+//! scheme for such payloads: a header value of the form
+//! `t=<unix seconds>,v1=<hex>`, where the hex value is the HMAC-SHA256 of
+//! `"<t>.<body>"` under a shared secret. Sender and receiver pick the
+//! header name. Verification also rejects payloads older than a tolerance
+//! to stop replays.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::time::{Duration, SystemTime};
+//! use spur::webhook::{self, EventData, WebhookEvent};
+//!
+//! let body = br#"{
+//!     "id": "evt_123",
+//!     "type": "quota.alert",
+//!     "created": "2026-03-01T00:00:00Z",
+//!     "data": { "queriesRemaining": 500, "threshold": 1000 }
+//! }"#;
+//! let secret = b"whsec_test";
+//! let now = SystemTime::now();
+//! let header = webhook::sign_hmac(secret, now, body);
+//!
+//! webhook::verify_hmac(secret, &header, body, now, Duration::from_secs(300)).unwrap();
+//! let event: WebhookEvent = serde_json::from_slice(body).unwrap();
+//! match event.data {
+//!     EventData::QuotaAlert(alert) => assert_eq!(alert.queries_remaining, Some(500)),
+//!     other => panic!("unexpected {}", other.event_type()),
+//! }
+//! ```
+
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::hex;
+
+/// An event relayed between services, in this crate's envelope format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawEvent", into = "RawEvent")]
+pub struct WebhookEvent {
+    /// Unique event identifier, for deduplicating redeliveries.
+    pub id: Option<String>,
+    /// When the event was created (RFC 3339).
+    pub created: Option<String>,
+    /// The event type and its payload.
+    pub data: EventData,
+}
+
+/// The payload of a [`WebhookEvent`], by event type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventData {
+    /// `quota.alert`: the token's remaining queries fell below a threshold.
+    QuotaAlert(QuotaAlert),
+    /// `feed.ready`: a new feed generation can be downloaded.
+    FeedReady(FeedReady),
+    /// An event type this version of the crate does not know.
+    Other {
+        /// The `type` of the event.
+        event_type: String,
+        /// The raw `data` object.
+        data: serde_json::Value,
+    },
+}
+
+impl EventData {
+    /// Returns the `type` string of the event.
+    pub fn event_type(&self) -> &str {
+        match self {
+            Self::QuotaAlert(_) => "quota.alert",
+            Self::FeedReady(_) => "feed.ready",
+            Self::Other { event_type, .. } => event_type,
+        }
+    }
+}
+
+/// Payload of a `quota.alert` event.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct QuotaAlert {
+    /// The number of queries remaining in this billing cycle.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queries_remaining: Option<u64>,
+
+    /// The alert threshold that was crossed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<u64>,
+
+    /// The service tier of the token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<String>,
+}
+
+/// Payload of a `feed.ready` event.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FeedReady {
+    /// Name of the feed (e.g. `anonymous`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feed: Option<String>,
+
+    /// Date of the feed generation (`YYYY-MM-DD`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+
+    /// Download location of the feed file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Size of the feed file in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+/// Wire form of [`WebhookEvent`].
+#[derive(Serialize, Deserialize)]
+struct RawEvent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(rename = "type")]
+    event_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created: Option<String>,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+impl TryFrom<RawEvent> for WebhookEvent {
+    type Error = serde_json::Error;
+
+    fn try_from(raw: RawEvent) -> Result<Self, Self::Error> {
+        let data = match raw.event_type.as_str() {
+            "quota.alert" => EventData::QuotaAlert(serde_json::from_value(raw.data)?),
+            "feed.ready" => EventData::FeedReady(serde_json::from_value(raw.data)?),
+            _ => EventData::Other {
+                event_type: raw.event_type,
+                data: raw.data,
+            },
+        };
+        Ok(Self {
+            id: raw.id,
+            created: raw.created,
+            data,
+        })
+    }
+}
+
+impl From<WebhookEvent> for RawEvent {
+    fn from(event: WebhookEvent) -> Self {
+        let event_type = event.data.event_type().to_string();
+        let data = match event.data {
+            EventData::QuotaAlert(alert) => serde_json::to_value(alert),
+            EventData::FeedReady(feed) => serde_json::to_value(feed),
+            EventData::Other { data, .. } => Ok(data),
+        };
+        Self {
+            id: event.id,
+            event_type,
+            created: event.created,
+            data: data.expect("payload should serialize"),
+        }
+    }
+}
+
+/// Errors returned by [`verify_hmac`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureError {
+    /// The header is not of the form `t=<unix seconds>,v1=<hex>`.
+    Malformed,
+    /// The timestamp is further from the current time than the tolerance.
+    Expired,
+    /// No `v1` signature matches the body.
+    Mismatch,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed signature header"),
+            Self::Expired => write!(f, "signature timestamp outside tolerance"),
+            Self::Mismatch => write!(f, "signature mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// Returns the `t=<unix seconds>,v1=<hex>` signature header value for
+/// `body` sent at `timestamp`.
+pub fn sign_hmac(secret: &[u8], timestamp: SystemTime, body: &[u8]) -> String {
+    let t = unix_seconds(timestamp);
+    let signature = hex::encode(&mac(secret, t, body).finalize().into_bytes());
+    format!("t={},v1={}", t, signature)
+}
+
+/// Check a signature header written by [`sign_hmac`].
+///
+/// `body` must be the raw request body, before any parsing. The header may
+/// list several `v1` signatures during secret rotation; any match is
+/// accepted. Signatures are compared in constant time.
+pub fn verify_hmac(
+    secret: &[u8],
+    header: &str,
+    body: &[u8],
+    now: SystemTime,
+    tolerance: Duration,
+) -> Result<(), SignatureError> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", t)) => timestamp = Some(t.parse().map_err(|_| SignatureError::Malformed)?),
+            Some(("v1", v1)) => signatures.push(hex::decode(v1).ok_or(SignatureError::Malformed)?),
+            Some(_) => {}
+            None => return Err(SignatureError::Malformed),
+        }
+    }
+    let timestamp: u64 = timestamp.ok_or(SignatureError::Malformed)?;
+    if signatures.is_empty() {
+        return Err(SignatureError::Malformed);
+    }
+    if unix_seconds(now).abs_diff(timestamp) > tolerance.as_secs() {
+        return Err(SignatureError::Expired);
+    }
+    signatures
+        .iter()
+        .any(|s| mac(secret, timestamp, body).verify_slice(s).is_ok())
+        .then_some(())
+        .ok_or(SignatureError::Mismatch)
+}
+
+fn mac(secret: &[u8], timestamp: u64, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"whsec_test";
+    const TOLERANCE: Duration = Duration::from_secs(300);
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_sign_known_vector() {
+        // HMAC-SHA256("whsec_test", "1700000000.{}")
+        assert_eq!(
+            sign_hmac(SECRET, at(1_700_000_000), b"{}"),
+            "t=1700000000,v1=35495024f4ef3f94e5a93e22221544c4b75e9a42300cd965ab81cb85cd994e91"
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let body = br#"{"type": "feed.ready", "data": {}}"#;
+        let header = sign_hmac(SECRET, at(1_700_000_000), body);
+        let verify =
+            |header: &str, body: &[u8], now| verify_hmac(SECRET, header, body, now, TOLERANCE);
+
+        assert_eq!(verify(&header, body, at(1_700_000_100)), Ok(()));
+        assert_eq!(
+            verify(&header, b"{}", at(1_700_000_000)),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify(&header, body, at(1_700_000_301)),
+            Err(SignatureError::Expired)
+        );
+        assert_eq!(
+            verify_hmac(b"other", &header, body, at(1_700_000_000), TOLERANCE),
+            Err(SignatureError::Mismatch)
+        );
+
+        // Secret rotation: one of several signatures matches
+        let old = sign_hmac(b"old", at(1_700_000_000), body);
+        let rotated = format!("{},{}", header, old.split_once(',').unwrap().1);
+        assert_eq!(verify(&rotated, body, at(1_700_000_000)), Ok(()));
+
+        for malformed in [
+            "",
+            "t=1700000000",
+            "v1=00",
+            "t=x,v1=00",
+            "t=1,v1=0g",
+            "garbage",
+        ] {
+            assert_eq!(
+                verify(malformed, body, at(1)),
+                Err(SignatureError::Malformed),
+                "{}",
+                malformed
+            );
+        }
+    }
+
+    #[test]
+    fn test_event_types() {
+        let feed: WebhookEvent = serde_json::from_str(
+            r#"{"id": "evt_1", "type": "feed.ready",
+                "data": {"feed": "anonymous", "date": "2026-03-01", "size": 1024}}"#,
+        )
+        .unwrap();
+        let EventData::FeedReady(ready) = &feed.data else {
+            panic!("unexpected {:?}", feed.data);
+        };
+        assert_eq!(ready.feed.as_deref(), Some("anonymous"));
+        assert_eq!(ready.size, Some(1024));
+
+        let unknown: WebhookEvent =
+            serde_json::from_str(r#"{"type": "token.rotated", "data": {"x": 1}}"#).unwrap();
+        assert_eq!(unknown.data.event_type(), "token.rotated");
+        assert_eq!(unknown.id, None);
+
+        for event in [feed, unknown] {
+            let json = serde_json::to_string(&event).unwrap();
+            assert_eq!(serde_json::from_str::<WebhookEvent>(&json).unwrap(), event);
+        }
+    }
+
+    #[test]
+    fn test_invalid_known_payload_is_an_error() {
+        let result = serde_json::from_str::<WebhookEvent>(
+            r#"{"type": "quota.alert", "data": {"queriesRemaining": "many"}}"#,
+        );
+        assert!(result.is_err());
+    }
+}
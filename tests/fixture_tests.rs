@@ -232,6 +232,35 @@ fn test_residential_fixtures_have_residential_infra() {
     }
 }
 
+/// Test that mobile and satellite fixtures have the matching infrastructure.
+#[test]
+fn test_mobile_and_satellite_fixtures_have_matching_infra() {
+    use spur::Infrastructure;
+
+    let fixtures = get_fixture_files();
+
+    for fixture_path in &fixtures {
+        let filename = fixture_path.file_name().unwrap().to_string_lossy();
+        let expected = if filename.starts_with("mobile_") {
+            Infrastructure::Mobile
+        } else if filename.starts_with("satellite_") {
+            Infrastructure::Other("SATELLITE".to_string())
+        } else {
+            continue;
+        };
+        let json = fs::read_to_string(fixture_path).unwrap();
+
+        let context: IpContext = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(
+            context.infrastructure,
+            Some(expected),
+            "Fixture {} should have infrastructure matching its prefix",
+            filename
+        );
+    }
+}
+
 /// Test that AI scraper fixtures have AI information.
 /// Note: Matches files starting with "ai_" or containing "scraper" or "bot".
 #[test]
@@ -268,6 +297,31 @@ fn test_ai_fixtures_have_ai_info() {
     }
 }
 
+/// Test that every fixture on disk is bundled into `test_utils`.
+#[test]
+fn test_all_fixtures_are_bundled() {
+    let bundled: Vec<&str> = spur::test_utils::fixture_names().collect();
+
+    for fixture_path in get_fixture_files() {
+        let stem = fixture_path.file_stem().unwrap().to_string_lossy();
+        assert!(
+            bundled.contains(&stem.as_ref()),
+            "Fixture {} is missing from src/test_utils/bundled.rs",
+            stem
+        );
+    }
+
+    for (name, context) in spur::test_utils::load_all_fixtures() {
+        let json = fs::read_to_string(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures")
+                .join(format!("{}.json", name)),
+        )
+        .unwrap();
+        assert_eq!(context, serde_json::from_str::<IpContext>(&json).unwrap());
+    }
+}
+
 /// Print a summary of all fixture files and their key properties.
 #[test]
 fn test_fixture_summary() {
@@ -380,4 +434,58 @@ mod individual_fixture_tests {
         let services = ai.services.as_ref().expect("Should have AI services");
         assert!(!services.is_empty());
     }
+
+    #[test]
+    fn test_mobile_ipv6_fixture() {
+        let json = include_str!("fixtures/mobile_ipv6.json");
+        let context: IpContext = serde_json::from_str(json).unwrap();
+
+        let ip: std::net::IpAddr = context.ip.as_deref().unwrap().parse().unwrap();
+        assert!(ip.is_ipv6());
+        assert_eq!(context.infrastructure, Some(Infrastructure::Mobile));
+    }
+
+    #[test]
+    fn test_satellite_fixture() {
+        let json = include_str!("fixtures/satellite_starlink.json");
+        let context: IpContext = serde_json::from_str(json).unwrap();
+
+        // Unknown infrastructure values are preserved
+        assert_eq!(
+            context.infrastructure,
+            Some(Infrastructure::Other("SATELLITE".to_string()))
+        );
+        let roundtrip = serde_json::to_string(&context).unwrap();
+        assert!(roundtrip.contains(r#""infrastructure":"SATELLITE""#));
+    }
+
+    #[test]
+    fn test_callback_proxy_fixture() {
+        let json = include_str!("fixtures/residential_callback_proxy.json");
+        let context: IpContext = serde_json::from_str(json).unwrap();
+
+        let risks = context.risks.as_ref().expect("Should have risks");
+        assert!(risks.contains(&Risk::CallbackProxy));
+        assert!(context.tunnels.is_none());
+
+        let proxies = context.client.as_ref().unwrap().proxies.as_ref().unwrap();
+        assert!(proxies.contains(&"OXYLABS_PROXY".to_string()));
+    }
+
+    #[test]
+    fn test_geo_mismatch_fixture() {
+        let json = include_str!("fixtures/residential_geo_mismatch.json");
+        let context: IpContext = serde_json::from_str(json).unwrap();
+
+        let risks = context.risks.as_ref().expect("Should have risks");
+        assert!(risks.contains(&Risk::GeoMismatch));
+
+        let location = context.location.as_ref().unwrap();
+        let concentration = context
+            .client
+            .as_ref()
+            .and_then(|c| c.concentration.as_ref())
+            .unwrap();
+        assert_ne!(location.country, concentration.country);
+    }
 }
@@ -4,7 +4,7 @@
 
 use proptest::prelude::*;
 use spur::proptest_strategies::*;
-use spur::{Infrastructure, IpContext, Risk, Service, TunnelType};
+use spur::{ApiStatus, Infrastructure, IpContext, Risk, Service, TagMetadata, TunnelType};
 
 proptest! {
     /// Verify that all generated IpContext values can roundtrip through JSON.
@@ -31,6 +31,33 @@ proptest! {
         prop_assert_eq!(context, parsed);
     }
 
+    /// Verify that tag metadata roundtrips and keeps the API's camelCase keys.
+    #[test]
+    fn fuzz_tag_metadata_roundtrip(meta in arb_tag_metadata()) {
+        let json = serde_json::to_string(&meta).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        for key in value.as_object().unwrap().keys() {
+            prop_assert!(!key.contains('_'), "snake_case key {}", key);
+        }
+        let parsed: TagMetadata = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(meta, parsed);
+    }
+
+    /// Verify that API status values roundtrip correctly.
+    #[test]
+    fn fuzz_api_status_roundtrip(status in arb_api_status()) {
+        let json = serde_json::to_string(&status).unwrap();
+        let parsed: ApiStatus = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(status, parsed);
+    }
+
+    /// Verify that unknown keys and novel enum strings never break parsing.
+    #[test]
+    fn fuzz_forward_compatibility(case in arb_forward_compat_case()) {
+        let parsed: IpContext = serde_json::from_str(&case.json).unwrap();
+        prop_assert_eq!(parsed, case.expected);
+    }
+
     /// Verify infrastructure enum display matches as_str.
     #[test]
     fn fuzz_infrastructure_display_consistency(infra in arb_infrastructure()) {